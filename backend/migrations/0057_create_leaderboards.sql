@@ -0,0 +1,24 @@
+-- Allow users to hide themselves from every leaderboard
+ALTER TABLE "user"
+ADD COLUMN leaderboard_opt_out BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Cached leaderboard results, keyed by a hash of viewer, scope, period and filters
+CREATE TABLE leaderboard_cache (
+    cache_key VARCHAR(64) PRIMARY KEY,
+
+    -- user the leaderboard was computed for (visibility is evaluated from their point of view)
+    viewer_id VARCHAR NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+    -- users that appear in the cached result, used for invalidation
+    member_ids TEXT[] NOT NULL DEFAULT '{}',
+
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    entries JSONB NOT NULL DEFAULT '[]',
+
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_leaderboard_cache_viewer_id ON leaderboard_cache(viewer_id);
+CREATE INDEX idx_leaderboard_cache_member_ids ON leaderboard_cache USING GIN(member_ids);
+CREATE INDEX idx_leaderboard_cache_expires_at ON leaderboard_cache(expires_at);
@@ -0,0 +1,41 @@
+CREATE TYPE project_member_role AS ENUM ('owner', 'editor', 'contributor');
+
+-- Users that have access to a project, the project creator is always an owner
+CREATE TABLE IF NOT EXISTS "project_member" (
+    project_id UUID NOT NULL,
+    user_id VARCHAR NOT NULL,
+    role project_member_role NOT NULL DEFAULT 'contributor',
+
+    -- Timestamps
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (project_id, user_id),
+
+    -- Foreign key constraints
+    CONSTRAINT fk_project_member_project FOREIGN KEY (project_id) REFERENCES "project"(id) ON DELETE CASCADE,
+    CONSTRAINT fk_project_member_user FOREIGN KEY (user_id) REFERENCES "user"(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_project_member_user_id ON project_member(user_id);
+
+-- Existing projects are owned by their creator
+INSERT INTO project_member (project_id, user_id, role, created_at)
+SELECT id, user_id, 'owner', created_at
+FROM project
+ON CONFLICT DO NOTHING;
+
+-- Trigger to automatically register the creator of a project as its owner
+CREATE OR REPLACE FUNCTION add_project_owner_member()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO project_member (project_id, user_id, role)
+    VALUES (NEW.id, NEW.user_id, 'owner')
+    ON CONFLICT DO NOTHING;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_add_project_owner_member
+    AFTER INSERT ON project
+    FOR EACH ROW
+    EXECUTE FUNCTION add_project_owner_member();
@@ -0,0 +1,15 @@
+-- Suggested users the user does not want to see again
+CREATE TABLE IF NOT EXISTS "friend_suggestion_dismissal" (
+    user_id VARCHAR NOT NULL,
+    dismissed_user_id VARCHAR NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (user_id, dismissed_user_id),
+    FOREIGN KEY (user_id) REFERENCES "user"(id) ON DELETE CASCADE,
+    FOREIGN KEY (dismissed_user_id) REFERENCES "user"(id) ON DELETE CASCADE
+);
+
+-- Category and tag names are compared case-insensitively between users
+CREATE INDEX IF NOT EXISTS idx_category_lower_name ON category(LOWER(name));
+CREATE INDEX IF NOT EXISTS idx_tag_lower_label ON tag(LOWER(label));
@@ -0,0 +1,48 @@
+-- Users that do not want any interaction with another user, checked in both directions
+CREATE TABLE IF NOT EXISTS "user_block" (
+    blocker_id VARCHAR NOT NULL,
+    blocked_id VARCHAR NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (blocker_id, blocked_id),
+    FOREIGN KEY (blocker_id) REFERENCES "user"(id) ON DELETE CASCADE,
+    FOREIGN KEY (blocked_id) REFERENCES "user"(id) ON DELETE CASCADE,
+    CHECK (blocker_id <> blocked_id)
+);
+
+CREATE INDEX idx_user_block_blocked_id ON user_block(blocked_id);
+
+CREATE TYPE user_report_reason AS ENUM ('spam', 'harassment', 'inappropriate_content', 'impersonation', 'other');
+CREATE TYPE user_report_status AS ENUM ('pending', 'resolved', 'dismissed');
+
+-- Reports queued for admin review
+CREATE TABLE IF NOT EXISTS "user_report" (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+
+    reporter_id VARCHAR NOT NULL,
+    reported_id VARCHAR NOT NULL,
+    reason user_report_reason NOT NULL,
+    details TEXT,
+
+    status user_report_status NOT NULL DEFAULT 'pending',
+    reviewed_by VARCHAR,
+    reviewed_at TIMESTAMPTZ,
+    resolution_note TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    FOREIGN KEY (reporter_id) REFERENCES "user"(id) ON DELETE CASCADE,
+    FOREIGN KEY (reported_id) REFERENCES "user"(id) ON DELETE CASCADE,
+    FOREIGN KEY (reviewed_by) REFERENCES "user"(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_user_report_status_created_at ON user_report(status, created_at DESC);
+CREATE INDEX idx_user_report_reported_id ON user_report(reported_id);
+
+-- Suspended users cannot log in or refresh their session
+ALTER TABLE "user"
+ADD COLUMN suspended_at TIMESTAMPTZ,
+ADD COLUMN suspension_reason TEXT;
+
+ALTER TYPE notification_type ADD VALUE IF NOT EXISTS 'admin:user:reported';
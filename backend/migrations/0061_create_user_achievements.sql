@@ -0,0 +1,18 @@
+-- Achievements earned by users, the catalog of achievements lives in the application
+CREATE TABLE IF NOT EXISTS "user_achievement" (
+    user_id VARCHAR NOT NULL,
+    achievement_key VARCHAR(64) NOT NULL,
+
+    -- Timestamps
+    awarded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (user_id, achievement_key),
+
+    -- Foreign key constraints
+    CONSTRAINT fk_user_achievement_user FOREIGN KEY (user_id) REFERENCES "user"(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_user_achievement_awarded_at ON user_achievement(user_id, awarded_at DESC);
+
+ALTER TYPE feed_event_type ADD VALUE IF NOT EXISTS 'achievement_unlocked';
+ALTER TYPE notification_type ADD VALUE IF NOT EXISTS 'achievement:unlocked';
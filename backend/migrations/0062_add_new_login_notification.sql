@@ -0,0 +1,3 @@
+ALTER TYPE notification_type ADD VALUE IF NOT EXISTS 'auth:new_login';
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_user_active ON refresh_tokens(user_id) WHERE revoked_at IS NULL;
@@ -0,0 +1,12 @@
+-- Refresh tokens issued by rotating another token share the family of the original login
+ALTER TABLE refresh_tokens
+ADD COLUMN family_id UUID;
+
+-- Every existing token starts its own family
+UPDATE refresh_tokens SET family_id = id WHERE family_id IS NULL;
+
+ALTER TABLE refresh_tokens
+ALTER COLUMN family_id SET NOT NULL,
+ALTER COLUMN family_id SET DEFAULT gen_random_uuid();
+
+CREATE INDEX idx_refresh_tokens_family_id ON refresh_tokens(family_id);
@@ -0,0 +1,15 @@
+-- RSA key pairs used to sign access tokens when JWT_KEYS_SOURCE=database
+-- Keys without a private key only verify tokens, e.g. keys of a rollover that are not active yet
+CREATE TABLE jwt_signing_key (
+    kid VARCHAR(64) PRIMARY KEY,
+    public_key_pem TEXT NOT NULL,
+    private_key_pem TEXT,
+    active BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    retired_at TIMESTAMPTZ,
+
+    CONSTRAINT jwt_signing_key_active_has_private_key CHECK (NOT active OR private_key_pem IS NOT NULL)
+);
+
+-- Only one key signs new tokens
+CREATE UNIQUE INDEX idx_jwt_signing_key_active ON jwt_signing_key(active) WHERE active;
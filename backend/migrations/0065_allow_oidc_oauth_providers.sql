@@ -0,0 +1,5 @@
+-- Generic OpenID Connect providers are named by the deployment, the enum can not list them
+ALTER TABLE oauth_accounts
+ALTER COLUMN provider TYPE VARCHAR(64) USING provider::text;
+
+DROP TYPE oauth_provider;
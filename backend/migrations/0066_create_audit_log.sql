@@ -0,0 +1,53 @@
+CREATE TYPE audit_action AS ENUM (
+    'auth_login',
+    'auth_login_failed',
+    'auth_logout',
+    'api_token_created',
+    'api_token_revoked',
+    'session_revoked',
+    'oauth_account_linked',
+    'oauth_account_unlinked',
+    'impersonation_started',
+    'impersonation_stopped',
+    'visibility_changed',
+    'user_suspended',
+    'user_unsuspended',
+    'report_reviewed',
+    'release_created',
+    'release_updated',
+    'release_deleted',
+    'release_published',
+    'release_unpublished',
+    'backup_downloaded',
+    'sandbox_reset'
+);
+
+CREATE TYPE audit_target_type AS ENUM (
+    'user',
+    'api_token',
+    'session',
+    'oauth_account',
+    'report',
+    'release',
+    'backup',
+    'sandbox'
+);
+
+-- Entries outlive the users they mention, so there are no foreign keys
+CREATE TABLE audit_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    action audit_action NOT NULL,
+    actor_id VARCHAR,
+    target_type audit_target_type,
+    target_id VARCHAR,
+    ip_address INET,
+    user_agent TEXT,
+    before JSONB,
+    after JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_log_created_at ON audit_log(created_at DESC);
+CREATE INDEX idx_audit_log_actor_id ON audit_log(actor_id, created_at DESC);
+CREATE INDEX idx_audit_log_target ON audit_log(target_type, target_id, created_at DESC);
+CREATE INDEX idx_audit_log_action ON audit_log(action, created_at DESC);
@@ -0,0 +1,23 @@
+-- Fixed window counters of the Postgres rate limit backend, shared by all instances
+CREATE UNLOGGED TABLE rate_limit_bucket (
+    key TEXT NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    hits INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (key, window_start)
+);
+
+CREATE INDEX idx_rate_limit_bucket_expires_at ON rate_limit_bucket(expires_at);
+
+CREATE TABLE metrics_rate_limit (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    request_id UUID,
+    policy TEXT NOT NULL,
+    key_type TEXT NOT NULL,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    user_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_metrics_rate_limit_created_at ON metrics_rate_limit(created_at);
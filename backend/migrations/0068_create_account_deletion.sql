@@ -0,0 +1,16 @@
+ALTER TYPE audit_action ADD VALUE 'account_deletion_requested';
+ALTER TYPE audit_action ADD VALUE 'account_deletion_cancelled';
+ALTER TYPE audit_action ADD VALUE 'account_deleted';
+
+-- Erased users keep an anonymized row, so data of other users that points at them stays valid
+ALTER TABLE "user" ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE TABLE account_deletion (
+    user_id VARCHAR PRIMARY KEY REFERENCES "user"(id) ON DELETE CASCADE,
+    requested_by VARCHAR NOT NULL,
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    scheduled_for TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_account_deletion_pending ON account_deletion(scheduled_for) WHERE completed_at IS NULL;
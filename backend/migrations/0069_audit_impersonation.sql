@@ -0,0 +1,20 @@
+ALTER TYPE notification_type ADD VALUE 'auth:account_accessed';
+
+-- Ended sessions are kept so users can see when their account was accessed
+ALTER TABLE impersonation_sessions
+    ADD COLUMN reason TEXT NOT NULL DEFAULT '',
+    ADD COLUMN read_only BOOLEAN NOT NULL DEFAULT true,
+    ADD COLUMN ended_at TIMESTAMPTZ;
+
+ALTER TABLE impersonation_sessions ALTER COLUMN reason DROP DEFAULT;
+
+CREATE TABLE impersonation_request (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    impersonation_session_id UUID NOT NULL REFERENCES impersonation_sessions(id) ON DELETE CASCADE,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    status_code SMALLINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_impersonation_request_session ON impersonation_request(impersonation_session_id, created_at);
@@ -0,0 +1,29 @@
+ALTER TYPE audit_action ADD VALUE 'passkey_registered';
+ALTER TYPE audit_action ADD VALUE 'passkey_removed';
+ALTER TYPE audit_target_type ADD VALUE 'passkey';
+
+-- WebAuthn credentials users can sign in with instead of an OAuth provider
+CREATE TABLE passkey_credential (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id VARCHAR NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+    -- WebAuthn user handle, the same for every passkey of a user
+    user_handle UUID NOT NULL,
+    credential_id BYTEA NOT NULL UNIQUE,
+    passkey JSONB NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_passkey_credential_user_id ON passkey_credential(user_id);
+
+-- Server side state of registration and login ceremonies until the browser answers
+CREATE TABLE passkey_challenge (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id VARCHAR REFERENCES "user"(id) ON DELETE CASCADE,
+    state JSONB NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_passkey_challenge_expires_at ON passkey_challenge(expires_at);
@@ -0,0 +1,58 @@
+-- Path with ids replaced by {id}, so requests can be grouped per route.
+-- Existing rows are backfilled in batches by a later migration.
+ALTER TABLE metrics_handler ADD COLUMN route TEXT;
+
+CREATE INDEX idx_metrics_handler_created_at ON metrics_handler(created_at);
+CREATE INDEX idx_metrics_handler_request_id ON metrics_handler(request_id);
+CREATE INDEX idx_metrics_db_query_created_at ON metrics_db_query(created_at);
+CREATE INDEX idx_metrics_db_query_request_id ON metrics_db_query(request_id);
+
+-- Latencies are counted in histogram buckets that grow by 10%, bucket n holds durations
+-- up to 0.1ms * 1.1^n. Percentiles can not be merged, histograms of any range can be added up.
+CREATE FUNCTION metrics_latency_bucket(duration_ms FLOAT8)
+RETURNS SMALLINT AS $$
+  SELECT LEAST(CEIL(LN(GREATEST(duration_ms, 0.1) / 0.1) / LN(1.1)), 200)::SMALLINT
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE FUNCTION metrics_latency_bucket_bound(latency_bucket SMALLINT)
+RETURNS FLOAT8 AS $$
+  SELECT 0.1 * POWER(1.1, latency_bucket)
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Hourly aggregates of raw rows older than the retention period
+CREATE TABLE metrics_handler_rollup (
+    bucket_start TIMESTAMPTZ NOT NULL,
+    method TEXT NOT NULL,
+    route TEXT NOT NULL,
+    status_code SMALLINT NOT NULL,
+    request_count BIGINT NOT NULL,
+    duration_sum_ms FLOAT8 NOT NULL,
+    duration_max_ms FLOAT8 NOT NULL,
+    PRIMARY KEY (bucket_start, method, route, status_code)
+);
+
+CREATE TABLE metrics_handler_latency_rollup (
+    bucket_start TIMESTAMPTZ NOT NULL,
+    method TEXT NOT NULL,
+    route TEXT NOT NULL,
+    latency_bucket SMALLINT NOT NULL,
+    request_count BIGINT NOT NULL,
+    PRIMARY KEY (bucket_start, method, route, latency_bucket)
+);
+
+CREATE TABLE metrics_db_query_rollup (
+    bucket_start TIMESTAMPTZ NOT NULL,
+    query_name TEXT NOT NULL,
+    query_count BIGINT NOT NULL,
+    duration_sum_ms FLOAT8 NOT NULL,
+    duration_max_ms FLOAT8 NOT NULL,
+    PRIMARY KEY (bucket_start, query_name)
+);
+
+CREATE TABLE metrics_db_query_latency_rollup (
+    bucket_start TIMESTAMPTZ NOT NULL,
+    query_name TEXT NOT NULL,
+    latency_bucket SMALLINT NOT NULL,
+    query_count BIGINT NOT NULL,
+    PRIMARY KEY (bucket_start, query_name, latency_bucket)
+);
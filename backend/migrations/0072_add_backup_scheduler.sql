@@ -0,0 +1,25 @@
+ALTER TYPE audit_action ADD VALUE 'backup_triggered';
+ALTER TYPE audit_action ADD VALUE 'backup_restore_requested';
+
+-- Filled by the in-app backup scheduler
+ALTER TABLE db_backups
+    ADD COLUMN checksum_sha256 TEXT,
+    -- When pg_restore could read the whole archive
+    ADD COLUMN verified_at TIMESTAMPTZ,
+    -- When retention removed the dump from the bucket
+    ADD COLUMN expired_at TIMESTAMPTZ;
+
+-- Restores of a backup into the scratch database
+CREATE TABLE db_backup_restore (
+    id SERIAL PRIMARY KEY,
+    backup_id INT NOT NULL REFERENCES db_backups(id) ON DELETE CASCADE,
+    requested_by VARCHAR REFERENCES "user"(id) ON DELETE SET NULL,
+    -- Database name only, credentials stay in the environment
+    target_database TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    error_message TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_db_backup_restore_started_at ON db_backup_restore (started_at DESC);
@@ -0,0 +1,46 @@
+ALTER TYPE audit_action ADD VALUE 'job_retried';
+ALTER TYPE audit_target_type ADD VALUE 'job';
+
+CREATE TYPE job_kind AS ENUM (
+    'cleanup_expired_tokens',
+    'cleanup_notifications',
+    'replenish_sandbox_pool',
+    'recycle_sandbox',
+    'recalculate_feed_visibility'
+);
+
+CREATE TYPE job_status AS ENUM ('pending', 'running', 'succeeded', 'failed');
+
+CREATE TABLE job (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind job_kind NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    status job_status NOT NULL DEFAULT 'pending',
+    -- Only one pending or running job may hold a key, enqueueing it again is a no-op
+    dedup_key TEXT,
+    attempts INT NOT NULL DEFAULT 0,
+    max_attempts INT NOT NULL DEFAULT 5,
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Worker that claimed the job, a running job whose lock is too old is claimed again
+    locked_by TEXT,
+    locked_at TIMESTAMPTZ,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX idx_job_dedup_key ON job (dedup_key)
+    WHERE dedup_key IS NOT NULL AND status IN ('pending', 'running');
+CREATE INDEX idx_job_pending_run_at ON job (run_at) WHERE status = 'pending';
+CREATE INDEX idx_job_created_at ON job (created_at DESC);
+
+-- Cron schedules enqueue a job whenever next_run_at has passed
+CREATE TABLE job_schedule (
+    name TEXT PRIMARY KEY,
+    kind job_kind NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    cron TEXT NOT NULL,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_run_at TIMESTAMPTZ,
+    last_job_id UUID REFERENCES job(id) ON DELETE SET NULL
+);
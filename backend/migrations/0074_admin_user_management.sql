@@ -0,0 +1,5 @@
+ALTER TYPE audit_action ADD VALUE 'user_role_changed';
+ALTER TYPE audit_action ADD VALUE 'user_sessions_revoked';
+
+-- Access tokens issued before this are rejected, set when an admin signs the user out everywhere
+ALTER TABLE "user" ADD COLUMN sessions_revoked_at TIMESTAMPTZ;
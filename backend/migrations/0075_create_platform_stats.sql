@@ -0,0 +1,161 @@
+ALTER TYPE job_kind ADD VALUE 'refresh_platform_stats';
+
+-- Stopwatch rows are deleted once the stopwatch is stopped, starts are kept here for statistics
+CREATE TABLE platform_stopwatch_start (
+    stopwatch_id UUID PRIMARY KEY,
+    user_id VARCHAR NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_platform_stopwatch_start_started_at ON platform_stopwatch_start(started_at);
+CREATE INDEX idx_platform_stopwatch_start_user_id ON platform_stopwatch_start(user_id);
+
+CREATE OR REPLACE FUNCTION record_stopwatch_start()
+RETURNS TRIGGER AS $$
+BEGIN
+  INSERT INTO platform_stopwatch_start (stopwatch_id, user_id, started_at)
+  VALUES (NEW.id, NEW.user_id, NEW.created_at)
+  ON CONFLICT DO NOTHING;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_record_stopwatch_start
+AFTER INSERT ON stopwatch_session
+FOR EACH ROW
+EXECUTE FUNCTION record_stopwatch_start();
+
+INSERT INTO platform_stopwatch_start (stopwatch_id, user_id, started_at)
+SELECT id, user_id, created_at FROM stopwatch_session;
+
+-- Users active on a day (UTC). Refresh tokens and request metrics are pruned after a while,
+-- so activity is rolled up into this table instead of being derived again on every refresh.
+CREATE TABLE platform_active_user (
+    day DATE NOT NULL,
+    user_id VARCHAR NOT NULL,
+    PRIMARY KEY (day, user_id)
+);
+
+CREATE INDEX idx_platform_active_user_user_id ON platform_active_user(user_id);
+
+-- Record users active since the given time, returns how many new (day, user) pairs were added
+CREATE OR REPLACE FUNCTION rollup_platform_active_users(since TIMESTAMPTZ)
+RETURNS BIGINT AS $$
+  WITH activity (user_id, active_at) AS (
+      SELECT user_id, created_at FROM refresh_tokens WHERE created_at >= since
+      UNION ALL
+      SELECT user_id, last_used_at FROM refresh_tokens WHERE last_used_at >= since
+      UNION ALL
+      SELECT user_id, last_used_at FROM api_tokens WHERE last_used_at >= since
+      UNION ALL
+      SELECT user_id, created_at FROM metrics_handler
+      WHERE created_at >= since AND user_id IS NOT NULL
+      UNION ALL
+      SELECT user_id, created_at FROM session WHERE created_at >= since AND user_id IS NOT NULL
+      UNION ALL
+      SELECT user_id, started_at FROM platform_stopwatch_start WHERE started_at >= since
+      UNION ALL
+      SELECT user_id, created_at FROM feed_reaction WHERE created_at >= since
+  ),
+  inserted AS (
+      INSERT INTO platform_active_user (day, user_id)
+      SELECT DISTINCT (activity.active_at AT TIME ZONE 'UTC')::DATE, activity.user_id
+      FROM activity
+      JOIN "user" u ON u.id = activity.user_id
+      ON CONFLICT DO NOTHING
+      RETURNING 1
+  )
+  SELECT COUNT(*) FROM inserted;
+$$ LANGUAGE sql;
+
+SELECT rollup_platform_active_users('-infinity');
+
+-- Tracking and feed engagement per day (UTC), refreshed by the refresh_platform_stats job
+CREATE MATERIALIZED VIEW platform_daily_activity AS
+SELECT
+    day,
+    SUM(sessions)::BIGINT AS sessions,
+    SUM(tracked_hours)::FLOAT8 AS tracked_hours,
+    SUM(tracking_users)::BIGINT AS tracking_users,
+    SUM(stopwatch_starts)::BIGINT AS stopwatch_starts,
+    SUM(stopwatch_users)::BIGINT AS stopwatch_users,
+    SUM(feed_events)::BIGINT AS feed_events,
+    SUM(feed_reactions)::BIGINT AS feed_reactions,
+    SUM(reacting_users)::BIGINT AS reacting_users
+FROM (
+    SELECT
+        (start_time AT TIME ZONE 'UTC')::DATE AS day,
+        COUNT(*) AS sessions,
+        SUM(EXTRACT(EPOCH FROM end_time - start_time)) / 3600 AS tracked_hours,
+        COUNT(DISTINCT user_id) AS tracking_users,
+        0 AS stopwatch_starts,
+        0 AS stopwatch_users,
+        0 AS feed_events,
+        0 AS feed_reactions,
+        0 AS reacting_users
+    FROM session
+    WHERE end_time > start_time
+    GROUP BY 1
+    UNION ALL
+    SELECT (started_at AT TIME ZONE 'UTC')::DATE, 0, 0, 0, COUNT(*), COUNT(DISTINCT user_id), 0, 0, 0
+    FROM platform_stopwatch_start
+    GROUP BY 1
+    UNION ALL
+    SELECT (created_at AT TIME ZONE 'UTC')::DATE, 0, 0, 0, 0, 0, COUNT(*), 0, 0
+    FROM feed_event
+    GROUP BY 1
+    UNION ALL
+    SELECT (created_at AT TIME ZONE 'UTC')::DATE, 0, 0, 0, 0, 0, 0, COUNT(*), COUNT(DISTINCT user_id)
+    FROM feed_reaction
+    GROUP BY 1
+) activity
+GROUP BY day;
+
+CREATE UNIQUE INDEX idx_platform_daily_activity_day ON platform_daily_activity(day);
+
+-- New users per day (UTC) and how they signed up: the OAuth provider or passkey they added first,
+-- users without either are sandbox guests. created_at holds UTC without a time zone.
+CREATE MATERIALIZED VIEW platform_daily_signups AS
+SELECT
+    ((u.created_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC')::DATE AS day,
+    COALESCE(first_account.provider, 'guest') AS provider,
+    COUNT(*)::BIGINT AS signups
+FROM "user" u
+LEFT JOIN LATERAL (
+    SELECT accounts.provider
+    FROM (
+        SELECT a.provider::TEXT AS provider, a.created_at AT TIME ZONE 'UTC' AS created_at
+        FROM oauth_accounts a
+        WHERE a.user_id = u.id
+        UNION ALL
+        SELECT 'passkey', p.created_at
+        FROM passkey_credential p
+        WHERE p.user_id = u.id
+    ) accounts
+    ORDER BY accounts.created_at
+    LIMIT 1
+) first_account ON TRUE
+GROUP BY 1, 2;
+
+CREATE UNIQUE INDEX idx_platform_daily_signups_day_provider ON platform_daily_signups(day, provider);
+
+-- Sandbox guests are handed out from a pre-seeded pool, a guest converts once they track time themselves
+ALTER TABLE "user" ADD COLUMN guest_assigned_at TIMESTAMPTZ;
+ALTER TABLE sandbox_lifecycle ADD COLUMN converted_users INTEGER NOT NULL DEFAULT 0;
+
+CREATE OR REPLACE FUNCTION sandbox_converted_guests()
+RETURNS BIGINT AS $$
+  SELECT COUNT(*)
+  FROM "user" u
+  WHERE u.guest_assigned_at IS NOT NULL
+    AND (
+        EXISTS (
+            SELECT 1 FROM session s
+            WHERE s.user_id = u.id AND s.created_at > u.guest_assigned_at
+        )
+        OR EXISTS (
+            SELECT 1 FROM platform_stopwatch_start w
+            WHERE w.user_id = u.id AND w.started_at > u.guest_assigned_at
+        )
+    );
+$$ LANGUAGE sql;
@@ -0,0 +1,22 @@
+ALTER TYPE job_kind ADD VALUE 'publish_scheduled_releases';
+ALTER TYPE audit_action ADD VALUE 'release_scheduled';
+ALTER TYPE audit_action ADD VALUE 'release_schedule_cancelled';
+
+-- Who is notified once a release is published, the release itself stays public
+CREATE TYPE release_audience AS ENUM ('everyone', 'admins', 'beta');
+
+ALTER TABLE release
+    ADD COLUMN audience release_audience NOT NULL DEFAULT 'everyone',
+    -- Structured changelog sections, e.g. [{"kind": "added", "items": ["..."]}]
+    ADD COLUMN changelog JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN scheduled_at TIMESTAMPTZ,
+    ADD COLUMN scheduled_by VARCHAR REFERENCES "user"(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_release_scheduled_at ON release(scheduled_at)
+WHERE released = FALSE AND scheduled_at IS NOT NULL;
+
+ALTER TABLE "user" ADD COLUMN beta_opt_in BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Release announcements are withdrawn again when a release is unpublished
+CREATE INDEX idx_notification_release_id ON notification(((content->>'release_id')::UUID))
+WHERE notification_type = 'system:new_release';
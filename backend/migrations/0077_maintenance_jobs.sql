@@ -0,0 +1,6 @@
+-- Maintenance that used to run in ad-hoc background loops
+ALTER TYPE job_kind ADD VALUE 'cleanup_rate_limits';
+ALTER TYPE job_kind ADD VALUE 'roll_up_metrics';
+ALTER TYPE job_kind ADD VALUE 'erase_deleted_accounts';
+ALTER TYPE job_kind ADD VALUE 'run_scheduled_backup';
+ALTER TYPE job_kind ADD VALUE 'backfill_achievements';
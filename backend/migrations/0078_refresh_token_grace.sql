@@ -0,0 +1,5 @@
+-- A rotated token presented again shortly after rotation gets the same successor back,
+-- sealed with a key derived from the rotated token so only its holder can open it
+ALTER TABLE refresh_tokens
+    ADD COLUMN replaced_by UUID REFERENCES refresh_tokens(id) ON DELETE SET NULL,
+    ADD COLUMN successor_sealed BYTEA;
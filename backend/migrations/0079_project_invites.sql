@@ -0,0 +1,21 @@
+ALTER TYPE notification_type ADD VALUE 'project:invite';
+
+CREATE TYPE project_invite_status AS ENUM ('pending', 'accepted', 'declined', 'cancelled');
+
+-- Members join a project by accepting an invite, only friends can be invited
+CREATE TABLE project_invite (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_id UUID NOT NULL REFERENCES project(id) ON DELETE CASCADE,
+    invitee_id VARCHAR NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+    inviter_id VARCHAR REFERENCES "user"(id) ON DELETE SET NULL,
+    role project_member_role NOT NULL,
+    status project_invite_status NOT NULL DEFAULT 'pending',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    responded_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX idx_project_invite_pending ON project_invite(project_id, invitee_id)
+WHERE status = 'pending';
+
+CREATE INDEX idx_project_invite_invitee ON project_invite(invitee_id)
+WHERE status = 'pending';
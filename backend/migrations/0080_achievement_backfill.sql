@@ -0,0 +1,6 @@
+-- Rule catalog versions existing users were backfilled for, every version is backfilled once
+CREATE TABLE achievement_backfill (
+    catalog_version VARCHAR(64) PRIMARY KEY,
+    awarded_count INTEGER NOT NULL DEFAULT 0,
+    completed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,4 @@
+-- The audit log is paged on (created_at, id), entries written in the same instant
+-- would otherwise be skipped or repeated between pages
+DROP INDEX IF EXISTS idx_audit_log_created_at;
+CREATE INDEX idx_audit_log_created_at ON audit_log(created_at DESC, id DESC);
@@ -0,0 +1,29 @@
+-- no-transaction
+-- Backfill the route of requests recorded before 0071 in batches, every batch is committed
+-- on its own so the table isn't locked by one update over all of its rows
+DO $$
+DECLARE
+    updated BIGINT;
+BEGIN
+    LOOP
+        UPDATE metrics_handler
+        SET route = regexp_replace(
+            regexp_replace(
+                split_part(path, '?', 1),
+                '/[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}(?=/|$)',
+                '/{id}',
+                'g'
+            ),
+            '/[0-9]+(?=/|$)',
+            '/{id}',
+            'g'
+        )
+        WHERE id IN (SELECT id FROM metrics_handler WHERE route IS NULL LIMIT 10000);
+
+        GET DIAGNOSTICS updated = ROW_COUNT;
+        EXIT WHEN updated = 0;
+        COMMIT;
+    END LOOP;
+
+    ALTER TABLE metrics_handler ALTER COLUMN route SET NOT NULL;
+END $$;
@@ -0,0 +1,3 @@
+-- A rotated token replayed within the grace window is exchanged for a new token of its family,
+-- the successor is no longer kept around for it
+ALTER TABLE refresh_tokens DROP COLUMN successor_sealed;
@@ -0,0 +1 @@
+ALTER TYPE job_kind ADD VALUE 'cleanup_leaderboard_cache';
@@ -0,0 +1,45 @@
+/// Human readable device label from a user agent, e.g. "Firefox on Linux"
+///
+/// Only recognizes the common browsers and platforms, anything else is reported as unknown.
+pub fn describe_user_agent(user_agent: Option<&str>) -> String {
+    let Some(user_agent) = user_agent else {
+        return "Unknown device".to_string();
+    };
+
+    // Order matters: Edge and Opera also announce Chrome, Chrome also announces Safari
+    let browser = if user_agent.contains("Edg/") {
+        Some("Edge")
+    } else if user_agent.contains("OPR/") {
+        Some("Opera")
+    } else if user_agent.contains("Firefox/") {
+        Some("Firefox")
+    } else if user_agent.contains("Chrome/") {
+        Some("Chrome")
+    } else if user_agent.contains("Safari/") {
+        Some("Safari")
+    } else {
+        None
+    };
+
+    // Android and iOS user agents also announce Linux and Mac OS X
+    let platform = if user_agent.contains("Android") {
+        Some("Android")
+    } else if user_agent.contains("iPhone") || user_agent.contains("iPad") {
+        Some("iOS")
+    } else if user_agent.contains("Windows") {
+        Some("Windows")
+    } else if user_agent.contains("Mac OS X") {
+        Some("macOS")
+    } else if user_agent.contains("Linux") {
+        Some("Linux")
+    } else {
+        None
+    };
+
+    match (browser, platform) {
+        (Some(browser), Some(platform)) => format!("{} on {}", browser, platform),
+        (Some(browser), None) => browser.to_string(),
+        (None, Some(platform)) => platform.to_string(),
+        (None, None) => "Unknown device".to_string(),
+    }
+}
@@ -1,10 +1,9 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
-use once_cell::sync::Lazy;
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, Header, TokenData, Validation};
 use serde::{Deserialize, Serialize};
 
-use crate::router::clerk::UserRole;
+use crate::{auth::keys::current_key_set, router::clerk::UserRole};
 
 /// JWT Claims structure
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -19,31 +18,6 @@ pub struct Claims {
     pub env: String,  // Environment
 }
 
-// Load RSA keys on application startup
-// Keys must be provided via environment variables (loaded from .env.keys file):
-// - JWT_PRIVATE_KEY: RSA private key in PEM format
-// - JWT_PUBLIC_KEY: RSA public key in PEM format
-//
-// Note: These are in a separate .env.keys file because the envy crate
-// (used for typed config) doesn't support multiline environment variables.
-static ENCODING_KEY: Lazy<EncodingKey> = Lazy::new(|| {
-    let private_key = std::env::var("JWT_PRIVATE_KEY")
-        .expect("JWT_PRIVATE_KEY environment variable must be set in .env.keys file")
-        .into_bytes();
-
-    EncodingKey::from_rsa_pem(&private_key)
-        .unwrap_or_else(|e| panic!("Invalid private key format: {}", e))
-});
-
-static DECODING_KEY: Lazy<DecodingKey> = Lazy::new(|| {
-    let public_key = std::env::var("JWT_PUBLIC_KEY")
-        .expect("JWT_PUBLIC_KEY environment variable must be set in .env.keys file")
-        .into_bytes();
-
-    DecodingKey::from_rsa_pem(&public_key)
-        .unwrap_or_else(|e| panic!("Invalid public key format: {}", e))
-});
-
 /// Generate a JWT access token
 ///
 /// # Arguments
@@ -53,7 +27,7 @@ static DECODING_KEY: Lazy<DecodingKey> = Lazy::new(|| {
 /// * `environment` - Environment identifier
 ///
 /// # Returns
-/// JWT token string valid for 15 minutes
+/// JWT token string valid for 15 minutes, signed with the active key and carrying its `kid`
 pub fn generate_access_token(
     user_id: &str,
     role: UserRole,
@@ -73,8 +47,12 @@ pub fn generate_access_token(
         env: environment,
     };
 
-    let header = Header::new(Algorithm::RS256);
-    encode(&header, &claims, &ENCODING_KEY).context("Failed to generate access token")
+    let key_set = current_key_set();
+    let (kid, encoding_key) = key_set.signing_key()?;
+
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(kid.to_string());
+    encode(&header, &claims, encoding_key).context("Failed to generate access token")
 }
 
 /// Validate and decode a JWT access token
@@ -90,8 +68,23 @@ pub fn validate_access_token(token: &str, expected_env: &str) -> Result<Claims>
     validation.set_issuer(&["nowaster-api"]);
     validation.set_audience(&["nowaster-web"]);
 
-    let token_data =
-        decode::<Claims>(token, &DECODING_KEY, &validation).context("Invalid or expired token")?;
+    let header = decode_header(token).context("Invalid or expired token")?;
+    let key_set = current_key_set();
+
+    let token_data: TokenData<Claims> = match header.kid {
+        Some(kid) => {
+            let key = key_set
+                .find(&kid)
+                .with_context(|| format!("Unknown signing key '{}'", kid))?;
+            decode(token, key.decoding_key(), &validation).context("Invalid or expired token")?
+        }
+        // Tokens issued before kid headers were added, try every key
+        None => key_set
+            .keys()
+            .iter()
+            .find_map(|key| decode(token, key.decoding_key(), &validation).ok())
+            .context("Invalid or expired token")?,
+    };
 
     // Validate environment matches
     if token_data.claims.env != expected_env {
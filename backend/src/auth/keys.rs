@@ -0,0 +1,372 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{
+    jwk::{
+        AlgorithmParameters, CommonParameters, Jwk, JwkSet, KeyAlgorithm, PublicKeyUse,
+        RSAKeyParameters, RSAKeyType,
+    },
+    DecodingKey, EncodingKey,
+};
+use once_cell::sync::Lazy;
+use rsa::{
+    pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey, EncodeRsaPrivateKey},
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    traits::PublicKeyParts,
+    RsaPrivateKey, RsaPublicKey,
+};
+use std::{
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use crate::{
+    auth::crypto::sha256_hash,
+    config::{
+        database::Database,
+        env::{JwtKeysConfig, JwtKeysSource},
+    },
+    repository::auth::jwt_keys::JwtKeyRepository,
+};
+
+// How often the key source is checked for rollovers
+const RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Where the JWT keys are loaded from, selected with `JWT_KEYS_SOURCE`:
+/// - `env` (default): a single key pair from `JWT_PRIVATE_KEY` / `JWT_PUBLIC_KEY`
+/// - `directory`: PEM files in `JWT_KEYS_DIR`, see [`JwtKeySource::load_directory`]
+/// - `database`: rows of the `jwt_signing_key` table, private keys are encrypted PKCS#8
+///   PEM that `JWT_KEYS_ENCRYPTION_KEY` decrypts
+#[derive(Clone)]
+pub enum JwtKeySource {
+    Environment,
+    Directory(PathBuf),
+    Database {
+        repo: JwtKeyRepository,
+        encryption_key: String,
+    },
+}
+
+/// Verification key of the key set, published in the JWKS
+pub struct JwtKey {
+    pub kid: String,
+    decoding_key: DecodingKey,
+    jwk: Jwk,
+}
+
+struct SigningKey {
+    kid: String,
+    encoding_key: EncodingKey,
+}
+
+/// Keys that verify access tokens plus the one key that signs new tokens
+#[derive(Default)]
+pub struct JwtKeySet {
+    signing_key: Option<SigningKey>,
+    keys: Vec<JwtKey>,
+}
+
+static KEY_SET: Lazy<RwLock<Arc<JwtKeySet>>> = Lazy::new(Default::default);
+
+/// Key set currently in use
+pub fn current_key_set() -> Arc<JwtKeySet> {
+    KEY_SET
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+fn install_key_set(key_set: JwtKeySet) {
+    *KEY_SET
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(key_set);
+}
+
+/// Load the key set once, fails when there is no key to sign tokens with
+pub async fn init_key_set(source: &JwtKeySource) -> Result<()> {
+    let key_set = source.load().await?;
+    tracing::info!(
+        "Loaded {} JWT keys, signing with '{}'",
+        key_set.keys.len(),
+        key_set.signing_kid().unwrap_or_default()
+    );
+    install_key_set(key_set);
+    Ok(())
+}
+
+/// Periodically reload the key set so keys can be rolled over without a restart.
+/// A broken key source keeps the previous key set in place.
+pub fn spawn_key_reloader(source: JwtKeySource) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            match source.load().await {
+                Ok(key_set) => {
+                    if key_set.fingerprint() != current_key_set().fingerprint() {
+                        tracing::info!(
+                            "JWT key set changed, signing with '{}' and verifying {:?}",
+                            key_set.signing_kid().unwrap_or_default(),
+                            key_set.kids()
+                        );
+                    }
+                    install_key_set(key_set);
+                }
+                Err(e) => tracing::error!("Failed to reload JWT keys: {:#}", e),
+            }
+        }
+    });
+}
+
+impl JwtKeySource {
+    pub fn from_config(config: &JwtKeysConfig, db: &Arc<Database>) -> Result<Self> {
+        match config.source {
+            JwtKeysSource::Env => Ok(Self::Environment),
+            JwtKeysSource::Directory => {
+                let dir = config
+                    .dir
+                    .as_ref()
+                    .context("JWT_KEYS_DIR must be set when JWT_KEYS_SOURCE=directory")?;
+                Ok(Self::Directory(PathBuf::from(dir)))
+            }
+            JwtKeysSource::Database => {
+                let encryption_key = config
+                    .encryption_key
+                    .clone()
+                    .context("JWT_KEYS_ENCRYPTION_KEY must be set when JWT_KEYS_SOURCE=database")?;
+                Ok(Self::Database {
+                    repo: JwtKeyRepository::new(db),
+                    encryption_key,
+                })
+            }
+        }
+    }
+
+    pub async fn load(&self) -> Result<JwtKeySet> {
+        let key_set = match self {
+            Self::Environment => Self::load_environment()?,
+            Self::Directory(dir) => Self::load_directory(dir).await?,
+            Self::Database {
+                repo,
+                encryption_key,
+            } => Self::load_database(repo, encryption_key).await?,
+        };
+
+        if key_set.signing_key.is_none() {
+            anyhow::bail!("No active JWT signing key");
+        }
+
+        Ok(key_set)
+    }
+
+    // Keys must be provided via environment variables (loaded from .env.keys file).
+    // These are in a separate .env.keys file because the envy crate
+    // (used for typed config) doesn't support multiline environment variables.
+    fn load_environment() -> Result<JwtKeySet> {
+        let private_key = std::env::var("JWT_PRIVATE_KEY")
+            .context("JWT_PRIVATE_KEY environment variable must be set in .env.keys file")?;
+        let public_key = std::env::var("JWT_PUBLIC_KEY")
+            .context("JWT_PUBLIC_KEY environment variable must be set in .env.keys file")?;
+
+        // The kid is derived from the key, so every instance agrees on it
+        let kid = derive_kid(&parse_public_key(&public_key)?);
+
+        let mut key_set = JwtKeySet::default();
+        key_set.add_key_pair(&kid, &private_key, true)?;
+        Ok(key_set)
+    }
+
+    /// Directory layout, the file name without extension is the kid:
+    /// - `<kid>.key.pem`: RSA private key, can sign and verifies
+    /// - `<kid>.pub.pem`: RSA public key, only verifies (new keys before they go active, retiring keys)
+    /// - `active_kid`: kid of the private key that signs new tokens
+    async fn load_directory(dir: &PathBuf) -> Result<JwtKeySet> {
+        let active_kid = tokio::fs::read_to_string(dir.join("active_kid"))
+            .await
+            .with_context(|| format!("Missing active_kid in {}", dir.display()))?;
+        let active_kid = active_kid.trim();
+
+        let mut private_keys = Vec::new();
+        let mut public_keys = Vec::new();
+
+        let mut entries = tokio::fs::read_dir(dir)
+            .await
+            .with_context(|| format!("Failed to read JWT key directory {}", dir.display()))?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+
+            if let Some(kid) = file_name.strip_suffix(".key.pem") {
+                let pem = tokio::fs::read_to_string(entry.path()).await?;
+                private_keys.push((kid.to_string(), pem));
+            } else if let Some(kid) = file_name.strip_suffix(".pub.pem") {
+                let pem = tokio::fs::read_to_string(entry.path()).await?;
+                public_keys.push((kid.to_string(), pem));
+            }
+        }
+
+        let mut key_set = JwtKeySet::default();
+        for (kid, pem) in &private_keys {
+            key_set
+                .add_key_pair(kid, pem, kid == active_kid)
+                .with_context(|| format!("Invalid JWT private key '{}'", kid))?;
+        }
+        for (kid, pem) in &public_keys {
+            key_set
+                .add_public_key(kid, pem)
+                .with_context(|| format!("Invalid JWT public key '{}'", kid))?;
+        }
+
+        Ok(key_set)
+    }
+
+    /// Private keys are never stored in plain text, a row with an unencrypted key is rejected
+    async fn load_database(repo: &JwtKeyRepository, encryption_key: &str) -> Result<JwtKeySet> {
+        let mut key_set = JwtKeySet::default();
+
+        for key in repo.list_usable_keys().await? {
+            match &key.private_key_pem {
+                Some(private_key) => {
+                    RsaPrivateKey::from_pkcs8_encrypted_pem(private_key, encryption_key.as_bytes())
+                        .context("Private key is not encrypted with JWT_KEYS_ENCRYPTION_KEY")
+                        .and_then(|private_key| {
+                            key_set.add_private_key(&key.kid, &private_key, key.active)
+                        })
+                }
+                None => key_set.add_public_key(&key.kid, &key.public_key_pem),
+            }
+            .with_context(|| format!("Invalid JWT key '{}'", key.kid))?;
+        }
+
+        Ok(key_set)
+    }
+}
+
+impl JwtKeySet {
+    /// Add a key pair, the public key is derived from the private key
+    pub fn add_key_pair(&mut self, kid: &str, private_key_pem: &str, active: bool) -> Result<()> {
+        self.add_private_key(kid, &parse_private_key(private_key_pem)?, active)
+    }
+
+    fn add_private_key(
+        &mut self,
+        kid: &str,
+        private_key: &RsaPrivateKey,
+        active: bool,
+    ) -> Result<()> {
+        self.add_verification_key(kid, &private_key.to_public_key())?;
+
+        if active {
+            let der = private_key
+                .to_pkcs1_der()
+                .context("Invalid RSA private key")?;
+            let encoding_key = EncodingKey::from_rsa_der(der.as_bytes());
+            self.signing_key = Some(SigningKey {
+                kid: kid.to_string(),
+                encoding_key,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Add a key that only verifies tokens
+    pub fn add_public_key(&mut self, kid: &str, public_key_pem: &str) -> Result<()> {
+        self.add_verification_key(kid, &parse_public_key(public_key_pem)?)
+    }
+
+    fn add_verification_key(&mut self, kid: &str, public_key: &RsaPublicKey) -> Result<()> {
+        // A key pair and a public key with the same kid are the same key
+        if self.find(kid).is_some() {
+            return Ok(());
+        }
+
+        let n = URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be());
+        let e = URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be());
+
+        let decoding_key = DecodingKey::from_rsa_components(&n, &e)?;
+        let jwk = Jwk {
+            common: CommonParameters {
+                public_key_use: Some(PublicKeyUse::Signature),
+                key_algorithm: Some(KeyAlgorithm::RS256),
+                key_id: Some(kid.to_string()),
+                ..Default::default()
+            },
+            algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n,
+                e,
+            }),
+        };
+
+        self.keys.push(JwtKey {
+            kid: kid.to_string(),
+            decoding_key,
+            jwk,
+        });
+        Ok(())
+    }
+
+    /// Key and kid to sign new tokens with
+    pub fn signing_key(&self) -> Result<(&str, &EncodingKey)> {
+        self.signing_key
+            .as_ref()
+            .map(|key| (key.kid.as_str(), &key.encoding_key))
+            .context("No active JWT signing key")
+    }
+
+    pub fn signing_kid(&self) -> Option<&str> {
+        self.signing_key.as_ref().map(|key| key.kid.as_str())
+    }
+
+    pub fn find(&self, kid: &str) -> Option<&JwtKey> {
+        self.keys.iter().find(|key| key.kid == kid)
+    }
+
+    pub fn keys(&self) -> &[JwtKey] {
+        &self.keys
+    }
+
+    pub fn kids(&self) -> Vec<&str> {
+        self.keys.iter().map(|key| key.kid.as_str()).collect()
+    }
+
+    /// Public keys in JWKS format for other services verifying our tokens
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.keys.iter().map(|key| key.jwk.clone()).collect(),
+        }
+    }
+
+    fn fingerprint(&self) -> (Option<&str>, Vec<&str>) {
+        let mut kids = self.kids();
+        kids.sort_unstable();
+        (self.signing_kid(), kids)
+    }
+}
+
+impl JwtKey {
+    pub fn decoding_key(&self) -> &DecodingKey {
+        &self.decoding_key
+    }
+}
+
+fn parse_public_key(pem: &str) -> Result<RsaPublicKey> {
+    RsaPublicKey::from_public_key_pem(pem)
+        .or_else(|_| RsaPublicKey::from_pkcs1_pem(pem))
+        .context("Invalid RSA public key")
+}
+
+fn parse_private_key(pem: &str) -> Result<RsaPrivateKey> {
+    RsaPrivateKey::from_pkcs8_pem(pem)
+        .or_else(|_| RsaPrivateKey::from_pkcs1_pem(pem))
+        .context("Invalid RSA private key")
+}
+
+fn derive_kid(public_key: &RsaPublicKey) -> String {
+    let n = URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be());
+    let e = URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be());
+    sha256_hash(&format!("{}.{}", n, e))[..16].to_string()
+}
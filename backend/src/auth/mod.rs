@@ -1,5 +1,7 @@
 pub mod crypto;
+pub mod device;
 pub mod jwt;
+pub mod keys;
 pub mod providers;
 
 // Re-export commonly used items
@@ -1,7 +1,7 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
 
-use crate::config::env::DiscordOAuthConfig;
+use crate::{config::env::DiscordOAuthConfig, telemetry::PropagateTraceContext};
 
 use super::{OAuthConfig, OAuthProvider, UserProfile};
 
@@ -19,6 +19,8 @@ struct DiscordUserInfo {
     email: Option<String>,
     global_name: Option<String>,
     avatar: Option<String>,
+    #[serde(default)]
+    verified: bool,
 }
 
 impl DiscordProvider {
@@ -69,6 +71,7 @@ impl OAuthProvider for DiscordProvider {
             .post(&config.token_url)
             .header("Content-Type", "application/x-www-form-urlencoded")
             .form(&params)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| {
@@ -112,6 +115,7 @@ impl OAuthProvider for DiscordProvider {
         let response = client
             .get("https://discord.com/api/users/@me")
             .bearer_auth(access_token)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to fetch user profile")?;
@@ -139,6 +143,7 @@ impl OAuthProvider for DiscordProvider {
         Ok(UserProfile {
             provider_user_id: user_info.id,
             email,
+            email_verified: user_info.verified,
             username: Some(user_info.username.clone()),
             display_name: user_info.global_name.or(Some(user_info.username)),
             avatar_url,
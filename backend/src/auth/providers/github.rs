@@ -1,7 +1,7 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
 
-use crate::config::env::GitHubOAuthConfig;
+use crate::{config::env::GitHubOAuthConfig, telemetry::PropagateTraceContext};
 
 use super::{OAuthConfig, OAuthProvider, UserProfile};
 
@@ -74,6 +74,7 @@ impl OAuthProvider for GitHubProvider {
             .post(&config.token_url)
             .header("Accept", "application/json")
             .form(&params)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| {
@@ -120,6 +121,7 @@ impl OAuthProvider for GitHubProvider {
             .header("Accept", "application/json")
             .header("User-Agent", "Nowaster-Auth")
             .bearer_auth(access_token)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to fetch user profile")?;
@@ -134,37 +136,41 @@ impl OAuthProvider for GitHubProvider {
             .await
             .context("Failed to parse user info")?;
 
-        // Fetch email if not public
-        let email = if let Some(email) = user_info.email {
-            email
-        } else {
-            // Fetch from emails endpoint
-            let emails_response = client
-                .get("https://api.github.com/user/emails")
-                .header("Accept", "application/json")
-                .header("User-Agent", "Nowaster-Auth")
-                .bearer_auth(access_token)
-                .send()
-                .await
-                .context("Failed to fetch user emails")?;
-
-            let emails: Vec<GitHubEmail> = emails_response
-                .json()
-                .await
-                .context("Failed to parse emails")?;
-
-            // Get primary verified email
-            emails
-                .iter()
-                .find(|e| e.primary && e.verified)
-                .or_else(|| emails.first())
-                .map(|e| e.email.clone())
-                .context("No email found for GitHub user")?
-        };
+        // The public email does not say whether it is verified, so always check the emails endpoint
+        let emails_response = client
+            .get("https://api.github.com/user/emails")
+            .header("Accept", "application/json")
+            .header("User-Agent", "Nowaster-Auth")
+            .bearer_auth(access_token)
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to fetch user emails")?;
+
+        let emails: Vec<GitHubEmail> = emails_response
+            .json()
+            .await
+            .context("Failed to parse emails")?;
+
+        // Prefer the primary verified email, then the public one, then any
+        let (email, email_verified) = emails
+            .iter()
+            .find(|e| e.primary && e.verified)
+            .or_else(|| {
+                user_info
+                    .email
+                    .as_ref()
+                    .and_then(|public| emails.iter().find(|e| &e.email == public))
+            })
+            .or_else(|| emails.first())
+            .map(|e| (e.email.clone(), e.verified))
+            .or_else(|| user_info.email.clone().map(|email| (email, false)))
+            .context("No email found for GitHub user")?;
 
         Ok(UserProfile {
             provider_user_id: user_info.id.to_string(),
             email,
+            email_verified,
             username: Some(user_info.login),
             display_name: user_info.name,
             avatar_url: user_info.avatar_url,
@@ -1,7 +1,7 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
 
-use crate::config::env::GoogleOAuthConfig;
+use crate::{config::env::GoogleOAuthConfig, telemetry::PropagateTraceContext};
 
 use super::{OAuthConfig, OAuthProvider, UserProfile};
 
@@ -72,6 +72,7 @@ impl OAuthProvider for GoogleProvider {
         let response = client
             .post(&config.token_url)
             .form(&params)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| {
@@ -115,6 +116,7 @@ impl OAuthProvider for GoogleProvider {
         let response = client
             .get("https://www.googleapis.com/oauth2/v2/userinfo")
             .bearer_auth(access_token)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to fetch user profile")?;
@@ -144,6 +146,7 @@ impl OAuthProvider for GoogleProvider {
         Ok(UserProfile {
             provider_user_id: user_info.id,
             email: email.clone(),
+            email_verified: user_info.verified_email,
             username: email.split('@').next().map(|s| s.to_string()),
             display_name: user_info.name,
             avatar_url: user_info.picture,
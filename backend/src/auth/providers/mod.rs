@@ -1,6 +1,7 @@
 pub mod discord;
 pub mod github;
 pub mod google;
+pub mod oidc;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -20,6 +21,8 @@ pub struct OAuthConfig {
 pub struct UserProfile {
     pub provider_user_id: String,
     pub email: String,
+    // only verified emails may be used to link the login to an existing user
+    pub email_verified: bool,
     pub username: Option<String>,
     pub display_name: Option<String>,
     pub avatar_url: Option<String>,
@@ -0,0 +1,319 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::RwLock;
+
+use crate::{
+    auth::crypto::generate_random_hex, config::env::OidcProviderConfig,
+    telemetry::PropagateTraceContext,
+};
+
+use super::UserProfile;
+
+// Discovery documents and keys rarely change, keys are refetched early when a kid is unknown
+const METADATA_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Generic OpenID Connect provider configured by its issuer URL
+pub struct OidcProvider<'a> {
+    config: &'a OidcProviderConfig,
+}
+
+/// PKCE verifier and nonce of a single login attempt, kept in a cookie until the callback
+pub struct OidcAuthRequest {
+    pub code_verifier: String,
+    pub nonce: String,
+}
+
+#[derive(Deserialize)]
+struct OidcDiscovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    userinfo_endpoint: Option<String>,
+}
+
+struct OidcMetadata {
+    discovery: OidcDiscovery,
+    jwks: JwkSet,
+    fetched_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct OidcTokenResponse {
+    access_token: String,
+    id_token: String,
+}
+
+// Keyed by issuer URL, shared by every request
+static METADATA: Lazy<RwLock<HashMap<String, Arc<OidcMetadata>>>> = Lazy::new(Default::default);
+
+impl OidcAuthRequest {
+    pub fn generate() -> Self {
+        Self {
+            code_verifier: generate_random_hex(32),
+            nonce: generate_random_hex(16),
+        }
+    }
+
+    /// S256 code challenge sent with the authorization request
+    pub fn code_challenge(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.code_verifier.as_bytes()))
+    }
+
+    pub fn to_cookie_value(&self) -> String {
+        format!("{}.{}", self.code_verifier, self.nonce)
+    }
+
+    pub fn from_cookie_value(value: &str) -> Option<Self> {
+        let (code_verifier, nonce) = value.split_once('.')?;
+        Some(Self {
+            code_verifier: code_verifier.to_string(),
+            nonce: nonce.to_string(),
+        })
+    }
+}
+
+impl<'a> OidcProvider<'a> {
+    pub fn new(config: &'a OidcProviderConfig) -> Self {
+        Self { config }
+    }
+
+    pub async fn build_authorization_url(
+        &self,
+        state: &str,
+        request: &OidcAuthRequest,
+    ) -> Result<String> {
+        let metadata = self.metadata(false).await?;
+        let scope = self.config.scopes.join(" ");
+
+        Ok(format!(
+            "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&state={}&nonce={}&code_challenge={}&code_challenge_method=S256",
+            metadata.discovery.authorization_endpoint,
+            urlencoding::encode(&self.config.client_id),
+            urlencoding::encode(&self.config.redirect_uri),
+            urlencoding::encode(&scope),
+            urlencoding::encode(state),
+            urlencoding::encode(&request.nonce),
+            request.code_challenge(),
+        ))
+    }
+
+    /// Exchange the code, validate the ID token and map its claims to a profile
+    pub async fn fetch_user_profile(
+        &self,
+        code: &str,
+        request: &OidcAuthRequest,
+    ) -> Result<UserProfile> {
+        let metadata = self.metadata(false).await?;
+        let tokens = self.exchange_code(&metadata, code, request).await?;
+
+        let mut claims = self.validate_id_token(&tokens.id_token).await?;
+        if claims.get("nonce").and_then(Value::as_str) != Some(request.nonce.as_str()) {
+            anyhow::bail!("ID token nonce mismatch");
+        }
+
+        // Some providers only put the profile claims in the userinfo response
+        if claims.get(&self.config.email_claim).is_none() {
+            if let Some(userinfo_endpoint) = &metadata.discovery.userinfo_endpoint {
+                let userinfo =
+                    Self::fetch_userinfo(userinfo_endpoint, &tokens.access_token).await?;
+                if userinfo.get("sub") != claims.get("sub") {
+                    anyhow::bail!("Userinfo subject does not match the ID token");
+                }
+                for (key, value) in userinfo {
+                    claims.entry(key).or_insert(value);
+                }
+            }
+        }
+
+        self.map_claims(&claims)
+    }
+
+    async fn exchange_code(
+        &self,
+        metadata: &OidcMetadata,
+        code: &str,
+        request: &OidcAuthRequest,
+    ) -> Result<OidcTokenResponse> {
+        let mut params = vec![
+            ("client_id", self.config.client_id.as_str()),
+            ("code", code),
+            ("grant_type", "authorization_code"),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("code_verifier", request.code_verifier.as_str()),
+        ];
+        if let Some(client_secret) = &self.config.client_secret {
+            params.push(("client_secret", client_secret.as_str()));
+        }
+
+        let response = reqwest::Client::new()
+            .post(&metadata.discovery.token_endpoint)
+            .header("Accept", "application/json")
+            .form(&params)
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to exchange code")?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Token exchange failed: {}", error_text);
+        }
+
+        response
+            .json()
+            .await
+            .context("Failed to parse token response")
+    }
+
+    async fn validate_id_token(&self, id_token: &str) -> Result<Map<String, Value>> {
+        let header = decode_header(id_token).context("Invalid ID token")?;
+        if !matches!(
+            header.alg,
+            Algorithm::RS256
+                | Algorithm::RS384
+                | Algorithm::RS512
+                | Algorithm::PS256
+                | Algorithm::PS384
+                | Algorithm::PS512
+                | Algorithm::ES256
+                | Algorithm::ES384
+        ) {
+            anyhow::bail!("Unsupported ID token algorithm {:?}", header.alg);
+        }
+
+        // An unknown kid usually means the provider rotated its keys
+        let mut metadata = self.metadata(false).await?;
+        if Self::find_key(&metadata.jwks, header.kid.as_deref()).is_none() {
+            metadata = self.metadata(true).await?;
+        }
+        let jwk = Self::find_key(&metadata.jwks, header.kid.as_deref())
+            .context("ID token signed with an unknown key")?;
+        let key = DecodingKey::from_jwk(jwk).context("Unsupported JWK")?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.discovery.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+
+        let token_data = decode::<Map<String, Value>>(id_token, &key, &validation)
+            .context("Invalid ID token")?;
+        Ok(token_data.claims)
+    }
+
+    fn find_key<'k>(jwks: &'k JwkSet, kid: Option<&str>) -> Option<&'k jsonwebtoken::jwk::Jwk> {
+        match kid {
+            Some(kid) => jwks.find(kid),
+            // Without a kid the provider must have exactly one key
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None,
+        }
+    }
+
+    async fn fetch_userinfo(endpoint: &str, access_token: &str) -> Result<Map<String, Value>> {
+        let response = reqwest::Client::new()
+            .get(endpoint)
+            .header("Accept", "application/json")
+            .bearer_auth(access_token)
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to fetch userinfo")?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to fetch userinfo: {}", error_text);
+        }
+
+        response.json().await.context("Failed to parse userinfo")
+    }
+
+    fn map_claims(&self, claims: &Map<String, Value>) -> Result<UserProfile> {
+        let claim = |name: &str| claims.get(name).and_then(Value::as_str).map(str::to_string);
+
+        let provider_user_id = claim("sub").context("ID token has no subject")?;
+        let email = claim(&self.config.email_claim).with_context(|| {
+            format!(
+                "{} user has no '{}' claim",
+                self.config.name, self.config.email_claim
+            )
+        })?;
+
+        Ok(UserProfile {
+            provider_user_id,
+            email_verified: claims
+                .get("email_verified")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            username: claim(&self.config.username_claim)
+                .or_else(|| email.split('@').next().map(|s| s.to_string())),
+            display_name: claim(&self.config.name_claim),
+            avatar_url: claim(&self.config.avatar_claim),
+            email,
+        })
+    }
+
+    async fn metadata(&self, force_refresh: bool) -> Result<Arc<OidcMetadata>> {
+        let issuer_url = self.config.issuer_url.trim_end_matches('/');
+
+        if !force_refresh {
+            if let Some(metadata) = METADATA.read().await.get(issuer_url) {
+                if metadata.fetched_at.elapsed() < METADATA_TTL {
+                    return Ok(Arc::clone(metadata));
+                }
+            }
+        }
+
+        let client = reqwest::Client::new();
+        let discovery: OidcDiscovery = client
+            .get(format!("{}/.well-known/openid-configuration", issuer_url))
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to fetch OIDC discovery document")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse OIDC discovery document")?;
+
+        if discovery.issuer.trim_end_matches('/') != issuer_url {
+            anyhow::bail!(
+                "OIDC issuer mismatch: expected '{}', got '{}'",
+                issuer_url,
+                discovery.issuer
+            );
+        }
+
+        let jwks: JwkSet = client
+            .get(&discovery.jwks_uri)
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to fetch OIDC JWKS")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse OIDC JWKS")?;
+
+        let metadata = Arc::new(OidcMetadata {
+            discovery,
+            jwks,
+            fetched_at: Instant::now(),
+        });
+        METADATA
+            .write()
+            .await
+            .insert(issuer_url.to_string(), Arc::clone(&metadata));
+
+        Ok(metadata)
+    }
+}
@@ -1,4 +1,6 @@
-use serde::Deserialize;
+use ipnetwork::IpNetwork;
+use serde::{Deserialize, Deserializer};
+use std::net::IpAddr;
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "kebab-case")]
@@ -28,6 +30,21 @@ pub struct ServerConfig {
     pub address: String,
     #[serde(rename = "app_env")]
     pub app_env: AppEnvironment,
+    // Reverse proxies whose forwarding headers are believed, addresses or CIDR ranges
+    #[serde(
+        rename = "trusted_proxies",
+        default,
+        deserialize_with = "deserialize_ip_networks"
+    )]
+    pub trusted_proxies: Vec<IpNetwork>,
+}
+
+impl ServerConfig {
+    pub fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies
+            .iter()
+            .any(|network| network.contains(ip))
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -66,6 +83,91 @@ pub struct DiscordOAuthConfig {
     pub redirect_uri: String,
 }
 
+/// Generic OpenID Connect provider, e.g. a self-hosted Keycloak.
+/// Providers are enabled with `OIDC_PROVIDERS=keycloak,corp`, each one is configured by
+/// `OIDC_<NAME>_*` variables, e.g. `OIDC_KEYCLOAK_ISSUER_URL`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct OidcProviderConfig {
+    #[serde(skip)]
+    pub name: String,
+    pub display_name: Option<String>,
+    pub issuer_url: String,
+    pub client_id: String,
+    // public clients only rely on PKCE
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: Vec<String>,
+    #[serde(default = "default_oidc_username_claim")]
+    pub username_claim: String,
+    #[serde(default = "default_oidc_email_claim")]
+    pub email_claim: String,
+    #[serde(default = "default_oidc_name_claim")]
+    pub name_claim: String,
+    #[serde(default = "default_oidc_avatar_claim")]
+    pub avatar_claim: String,
+}
+
+fn default_oidc_scopes() -> Vec<String> {
+    vec![
+        "openid".to_string(),
+        "profile".to_string(),
+        "email".to_string(),
+    ]
+}
+
+fn default_oidc_username_claim() -> String {
+    "preferred_username".to_string()
+}
+
+fn default_oidc_email_claim() -> String {
+    "email".to_string()
+}
+
+fn default_oidc_name_claim() -> String {
+    "name".to_string()
+}
+
+fn default_oidc_avatar_claim() -> String {
+    "picture".to_string()
+}
+
+// Names of the built-in providers, OIDC providers share the same routes
+const BUILT_IN_PROVIDERS: [&str; 3] = ["google", "github", "discord"];
+
+impl OidcProviderConfig {
+    pub fn load_all() -> Result<Vec<Self>, String> {
+        let Ok(names) = std::env::var("OIDC_PROVIDERS") else {
+            return Ok(vec![]);
+        };
+
+        names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                let valid_name = name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+                if !valid_name || BUILT_IN_PROVIDERS.contains(&name) {
+                    return Err(format!("Invalid OIDC provider name '{}'", name));
+                }
+
+                let prefix = format!("OIDC_{}_", name.to_uppercase().replace('-', "_"));
+                let mut config = envy::prefixed(prefix.as_str())
+                    .from_env::<Self>()
+                    .map_err(|e| format!("OIDC provider '{}': {}", name, e))?;
+                config.name = name.to_string();
+                Ok(config)
+            })
+            .collect()
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct FrontendConfig {
     #[serde(rename = "frontend_url")]
@@ -86,6 +188,247 @@ pub struct S3Config {
     pub secret_access_key: String,
 }
 
+/// Where rate limit counters are kept, selected with `RATE_LIMIT_BACKEND`.
+/// The in-memory backend counts per instance, deployments with several instances use Postgres.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RateLimitBackend {
+    #[default]
+    Memory,
+    Postgres,
+    Disabled,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RateLimitConfig {
+    #[serde(rename = "rate_limit_backend", default)]
+    pub backend: RateLimitBackend,
+}
+
+/// Where the JWT keys are loaded from, selected with `JWT_KEYS_SOURCE`
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum JwtKeysSource {
+    // A single key pair from `JWT_PRIVATE_KEY` / `JWT_PUBLIC_KEY` in .env.keys
+    #[default]
+    Env,
+    Directory,
+    Database,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct JwtKeysConfig {
+    #[serde(rename = "jwt_keys_source", default)]
+    pub source: JwtKeysSource,
+    #[serde(rename = "jwt_keys_dir")]
+    pub dir: Option<String>,
+    // Passphrase of the encrypted PKCS#8 private keys in the jwt_signing_key table
+    #[serde(rename = "jwt_keys_encryption_key")]
+    pub encryption_key: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MetricsConfig {
+    // Raw metrics older than this are rolled up per hour
+    #[serde(
+        rename = "metrics_retention_days",
+        default = "default_metrics_retention_days",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub retention_days: u32,
+    #[serde(
+        rename = "metrics_postgres_enabled",
+        default = "default_true",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub postgres_enabled: bool,
+    #[serde(
+        rename = "metrics_prometheus_enabled",
+        default,
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub prometheus_enabled: bool,
+    // Bearer token Prometheus sends when scraping `/metrics`
+    #[serde(rename = "metrics_prometheus_token")]
+    pub prometheus_token: Option<String>,
+    // Share of each event kind written to Postgres, between 0 and 1
+    #[serde(
+        rename = "metrics_sample_rate_http",
+        default = "default_sample_rate",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub sample_rate_http: f64,
+    #[serde(
+        rename = "metrics_sample_rate_db_query",
+        default = "default_sample_rate",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub sample_rate_db_query: f64,
+    #[serde(
+        rename = "metrics_sample_rate_rate_limit",
+        default = "default_sample_rate",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub sample_rate_rate_limit: f64,
+}
+
+fn default_metrics_retention_days() -> u32 {
+    7
+}
+
+fn default_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BackupConfig {
+    // Scheduled backups need pg_dump and pg_restore on the PATH
+    #[serde(
+        rename = "backup_schedule_enabled",
+        default,
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub schedule_enabled: bool,
+    #[serde(
+        rename = "backup_interval_hours",
+        default = "default_backup_interval_hours",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub interval_hours: u32,
+    // Newest backup of each of the last N days and M weeks is kept, the rest is removed
+    #[serde(
+        rename = "backup_keep_daily",
+        default = "default_backup_keep_daily",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub keep_daily: u32,
+    #[serde(
+        rename = "backup_keep_weekly",
+        default = "default_backup_keep_weekly",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub keep_weekly: u32,
+    // Scratch database restores are written to, restores are disabled without it
+    #[serde(rename = "backup_restore_database_url")]
+    pub restore_database_url: Option<String>,
+    // How long a presigned download URL stays valid
+    #[serde(
+        rename = "backup_download_url_ttl_seconds",
+        default = "default_backup_download_url_ttl_seconds",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub download_url_ttl_seconds: u64,
+}
+
+fn default_backup_interval_hours() -> u32 {
+    24
+}
+
+fn default_backup_download_url_ttl_seconds() -> u64 {
+    300
+}
+
+fn default_backup_keep_daily() -> u32 {
+    7
+}
+
+fn default_backup_keep_weekly() -> u32 {
+    4
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct JobsConfig {
+    // Instances that should only serve requests can leave the queue to others
+    #[serde(
+        rename = "jobs_worker_enabled",
+        default = "default_true",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub worker_enabled: bool,
+    // Jobs one instance runs at the same time
+    #[serde(
+        rename = "jobs_concurrency",
+        default = "default_jobs_concurrency",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub concurrency: u32,
+    // Shown on the jobs this instance runs, e.g. the host or pod name
+    #[serde(rename = "jobs_worker_id", default = "default_jobs_worker_id")]
+    pub worker_id: String,
+    // Succeeded and failed jobs are deleted after this many days
+    #[serde(
+        rename = "jobs_retention_days",
+        default = "default_jobs_retention_days",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub retention_days: u32,
+    #[serde(
+        rename = "notification_retention_days",
+        default = "default_notification_retention_days",
+        deserialize_with = "deserialize_parsed"
+    )]
+    pub notification_retention_days: u32,
+}
+
+fn default_jobs_concurrency() -> u32 {
+    4
+}
+
+fn default_jobs_worker_id() -> String {
+    "worker".to_string()
+}
+
+fn default_jobs_retention_days() -> u32 {
+    14
+}
+
+fn default_notification_retention_days() -> u32 {
+    90
+}
+
+/// OTLP trace export, enabled when an endpoint is set.
+/// The exporter reads the other standard `OTEL_EXPORTER_OTLP_*` variables itself.
+#[derive(Deserialize, Debug, Clone)]
+pub struct TracingConfig {
+    #[serde(rename = "otel_exporter_otlp_endpoint")]
+    pub otlp_endpoint: Option<String>,
+    #[serde(rename = "otel_service_name", default = "default_service_name")]
+    pub service_name: String,
+}
+
+fn default_service_name() -> String {
+    "nowaster-backend".to_string()
+}
+
+// Flattened configs only see strings in the environment
+fn deserialize_parsed<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+// Comma separated, e.g. "10.0.0.0/8, 127.0.0.1"
+fn deserialize_ip_networks<'de, D>(deserializer: D) -> Result<Vec<IpNetwork>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer)?
+        .split(',')
+        .map(str::trim)
+        .filter(|network| !network.is_empty())
+        .map(|network| network.parse().map_err(serde::de::Error::custom))
+        .collect()
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct Config {
     #[serde(flatten)]
@@ -102,4 +445,24 @@ pub struct Config {
     pub frontend: FrontendConfig,
     #[serde(flatten)]
     pub s3: S3Config,
+    #[serde(flatten)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(flatten)]
+    pub jwt_keys: JwtKeysConfig,
+    #[serde(flatten)]
+    pub metrics: MetricsConfig,
+    #[serde(flatten)]
+    pub tracing: TracingConfig,
+    #[serde(flatten)]
+    pub backup: BackupConfig,
+    #[serde(flatten)]
+    pub jobs: JobsConfig,
+    #[serde(skip)]
+    pub oidc: Vec<OidcProviderConfig>,
+}
+
+impl Config {
+    pub fn find_oidc_provider(&self, name: &str) -> Option<&OidcProviderConfig> {
+        self.oidc.iter().find(|provider| provider.name == name)
+    }
 }
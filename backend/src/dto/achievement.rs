@@ -0,0 +1,36 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::entity::achievement::{AchievementMetric, AchievementRule};
+
+/// Achievement earned by a user, shown as a badge on their profile
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReadAchievementDto {
+    pub key: String,
+    pub name: String,
+    pub description: String,
+    pub awarded_at: DateTime<Local>,
+}
+
+/// Entry of the achievement catalog together with the actor's progress towards it
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AchievementProgressDto {
+    pub key: String,
+    pub name: String,
+    pub description: String,
+    pub metric: AchievementMetric,
+    pub threshold: i64,
+    pub current: i64,
+    pub awarded_at: Option<DateTime<Local>>,
+}
+
+impl ReadAchievementDto {
+    pub fn from_rule(rule: &AchievementRule, awarded_at: DateTime<Local>) -> Self {
+        Self {
+            key: rule.key.to_string(),
+            name: rule.name.to_string(),
+            description: rule.description.to_string(),
+            awarded_at,
+        }
+    }
+}
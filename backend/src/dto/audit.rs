@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    entity::audit::{AuditAction, AuditTargetType},
+    router::request::ClientInfo,
+};
+
+#[derive(Clone, Debug)]
+pub struct CreateAuditLogDto {
+    pub action: AuditAction,
+    pub actor_id: Option<String>,
+    pub target_type: Option<AuditTargetType>,
+    pub target_id: Option<String>,
+    pub client: ClientInfo,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl CreateAuditLogDto {
+    pub fn new(action: AuditAction, actor_id: Option<&str>, client: &ClientInfo) -> Self {
+        Self {
+            action,
+            actor_id: actor_id.map(str::to_string),
+            target_type: None,
+            target_id: None,
+            client: client.clone(),
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn target(mut self, target_type: AuditTargetType, target_id: impl ToString) -> Self {
+        self.target_type = Some(target_type);
+        self.target_id = Some(target_id.to_string());
+        self
+    }
+
+    /// State of the target before and after the action, serialized as JSON
+    pub fn changes(
+        mut self,
+        before: Option<impl Serialize>,
+        after: Option<impl Serialize>,
+    ) -> Self {
+        self.before = before.and_then(|before| serde_json::to_value(before).ok());
+        self.after = after.and_then(|after| serde_json::to_value(after).ok());
+        self
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadAuditLogDto {
+    pub id: Uuid,
+    pub action: AuditAction,
+    pub actor_id: Option<String>,
+    pub target_type: Option<AuditTargetType>,
+    pub target_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AuditLogQueryDto {
+    pub action: Option<AuditAction>,
+    pub actor_id: Option<String>,
+    pub target_type: Option<AuditTargetType>,
+    pub target_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    // created_at and id of the last entry of the previous page, entries created in the
+    // same instant are told apart by their id
+    pub cursor: Option<DateTime<Utc>>,
+    pub cursor_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
@@ -16,6 +16,9 @@ pub struct ReadDbBackupDto {
     pub started_at: Option<DateTime<Utc>>,
     pub finished_at: Option<DateTime<Utc>>,
     pub duration_seconds: Option<i32>,
+    pub checksum_sha256: Option<String>,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub expired_at: Option<DateTime<Utc>>,
     pub user_username: Option<String>,
     pub user_avatar_url: Option<String>,
 }
@@ -33,8 +36,32 @@ impl From<DbBackup> for ReadDbBackupDto {
             started_at: backup.started_at,
             finished_at: backup.finished_at,
             duration_seconds: backup.duration_seconds,
+            checksum_sha256: backup.checksum_sha256,
+            verified_at: backup.verified_at,
+            expired_at: backup.expired_at,
             user_username: backup.user_username,
             user_avatar_url: backup.user_avatar_url,
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadDbBackupRestoreDto {
+    pub id: i32,
+    pub backup_id: i32,
+    pub requested_by: Option<String>,
+    pub target_database: String,
+    pub status: String,
+    pub error_message: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadBackupDownloadUrlDto {
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+    pub checksum_sha256: Option<String>,
+}
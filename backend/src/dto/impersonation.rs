@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct StartImpersonationDto {
+    #[validate(length(
+        min = 3,
+        max = 500,
+        message = "Reason must be between 3 and 500 characters"
+    ))]
+    pub reason: String,
+    // Mutating requests are rejected unless the admin explicitly asks for write access
+    #[serde(default = "default_read_only")]
+    pub read_only: bool,
+}
+
+fn default_read_only() -> bool {
+    true
+}
+
+/// Active impersonation session, resolved from the `X-Impersonation-Token` header
+#[derive(Clone, Debug)]
+pub struct ImpersonationSession {
+    pub id: Uuid,
+    pub admin_user_id: String,
+    pub target_user_id: String,
+    pub read_only: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadImpersonationSessionDto {
+    pub id: Uuid,
+    pub admin_user_id: String,
+    pub target_user_id: String,
+    pub reason: String,
+    pub read_only: bool,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub request_count: i64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadImpersonatedRequestDto {
+    pub method: String,
+    pub path: String,
+    pub status_code: i16,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::entity::job::{Job, JobKind, JobSchedule, JobStatus};
+
+const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+
+#[derive(Clone, Debug)]
+pub struct EnqueueJobDto {
+    pub kind: JobKind,
+    pub payload: Value,
+    pub dedup_key: Option<String>,
+    pub max_attempts: i32,
+}
+
+impl EnqueueJobDto {
+    pub fn new(kind: JobKind) -> Self {
+        Self {
+            kind,
+            payload: Value::Object(Default::default()),
+            dedup_key: None,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    pub fn payload(mut self, payload: impl Serialize) -> Self {
+        self.payload = serde_json::to_value(payload).unwrap_or(Value::Null);
+        self
+    }
+
+    /// Skip enqueueing while a pending or running job holds the same key
+    pub fn dedup_key(mut self, dedup_key: impl ToString) -> Self {
+        self.dedup_key = Some(dedup_key.to_string());
+        self
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadJobDto {
+    pub id: Uuid,
+    pub kind: JobKind,
+    pub payload: Value,
+    pub status: JobStatus,
+    pub dedup_key: Option<String>,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: DateTime<Utc>,
+    pub locked_by: Option<String>,
+    pub locked_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<Job> for ReadJobDto {
+    fn from(job: Job) -> Self {
+        Self {
+            id: job.id,
+            kind: job.kind,
+            payload: job.payload,
+            status: job.status,
+            dedup_key: job.dedup_key,
+            attempts: job.attempts,
+            max_attempts: job.max_attempts,
+            run_at: job.run_at,
+            locked_by: job.locked_by,
+            locked_at: job.locked_at,
+            last_error: job.last_error,
+            created_at: job.created_at,
+            finished_at: job.finished_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadJobScheduleDto {
+    pub name: String,
+    pub kind: JobKind,
+    pub payload: Value,
+    pub cron: String,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_job_id: Option<Uuid>,
+}
+
+impl From<JobSchedule> for ReadJobScheduleDto {
+    fn from(schedule: JobSchedule) -> Self {
+        Self {
+            name: schedule.name,
+            kind: schedule.kind,
+            payload: schedule.payload,
+            cron: schedule.cron,
+            next_run_at: schedule.next_run_at,
+            last_run_at: schedule.last_run_at,
+            last_job_id: schedule.last_job_id,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct JobQueryDto {
+    pub status: Option<JobStatus>,
+    pub kind: Option<JobKind>,
+    // jobs created before the cursor, for pagination
+    pub cursor: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::dto::user::read_user::ReadUserDto;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeaderboardPeriod {
+    Day,
+    Week,
+    Month,
+    Custom,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
+pub struct LeaderboardQueryDto {
+    pub period: LeaderboardPeriod,
+    // Only used (and required) for the custom period
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub category_ids: Option<Vec<Uuid>>,
+    pub tag_ids: Option<Vec<Uuid>>,
+    #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LeaderboardEntryDto {
+    pub rank: i64,
+    pub user: ReadUserDto,
+    pub minutes: f64,
+    pub session_count: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReadLeaderboardDto {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub computed_at: DateTime<Utc>,
+    pub entries: Vec<LeaderboardEntryDto>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
+pub struct LeaderboardSettingsDto {
+    pub opt_out: bool,
+}
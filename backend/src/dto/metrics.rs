@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Width of the time buckets metrics are grouped into
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricsBucket {
+    Minute,
+    #[default]
+    Hour,
+    Day,
+}
+
+impl MetricsBucket {
+    /// Unit accepted by `date_trunc`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricsBucket::Minute => "minute",
+            MetricsBucket::Hour => "hour",
+            MetricsBucket::Day => "day",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct MetricsQueryDto {
+    // defaults to the last 24 hours
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub bucket: MetricsBucket,
+    pub limit: Option<i64>,
+}
+
+/// Percentiles are approximate, they are the upper bound of the latency histogram bucket
+/// the percentile falls into and overestimate the latency by at most 10%
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadRouteLatencyDto {
+    pub bucket_start: DateTime<Utc>,
+    pub method: String,
+    pub route: String,
+    pub request_count: i64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+/// Percentiles are approximate, see [`ReadRouteLatencyDto`]
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadQueryLatencyDto {
+    pub bucket_start: DateTime<Utc>,
+    pub query_name: String,
+    pub query_count: i64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadStatusCodeRateDto {
+    pub bucket_start: DateTime<Utc>,
+    pub status_code: i16,
+    pub request_count: i64,
+    // share of all requests in the bucket
+    pub rate: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadRequestQueryDto {
+    pub query_name: String,
+    pub duration_ms: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Only raw metrics within the retention period have request ids
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadSlowRequestDto {
+    pub request_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub status_code: i16,
+    pub duration_ms: f64,
+    pub user_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub queries: Vec<ReadRequestQueryDto>,
+}
@@ -1,7 +1,16 @@
+pub mod achievement;
+pub mod audit;
 pub mod category;
 pub mod db_backup;
 pub mod feed;
+pub mod impersonation;
+pub mod job;
+pub mod leaderboard;
+pub mod metrics;
+pub mod moderation;
 pub mod notification;
+pub mod passkey;
+pub mod platform_stats;
 pub mod project;
 pub mod release;
 pub mod serde_utils;
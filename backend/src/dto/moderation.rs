@@ -0,0 +1,74 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    dto::user::read_user::ReadUserDto,
+    entity::moderation::{UserReportReason, UserReportStatus},
+};
+
+#[derive(Clone, Serialize, Deserialize, Debug, Validate)]
+pub struct BlockUserDto {
+    #[validate(length(min = 1))]
+    pub user_id: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReadBlockedUserDto {
+    pub user: ReadUserDto,
+    pub created_at: DateTime<Local>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Validate)]
+pub struct CreateUserReportDto {
+    #[validate(length(min = 1))]
+    pub user_id: String,
+    pub reason: UserReportReason,
+    #[validate(length(max = 2000, message = "Details cannot be longer than 2000 characters"))]
+    pub details: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReadUserReportDto {
+    pub id: Uuid,
+    pub reporter: ReadUserDto,
+    pub reported: ReadUserDto,
+    pub reason: UserReportReason,
+    pub details: Option<String>,
+    pub status: UserReportStatus,
+    pub reviewed_by: Option<String>,
+    pub reviewed_at: Option<DateTime<Local>>,
+    pub resolution_note: Option<String>,
+    pub created_at: DateTime<Local>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct FilterUserReportsDto {
+    pub status: Option<UserReportStatus>,
+    pub reported_id: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Validate)]
+pub struct ReviewUserReportDto {
+    pub status: UserReportStatus,
+    #[validate(length(max = 2000))]
+    pub resolution_note: Option<String>,
+    // Suspends the reported user together with resolving the report
+    #[serde(default)]
+    pub suspend: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Validate)]
+pub struct SuspendUserDto {
+    #[validate(length(min = 1, max = 2000))]
+    pub reason: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReadSuspensionDto {
+    pub user_id: String,
+    pub suspended_at: Option<DateTime<Local>>,
+    pub suspension_reason: Option<String>,
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+use webauthn_rs::prelude::{PublicKeyCredential, RegisterPublicKeyCredential};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadPasskeyDto {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Options for `navigator.credentials.create()` or `.get()` and the id to finish the ceremony with
+#[derive(Clone, Debug, Serialize)]
+pub struct PasskeyChallengeDto<T> {
+    pub challenge_id: Uuid,
+    pub options: T,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct FinishPasskeyRegistrationDto {
+    pub challenge_id: Uuid,
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+    pub credential: RegisterPublicKeyCredential,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct StartPasskeySignupDto {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Username must be between 1 and 100 characters"
+    ))]
+    pub username: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct FinishPasskeySignupDto {
+    pub challenge_id: Uuid,
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+    pub credential: RegisterPublicKeyCredential,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct FinishPasskeyLoginDto {
+    pub challenge_id: Uuid,
+    pub credential: PublicKeyCredential,
+}
+
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct RenamePasskeyDto {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PlatformStatsQueryDto {
+    // days are UTC, defaults to the last 30 days
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Distinct users active on the day and in the 7 and 30 days up to it
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadActiveUsersDto {
+    pub day: NaiveDate,
+    pub daily: i64,
+    pub weekly: i64,
+    pub monthly: i64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadSignupsDto {
+    pub day: NaiveDate,
+    // OAuth provider, "passkey" or "guest" for sandbox guests
+    pub provider: String,
+    pub signups: i64,
+}
+
+/// Time tracking and feed engagement of a day, sessions count towards the day they started
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadDailyActivityDto {
+    pub day: NaiveDate,
+    pub sessions: i64,
+    pub tracked_hours: f64,
+    pub tracking_users: i64,
+    pub stopwatch_starts: i64,
+    pub stopwatch_users: i64,
+    pub feed_events: i64,
+    pub feed_reactions: i64,
+    pub reacting_users: i64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadApiTokenAdoptionDto {
+    pub active_tokens: i64,
+    pub users_with_active_tokens: i64,
+    pub tokens_used_last_30_days: i64,
+    pub users_using_tokens_last_30_days: i64,
+    // requests made with API tokens since usage tracking started
+    pub total_requests: i64,
+    pub monthly_active_users: i64,
+    // share of monthly active users that used an API token in the same period
+    pub adoption_rate: f64,
+}
+
+/// Guests handed out by a sandbox instance and how many of them tracked time themselves
+#[derive(Clone, Debug, Serialize)]
+pub struct ReadSandboxConversionDto {
+    pub id: Uuid,
+    pub status: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub unique_users: i32,
+    pub converted_users: i32,
+    pub conversion_rate: f64,
+}
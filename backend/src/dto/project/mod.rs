@@ -1,4 +1,6 @@
 pub mod create_project;
 pub mod filter_project;
+pub mod project_invite;
+pub mod project_member;
 pub mod read_project;
 pub mod update_project;
@@ -0,0 +1,20 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    dto::user::read_user::ReadUserDto,
+    entity::project::{ProjectInviteStatus, ProjectMemberRole},
+};
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ReadProjectInviteDto {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub invitee: ReadUserDto,
+    pub inviter: Option<ReadUserDto>,
+    pub role: ProjectMemberRole,
+    pub status: ProjectInviteStatus,
+    pub created_at: DateTime<Local>,
+}
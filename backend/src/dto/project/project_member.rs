@@ -0,0 +1,33 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{dto::user::read_user::ReadUserDto, entity::project::ProjectMemberRole};
+
+#[derive(Clone, Deserialize, Serialize, Debug, Validate)]
+pub struct AddProjectMemberDto {
+    #[validate(length(min = 1))]
+    pub user_id: String,
+    pub role: ProjectMemberRole,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, Validate)]
+pub struct UpdateProjectMemberDto {
+    pub role: ProjectMemberRole,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ReadProjectMemberDto {
+    pub user: ReadUserDto,
+    pub role: ProjectMemberRole,
+    pub created_at: DateTime<Local>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct MemberTimeBreakdownDto {
+    pub user: ReadUserDto,
+    #[serde(rename = "sessionCount")]
+    pub session_count: i64,
+    #[serde(rename = "totalTimeMinutes")]
+    pub total_time_minutes: f64,
+}
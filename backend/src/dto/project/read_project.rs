@@ -3,7 +3,10 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::entity::project::Project;
+use crate::{
+    dto::project::project_member::MemberTimeBreakdownDto,
+    entity::project::{Project, ProjectMemberRole},
+};
 
 #[derive(Clone, Serialize, Deserialize, Validate, Debug)]
 pub struct ReadProjectDto {
@@ -29,6 +32,9 @@ pub struct ReadProjectDetailsDto {
     pub task_count: i64,
     #[serde(rename = "completedTaskCount")]
     pub completed_task_count: i64,
+    pub role: ProjectMemberRole,
+    #[serde(rename = "memberBreakdown")]
+    pub member_breakdown: Vec<MemberTimeBreakdownDto>,
     pub created_at: DateTime<Local>,
     pub updated_at: DateTime<Local>,
 }
@@ -41,6 +47,7 @@ pub struct ProjectStatsDto {
     pub total_tasks: i64,
     pub total_sessions: i64,
     pub total_time_minutes: Option<f64>,
+    pub member_breakdown: Vec<MemberTimeBreakdownDto>,
 }
 
 impl ReadProjectDto {
@@ -1,9 +1,12 @@
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
-use crate::entity::release::{Release, ReleaseUser};
+use crate::entity::release::{ChangelogSection, Release, ReleaseAudience, ReleaseUser};
+
+const MAX_CHANGELOG_ITEMS: usize = 50;
+const MAX_CHANGELOG_ITEM_LENGTH: usize = 500;
 
 // Read DTOs
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -16,6 +19,9 @@ pub struct ReadReleaseDto {
     pub released_at: Option<DateTime<Local>>,
     pub released_by: Option<ReleaseUser>,
     pub tags: Vec<String>,
+    pub changelog: Vec<ChangelogSection>,
+    pub audience: ReleaseAudience,
+    pub scheduled_at: Option<DateTime<Local>>,
     pub seo_title: Option<String>,
     pub seo_description: Option<String>,
     pub seo_keywords: Option<String>,
@@ -31,6 +37,7 @@ pub struct ReadPublicReleaseDto {
     pub released_at: DateTime<Local>,
     pub released_by: Option<ReleaseUser>,
     pub tags: Vec<String>,
+    pub changelog: Vec<ChangelogSection>,
 }
 
 // Create/Update DTOs
@@ -47,6 +54,13 @@ pub struct CreateReleaseDto {
     #[validate(length(max = 10, message = "Maximum 10 tags allowed"))]
     pub tags: Vec<String>,
 
+    #[serde(default)]
+    #[validate(custom(function = "validate_changelog"))]
+    pub changelog: Vec<ChangelogSection>,
+
+    #[serde(default)]
+    pub audience: ReleaseAudience,
+
     pub seo_title: Option<String>,
     pub seo_description: Option<String>,
     pub seo_keywords: Option<String>,
@@ -58,11 +72,19 @@ pub struct UpdateReleaseDto {
     pub name: Option<String>,
     pub short_description: Option<String>,
     pub tags: Option<Vec<String>>,
+    #[validate(custom(function = "validate_changelog"))]
+    pub changelog: Option<Vec<ChangelogSection>>,
+    pub audience: Option<ReleaseAudience>,
     pub seo_title: Option<String>,
     pub seo_description: Option<String>,
     pub seo_keywords: Option<String>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduleReleaseDto {
+    pub scheduled_at: DateTime<Local>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PublishReleaseDto {
     pub release_id: Uuid,
@@ -76,6 +98,7 @@ pub struct MarkReleaseSeenDto {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ReleaseListQueryDto {
     pub released_only: Option<bool>,
+    pub audience: Option<ReleaseAudience>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
@@ -98,6 +121,9 @@ impl From<Release> for ReadReleaseDto {
             released_at: release.released_at,
             released_by: release.released_by,
             tags: release.tags,
+            changelog: release.changelog,
+            audience: release.audience,
+            scheduled_at: release.scheduled_at,
             seo_title: release.seo_title,
             seo_description: release.seo_description,
             seo_keywords: release.seo_keywords,
@@ -116,6 +142,36 @@ impl From<Release> for ReadPublicReleaseDto {
             released_at: release.released_at.unwrap_or_else(chrono::Local::now),
             released_by: release.released_by,
             tags: release.tags,
+            changelog: release.changelog,
         }
     }
 }
+
+fn validate_changelog(sections: &[ChangelogSection]) -> Result<(), ValidationError> {
+    let items = sections.iter().map(|s| s.items.len()).sum::<usize>();
+    if items > MAX_CHANGELOG_ITEMS {
+        return Err(ValidationError::new("changelog_too_long").with_message(
+            format!(
+                "The changelog can have at most {} items",
+                MAX_CHANGELOG_ITEMS
+            )
+            .into(),
+        ));
+    }
+
+    let item_length_ok = sections
+        .iter()
+        .flat_map(|s| &s.items)
+        .all(|item| !item.trim().is_empty() && item.len() <= MAX_CHANGELOG_ITEM_LENGTH);
+    if !item_length_ok {
+        return Err(ValidationError::new("changelog_item_length").with_message(
+            format!(
+                "Changelog items must be 1-{} characters",
+                MAX_CHANGELOG_ITEM_LENGTH
+            )
+            .into(),
+        ));
+    }
+
+    Ok(())
+}
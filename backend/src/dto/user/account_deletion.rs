@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReadAccountDeletionDto {
+    pub user_id: String,
+    pub requested_by: String,
+    pub requested_at: DateTime<Utc>,
+    pub scheduled_for: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AdminDeleteAccountDto {
+    // Skips the grace period and erases the account right away
+    #[serde(default)]
+    pub immediate: bool,
+}
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    dto::user::read_user::ReadUserDto,
+    repository::auth::api_tokens::ApiTokenRecord,
+    router::{
+        auth::{accounts::ReadLinkedAccountDto, sessions::ReadDeviceSessionDto},
+        clerk::UserRole,
+    },
+};
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct UpdateUserRoleDto {
+    pub role: UserRole,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReadUserRoleDto {
+    pub user_id: String,
+    pub role: UserRole,
+    pub previous_role: UserRole,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct ForceLogoutDto {
+    // API tokens keep working after a logout unless they are revoked as well
+    #[serde(default)]
+    pub revoke_api_tokens: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReadForceLogoutDto {
+    pub user_id: String,
+    pub revoked_api_tokens: u64,
+}
+
+/// API token of a user as admins see it, the secret is only shown once on creation
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReadUserApiTokenDto {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub usage_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiTokenRecord> for ReadUserApiTokenDto {
+    fn from(token: ApiTokenRecord) -> Self {
+        Self {
+            id: token.id,
+            name: token.name,
+            description: token.description,
+            usage_count: token.usage_count,
+            created_at: token.created_at,
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+            revoked_at: token.revoked_at,
+        }
+    }
+}
+
+/// Everything admins need to know about an account and the ways it is signed in
+#[derive(Serialize, Debug)]
+pub struct ReadUserAccountDto {
+    #[serde(flatten)]
+    pub user: ReadUserDto,
+    pub role: UserRole,
+    pub suspended_at: Option<DateTime<Utc>>,
+    pub suspension_reason: Option<String>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub sessions_revoked_at: Option<DateTime<Utc>>,
+    pub linked_accounts: Vec<ReadLinkedAccountDto>,
+    pub api_tokens: Vec<ReadUserApiTokenDto>,
+    pub sessions: Vec<ReadDeviceSessionDto>,
+}
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Whether the user receives announcements of beta releases
+#[derive(Clone, Deserialize, Serialize, Debug, Validate)]
+pub struct BetaOptInDto {
+    pub beta_opt_in: bool,
+}
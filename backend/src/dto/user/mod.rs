@@ -1,3 +1,6 @@
+pub mod account_deletion;
+pub mod admin_user;
+pub mod beta_opt_in;
 pub mod read_user;
 pub mod register;
 pub mod update_user;
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{entity::user::User, entity::visibility::VisibilityFlags};
+use crate::{
+    dto::achievement::ReadAchievementDto, entity::user::User, entity::visibility::VisibilityFlags,
+};
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ReadUserDto {
@@ -20,3 +22,11 @@ impl ReadUserDto {
         }
     }
 }
+
+/// Current user together with the badges they earned
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReadUserProfileDto {
+    #[serde(flatten)]
+    pub user: ReadUserDto,
+    pub badges: Vec<ReadAchievementDto>,
+}
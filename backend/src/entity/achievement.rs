@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+/// Aggregated statistic of a user that achievement rules are evaluated against
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AchievementMetric {
+    TotalMinutes,
+    SessionCount,
+    LongestStreakDays,
+    CompletedTasks,
+    CompletedProjects,
+    // minutes logged in the user's most used category
+    TopCategoryMinutes,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AchievementRule {
+    pub key: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub metric: AchievementMetric,
+    pub threshold: i64,
+    // whether unlocking the achievement is announced in the feed
+    pub publish_to_feed: bool,
+}
+
+/// Statistics of a single user, see [`AchievementMetric`]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AchievementProgress {
+    pub total_minutes: i64,
+    pub session_count: i64,
+    pub longest_streak_days: i64,
+    pub completed_tasks: i64,
+    pub completed_projects: i64,
+    pub top_category_minutes: i64,
+}
+
+impl AchievementProgress {
+    pub fn value(&self, metric: AchievementMetric) -> i64 {
+        match metric {
+            AchievementMetric::TotalMinutes => self.total_minutes,
+            AchievementMetric::SessionCount => self.session_count,
+            AchievementMetric::LongestStreakDays => self.longest_streak_days,
+            AchievementMetric::CompletedTasks => self.completed_tasks,
+            AchievementMetric::CompletedProjects => self.completed_projects,
+            AchievementMetric::TopCategoryMinutes => self.top_category_minutes,
+        }
+    }
+}
+
+impl AchievementRule {
+    pub fn is_satisfied(&self, progress: &AchievementProgress) -> bool {
+        progress.value(self.metric) >= self.threshold
+    }
+
+    pub fn find(key: &str) -> Option<&'static AchievementRule> {
+        ACHIEVEMENTS.iter().find(|rule| rule.key == key)
+    }
+}
+
+// INFO: keys are persisted, never rename or remove an existing one
+pub const ACHIEVEMENTS: &[AchievementRule] = &[
+    AchievementRule {
+        key: "first_session",
+        name: "First Steps",
+        description: "Log your first session",
+        metric: AchievementMetric::SessionCount,
+        threshold: 1,
+        publish_to_feed: false,
+    },
+    AchievementRule {
+        key: "sessions_100",
+        name: "Centurion",
+        description: "Log 100 sessions",
+        metric: AchievementMetric::SessionCount,
+        threshold: 100,
+        publish_to_feed: true,
+    },
+    AchievementRule {
+        key: "sessions_1000",
+        name: "Creature of Habit",
+        description: "Log 1000 sessions",
+        metric: AchievementMetric::SessionCount,
+        threshold: 1000,
+        publish_to_feed: true,
+    },
+    AchievementRule {
+        key: "hours_10",
+        name: "Getting Started",
+        description: "Log 10 hours in total",
+        metric: AchievementMetric::TotalMinutes,
+        threshold: 10 * 60,
+        publish_to_feed: true,
+    },
+    AchievementRule {
+        key: "hours_100",
+        name: "Dedicated",
+        description: "Log 100 hours in total",
+        metric: AchievementMetric::TotalMinutes,
+        threshold: 100 * 60,
+        publish_to_feed: true,
+    },
+    AchievementRule {
+        key: "hours_1000",
+        name: "Master of Time",
+        description: "Log 1000 hours in total",
+        metric: AchievementMetric::TotalMinutes,
+        threshold: 1000 * 60,
+        publish_to_feed: true,
+    },
+    AchievementRule {
+        key: "streak_7",
+        name: "On a Roll",
+        description: "Log sessions 7 days in a row",
+        metric: AchievementMetric::LongestStreakDays,
+        threshold: 7,
+        publish_to_feed: true,
+    },
+    AchievementRule {
+        key: "streak_30",
+        name: "Unstoppable",
+        description: "Log sessions 30 days in a row",
+        metric: AchievementMetric::LongestStreakDays,
+        threshold: 30,
+        publish_to_feed: true,
+    },
+    AchievementRule {
+        key: "first_task_completed",
+        name: "Checked Off",
+        description: "Complete a task",
+        metric: AchievementMetric::CompletedTasks,
+        threshold: 1,
+        publish_to_feed: false,
+    },
+    AchievementRule {
+        key: "first_project_completed",
+        name: "Finisher",
+        description: "Complete a project",
+        metric: AchievementMetric::CompletedProjects,
+        threshold: 1,
+        publish_to_feed: true,
+    },
+    AchievementRule {
+        key: "category_hours_50",
+        name: "Specialist",
+        description: "Log 50 hours in a single category",
+        metric: AchievementMetric::TopCategoryMinutes,
+        threshold: 50 * 60,
+        publish_to_feed: true,
+    },
+];
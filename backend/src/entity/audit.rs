@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// Security relevant or administrative action recorded in the audit log
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "audit_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    AuthLogin,
+    AuthLoginFailed,
+    AuthLogout,
+    ApiTokenCreated,
+    ApiTokenRevoked,
+    SessionRevoked,
+    OauthAccountLinked,
+    OauthAccountUnlinked,
+    ImpersonationStarted,
+    ImpersonationStopped,
+    VisibilityChanged,
+    UserSuspended,
+    UserUnsuspended,
+    UserRoleChanged,
+    UserSessionsRevoked,
+    ReportReviewed,
+    ReleaseCreated,
+    ReleaseUpdated,
+    ReleaseDeleted,
+    ReleasePublished,
+    ReleaseUnpublished,
+    ReleaseScheduled,
+    ReleaseScheduleCancelled,
+    BackupDownloaded,
+    BackupTriggered,
+    BackupRestoreRequested,
+    SandboxReset,
+    AccountDeletionRequested,
+    AccountDeletionCancelled,
+    AccountDeleted,
+    PasskeyRegistered,
+    PasskeyRemoved,
+    JobRetried,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "audit_target_type", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditTargetType {
+    User,
+    ApiToken,
+    Session,
+    OauthAccount,
+    Report,
+    Release,
+    Backup,
+    Sandbox,
+    Passkey,
+    Job,
+}
@@ -13,6 +13,9 @@ pub struct DbBackup {
     pub started_at: Option<DateTime<Utc>>,
     pub finished_at: Option<DateTime<Utc>>,
     pub duration_seconds: Option<i32>,
+    pub checksum_sha256: Option<String>,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub expired_at: Option<DateTime<Utc>>,
     // User information (when trigger_type = 'user')
     // Option because of LEFT JOIN - NULL when no matching user
     pub user_username: Option<String>,
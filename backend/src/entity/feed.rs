@@ -12,6 +12,7 @@ pub enum FeedEventType {
     SessionCompleted(SessionEventData),
     TaskCompleted(TaskEventData),
     ProjectCompleted(ProjectEventData),
+    AchievementUnlocked(AchievementEventData),
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -54,6 +55,8 @@ pub struct TaskEventData {
     pub task_description: Option<String>,
     pub hours_of_work: f64,
     pub project: FeedProject,
+    #[serde(default)]
+    pub contributors: Vec<FeedContributor>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -66,6 +69,15 @@ pub struct ProjectEventData {
     pub total_sessions: i64,
     pub tasks_time_breakdown: Vec<TaskTimeBreakdown>,
     pub categories_time_breakdown: Vec<CategoryTimeBreakdown>,
+    #[serde(default)]
+    pub contributors: Vec<FeedContributor>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AchievementEventData {
+    pub achievement_key: String,
+    pub name: String,
+    pub description: String,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -83,6 +95,13 @@ pub struct CategoryTimeBreakdown {
     pub minutes: f64,
 }
 
+/// Member of a shared project that logged time towards the completed task or project
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct FeedContributor {
+    pub user: ReadUserDto,
+    pub minutes: f64,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct FeedReaction {
     pub id: Uuid,
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Work a background job performs, each kind has its own handler in the job service
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "job_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    CleanupExpiredTokens,
+    CleanupNotifications,
+    ReplenishSandboxPool,
+    RecycleSandbox,
+    RecalculateFeedVisibility,
+    RefreshPlatformStats,
+    PublishScheduledReleases,
+    CleanupRateLimits,
+    RollUpMetrics,
+    EraseDeletedAccounts,
+    RunScheduledBackup,
+    BackfillAchievements,
+    CleanupLeaderboardCache,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "job_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: JobKind,
+    pub payload: Value,
+    pub status: JobStatus,
+    pub dedup_key: Option<String>,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: DateTime<Utc>,
+    pub locked_by: Option<String>,
+    pub locked_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct JobSchedule {
+    pub name: String,
+    pub kind: JobKind,
+    pub payload: Value,
+    pub cron: String,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_job_id: Option<Uuid>,
+}
@@ -1,6 +1,10 @@
+pub mod achievement;
+pub mod audit;
 pub mod category;
 pub mod db_backup;
 pub mod feed;
+pub mod job;
+pub mod moderation;
 pub mod notification;
 pub mod project;
 pub mod release;
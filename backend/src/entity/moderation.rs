@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "user_report_reason", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum UserReportReason {
+    Spam,
+    Harassment,
+    InappropriateContent,
+    Impersonation,
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "user_report_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum UserReportStatus {
+    Pending,
+    Resolved,
+    Dismissed,
+}
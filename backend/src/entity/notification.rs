@@ -4,7 +4,7 @@ use uuid::Uuid;
 
 use crate::{
     dto::{feed::ReadFeedReactionDto, user::read_user::ReadUserDto},
-    entity::category::Category,
+    entity::{category::Category, moderation::UserReportReason, project::ProjectMemberRole},
 };
 
 #[derive(Clone, Debug, PartialEq, PartialOrd, sqlx::Type, Deserialize, Serialize)]
@@ -35,6 +35,10 @@ pub enum NotificationTypeSql {
     #[serde(rename = "project:completed")]
     ProjectCompleted,
 
+    #[sqlx(rename = "project:invite")]
+    #[serde(rename = "project:invite")]
+    ProjectInvite,
+
     #[sqlx(rename = "admin:sandbox:failed-deploy")]
     #[serde(rename = "admin:sandbox:failed-deploy")]
     AdminSandboxFailedDeploy,
@@ -46,6 +50,22 @@ pub enum NotificationTypeSql {
     #[sqlx(rename = "admin:backup:failed")]
     #[serde(rename = "admin:backup:failed")]
     AdminBackupFailed,
+
+    #[sqlx(rename = "admin:user:reported")]
+    #[serde(rename = "admin:user:reported")]
+    AdminUserReported,
+
+    #[sqlx(rename = "achievement:unlocked")]
+    #[serde(rename = "achievement:unlocked")]
+    AchievementUnlocked,
+
+    #[sqlx(rename = "auth:new_login")]
+    #[serde(rename = "auth:new_login")]
+    AuthNewLogin,
+
+    #[sqlx(rename = "auth:account_accessed")]
+    #[serde(rename = "auth:account_accessed")]
+    AuthAccountAccessed,
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd, sqlx::Type, Deserialize, Serialize)]
@@ -104,6 +124,15 @@ pub struct ProjectCompletedData {
     pub total_hours: f64,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ProjectInviteData {
+    pub invite_id: Uuid,
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub inviter: ReadUserDto,
+    pub role: ProjectMemberRole,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 #[serde(tag = "notification_type", content = "data", rename_all = "kebab-case")]
 pub enum NotificationType {
@@ -125,6 +154,9 @@ pub enum NotificationType {
     #[serde(rename = "project:completed")]
     ProjectCompleted(ProjectCompletedData),
 
+    #[serde(rename = "project:invite")]
+    ProjectInvite(ProjectInviteData),
+
     #[serde(rename = "admin:sandbox:failed-deploy")]
     AdminSandboxFailedDeploy(SandboxFailedDeployData),
 
@@ -133,6 +165,18 @@ pub enum NotificationType {
 
     #[serde(rename = "admin:backup:failed")]
     AdminBackupFailed(BackupFailedData),
+
+    #[serde(rename = "admin:user:reported")]
+    AdminUserReported(UserReportedData),
+
+    #[serde(rename = "achievement:unlocked")]
+    AchievementUnlocked(AchievementUnlockedData),
+
+    #[serde(rename = "auth:new_login")]
+    AuthNewLogin(NewLoginData),
+
+    #[serde(rename = "auth:account_accessed")]
+    AuthAccountAccessed(AccountAccessedData),
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -156,6 +200,36 @@ pub struct BackupFailedData {
     pub started_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct UserReportedData {
+    pub report_id: Uuid,
+    pub reported: ReadUserDto,
+    pub reason: UserReportReason,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AchievementUnlockedData {
+    pub achievement_key: String,
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct NewLoginData {
+    pub device: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub logged_in_at: DateTime<Local>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AccountAccessedData {
+    pub reason: String,
+    pub read_only: bool,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct SystemNotificationData {
     pub system_id: String,
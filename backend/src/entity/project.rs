@@ -31,3 +31,33 @@ impl From<Project> for ReadProjectDto {
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "project_member_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectMemberRole {
+    Owner,
+    Editor,
+    Contributor,
+}
+
+impl ProjectMemberRole {
+    /// Owners and editors can change the project and its tasks, contributors only log time
+    pub fn can_edit(&self) -> bool {
+        matches!(self, Self::Owner | Self::Editor)
+    }
+
+    pub fn can_manage_members(&self) -> bool {
+        matches!(self, Self::Owner)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "project_invite_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectInviteStatus {
+    Pending,
+    Accepted,
+    Declined,
+    Cancelled,
+}
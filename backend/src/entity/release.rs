@@ -9,6 +9,35 @@ pub struct ReleaseUser {
     pub avatar_url: Option<String>,
 }
 
+/// Users notified when a release is published
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, sqlx::Type, Serialize, Deserialize)]
+#[sqlx(type_name = "release_audience", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseAudience {
+    #[default]
+    Everyone,
+    Admins,
+    // Users who opted into beta announcements, and admins
+    Beta,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangelogKind {
+    Added,
+    Changed,
+    Fixed,
+    Deprecated,
+    Removed,
+    Security,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChangelogSection {
+    pub kind: ChangelogKind,
+    pub items: Vec<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Release {
     pub id: Uuid,
@@ -19,6 +48,9 @@ pub struct Release {
     pub released_at: Option<DateTime<Local>>,
     pub released_by: Option<ReleaseUser>,
     pub tags: Vec<String>,
+    pub changelog: Vec<ChangelogSection>,
+    pub audience: ReleaseAudience,
+    pub scheduled_at: Option<DateTime<Local>>,
     pub seo_title: Option<String>,
     pub seo_description: Option<String>,
     pub seo_keywords: Option<String>,
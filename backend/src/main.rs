@@ -1,10 +1,11 @@
 use std::{path::Path, sync::Arc};
 
 use config::database::{Database, DatabaseTrait};
+use opentelemetry::trace::TracerProvider;
 use router::root::get_router;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
-use crate::config::env::Config;
+use crate::config::env::{Config, OidcProviderConfig};
 
 mod auth;
 mod config;
@@ -15,8 +16,11 @@ mod repository;
 mod router;
 mod seeding;
 mod service;
+mod telemetry;
 
-use metrics::{metrics_worker, MetricEvent, MetricsLayer};
+use metrics::{
+    metrics_worker, DroppedEvents, MetricEvent, MetricsLayer, MetricsSinks, PrometheusExporter,
+};
 
 #[tokio::main]
 async fn main() {
@@ -24,8 +28,10 @@ async fn main() {
 
     dotenv::dotenv().ok();
     dotenv::from_path(Path::new(".env.keys")).ok();
-    let config = envy::from_env::<Config>()
+    let mut config = envy::from_env::<Config>()
         .unwrap_or_else(|e| panic!("Failed to load configuration from environment: {}", e));
+    config.oidc = OidcProviderConfig::load_all()
+        .unwrap_or_else(|e| panic!("Failed to load OIDC providers: {}", e));
 
     println!("🔧 [CONFIG] Loaded configuration successfully");
     println!(
@@ -33,13 +39,26 @@ async fn main() {
         config.server.address, config.server.port
     );
     println!("🔧 [CONFIG] Frontend URL: {}", config.frontend.url);
+    println!("🔧 [CONFIG] OIDC providers: {}", config.oidc.len());
 
     let (metrics_tx, metrics_rx) = tokio::sync::mpsc::channel::<MetricEvent>(10_000);
+    let dropped_events = Arc::new(DroppedEvents::default());
+
+    let tracer_provider = telemetry::init_tracer_provider(&config.tracing)
+        .unwrap_or_else(|e| panic!("Failed to create OTLP exporter: {}", e));
+    let otel_layer = tracer_provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer(config.tracing.service_name.clone()))
+    });
+    if tracer_provider.is_some() {
+        println!("🔧 [CONFIG] Exporting traces as {}", config.tracing.service_name);
+    }
 
     tracing_subscriber::registry()
         .with(fmt::layer().json().with_current_span(false))
         .with(EnvFilter::new(log_level))
-        .with(MetricsLayer::new(metrics_tx))
+        .with(MetricsLayer::new(metrics_tx, dropped_events.clone()))
+        .with(otel_layer)
         .init();
 
     let db = Database::init(config.database.connection_url.clone())
@@ -47,9 +66,33 @@ async fn main() {
         .unwrap_or_else(|e| panic!("Database error: {}", e));
 
     let pool = db.get_pool().clone();
-    tokio::spawn(metrics_worker(metrics_rx, pool));
+    let prometheus = config.metrics.prometheus_enabled.then(|| {
+        PrometheusExporter::new(pool.clone())
+            .unwrap_or_else(|e| panic!("Failed to create Prometheus registry: {}", e))
+    });
+    if prometheus.is_some() && config.metrics.prometheus_token.is_none() {
+        println!("⚠️ [METRICS] METRICS_PROMETHEUS_TOKEN is not set, /metrics is not served");
+    }
+    let sinks = MetricsSinks {
+        postgres: config.metrics.postgres_enabled.then(|| pool.clone()),
+        prometheus: prometheus.clone(),
+        sample_rates: [
+            config.metrics.sample_rate_http,
+            config.metrics.sample_rate_db_query,
+            config.metrics.sample_rate_rate_limit,
+        ],
+    };
+    let (metrics_shutdown_tx, metrics_shutdown_rx) = tokio::sync::oneshot::channel();
+    let metrics_handle = tokio::spawn(metrics_worker(
+        metrics_rx,
+        sinks,
+        dropped_events,
+        metrics_shutdown_rx,
+    ));
 
-    let router = get_router(Arc::new(db), Arc::new(config.clone())).await;
+    let router = get_router(Arc::new(db), Arc::new(config.clone()), prometheus)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to build router: {:#}", e));
     let addr = format!("{}:{}", config.server.address, config.server.port);
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
 
@@ -57,5 +100,39 @@ async fn main() {
         "🚀 [SERVER] Listening on {}",
         listener.local_addr().unwrap()
     );
-    axum::serve(listener, router).await.unwrap()
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .unwrap();
+
+    // Requests finished during shutdown still have events in the queue
+    println!("🛑 [SERVER] Shutting down, flushing metrics");
+    let _ = metrics_shutdown_tx.send(());
+    let _ = metrics_handle.await;
+
+    if let Some(provider) = tracer_provider {
+        let _ = provider.shutdown();
+    }
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        if let Ok(mut signal) =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        {
+            signal.recv().await;
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
 }
@@ -0,0 +1,152 @@
+use anyhow::Result;
+use prometheus::{
+    exponential_buckets, Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder, TEXT_FORMAT,
+};
+use sqlx::PgPool;
+
+use super::{MetricEvent, MetricKind};
+
+/// In-process Prometheus registry fed by the same events as the Postgres sink.
+/// Cloning is cheap, every collector is reference counted.
+#[derive(Clone)]
+pub struct PrometheusExporter {
+    registry: Registry,
+    pool: PgPool,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    db_query_duration: HistogramVec,
+    rate_limit_hits: IntCounterVec,
+    db_pool_connections: IntGauge,
+    db_pool_idle_connections: IntGauge,
+    queue_depth: IntGauge,
+    events_dropped: IntCounterVec,
+}
+
+impl PrometheusExporter {
+    pub const CONTENT_TYPE: &'static str = TEXT_FORMAT;
+
+    pub fn new(pool: PgPool) -> Result<Self> {
+        let registry = Registry::new_custom(Some("nowaster".to_string()), None)?;
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route template"),
+            &["method", "route", "status"],
+        )?;
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency by route template",
+            ),
+            &["method", "route"],
+        )?;
+        // Most queries finish well under the smallest HTTP bucket
+        let db_query_duration = HistogramVec::new(
+            HistogramOpts::new("db_query_duration_seconds", "Latency of named queries")
+                .buckets(exponential_buckets(0.0005, 2.0, 14)?),
+            &["query"],
+        )?;
+        let rate_limit_hits = IntCounterVec::new(
+            Opts::new(
+                "rate_limit_hits_total",
+                "Requests rejected by the rate limiter",
+            ),
+            &["policy", "key_type"],
+        )?;
+        let db_pool_connections =
+            IntGauge::new("db_pool_connections", "Open database connections")?;
+        let db_pool_idle_connections =
+            IntGauge::new("db_pool_idle_connections", "Idle database connections")?;
+        let queue_depth = IntGauge::new(
+            "metrics_queue_depth",
+            "Metric events waiting for the metrics worker",
+        )?;
+        let events_dropped = IntCounterVec::new(
+            Opts::new(
+                "metrics_events_dropped_total",
+                "Metric events dropped because the queue was full",
+            ),
+            &["event"],
+        )?;
+
+        registry.register(Box::new(http_requests.clone()))?;
+        registry.register(Box::new(http_request_duration.clone()))?;
+        registry.register(Box::new(db_query_duration.clone()))?;
+        registry.register(Box::new(rate_limit_hits.clone()))?;
+        registry.register(Box::new(db_pool_connections.clone()))?;
+        registry.register(Box::new(db_pool_idle_connections.clone()))?;
+        registry.register(Box::new(queue_depth.clone()))?;
+        registry.register(Box::new(events_dropped.clone()))?;
+
+        Ok(Self {
+            registry,
+            pool,
+            http_requests,
+            http_request_duration,
+            db_query_duration,
+            rate_limit_hits,
+            db_pool_connections,
+            db_pool_idle_connections,
+            queue_depth,
+            events_dropped,
+        })
+    }
+
+    pub fn observe(&self, event: &MetricEvent) {
+        match event {
+            MetricEvent::HttpRequest {
+                method,
+                route,
+                status_code,
+                duration_ms,
+                ..
+            } => {
+                // Unmatched paths are whatever clients sent, they would explode the label set
+                let route = route.as_deref().unwrap_or("unmatched");
+                self.http_requests
+                    .with_label_values(&[method.as_str(), route, &status_code.to_string()])
+                    .inc();
+                self.http_request_duration
+                    .with_label_values(&[method.as_str(), route])
+                    .observe(duration_ms / 1000.0);
+            }
+            MetricEvent::DbQuery {
+                query_name,
+                duration_ms,
+                ..
+            } => {
+                self.db_query_duration
+                    .with_label_values(&[query_name])
+                    .observe(duration_ms / 1000.0);
+            }
+            MetricEvent::RateLimitHit {
+                policy, key_type, ..
+            } => {
+                self.rate_limit_hits
+                    .with_label_values(&[policy, key_type])
+                    .inc();
+            }
+        }
+    }
+
+    pub fn set_queue_depth(&self, depth: usize) {
+        self.queue_depth.set(depth as i64);
+    }
+
+    pub fn record_dropped(&self, kind: MetricKind, count: u64) {
+        self.events_dropped
+            .with_label_values(&[kind.as_str()])
+            .inc_by(count);
+    }
+
+    /// Text exposition of every collector, pool gauges are read at scrape time
+    pub fn render(&self) -> Result<String> {
+        self.db_pool_connections.set(self.pool.size() as i64);
+        self.db_pool_idle_connections
+            .set(self.pool.num_idle() as i64);
+
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, time::Instant};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
 use tokio::sync::mpsc;
 use tracing::{
@@ -9,15 +9,47 @@ use tracing::{
 use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
 use uuid::Uuid;
 
-use super::MetricEvent;
+use super::{DroppedEvents, MetricEvent};
 
 pub struct MetricsLayer {
     tx: mpsc::Sender<MetricEvent>,
+    dropped: Arc<DroppedEvents>,
 }
 
 impl MetricsLayer {
-    pub fn new(tx: mpsc::Sender<MetricEvent>) -> Self {
-        Self { tx }
+    pub fn new(tx: mpsc::Sender<MetricEvent>, dropped: Arc<DroppedEvents>) -> Self {
+        Self { tx, dropped }
+    }
+
+    // Never waits for the worker, a full queue drops the event and counts it
+    fn send(&self, event: MetricEvent) {
+        let kind = event.kind();
+        if self.tx.try_send(event).is_err() {
+            self.dropped.record(kind);
+        }
+    }
+
+    fn on_rate_limit_event<S>(&self, event: &Event<'_>, ctx: Context<'_, S>)
+    where
+        S: Subscriber + for<'lookup> LookupSpan<'lookup>,
+    {
+        let request_id = ctx.lookup_current().and_then(|span| {
+            span.extensions()
+                .get::<RequestContext>()
+                .map(|rc| rc.request_id)
+        });
+
+        let mut visitor = FieldVisitor::new();
+        event.record(&mut visitor);
+
+        self.send(MetricEvent::RateLimitHit {
+            request_id,
+            policy: visitor.get("policy").unwrap_or_default(),
+            key_type: visitor.get("key_type").unwrap_or_default(),
+            method: visitor.get("method").unwrap_or_default(),
+            path: visitor.get("path").unwrap_or_default(),
+            user_id: visitor.get("user_id").filter(|id| !id.is_empty()),
+        });
     }
 }
 
@@ -26,6 +58,7 @@ struct HttpSpanData {
     request_id: Uuid,
     method: String,
     path: String,
+    route: Option<String>,
     status_code: Option<i16>,
     user_id: Option<String>,
 }
@@ -129,6 +162,7 @@ where
                 request_id,
                 method: visitor.get("method").unwrap_or_default(),
                 path: visitor.get("uri").unwrap_or_default(),
+                route: None,
                 status_code: None,
                 user_id: None,
             });
@@ -162,13 +196,22 @@ where
             if let Some(user_id) = visitor.get("user_id") {
                 data.user_id = Some(user_id);
             }
+            if let Some(route) = visitor.get("route") {
+                data.route = Some(route);
+            }
         }
     }
 
     // Fires when sqlx finishes executing a query. If the current span is one of
     // our named db_query spans we extract elapsed_secs + rows_returned from sqlx
     // and emit a DbQuery metric with the human-readable name.
+    // Rejections of the rate limiter are reported as rate_limit events.
     fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if event.metadata().target() == "rate_limit" {
+            self.on_rate_limit_event(event, ctx);
+            return;
+        }
+
         if event.metadata().target() != "sqlx::query" {
             return;
         }
@@ -191,7 +234,7 @@ where
         event.record(&mut visitor);
 
         if let Some(elapsed_secs) = visitor.elapsed_secs {
-            let _ = self.tx.try_send(MetricEvent::DbQuery {
+            self.send(MetricEvent::DbQuery {
                 request_id,
                 query_name,
                 duration_ms: elapsed_secs * 1000.0,
@@ -213,10 +256,11 @@ where
         if let Some(data) = extensions.get::<HttpSpanData>() {
             if let Some(status_code) = data.status_code {
                 let duration_ms = data.start.elapsed().as_secs_f64() * 1000.0;
-                let _ = self.tx.try_send(MetricEvent::HttpRequest {
+                self.send(MetricEvent::HttpRequest {
                     request_id: data.request_id,
                     method: data.method.clone(),
                     path: data.path.clone(),
+                    route: data.route.clone(),
                     status_code,
                     duration_ms,
                     user_id: data.user_id.clone(),
@@ -1,8 +1,12 @@
+mod exporter;
 mod layer;
 mod worker;
 
+pub use exporter::PrometheusExporter;
 pub use layer::MetricsLayer;
-pub use worker::metrics_worker;
+pub use worker::{metrics_worker, MetricsSinks};
+
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use uuid::Uuid;
 
@@ -11,6 +15,8 @@ pub enum MetricEvent {
         request_id: Uuid,
         method: String,
         path: String,
+        // Template of the matched route, None when no route matched
+        route: Option<String>,
         status_code: i16,
         duration_ms: f64,
         user_id: Option<String>,
@@ -20,6 +26,79 @@ pub enum MetricEvent {
         query_name: String,
         duration_ms: f64,
     },
+    RateLimitHit {
+        request_id: Option<Uuid>,
+        policy: String,
+        key_type: String,
+        method: String,
+        path: String,
+        user_id: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum MetricKind {
+    HttpRequest,
+    DbQuery,
+    RateLimitHit,
+}
+
+impl MetricKind {
+    pub const ALL: [Self; 3] = [Self::HttpRequest, Self::DbQuery, Self::RateLimitHit];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::HttpRequest => "http_request",
+            Self::DbQuery => "db_query",
+            Self::RateLimitHit => "rate_limit_hit",
+        }
+    }
+}
+
+impl MetricEvent {
+    pub fn kind(&self) -> MetricKind {
+        match self {
+            Self::HttpRequest { .. } => MetricKind::HttpRequest,
+            Self::DbQuery { .. } => MetricKind::DbQuery,
+            Self::RateLimitHit { .. } => MetricKind::RateLimitHit,
+        }
+    }
+}
+
+/// Events the layer could not queue because the worker fell behind
+#[derive(Default)]
+pub struct DroppedEvents([AtomicU64; 3]);
+
+impl DroppedEvents {
+    pub fn record(&self, kind: MetricKind) {
+        self.0[kind as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Drops counted since the previous call, per kind
+    pub fn take(&self) -> impl Iterator<Item = (MetricKind, u64)> + '_ {
+        MetricKind::ALL
+            .into_iter()
+            .map(|kind| (kind, self.0[kind as usize].swap(0, Ordering::Relaxed)))
+            .filter(|(_, count)| *count > 0)
+    }
+}
+
+/// Path without the query string and with ids replaced by `{id}`, e.g. `/api/task/{id}`
+pub fn normalize_route(path: &str) -> String {
+    let path = path.split('?').next().unwrap_or_default();
+
+    path.split('/')
+        .map(|segment| {
+            let is_id = Uuid::parse_str(segment).is_ok()
+                || (!segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit()));
+            if is_id {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
 }
 
 #[macro_export]
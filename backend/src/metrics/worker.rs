@@ -1,54 +1,235 @@
-use sqlx::PgPool;
-use tokio::sync::mpsc;
+use std::{sync::Arc, time::Duration};
+
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use tokio::sync::{mpsc, oneshot};
 use tracing::{debug, warn};
+use uuid::Uuid;
+
+use super::{normalize_route, DroppedEvents, MetricEvent, PrometheusExporter};
 
-use super::MetricEvent;
+// Buffered events are written once this many are waiting or FLUSH_INTERVAL passed
+const BATCH_SIZE: usize = 500;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
 
-pub async fn metrics_worker(mut rx: mpsc::Receiver<MetricEvent>, pool: PgPool) {
-    while let Some(event) = rx.recv().await {
+pub struct MetricsSinks {
+    pub postgres: Option<PgPool>,
+    pub prometheus: Option<PrometheusExporter>,
+    /// Share of each event kind written to Postgres, indexed by `MetricKind`
+    pub sample_rates: [f64; 3],
+}
+
+struct HttpRequestRow {
+    request_id: Uuid,
+    method: String,
+    path: String,
+    route: String,
+    status_code: i16,
+    duration_ms: f64,
+    user_id: Option<String>,
+}
+
+struct DbQueryRow {
+    request_id: Option<Uuid>,
+    query_name: String,
+    duration_ms: f64,
+}
+
+struct RateLimitRow {
+    request_id: Option<Uuid>,
+    policy: String,
+    key_type: String,
+    method: String,
+    path: String,
+    user_id: Option<String>,
+}
+
+#[derive(Default)]
+struct Batch {
+    requests: Vec<HttpRequestRow>,
+    queries: Vec<DbQueryRow>,
+    rate_limits: Vec<RateLimitRow>,
+}
+
+impl Batch {
+    fn len(&self) -> usize {
+        self.requests.len() + self.queries.len() + self.rate_limits.len()
+    }
+
+    fn push(&mut self, event: MetricEvent) {
         match event {
             MetricEvent::HttpRequest {
                 request_id,
                 method,
                 path,
+                route,
                 status_code,
                 duration_ms,
                 user_id,
-            } => {
-                match sqlx::query(
-                    "INSERT INTO metrics_handler (request_id, method, path, status_code, duration_ms, user_id) VALUES ($1, $2, $3, $4, $5, $6)",
-                )
-                .bind(request_id)
-                .bind(&method)
-                .bind(&path)
-                .bind(status_code)
-                .bind(duration_ms)
-                .bind(user_id.as_deref())
-                .execute(&pool)
-                .await
-                {
-                    Ok(_) => debug!(request_id = %request_id, method, path, status_code, duration_ms, user_id, "tracked http request"),
-                    Err(e) => warn!(error = %e, "failed to track http request metric"),
-                }
-            }
+            } => self.requests.push(HttpRequestRow {
+                request_id,
+                route: route.unwrap_or_else(|| normalize_route(&path)),
+                method,
+                path,
+                status_code,
+                duration_ms,
+                user_id,
+            }),
             MetricEvent::DbQuery {
                 request_id,
                 query_name,
                 duration_ms,
-            } => {
-                match sqlx::query(
-                    "INSERT INTO metrics_db_query (request_id, query_name, duration_ms) VALUES ($1, $2, $3)",
-                )
-                .bind(request_id)
-                .bind(&query_name)
-                .bind(duration_ms)
-                .execute(&pool)
-                .await
-                {
-                    Ok(_) => debug!(request_id = ?request_id, query_name, duration_ms, "tracked db query"),
-                    Err(e) => warn!(error = %e, "failed to track db query metric"),
+            } => self.queries.push(DbQueryRow {
+                request_id,
+                query_name,
+                duration_ms,
+            }),
+            MetricEvent::RateLimitHit {
+                request_id,
+                policy,
+                key_type,
+                method,
+                path,
+                user_id,
+            } => self.rate_limits.push(RateLimitRow {
+                request_id,
+                policy,
+                key_type,
+                method,
+                path,
+                user_id,
+            }),
+        }
+    }
+
+    /// Write every buffered event with one multi-row INSERT per table.
+    /// A failed batch is dropped, retrying would only pile up more work for a struggling database.
+    async fn flush(&mut self, pool: &PgPool) {
+        let Batch {
+            requests,
+            queries,
+            rate_limits,
+        } = std::mem::take(self);
+
+        if !requests.is_empty() {
+            let count = requests.len();
+            let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new(
+                "INSERT INTO metrics_handler (request_id, method, path, route, status_code, duration_ms, user_id) ",
+            );
+            builder.push_values(requests, |mut b, row| {
+                b.push_bind(row.request_id)
+                    .push_bind(row.method)
+                    .push_bind(row.path)
+                    .push_bind(row.route)
+                    .push_bind(row.status_code)
+                    .push_bind(row.duration_ms)
+                    .push_bind(row.user_id);
+            });
+            match builder.build().execute(pool).await {
+                Ok(_) => debug!(count, "tracked http requests"),
+                Err(e) => warn!(error = %e, count, "failed to track http request metrics"),
+            }
+        }
+
+        if !queries.is_empty() {
+            let count = queries.len();
+            let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new(
+                "INSERT INTO metrics_db_query (request_id, query_name, duration_ms) ",
+            );
+            builder.push_values(queries, |mut b, row| {
+                b.push_bind(row.request_id)
+                    .push_bind(row.query_name)
+                    .push_bind(row.duration_ms);
+            });
+            match builder.build().execute(pool).await {
+                Ok(_) => debug!(count, "tracked db queries"),
+                Err(e) => warn!(error = %e, count, "failed to track db query metrics"),
+            }
+        }
+
+        if !rate_limits.is_empty() {
+            let count = rate_limits.len();
+            let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new(
+                "INSERT INTO metrics_rate_limit (request_id, policy, key_type, method, path, user_id) ",
+            );
+            builder.push_values(rate_limits, |mut b, row| {
+                b.push_bind(row.request_id)
+                    .push_bind(row.policy)
+                    .push_bind(row.key_type)
+                    .push_bind(row.method)
+                    .push_bind(row.path)
+                    .push_bind(row.user_id);
+            });
+            match builder.build().execute(pool).await {
+                Ok(_) => debug!(count, "tracked rate limit hits"),
+                Err(e) => warn!(error = %e, count, "failed to track rate limit metrics"),
+            }
+        }
+    }
+}
+
+/// Fans every event out to the enabled sinks, Postgres writes are sampled and batched.
+/// On shutdown the queued events are drained and flushed before returning.
+pub async fn metrics_worker(
+    mut rx: mpsc::Receiver<MetricEvent>,
+    sinks: MetricsSinks,
+    dropped: Arc<DroppedEvents>,
+    mut shutdown: oneshot::Receiver<()>,
+) {
+    let mut batch = Batch::default();
+    let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => {
+                let Some(event) = event else { break };
+                sinks.handle(event, &mut batch, rx.len());
+
+                if batch.len() >= BATCH_SIZE {
+                    sinks.flush(&mut batch, &dropped).await;
                 }
             }
+            _ = interval.tick() => sinks.flush(&mut batch, &dropped).await,
+            _ = &mut shutdown => {
+                while let Ok(event) = rx.try_recv() {
+                    sinks.handle(event, &mut batch, 0);
+
+                    if batch.len() >= BATCH_SIZE {
+                        sinks.flush(&mut batch, &dropped).await;
+                    }
+                }
+                break;
+            }
+        }
+    }
+
+    sinks.flush(&mut batch, &dropped).await;
+}
+
+impl MetricsSinks {
+    fn handle(&self, event: MetricEvent, batch: &mut Batch, queue_depth: usize) {
+        if let Some(prometheus) = &self.prometheus {
+            prometheus.set_queue_depth(queue_depth);
+            prometheus.observe(&event);
+        }
+
+        if self.postgres.is_some() {
+            let sample_rate = self.sample_rates[event.kind() as usize];
+            if sample_rate >= 1.0 || rand::random::<f64>() < sample_rate {
+                batch.push(event);
+            }
+        }
+    }
+
+    async fn flush(&self, batch: &mut Batch, dropped: &DroppedEvents) {
+        for (kind, count) in dropped.take() {
+            warn!(event = kind.as_str(), count, "dropped metric events");
+            if let Some(prometheus) = &self.prometheus {
+                prometheus.record_dropped(kind, count);
+            }
+        }
+
+        if let Some(pool) = &self.postgres {
+            batch.flush(pool).await;
         }
     }
 }
@@ -0,0 +1,244 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, prelude::FromRow, Row};
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::{
+    config::database::{Database, DatabaseTrait},
+    dto::user::account_deletion::ReadAccountDeletionDto,
+};
+
+// Run in order in one transaction, $1 is the erased user.
+// Rows of other users are never deleted, only detached from the erased user.
+// Reports and audit entries are kept for moderation, they point at the anonymized row.
+const ERASURE_STATEMENTS: [&str; 31] = [
+    // Shared projects are handed over to the longest standing other member
+    r#"
+    WITH transferred AS (
+        UPDATE project p
+        SET user_id = (
+            SELECT pm.user_id
+            FROM project_member pm
+            WHERE pm.project_id = p.id AND pm.user_id <> $1
+            ORDER BY (pm.role = 'owner') DESC, pm.created_at
+            LIMIT 1
+        )
+        WHERE p.user_id = $1
+          AND EXISTS (
+              SELECT 1 FROM project_member pm WHERE pm.project_id = p.id AND pm.user_id <> $1
+          )
+        RETURNING p.id, p.user_id
+    )
+    UPDATE project_member pm
+    SET role = 'owner'
+    FROM transferred t
+    WHERE pm.project_id = t.id AND pm.user_id = t.user_id
+    "#,
+    // Tasks in projects that stay are handed over to the project owner
+    r#"
+    UPDATE task t
+    SET user_id = p.user_id
+    FROM project p
+    WHERE t.project_id = p.id AND t.user_id = $1 AND p.user_id <> $1
+    "#,
+    r#"DELETE FROM project WHERE user_id = $1"#,
+    r#"DELETE FROM project_member WHERE user_id = $1"#,
+    r#"DELETE FROM project_invite WHERE invitee_id = $1 OR inviter_id = $1"#,
+    r#"DELETE FROM stopwatch_session WHERE user_id = $1"#,
+    r#"DELETE FROM platform_stopwatch_start WHERE user_id = $1"#,
+    r#"DELETE FROM platform_active_user WHERE user_id = $1"#,
+    r#"DELETE FROM session WHERE user_id = $1"#,
+    r#"DELETE FROM recurring_session WHERE user_id = $1"#,
+    r#"DELETE FROM session_template WHERE user_id = $1"#,
+    // Categories and tags still used by other users' sessions are kept
+    r#"
+    DELETE FROM category c
+    WHERE c.created_by = $1
+      AND NOT EXISTS (SELECT 1 FROM session s WHERE s.category_id = c.id)
+      AND NOT EXISTS (SELECT 1 FROM stopwatch_session s WHERE s.category_id = c.id)
+      AND NOT EXISTS (SELECT 1 FROM recurring_session s WHERE s.category_id = c.id)
+    "#,
+    r#"
+    DELETE FROM tag t
+    WHERE t.created_by = $1
+      AND NOT EXISTS (SELECT 1 FROM tag_to_session ts WHERE ts.tag_id = t.id)
+      AND NOT EXISTS (SELECT 1 FROM tag_to_stopwatch_session ts WHERE ts.tag_id = t.id)
+      AND NOT EXISTS (SELECT 1 FROM tag_to_recurring_session ts WHERE ts.tag_id = t.id)
+    "#,
+    r#"DELETE FROM feed_event WHERE source_type = 'user' AND source_id = $1"#,
+    r#"DELETE FROM feed_reaction WHERE user_id = $1"#,
+    r#"DELETE FROM feed_subscription WHERE subscriber_id = $1 OR source_id = $1"#,
+    r#"DELETE FROM friend WHERE friend_1_id = $1 OR friend_2_id = $1"#,
+    r#"DELETE FROM friend_request WHERE requestor_id = $1 OR recipient_id = $1"#,
+    r#"DELETE FROM friend_suggestion_dismissal WHERE user_id = $1 OR dismissed_user_id = $1"#,
+    r#"DELETE FROM user_block WHERE blocker_id = $1 OR blocked_id = $1"#,
+    r#"DELETE FROM notification WHERE user_id = $1 OR (source_type = 'user' AND source_id = $1)"#,
+    r#"DELETE FROM oauth_accounts WHERE user_id = $1"#,
+    r#"DELETE FROM passkey_credential WHERE user_id = $1"#,
+    r#"DELETE FROM api_tokens WHERE user_id = $1"#,
+    r#"DELETE FROM refresh_tokens WHERE user_id = $1"#,
+    // Sessions the user ran as an admin are kept for the audit trail
+    r#"DELETE FROM impersonation_sessions WHERE target_user_id = $1"#,
+    r#"DELETE FROM user_achievement WHERE user_id = $1"#,
+    r#"DELETE FROM seen_release WHERE user_id = $1"#,
+    // Cached leaderboards of other viewers may list the user
+    r#"DELETE FROM leaderboard_cache WHERE viewer_id = $1 OR $1 = ANY(member_ids)"#,
+    r#"
+    UPDATE "user"
+    SET
+        displayname = 'Deleted user',
+        avatar_url = NULL,
+        email = NULL,
+        visibility_flags = 0,
+        leaderboard_opt_out = true,
+        beta_opt_in = false,
+        role = 'user',
+        deleted_at = NOW()
+    WHERE id = $1
+    "#,
+    r#"UPDATE account_deletion SET completed_at = NOW() WHERE user_id = $1"#,
+];
+
+#[derive(Clone)]
+pub struct AccountDeletionRepository {
+    db: Arc<Database>,
+}
+
+struct AccountDeletionRow(ReadAccountDeletionDto);
+
+impl FromRow<'_, PgRow> for AccountDeletionRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self(ReadAccountDeletionDto {
+            user_id: row.try_get("user_id")?,
+            requested_by: row.try_get("requested_by")?,
+            requested_at: row.try_get("requested_at")?,
+            scheduled_for: row.try_get("scheduled_for")?,
+            completed_at: row.try_get("completed_at")?,
+        }))
+    }
+}
+
+impl AccountDeletionRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db: Arc::clone(db_conn),
+        }
+    }
+
+    /// Schedule the erasure, a pending request is rescheduled.
+    /// Returns None when the account was already erased.
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn schedule(
+        &self,
+        user_id: &str,
+        requested_by: &str,
+        scheduled_for: DateTime<Utc>,
+    ) -> Result<Option<ReadAccountDeletionDto>> {
+        let row = crate::named_query!(
+            "account_deletion_schedule",
+            sqlx::query_as::<_, AccountDeletionRow>(
+                r#"
+                INSERT INTO account_deletion (user_id, requested_by, scheduled_for)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (user_id) DO UPDATE
+                SET requested_by = EXCLUDED.requested_by,
+                    requested_at = NOW(),
+                    scheduled_for = EXCLUDED.scheduled_for
+                WHERE account_deletion.completed_at IS NULL
+                RETURNING user_id, requested_by, requested_at, scheduled_for, completed_at
+            "#,
+            )
+            .bind(user_id)
+            .bind(requested_by)
+            .bind(scheduled_for)
+            .fetch_optional(self.db.get_pool())
+        )?;
+
+        Ok(row.map(|row| row.0))
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn get(&self, user_id: &str) -> Result<Option<ReadAccountDeletionDto>> {
+        let row = crate::named_query!(
+            "account_deletion_get",
+            sqlx::query_as::<_, AccountDeletionRow>(
+                r#"
+                SELECT user_id, requested_by, requested_at, scheduled_for, completed_at
+                FROM account_deletion
+                WHERE user_id = $1
+            "#,
+            )
+            .bind(user_id)
+            .fetch_optional(self.db.get_pool())
+        )?;
+
+        Ok(row.map(|row| row.0))
+    }
+
+    /// Cancel a pending request, returns whether there was one
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn cancel(&self, user_id: &str) -> Result<bool> {
+        let result = crate::named_query!(
+            "account_deletion_cancel",
+            sqlx::query("DELETE FROM account_deletion WHERE user_id = $1 AND completed_at IS NULL")
+                .bind(user_id)
+                .execute(self.db.get_pool())
+        )?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn list_pending(&self) -> Result<Vec<ReadAccountDeletionDto>> {
+        let rows = crate::named_query!(
+            "account_deletion_list_pending",
+            sqlx::query_as::<_, AccountDeletionRow>(
+                r#"
+                SELECT user_id, requested_by, requested_at, scheduled_for, completed_at
+                FROM account_deletion
+                WHERE completed_at IS NULL
+                ORDER BY scheduled_for
+            "#,
+            )
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    /// Pending requests whose grace period is over
+    #[instrument(err, skip(self))]
+    pub async fn list_due(&self) -> Result<Vec<ReadAccountDeletionDto>> {
+        let rows = crate::named_query!(
+            "account_deletion_list_due",
+            sqlx::query_as::<_, AccountDeletionRow>(
+                r#"
+                SELECT user_id, requested_by, requested_at, scheduled_for, completed_at
+                FROM account_deletion
+                WHERE completed_at IS NULL AND scheduled_for <= NOW()
+                ORDER BY scheduled_for
+            "#,
+            )
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    /// Erase the personal data of the user and anonymize the user row
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn erase_user(&self, user_id: &str) -> Result<()> {
+        let mut tx = self.db.get_pool().begin().await?;
+
+        for statement in ERASURE_STATEMENTS {
+            sqlx::query(statement)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
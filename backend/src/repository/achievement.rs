@@ -0,0 +1,208 @@
+use anyhow::Result;
+use sqlx::{postgres::PgRow, prelude::FromRow, Row};
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::{
+    config::database::{Database, DatabaseTrait},
+    dto::achievement::ReadAchievementDto,
+    entity::achievement::{AchievementProgress, AchievementRule},
+};
+
+#[derive(Clone)]
+pub struct AchievementRepository {
+    db: Arc<Database>,
+}
+
+struct AchievementProgressRow(AchievementProgress);
+
+impl FromRow<'_, PgRow> for AchievementProgressRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self(AchievementProgress {
+            total_minutes: row.try_get("total_minutes")?,
+            session_count: row.try_get("session_count")?,
+            longest_streak_days: row.try_get("longest_streak_days")?,
+            completed_tasks: row.try_get("completed_tasks")?,
+            completed_projects: row.try_get("completed_projects")?,
+            top_category_minutes: row.try_get("top_category_minutes")?,
+        }))
+    }
+}
+
+impl AchievementRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db: Arc::clone(db_conn),
+        }
+    }
+
+    /// Computes every statistic the achievement rules are evaluated against in one go
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn get_progress(&self, user_id: &str) -> Result<AchievementProgress> {
+        let row = crate::named_query!(
+            "achievement_progress",
+            sqlx::query_as::<_, AchievementProgressRow>(
+                r#"
+                WITH user_sessions AS (
+                    SELECT
+                        category_id,
+                        CAST(start_time AS DATE) AS day,
+                        EXTRACT(EPOCH FROM (end_time - start_time)) / 60 AS minutes
+                    FROM session
+                    WHERE user_id = $1 AND end_time IS NOT NULL
+                ),
+                days AS (
+                    SELECT DISTINCT day FROM user_sessions
+                ),
+                -- consecutive days share the same anchor (gaps and islands)
+                streaks AS (
+                    SELECT COUNT(*) AS length
+                    FROM (
+                        SELECT day - CAST(ROW_NUMBER() OVER (ORDER BY day) AS INT) AS anchor
+                        FROM days
+                    ) d
+                    GROUP BY anchor
+                ),
+                categories AS (
+                    SELECT SUM(minutes) AS minutes
+                    FROM user_sessions
+                    GROUP BY category_id
+                )
+                SELECT
+                    CAST(COALESCE((SELECT SUM(minutes) FROM user_sessions), 0) AS BIGINT) AS total_minutes,
+                    (SELECT COUNT(*) FROM user_sessions) AS session_count,
+                    COALESCE((SELECT MAX(length) FROM streaks), 0) AS longest_streak_days,
+                    (
+                        SELECT COUNT(*)
+                        FROM task t
+                        JOIN project_member pm ON pm.project_id = t.project_id AND pm.user_id = $1
+                        WHERE t.completed = true
+                    ) AS completed_tasks,
+                    (
+                        SELECT COUNT(*)
+                        FROM project p
+                        JOIN project_member pm ON pm.project_id = p.id AND pm.user_id = $1
+                        WHERE p.completed = true
+                    ) AS completed_projects,
+                    CAST(COALESCE((SELECT MAX(minutes) FROM categories), 0) AS BIGINT) AS top_category_minutes
+            "#,
+            )
+            .bind(user_id)
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(row.0)
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn list_badges(&self, user_id: &str) -> Result<Vec<ReadAchievementDto>> {
+        let rows = crate::named_query!(
+            "achievement_list_awarded",
+            sqlx::query(
+                r#"
+                SELECT achievement_key, awarded_at
+                FROM user_achievement
+                WHERE user_id = $1
+                ORDER BY awarded_at DESC
+            "#,
+            )
+            .bind(user_id)
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Self::map_badges(rows)
+    }
+
+    /// Awards the achievements, returns only the ones the user did not have yet
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn award(&self, user_id: &str, keys: &[String]) -> Result<Vec<ReadAchievementDto>> {
+        if keys.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let rows = crate::named_query!(
+            "achievement_award",
+            sqlx::query(
+                r#"
+                INSERT INTO user_achievement (user_id, achievement_key)
+                SELECT $1, key
+                FROM UNNEST($2::text[]) AS key
+                ON CONFLICT DO NOTHING
+                RETURNING achievement_key, awarded_at
+            "#,
+            )
+            .bind(user_id)
+            .bind(keys)
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Self::map_badges(rows)
+    }
+
+    /// Users with sessions or project memberships, used to backfill achievements
+    #[instrument(err, skip(self))]
+    pub async fn list_active_user_ids(&self) -> Result<Vec<String>> {
+        let ids: Vec<String> = crate::named_query!(
+            "achievement_active_users",
+            sqlx::query_scalar(
+                r#"
+                SELECT u.id
+                FROM "user" u
+                WHERE EXISTS (SELECT 1 FROM session s WHERE s.user_id = u.id)
+                   OR EXISTS (SELECT 1 FROM project_member pm WHERE pm.user_id = u.id)
+            "#,
+            )
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(ids)
+    }
+
+    #[instrument(err, skip(self), fields(catalog_version = %catalog_version))]
+    pub async fn is_backfilled(&self, catalog_version: &str) -> Result<bool> {
+        let backfilled: bool = crate::named_query!(
+            "achievement_is_backfilled",
+            sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM achievement_backfill WHERE catalog_version = $1)"
+            )
+            .bind(catalog_version)
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(backfilled)
+    }
+
+    #[instrument(err, skip(self), fields(catalog_version = %catalog_version, awarded_count = awarded_count))]
+    pub async fn mark_backfilled(&self, catalog_version: &str, awarded_count: i32) -> Result<()> {
+        crate::named_query!(
+            "achievement_mark_backfilled",
+            sqlx::query(
+                r#"
+                INSERT INTO achievement_backfill (catalog_version, awarded_count)
+                VALUES ($1, $2)
+                ON CONFLICT (catalog_version) DO NOTHING
+            "#,
+            )
+            .bind(catalog_version)
+            .bind(awarded_count)
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
+
+    // Achievements that were removed from the catalog are not shown anymore
+    fn map_badges(rows: Vec<PgRow>) -> Result<Vec<ReadAchievementDto>> {
+        let mut badges = Vec::with_capacity(rows.len());
+        for row in rows {
+            let key: String = row.try_get("achievement_key")?;
+            if let Some(rule) = AchievementRule::find(&key) {
+                badges.push(ReadAchievementDto::from_rule(
+                    rule,
+                    row.try_get("awarded_at")?,
+                ));
+            }
+        }
+        Ok(badges)
+    }
+}
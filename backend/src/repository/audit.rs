@@ -0,0 +1,128 @@
+use anyhow::Result;
+use ipnetwork::IpNetwork;
+use sqlx::{postgres::PgRow, prelude::FromRow, Postgres, QueryBuilder, Row};
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::{
+    config::database::{Database, DatabaseTrait},
+    dto::audit::{AuditLogQueryDto, CreateAuditLogDto, ReadAuditLogDto},
+};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+#[derive(Clone)]
+pub struct AuditRepository {
+    db: Arc<Database>,
+}
+
+struct AuditLogRow(ReadAuditLogDto);
+
+impl FromRow<'_, PgRow> for AuditLogRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let ip_address: Option<IpNetwork> = row.try_get("ip_address")?;
+
+        Ok(Self(ReadAuditLogDto {
+            id: row.try_get("id")?,
+            action: row.try_get("action")?,
+            actor_id: row.try_get("actor_id")?,
+            target_type: row.try_get("target_type")?,
+            target_id: row.try_get("target_id")?,
+            ip_address: ip_address.map(|ip| ip.ip().to_string()),
+            user_agent: row.try_get("user_agent")?,
+            before: row.try_get("before")?,
+            after: row.try_get("after")?,
+            created_at: row.try_get("created_at")?,
+        }))
+    }
+}
+
+impl AuditRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db: Arc::clone(db_conn),
+        }
+    }
+
+    #[instrument(err, skip(self, dto), fields(action = ?dto.action))]
+    pub async fn create(&self, dto: CreateAuditLogDto) -> Result<()> {
+        crate::named_query!(
+            "audit_log_create",
+            sqlx::query(
+                r#"
+                INSERT INTO audit_log
+                    (action, actor_id, target_type, target_id, ip_address, user_agent, before, after)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            )
+            .bind(dto.action)
+            .bind(dto.actor_id)
+            .bind(dto.target_type)
+            .bind(dto.target_id)
+            .bind(dto.client.ip.map(IpNetwork::from))
+            .bind(dto.client.user_agent)
+            .bind(dto.before)
+            .bind(dto.after)
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn filter(&self, query: AuditLogQueryDto) -> Result<Vec<ReadAuditLogDto>> {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new(
+            r#"
+            SELECT id, action, actor_id, target_type, target_id, ip_address, user_agent, before, after, created_at
+            FROM audit_log
+            WHERE 1 = 1"#,
+        );
+
+        if let Some(action) = query.action {
+            builder.push(" AND action = ").push_bind(action);
+        }
+        if let Some(actor_id) = query.actor_id {
+            builder.push(" AND actor_id = ").push_bind(actor_id);
+        }
+        if let Some(target_type) = query.target_type {
+            builder.push(" AND target_type = ").push_bind(target_type);
+        }
+        if let Some(target_id) = query.target_id {
+            builder.push(" AND target_id = ").push_bind(target_id);
+        }
+        if let Some(from) = query.from {
+            builder.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = query.to {
+            builder.push(" AND created_at < ").push_bind(to);
+        }
+        match (query.cursor, query.cursor_id) {
+            (Some(cursor), Some(cursor_id)) => {
+                builder
+                    .push(" AND (created_at, id) < (")
+                    .push_bind(cursor)
+                    .push(", ")
+                    .push_bind(cursor_id)
+                    .push(")");
+            }
+            (Some(cursor), None) => {
+                builder.push(" AND created_at < ").push_bind(cursor);
+            }
+            (None, _) => {}
+        }
+
+        builder
+            .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+            .push_bind(query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT));
+
+        let rows = crate::named_query!(
+            "audit_log_filter",
+            builder
+                .build_query_as::<AuditLogRow>()
+                .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+}
@@ -61,6 +61,23 @@ impl ApiTokenRepository {
         Ok((token, record.id))
     }
 
+    /// Id of the token when it exists and can still be used, without counting it as a use
+    pub async fn find_active_token_id(&self, token: &str) -> Result<Option<Uuid>> {
+        let token_id = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM api_tokens
+            WHERE token_hash = $1
+              AND revoked_at IS NULL
+              AND (expires_at IS NULL OR expires_at > NOW())
+            "#,
+            sha256_hash(token)
+        )
+        .fetch_optional(self.db_conn.get_pool())
+        .await?;
+
+        Ok(token_id)
+    }
+
     pub async fn validate_api_token(&self, token: &str) -> Result<(String, UserRole)> {
         let token_hash = sha256_hash(token);
 
@@ -158,4 +175,22 @@ impl ApiTokenRepository {
 
         Ok(())
     }
+
+    /// Revoke every active token of the user, returns how many were revoked
+    pub async fn revoke_all_user_tokens(&self, user_id: &str, reason: &str) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE api_tokens
+            SET revoked_at = NOW(), revoked_reason = $1
+            WHERE user_id = $2 AND revoked_at IS NULL
+            "#,
+            reason,
+            user_id
+        )
+        .execute(self.db_conn.get_pool())
+        .await
+        .context("Failed to revoke API tokens")?;
+
+        Ok(result.rows_affected())
+    }
 }
@@ -1,12 +1,19 @@
 use anyhow::Result;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use std::sync::Arc;
+use uuid::Uuid;
 
 use crate::{
     auth::crypto::{generate_random_hex, sha256_hash},
     config::database::{Database, DatabaseTrait},
+    dto::impersonation::{
+        ImpersonationSession, ReadImpersonatedRequestDto, ReadImpersonationSessionDto,
+    },
 };
 
+// Impersonation tokens are meant for a single support task, not for a working day
+const SESSION_DURATION_MINUTES: i64 = 15;
+
 #[derive(Clone)]
 pub struct ImpersonationRepository {
     db_conn: Arc<Database>,
@@ -23,38 +30,42 @@ impl ImpersonationRepository {
         &self,
         admin_user_id: &str,
         target_user_id: &str,
-    ) -> Result<String> {
+        reason: &str,
+        read_only: bool,
+    ) -> Result<(String, DateTime<Utc>)> {
         let token = generate_random_hex(32);
         let token_hash = sha256_hash(&token);
-        let expires_at = Utc::now() + Duration::hours(1);
+        let expires_at = Utc::now() + Duration::minutes(SESSION_DURATION_MINUTES);
 
         sqlx::query!(
             r#"
-            INSERT INTO impersonation_sessions (admin_user_id, target_user_id, token_hash, expires_at)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO impersonation_sessions (admin_user_id, target_user_id, token_hash, expires_at, reason, read_only)
+            VALUES ($1, $2, $3, $4, $5, $6)
             "#,
             admin_user_id,
             target_user_id,
             token_hash,
-            expires_at
+            expires_at,
+            reason,
+            read_only
         )
         .execute(self.db_conn.get_pool())
         .await?;
 
-        Ok(token)
+        Ok((token, expires_at))
     }
 
     pub async fn validate_impersonation_token(
         &self,
         token: &str,
-    ) -> Result<Option<(String, String)>> {
+    ) -> Result<Option<ImpersonationSession>> {
         let token_hash = sha256_hash(token);
 
         let record = sqlx::query!(
             r#"
-            SELECT admin_user_id, target_user_id, expires_at
+            SELECT id, admin_user_id, target_user_id, read_only, created_at, expires_at
             FROM impersonation_sessions
-            WHERE token_hash = $1
+            WHERE token_hash = $1 AND ended_at IS NULL
             "#,
             token_hash
         )
@@ -63,20 +74,28 @@ impl ImpersonationRepository {
 
         if let Some(record) = record {
             if record.expires_at > Utc::now() {
-                return Ok(Some((record.target_user_id, record.admin_user_id)));
+                return Ok(Some(ImpersonationSession {
+                    id: record.id,
+                    admin_user_id: record.admin_user_id,
+                    target_user_id: record.target_user_id,
+                    read_only: record.read_only,
+                    created_at: record.created_at,
+                }));
             }
         }
 
         Ok(None)
     }
 
+    /// Ends the session, it is kept for the access history of the target
     pub async fn revoke_impersonation_token(&self, token: &str) -> Result<()> {
         let token_hash = sha256_hash(token);
 
         sqlx::query!(
             r#"
-            DELETE FROM impersonation_sessions
-            WHERE token_hash = $1
+            UPDATE impersonation_sessions
+            SET ended_at = NOW()
+            WHERE token_hash = $1 AND ended_at IS NULL
             "#,
             token_hash
         )
@@ -85,4 +104,87 @@ impl ImpersonationRepository {
 
         Ok(())
     }
+
+    pub async fn log_request(
+        &self,
+        session_id: Uuid,
+        method: &str,
+        path: &str,
+        status_code: i16,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO impersonation_request (impersonation_session_id, method, path, status_code)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            session_id,
+            method,
+            path,
+            status_code
+        )
+        .execute(self.db_conn.get_pool())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_sessions_for_target(
+        &self,
+        target_user_id: &str,
+    ) -> Result<Vec<ReadImpersonationSessionDto>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                s.id,
+                s.admin_user_id,
+                s.target_user_id,
+                s.reason,
+                s.read_only,
+                s.created_at,
+                s.expires_at,
+                s.ended_at,
+                COUNT(r.id) as "request_count!"
+            FROM impersonation_sessions s
+            LEFT JOIN impersonation_request r ON r.impersonation_session_id = s.id
+            WHERE s.target_user_id = $1
+            GROUP BY s.id
+            ORDER BY s.created_at DESC
+            "#,
+            target_user_id
+        )
+        .fetch_all(self.db_conn.get_pool())
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| ReadImpersonationSessionDto {
+                id: record.id,
+                admin_user_id: record.admin_user_id,
+                target_user_id: record.target_user_id,
+                reason: record.reason,
+                read_only: record.read_only,
+                created_at: record.created_at,
+                expires_at: record.expires_at,
+                ended_at: record.ended_at,
+                request_count: record.request_count,
+            })
+            .collect())
+    }
+
+    pub async fn list_requests(&self, session_id: Uuid) -> Result<Vec<ReadImpersonatedRequestDto>> {
+        let records = sqlx::query_as!(
+            ReadImpersonatedRequestDto,
+            r#"
+            SELECT method, path, status_code, created_at
+            FROM impersonation_request
+            WHERE impersonation_session_id = $1
+            ORDER BY created_at
+            "#,
+            session_id
+        )
+        .fetch_all(self.db_conn.get_pool())
+        .await?;
+
+        Ok(records)
+    }
 }
@@ -0,0 +1,44 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::config::database::{Database, DatabaseTrait};
+
+#[derive(Debug)]
+pub struct JwtKeyRecord {
+    pub kid: String,
+    pub public_key_pem: String,
+    pub private_key_pem: Option<String>,
+    pub active: bool,
+}
+
+#[derive(Clone)]
+pub struct JwtKeyRepository {
+    db_conn: Arc<Database>,
+}
+
+impl JwtKeyRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db_conn: Arc::clone(db_conn),
+        }
+    }
+
+    /// Keys that are not retired, the active one signs and all of them verify
+    pub async fn list_usable_keys(&self) -> Result<Vec<JwtKeyRecord>> {
+        let keys = crate::named_query!(
+            "jwt_keys_list_usable",
+            sqlx::query_as!(
+                JwtKeyRecord,
+                r#"
+                SELECT kid, public_key_pem, private_key_pem, active
+                FROM jwt_signing_key
+                WHERE retired_at IS NULL
+                ORDER BY created_at
+                "#
+            )
+            .fetch_all(self.db_conn.get_pool())
+        )?;
+
+        Ok(keys)
+    }
+}
@@ -1,4 +1,6 @@
 pub mod api_tokens;
 pub mod impersonation;
+pub mod jwt_keys;
 pub mod oauth_account;
+pub mod passkey;
 pub mod tokens;
@@ -47,6 +47,8 @@ pub trait OAuthAccountRepositoryTrait {
         provider: &str,
         email: &str,
     ) -> Result<Option<OAuthAccount>>;
+
+    async fn delete_by_user_and_provider(&self, user_id: &str, provider: &str) -> Result<bool>;
 }
 
 impl OAuthAccountRepositoryTrait for OAuthAccountRepository {
@@ -68,7 +70,7 @@ impl OAuthAccountRepositoryTrait for OAuthAccountRepository {
             OAuthAccount,
             r#"
             INSERT INTO oauth_accounts (user_id, provider, provider_user_id, provider_email)
-            VALUES ($1, $2::oauth_provider, $3, $4)
+            VALUES ($1, $2, $3, $4)
             ON CONFLICT (provider, provider_user_id)
             DO UPDATE SET
                 user_id = EXCLUDED.user_id,
@@ -84,7 +86,7 @@ impl OAuthAccountRepositoryTrait for OAuthAccountRepository {
                 updated_at
             "#,
             user_id,
-            provider as _,
+            provider,
             provider_user_id,
             provider_email
         )
@@ -112,9 +114,9 @@ impl OAuthAccountRepositoryTrait for OAuthAccountRepository {
                 created_at,
                 updated_at
             FROM oauth_accounts
-            WHERE provider = $1::oauth_provider AND provider_user_id = $2
+            WHERE provider = $1 AND provider_user_id = $2
             "#,
-            provider as _,
+            provider,
             provider_user_id
         )
         .fetch_optional(self.db_conn.get_pool())
@@ -166,9 +168,9 @@ impl OAuthAccountRepositoryTrait for OAuthAccountRepository {
                 created_at,
                 updated_at
             FROM oauth_accounts
-            WHERE provider = $1::oauth_provider AND provider_email = $2
+            WHERE provider = $1 AND provider_email = $2
             "#,
-            provider as _,
+            provider,
             email
         )
         .fetch_optional(self.db_conn.get_pool())
@@ -176,4 +178,20 @@ impl OAuthAccountRepositoryTrait for OAuthAccountRepository {
 
         Ok(record)
     }
+
+    #[instrument(err, skip(self))]
+    async fn delete_by_user_and_provider(&self, user_id: &str, provider: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM oauth_accounts
+            WHERE user_id = $1 AND provider = $2
+            "#,
+            user_id,
+            provider
+        )
+        .execute(self.db_conn.get_pool())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
 }
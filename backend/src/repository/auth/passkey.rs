@@ -0,0 +1,285 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{postgres::PgRow, prelude::FromRow, types::Json, PgExecutor, Row};
+use std::sync::Arc;
+use tracing::instrument;
+use uuid::Uuid;
+use webauthn_rs::prelude::Passkey;
+
+use crate::{
+    config::database::{Database, DatabaseTrait},
+    dto::passkey::ReadPasskeyDto,
+    entity::visibility::VisibilityFlags,
+};
+
+#[derive(Clone)]
+pub struct PasskeyRepository {
+    db: Arc<Database>,
+}
+
+#[derive(Debug)]
+pub struct PasskeyRecord {
+    pub id: Uuid,
+    pub user_handle: Uuid,
+    pub name: String,
+    pub passkey: Passkey,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<PasskeyRecord> for ReadPasskeyDto {
+    fn from(record: PasskeyRecord) -> Self {
+        Self {
+            id: record.id,
+            name: record.name,
+            created_at: record.created_at,
+            last_used_at: record.last_used_at,
+        }
+    }
+}
+
+impl FromRow<'_, PgRow> for PasskeyRecord {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let passkey: Json<Passkey> = row.try_get("passkey")?;
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            user_handle: row.try_get("user_handle")?,
+            name: row.try_get("name")?,
+            passkey: passkey.0,
+            created_at: row.try_get("created_at")?,
+            last_used_at: row.try_get("last_used_at")?,
+        })
+    }
+}
+
+impl PasskeyRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db: Arc::clone(db_conn),
+        }
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn list_by_user(&self, user_id: &str) -> Result<Vec<PasskeyRecord>> {
+        let rows = crate::named_query!(
+            "passkey_list_by_user",
+            sqlx::query_as::<_, PasskeyRecord>(
+                r#"
+                SELECT id, user_handle, name, passkey, created_at, last_used_at
+                FROM passkey_credential
+                WHERE user_id = $1
+                ORDER BY created_at
+            "#,
+            )
+            .bind(user_id)
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows)
+    }
+
+    /// The passkey and the user it belongs to
+    #[instrument(err, skip(self, credential_id))]
+    pub async fn find_by_credential_id(
+        &self,
+        credential_id: &[u8],
+    ) -> Result<Option<(String, PasskeyRecord)>> {
+        let row = crate::named_query!(
+            "passkey_find_by_credential_id",
+            sqlx::query(
+                r#"
+                SELECT id, user_id, user_handle, name, passkey, created_at, last_used_at
+                FROM passkey_credential
+                WHERE credential_id = $1
+            "#,
+            )
+            .bind(credential_id)
+            .fetch_optional(self.db.get_pool())
+        )?;
+
+        row.map(|row| Ok((row.try_get("user_id")?, PasskeyRecord::from_row(&row)?)))
+            .transpose()
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn count_by_user(&self, user_id: &str) -> Result<i64> {
+        let count = crate::named_query!(
+            "passkey_count_by_user",
+            sqlx::query_scalar("SELECT COUNT(*) FROM passkey_credential WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(count)
+    }
+
+    #[instrument(err, skip(self, passkey), fields(user_id = %user_id))]
+    pub async fn create(
+        &self,
+        user_id: &str,
+        user_handle: Uuid,
+        name: &str,
+        passkey: &Passkey,
+    ) -> Result<PasskeyRecord> {
+        insert_credential(self.db.get_pool(), user_id, user_handle, name, passkey).await
+    }
+
+    /// Create a user that signs in with the passkey and has no email, returns the user id.
+    /// The user and the passkey are stored together, a failed signup leaves neither behind.
+    #[instrument(err, skip(self, passkey))]
+    pub async fn create_account(
+        &self,
+        display_name: &str,
+        user_handle: Uuid,
+        name: &str,
+        passkey: &Passkey,
+    ) -> Result<String> {
+        let mut tx = self.db.get_pool().begin().await?;
+
+        let user_id: String = crate::named_query!(
+            "passkey_create_account_user",
+            sqlx::query_scalar(
+                r#"
+                INSERT INTO "user" (id, displayname, visibility_flags)
+                VALUES (gen_random_uuid()::text, $1, $2)
+                RETURNING id
+            "#,
+            )
+            .bind(display_name)
+            .bind(VisibilityFlags::default().as_raw())
+            .fetch_one(tx.as_mut())
+        )?;
+        insert_credential(tx.as_mut(), &user_id, user_handle, name, passkey).await?;
+
+        tx.commit().await?;
+
+        Ok(user_id)
+    }
+
+    /// Store the updated signature counter and backup state after a login
+    #[instrument(err, skip(self, passkey), fields(passkey_id = %id))]
+    pub async fn record_login(&self, id: Uuid, passkey: &Passkey) -> Result<()> {
+        crate::named_query!(
+            "passkey_record_login",
+            sqlx::query(
+                "UPDATE passkey_credential SET passkey = $2, last_used_at = NOW() WHERE id = $1"
+            )
+            .bind(id)
+            .bind(Json(passkey))
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns whether the passkey exists and belongs to the user
+    #[instrument(err, skip(self), fields(passkey_id = %id, user_id = %user_id))]
+    pub async fn rename(&self, id: Uuid, user_id: &str, name: &str) -> Result<bool> {
+        let result = crate::named_query!(
+            "passkey_rename",
+            sqlx::query("UPDATE passkey_credential SET name = $3 WHERE id = $1 AND user_id = $2")
+                .bind(id)
+                .bind(user_id)
+                .bind(name)
+                .execute(self.db.get_pool())
+        )?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns whether the passkey existed and belonged to the user
+    #[instrument(err, skip(self), fields(passkey_id = %id, user_id = %user_id))]
+    pub async fn delete(&self, id: Uuid, user_id: &str) -> Result<bool> {
+        let result = crate::named_query!(
+            "passkey_delete",
+            sqlx::query("DELETE FROM passkey_credential WHERE id = $1 AND user_id = $2")
+                .bind(id)
+                .bind(user_id)
+                .execute(self.db.get_pool())
+        )?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Store the state of a ceremony, abandoned ceremonies are removed on the way
+    #[instrument(err, skip(self, state))]
+    pub async fn create_challenge(
+        &self,
+        user_id: Option<&str>,
+        state: Value,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Uuid> {
+        crate::named_query!(
+            "passkey_challenge_delete_expired",
+            sqlx::query("DELETE FROM passkey_challenge WHERE expires_at <= NOW()")
+                .execute(self.db.get_pool())
+        )?;
+
+        let id = crate::named_query!(
+            "passkey_challenge_create",
+            sqlx::query_scalar(
+                r#"
+                INSERT INTO passkey_challenge (user_id, state, expires_at)
+                VALUES ($1, $2, $3)
+                RETURNING id
+            "#,
+            )
+            .bind(user_id)
+            .bind(state)
+            .bind(expires_at)
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(id)
+    }
+
+    /// Remove the ceremony state so every challenge can only be answered once.
+    /// Returns the user that started it and the state, None when it is unknown or expired.
+    #[instrument(err, skip(self), fields(challenge_id = %id))]
+    pub async fn take_challenge(&self, id: Uuid) -> Result<Option<(Option<String>, Value)>> {
+        let row = crate::named_query!(
+            "passkey_challenge_take",
+            sqlx::query(
+                r#"
+                DELETE FROM passkey_challenge
+                WHERE id = $1 AND expires_at > NOW()
+                RETURNING user_id, state
+            "#,
+            )
+            .bind(id)
+            .fetch_optional(self.db.get_pool())
+        )?;
+
+        row.map(|row| Ok((row.try_get("user_id")?, row.try_get("state")?)))
+            .transpose()
+    }
+}
+
+async fn insert_credential<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: &str,
+    user_handle: Uuid,
+    name: &str,
+    passkey: &Passkey,
+) -> Result<PasskeyRecord> {
+    let row = crate::named_query!(
+        "passkey_create",
+        sqlx::query_as::<_, PasskeyRecord>(
+            r#"
+            INSERT INTO passkey_credential (user_id, user_handle, credential_id, passkey, name)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, user_handle, name, passkey, created_at, last_used_at
+        "#,
+        )
+        .bind(user_id)
+        .bind(user_handle)
+        .bind(passkey.cred_id().as_ref())
+        .bind(Json(passkey))
+        .bind(name)
+        .fetch_one(executor)
+    )?;
+
+    Ok(row)
+}
@@ -1,11 +1,48 @@
 use anyhow::{Context, Result};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use ipnetwork::IpNetwork;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use std::net::IpAddr;
+use uuid::Uuid;
 
 use crate::auth::crypto::{generate_random_hex, sha256_hash};
 
+/// Revocation reason of tokens that were exchanged for a new one, seeing one again means reuse
+const ROTATED_REASON: &str = "rotated";
+
+/// Concurrent requests can refresh with the same token, within this window after a rotation the
+/// token is exchanged for another token of its family instead of counting as reuse
+const ROTATION_GRACE_SECONDS: i64 = 30;
+
+// Each request rotates once, a few links cover a burst of concurrent requests
+const MAX_GRACE_LINKS: usize = 5;
+
+/// Where a token went after rotation
+struct RotationLink {
+    revoked_at: Option<DateTime<Utc>>,
+    revoked_reason: Option<String>,
+    replaced_by: Option<Uuid>,
+    expires_at: DateTime<Utc>,
+}
+
+impl RotationLink {
+    /// The successor, when this token was rotated within the grace window
+    fn in_grace(&self) -> Option<Uuid> {
+        let grace_start = Utc::now() - Duration::seconds(ROTATION_GRACE_SECONDS);
+
+        match (
+            self.revoked_at,
+            self.revoked_reason.as_deref(),
+            self.replaced_by,
+        ) {
+            (Some(revoked_at), Some(ROTATED_REASON), Some(id)) if revoked_at > grace_start => {
+                Some(id)
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Generate a refresh token and store it in the database
 ///
 /// # Arguments
@@ -57,7 +94,7 @@ pub async fn validate_refresh_token(token: &str, pool: &PgPool) -> Result<String
 
     let record = sqlx::query!(
         r#"
-        SELECT user_id, expires_at, revoked_at
+        SELECT user_id, family_id, expires_at, revoked_at, revoked_reason
         FROM refresh_tokens
         WHERE token_hash = $1
         "#,
@@ -68,15 +105,19 @@ pub async fn validate_refresh_token(token: &str, pool: &PgPool) -> Result<String
     .context("Database query failed")?
     .context("Invalid refresh token")?;
 
+    if record.revoked_at.is_some() {
+        if record.revoked_reason.as_deref() == Some(ROTATED_REASON) {
+            revoke_token_family(record.family_id, &record.user_id, pool).await?;
+            anyhow::bail!("Refresh token reuse detected");
+        }
+        anyhow::bail!("Refresh token revoked");
+    }
+
     let now = Utc::now();
     if record.expires_at < now {
         anyhow::bail!("Refresh token expired");
     }
 
-    if record.revoked_at.is_some() {
-        anyhow::bail!("Refresh token revoked");
-    }
-
     sqlx::query!(
         "UPDATE refresh_tokens SET last_used_at = NOW() WHERE token_hash = $1",
         token_hash
@@ -88,6 +129,196 @@ pub async fn validate_refresh_token(token: &str, pool: &PgPool) -> Result<String
     Ok(record.user_id)
 }
 
+/// Exchange a refresh token for a new one in the same family, invalidating the old one
+///
+/// Presenting a token that was already rotated out means it was copied, so the whole
+/// family is revoked and the legitimate client has to sign in again as well. Within a short
+/// grace window after the rotation the token returns its successor instead, so concurrent
+/// refreshes from the same client do not sign it out, the token is exchanged for a new one of
+/// the family as long as its successors still lead to an active token.
+///
+/// # Arguments
+/// * `token` - Plaintext refresh token
+/// * `user_agent` - User agent string from request
+/// * `ip` - IP address from request
+/// * `pool` - Database connection pool
+///
+/// # Returns
+/// User ID (string) and the new plaintext refresh token
+pub async fn rotate_refresh_token(
+    token: &str,
+    user_agent: Option<&str>,
+    ip: Option<IpAddr>,
+    pool: &PgPool,
+) -> Result<(String, String)> {
+    let token_hash = sha256_hash(token);
+    let mut tx = pool.begin().await?;
+
+    // Lock the token so concurrent refreshes with the same token are serialized
+    let record = sqlx::query!(
+        r#"
+        SELECT id, user_id, family_id, expires_at, revoked_at, revoked_reason, replaced_by
+        FROM refresh_tokens
+        WHERE token_hash = $1
+        FOR UPDATE
+        "#,
+        token_hash
+    )
+    .fetch_optional(tx.as_mut())
+    .await
+    .context("Database query failed")?
+    .context("Invalid refresh token")?;
+
+    if record.revoked_at.is_some() {
+        let link = RotationLink {
+            revoked_at: record.revoked_at,
+            revoked_reason: record.revoked_reason.clone(),
+            replaced_by: record.replaced_by,
+            expires_at: record.expires_at,
+        };
+        if family_active_after(tx.as_mut(), link).await? {
+            let (_, new_token) = insert_family_token(
+                tx.as_mut(),
+                &record.user_id,
+                record.family_id,
+                user_agent,
+                ip,
+            )
+            .await?;
+            tx.commit().await?;
+            return Ok((record.user_id, new_token));
+        }
+
+        tx.rollback().await?;
+        if record.revoked_reason.as_deref() == Some(ROTATED_REASON) {
+            revoke_token_family(record.family_id, &record.user_id, pool).await?;
+            anyhow::bail!("Refresh token reuse detected");
+        }
+        anyhow::bail!("Refresh token revoked");
+    }
+
+    if record.expires_at < Utc::now() {
+        anyhow::bail!("Refresh token expired");
+    }
+
+    let (successor_id, new_token) = insert_family_token(
+        tx.as_mut(),
+        &record.user_id,
+        record.family_id,
+        user_agent,
+        ip,
+    )
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW(), revoked_reason = $1, last_used_at = NOW(),
+            replaced_by = $3
+        WHERE id = $2
+        "#,
+        ROTATED_REASON,
+        record.id,
+        successor_id
+    )
+    .execute(tx.as_mut())
+    .await
+    .context("Failed to revoke rotated token")?;
+
+    tx.commit().await?;
+
+    Ok((record.user_id, new_token))
+}
+
+/// Store a new token in the family of a rotated one
+async fn insert_family_token(
+    conn: &mut PgConnection,
+    user_id: &str,
+    family_id: Uuid,
+    user_agent: Option<&str>,
+    ip: Option<IpAddr>,
+) -> Result<(Uuid, String)> {
+    let token = generate_random_hex(32);
+    let token_hash = sha256_hash(&token);
+    let expires_at = Utc::now() + Duration::days(30);
+
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO refresh_tokens (user_id, token_hash, expires_at, user_agent, ip_address, family_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id
+        "#,
+        user_id,
+        token_hash,
+        expires_at,
+        user_agent,
+        ip.map(IpNetwork::from),
+        family_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .context("Failed to store refresh token")?;
+
+    Ok((id, token))
+}
+
+/// Whether the successors of a token rotated moments ago still lead to an active token,
+/// a family that was signed out in the meantime stays signed out
+async fn family_active_after(conn: &mut PgConnection, mut link: RotationLink) -> Result<bool> {
+    for _ in 0..MAX_GRACE_LINKS {
+        let Some(successor_id) = link.in_grace() else {
+            return Ok(false);
+        };
+
+        let Some(successor) = sqlx::query_as!(
+            RotationLink,
+            r#"
+            SELECT revoked_at, revoked_reason, replaced_by, expires_at
+            FROM refresh_tokens
+            WHERE id = $1
+            "#,
+            successor_id
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .context("Database query failed")?
+        else {
+            return Ok(false);
+        };
+
+        if successor.revoked_at.is_none() {
+            return Ok(successor.expires_at > Utc::now());
+        }
+        link = successor;
+    }
+
+    Ok(false)
+}
+
+/// Revoke every active token of a family after one of its rotated tokens was reused
+async fn revoke_token_family(family_id: Uuid, user_id: &str, pool: &PgPool) -> Result<()> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW(), revoked_reason = 'reuse_detected'
+        WHERE family_id = $1 AND revoked_at IS NULL
+        "#,
+        family_id
+    )
+    .execute(pool)
+    .await
+    .context("Failed to revoke token family")?;
+
+    tracing::warn!(
+        user_id = %user_id,
+        family_id = %family_id,
+        revoked = result.rows_affected(),
+        "Refresh token reuse detected, revoked the whole token family"
+    );
+
+    Ok(())
+}
+
 /// Revoke a refresh token
 ///
 /// # Arguments
@@ -184,3 +415,153 @@ pub async fn limit_user_tokens(user_id: &str, max_tokens: i64, pool: &PgPool) ->
 
     Ok(())
 }
+
+/// Active refresh token of a user, every token family is a signed in device
+#[derive(Debug)]
+pub struct RefreshTokenSession {
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<IpNetwork>,
+}
+
+/// List all refresh tokens of a user that are neither revoked nor expired
+///
+/// # Arguments
+/// * `user_id` - User's ID (string)
+/// * `pool` - Database connection pool
+pub async fn list_active_user_tokens(
+    user_id: &str,
+    pool: &PgPool,
+) -> Result<Vec<RefreshTokenSession>> {
+    let sessions = sqlx::query_as!(
+        RefreshTokenSession,
+        r#"
+        SELECT
+            family_id,
+            token_hash,
+            -- the family was created when the user signed in, later tokens are rotations
+            (
+                SELECT MIN(f.created_at)
+                FROM refresh_tokens f
+                WHERE f.family_id = refresh_tokens.family_id
+            ) AS "created_at!",
+            -- rotated tokens record when they were exchanged last
+            (
+                SELECT MAX(f.last_used_at)
+                FROM refresh_tokens f
+                WHERE f.family_id = refresh_tokens.family_id
+            ) AS last_used_at,
+            expires_at,
+            user_agent,
+            ip_address
+        FROM refresh_tokens
+        WHERE user_id = $1
+          AND revoked_at IS NULL
+          AND expires_at > NOW()
+        ORDER BY COALESCE(last_used_at, created_at) DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to list user tokens")?;
+
+    Ok(sessions)
+}
+
+/// Revoke the active token of a family, signing out the device
+///
+/// # Returns
+/// Whether an active token was revoked
+pub async fn revoke_user_token_family(
+    family_id: Uuid,
+    user_id: &str,
+    reason: &str,
+    pool: &PgPool,
+) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW(), revoked_reason = $1
+        WHERE family_id = $2 AND user_id = $3 AND revoked_at IS NULL
+        "#,
+        reason,
+        family_id,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .context("Failed to revoke token")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revoke all refresh tokens of a user except the given one
+///
+/// # Returns
+/// Number of tokens revoked
+pub async fn revoke_other_user_tokens(
+    user_id: &str,
+    keep_token: &str,
+    reason: &str,
+    pool: &PgPool,
+) -> Result<u64> {
+    let keep_token_hash = sha256_hash(keep_token);
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW(), revoked_reason = $1
+        WHERE user_id = $2 AND token_hash <> $3 AND revoked_at IS NULL
+        "#,
+        reason,
+        user_id,
+        keep_token_hash
+    )
+    .execute(pool)
+    .await
+    .context("Failed to revoke user tokens")?;
+
+    Ok(result.rows_affected())
+}
+
+/// Whether the user signed in before and never from this device or IP
+///
+/// Must be called before the refresh token of the new login is stored.
+/// The very first login of a user is not considered new, there is nothing to compare with.
+/// A missing user agent or IP, on this login or on a stored session, leaves the device unknown
+/// rather than new, e.g. sessions created before they were recorded.
+pub async fn is_new_device(
+    user_id: &str,
+    user_agent: Option<&str>,
+    ip: Option<IpAddr>,
+    pool: &PgPool,
+) -> Result<bool> {
+    let (Some(user_agent), Some(ip)) = (user_agent, ip) else {
+        return Ok(false);
+    };
+
+    let record = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) AS "total!",
+            COUNT(*) FILTER (WHERE user_agent IS NULL OR ip_address IS NULL) AS "unknown!",
+            COUNT(*) FILTER (WHERE user_agent = $2) AS "same_device!",
+            COUNT(*) FILTER (WHERE ip_address = $3) AS "same_ip!"
+        FROM refresh_tokens
+        WHERE user_id = $1
+        "#,
+        user_id,
+        user_agent,
+        IpNetwork::from(ip)
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to look up known devices")?;
+
+    Ok(record.total > 0 && record.unknown == 0 && (record.same_device == 0 || record.same_ip == 0))
+}
@@ -1,8 +1,22 @@
 use anyhow::Result;
+use sqlx::{PgConnection, PgExecutor};
 use std::sync::Arc;
 use tracing::instrument;
 
-use crate::{config::database::{Database, DatabaseTrait}, entity::db_backup::DbBackup};
+use crate::{
+    config::database::{Database, DatabaseTrait},
+    dto::db_backup::ReadDbBackupRestoreDto,
+    entity::db_backup::DbBackup,
+};
+
+// Serializes claiming a backup run across instances
+const BACKUP_LOCK_KEY: i64 = 0x6e6f_7761_7374_6572;
+
+// Held by the instance running a restore, restores into the scratch database don't overlap
+const RESTORE_LOCK_KEY: i64 = 0x6e6f_7761_7374_7273;
+
+// Pending runs older than this were interrupted, e.g. by a restart
+const STALE_AFTER_HOURS: i32 = 6;
 
 #[derive(Clone)]
 pub struct DbBackupRepository {
@@ -32,6 +46,9 @@ impl DbBackupRepository {
                 db.started_at,
                 db.finished_at,
                 db.duration_seconds,
+                db.checksum_sha256,
+                db.verified_at,
+                db.expired_at,
                 u.displayname as "user_username?",
                 u.avatar_url as "user_avatar_url?"
             FROM db_backups db
@@ -61,6 +78,9 @@ impl DbBackupRepository {
                 db.started_at,
                 db.finished_at,
                 db.duration_seconds,
+                db.checksum_sha256,
+                db.verified_at,
+                db.expired_at,
                 u.displayname as "user_username?",
                 u.avatar_url as "user_avatar_url?"
             FROM db_backups db
@@ -74,4 +94,230 @@ impl DbBackupRepository {
 
         Ok(backup)
     }
+
+    /// Record a new pending run, unless one is already running or, when `min_interval_hours`
+    /// is set, a scheduled run started within that many hours. Returns the id of the run.
+    #[instrument(err, skip(self))]
+    pub async fn claim(
+        &self,
+        trigger_by: &str,
+        trigger_type: &str,
+        backup_file: &str,
+        min_interval_hours: Option<i32>,
+    ) -> Result<Option<i32>> {
+        let mut tx = self.db_conn.get_pool().begin().await?;
+
+        // The insert below must see runs claimed by other instances, so it runs after the lock
+        sqlx::query!("SELECT pg_advisory_xact_lock($1)", BACKUP_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO db_backups (trigger_by, trigger_type, backup_file, status)
+            SELECT $1, $2, $3, 'pending'
+            WHERE NOT EXISTS (
+                SELECT 1 FROM db_backups
+                WHERE status = 'pending'
+                  AND started_at > NOW() - make_interval(hours => $4)
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM db_backups
+                WHERE $5::INT IS NOT NULL
+                  AND trigger_type = 'system'
+                  AND status <> 'failed'
+                  AND started_at > NOW() - make_interval(hours => $5::INT)
+            )
+            RETURNING id
+            "#,
+            trigger_by,
+            trigger_type,
+            backup_file,
+            STALE_AFTER_HOURS,
+            min_interval_hours,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(id)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn mark_success(
+        &self,
+        id: i32,
+        size_bytes: i64,
+        checksum_sha256: &str,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE db_backups
+            SET status = 'success',
+                backup_size_bytes = $2,
+                checksum_sha256 = $3,
+                verified_at = NOW(),
+                finished_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            size_bytes,
+            checksum_sha256,
+        )
+        .execute(self.db_conn.get_pool())
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn mark_failed(&self, id: i32, error_message: &str) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE db_backups
+            SET status = 'failed', error_message = $2, finished_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            error_message,
+        )
+        .execute(self.db_conn.get_pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fail pending runs that never finished, returns how many were failed
+    #[instrument(err, skip(self))]
+    pub async fn fail_stale(&self) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE db_backups
+            SET status = 'failed', error_message = 'Interrupted', finished_at = NOW()
+            WHERE status = 'pending' AND started_at <= NOW() - make_interval(hours => $1)
+            "#,
+            STALE_AFTER_HOURS,
+        )
+        .execute(self.db_conn.get_pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn mark_expired(&self, id: i32) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE db_backups
+            SET status = 'expired', expired_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+        )
+        .execute(self.db_conn.get_pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Whether the connection ends up in the application database. The cluster is told apart
+    /// by its system identifier, the same server can be reached through different hosts.
+    #[instrument(err, skip(self, conn))]
+    pub async fn is_app_database(&self, conn: &mut PgConnection) -> Result<bool> {
+        let app = database_identity(self.db_conn.get_pool()).await?;
+        Ok(database_identity(conn).await? == app)
+    }
+
+    /// Takes the restore lock on a connection of its own, it is held until the connection
+    /// is closed or dropped. Returns `None` when another restore holds the lock.
+    #[instrument(err, skip(self))]
+    pub async fn try_lock_restore(&self) -> Result<Option<PgConnection>> {
+        let mut conn = self.db_conn.get_pool().acquire().await?.detach();
+
+        let locked = sqlx::query_scalar!(
+            r#"SELECT pg_try_advisory_lock($1) AS "locked!""#,
+            RESTORE_LOCK_KEY
+        )
+        .fetch_one(&mut conn)
+        .await?;
+
+        Ok(locked.then_some(conn))
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn create_restore(
+        &self,
+        backup_id: i32,
+        requested_by: &str,
+        target_database: &str,
+    ) -> Result<ReadDbBackupRestoreDto> {
+        let restore = sqlx::query_as!(
+            ReadDbBackupRestoreDto,
+            r#"
+            INSERT INTO db_backup_restore (backup_id, requested_by, target_database)
+            VALUES ($1, $2, $3)
+            RETURNING id, backup_id, requested_by, target_database, status, error_message,
+                started_at, finished_at
+            "#,
+            backup_id,
+            requested_by,
+            target_database,
+        )
+        .fetch_one(self.db_conn.get_pool())
+        .await?;
+
+        Ok(restore)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn finish_restore(&self, id: i32, error_message: Option<&str>) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE db_backup_restore
+            SET status = CASE WHEN $2::TEXT IS NULL THEN 'success' ELSE 'failed' END,
+                error_message = $2,
+                finished_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            error_message,
+        )
+        .execute(self.db_conn.get_pool())
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn list_restores(&self) -> Result<Vec<ReadDbBackupRestoreDto>> {
+        let restores = sqlx::query_as!(
+            ReadDbBackupRestoreDto,
+            r#"
+            SELECT id, backup_id, requested_by, target_database, status, error_message,
+                started_at, finished_at
+            FROM db_backup_restore
+            ORDER BY started_at DESC
+            LIMIT 100
+            "#
+        )
+        .fetch_all(self.db_conn.get_pool())
+        .await?;
+
+        Ok(restores)
+    }
+}
+
+/// System identifier of the cluster and name of the database the executor is connected to
+async fn database_identity<'e>(executor: impl PgExecutor<'e>) -> Result<(i64, String)> {
+    let identity = sqlx::query!(
+        r#"
+        SELECT system_identifier AS "system_identifier!", current_database()::TEXT AS "database!"
+        FROM pg_control_system()
+        "#
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok((identity.system_identifier, identity.database))
 }
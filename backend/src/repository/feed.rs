@@ -39,6 +39,8 @@ enum FeedEventSqlType {
     TaskCompleted,
     #[sqlx(rename = "project_completed")]
     ProjectCompleted,
+    #[sqlx(rename = "achievement_unlocked")]
+    AchievementUnlocked,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, FromRow)]
@@ -147,6 +149,9 @@ impl FeedEventMapper {
             FeedEventSqlType::ProjectCompleted => Ok(FeedEventType::ProjectCompleted(
                 serde_json::from_value(event_data)?,
             )),
+            FeedEventSqlType::AchievementUnlocked => Ok(FeedEventType::AchievementUnlocked(
+                serde_json::from_value(event_data)?,
+            )),
         }
     }
 
@@ -164,6 +169,10 @@ impl FeedEventMapper {
                 FeedEventSqlType::ProjectCompleted,
                 serde_json::to_value(project_data)?,
             )),
+            FeedEventType::AchievementUnlocked(achievement_data) => Ok((
+                FeedEventSqlType::AchievementUnlocked,
+                serde_json::to_value(achievement_data)?,
+            )),
         }
     }
 }
@@ -205,7 +214,13 @@ impl FeedRepository {
         sqlx::query!(
             r#"
                 INSERT INTO feed_subscription (subscriber_id, source_type, source_id)
-                VALUES ($1, $2, $3)
+                SELECT $1::varchar, $2::feed_source_type, $3::varchar
+                WHERE NOT EXISTS (
+                    SELECT 1
+                    FROM user_block b
+                    WHERE (b.blocker_id = $1 AND b.blocked_id = $3)
+                       OR (b.blocker_id = $3 AND b.blocked_id = $1)
+                )
                 ON CONFLICT (subscriber_id, source_type, source_id) DO NOTHING
             "#,
             subscriber_id,
@@ -514,6 +529,16 @@ impl FeedRepository {
             r#"
                 UPDATE feed_subscription fs
                 SET is_allowed_by_visibility =
+                    (
+                        -- Never allow if either user blocked the other
+                        NOT EXISTS (
+                            SELECT 1
+                            FROM user_block b
+                            WHERE (b.blocker_id = fs.subscriber_id AND b.blocked_id = fs.source_id)
+                               OR (b.blocker_id = fs.source_id AND b.blocked_id = fs.subscriber_id)
+                        )
+                    )
+                    AND
                     (
                         -- Allow if source user's visibility is public
                         ((u.visibility_flags & 3) = 3)
@@ -553,6 +578,22 @@ impl FeedRepository {
         Ok(affected_rows.rows_affected())
     }
 
+    /// Users other users are subscribed to
+    #[instrument(err, skip(self))]
+    pub async fn get_user_source_ids(&self) -> Result<Vec<String>> {
+        let source_ids = sqlx::query_scalar!(
+            r#"
+                SELECT DISTINCT source_id
+                FROM feed_subscription
+                WHERE source_type = 'user'
+            "#
+        )
+        .fetch_all(self.db.get_pool())
+        .await?;
+
+        Ok(source_ids)
+    }
+
     #[instrument(err, skip(self), fields(feed_event_id = %feed_event_id))]
     pub async fn get_feed_event_by_id(&self, feed_event_id: Uuid) -> Result<Option<FeedEvent>> {
         let mut base_query: QueryBuilder<'_, Postgres> = QueryBuilder::new(
@@ -11,8 +11,9 @@ use crate::{
     config::database::{Database, DatabaseTrait},
     router::clerk::Actor,
     service::friend_service::{
-        CreateFriendRequestDto, FriendRequestStatus, ReadFriendRequestDto, ReadFriendRequestsDto,
-        ReadFriendshipDto, RemoveFriendDto,
+        CreateFriendRequestDto, DismissFriendSuggestionDto, FriendRequestStatus,
+        ReadFriendRequestDto, ReadFriendRequestsDto, ReadFriendSuggestionDto, ReadFriendshipDto,
+        RemoveFriendDto,
     },
 };
 
@@ -250,6 +251,24 @@ impl FriendsRepository {
         actor: &Actor,
     ) -> Result<ReadFriendRequestDto> {
         let mut tx = self.db.get_pool().begin().await?;
+        let existing_block = sqlx::query!(
+            r#"
+                SELECT blocker_id
+                FROM user_block
+                WHERE (blocker_id = $1 AND blocked_id = $2) OR (blocker_id = $2 AND blocked_id = $1)
+            "#,
+            actor.user_id,
+            data.recipient_id
+        )
+        .fetch_optional(tx.as_mut())
+        .await?;
+
+        if existing_block.is_some() {
+            return Err(anyhow::anyhow!(
+                "You cannot send a friend request to this user"
+            ));
+        }
+
         let exising_request = sqlx::query!(
             r#"
                 SELECT id
@@ -407,4 +426,129 @@ impl FriendsRepository {
             None => Ok(None),
         }
     }
+
+    /// Ranks users the actor is not connected to yet by mutual friends, shared projects
+    /// and, for users with public visibility, categories and tags with the same name.
+    /// Users with pending or rejected requests in either direction, dismissed and blocked users are skipped.
+    #[instrument(err, skip(self), fields(actor_id = %actor, limit = limit))]
+    pub async fn list_friend_suggestions(
+        &self,
+        limit: i64,
+        actor: &Actor,
+    ) -> Result<Vec<ReadFriendSuggestionDto>> {
+        let result = crate::named_query!(
+            "friend_suggestion_list",
+            sqlx::query_as::<_, ReadFriendSuggestionDto>(
+                r#"
+                WITH my_friends AS (
+                    SELECT CASE WHEN f.friend_1_id = $1 THEN f.friend_2_id ELSE f.friend_1_id END AS id
+                    FROM friend f
+                    WHERE (f.friend_1_id = $1 OR f.friend_2_id = $1) AND f.deleted IS NOT TRUE
+                ),
+                mutual AS (
+                    SELECT
+                        CASE WHEN f.friend_1_id = mf.id THEN f.friend_2_id ELSE f.friend_1_id END AS candidate_id,
+                        COUNT(*) AS mutual_friends
+                    FROM friend f
+                    JOIN my_friends mf ON f.friend_1_id = mf.id OR f.friend_2_id = mf.id
+                    WHERE f.deleted IS NOT TRUE
+                    GROUP BY 1
+                ),
+                projects AS (
+                    SELECT other.user_id AS candidate_id, COUNT(DISTINCT other.project_id) AS shared_projects
+                    FROM project_member mine
+                    JOIN project_member other ON other.project_id = mine.project_id AND other.user_id <> mine.user_id
+                    WHERE mine.user_id = $1
+                    GROUP BY 1
+                ),
+                categories AS (
+                    SELECT other.created_by AS candidate_id, COUNT(DISTINCT LOWER(other.name)) AS shared_categories
+                    FROM category mine
+                    JOIN category other ON LOWER(other.name) = LOWER(mine.name) AND other.created_by <> mine.created_by
+                    WHERE mine.created_by = $1
+                    GROUP BY 1
+                ),
+                tags AS (
+                    SELECT other.created_by AS candidate_id, COUNT(DISTINCT LOWER(other.label)) AS shared_tags
+                    FROM tag mine
+                    JOIN tag other ON LOWER(other.label) = LOWER(mine.label) AND other.created_by <> mine.created_by
+                    WHERE mine.created_by = $1
+                    GROUP BY 1
+                ),
+                candidates AS (
+                    SELECT
+                        u.id,
+                        u.displayname,
+                        u.avatar_url,
+                        u.visibility_flags,
+                        COALESCE(m.mutual_friends, 0) AS mutual_friends,
+                        COALESCE(p.shared_projects, 0) AS shared_projects,
+                        CASE WHEN (u.visibility_flags & 3) = 3 THEN COALESCE(c.shared_categories, 0) ELSE 0 END AS shared_categories,
+                        CASE WHEN (u.visibility_flags & 3) = 3 THEN COALESCE(t.shared_tags, 0) ELSE 0 END AS shared_tags
+                    FROM "user" u
+                    LEFT JOIN mutual m ON m.candidate_id = u.id
+                    LEFT JOIN projects p ON p.candidate_id = u.id
+                    LEFT JOIN categories c ON c.candidate_id = u.id
+                    LEFT JOIN tags t ON t.candidate_id = u.id
+                    WHERE u.id <> $1
+                      AND u.visibility_flags <> 0
+                      AND (m.candidate_id IS NOT NULL OR p.candidate_id IS NOT NULL OR c.candidate_id IS NOT NULL OR t.candidate_id IS NOT NULL)
+                      AND u.id NOT IN (SELECT id FROM my_friends)
+                      AND NOT EXISTS (
+                        SELECT 1
+                        FROM friend_request fr
+                        WHERE ((fr.requestor_id = $1 AND fr.recipient_id = u.id) OR (fr.requestor_id = u.id AND fr.recipient_id = $1))
+                          AND fr.status IN ('pending', 'rejected')
+                      )
+                      AND NOT EXISTS (
+                        SELECT 1
+                        FROM friend_suggestion_dismissal d
+                        WHERE d.user_id = $1 AND d.dismissed_user_id = u.id
+                      )
+                      AND NOT EXISTS (
+                        SELECT 1
+                        FROM user_block b
+                        WHERE (b.blocker_id = $1 AND b.blocked_id = u.id) OR (b.blocker_id = u.id AND b.blocked_id = $1)
+                      )
+                )
+                SELECT
+                    c.*,
+                    -- mutual friends are the strongest signal, name overlaps the weakest
+                    (c.mutual_friends * 3 + c.shared_projects * 2 + c.shared_categories + c.shared_tags) AS score
+                FROM candidates c
+                WHERE (c.mutual_friends + c.shared_projects + c.shared_categories + c.shared_tags) > 0
+                ORDER BY score DESC, c.displayname ASC
+                LIMIT $2
+            "#,
+            )
+            .bind(&actor.user_id)
+            .bind(limit)
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(result)
+    }
+
+    #[instrument(err, skip(self), fields(dismissed_user_id = %dto.user_id, actor_id = %actor))]
+    pub async fn dismiss_friend_suggestion(
+        &self,
+        dto: DismissFriendSuggestionDto,
+        actor: &Actor,
+    ) -> Result<()> {
+        crate::named_query!(
+            "friend_suggestion_dismiss",
+            sqlx::query(
+                r#"
+                INSERT INTO friend_suggestion_dismissal (user_id, dismissed_user_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+            "#,
+            )
+            .bind(&actor.user_id)
+            .bind(dto.user_id)
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
 }
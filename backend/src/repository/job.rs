@@ -0,0 +1,361 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    config::database::{Database, DatabaseTrait},
+    dto::job::{EnqueueJobDto, JobQueryDto},
+    entity::job::{Job, JobKind, JobSchedule, JobStatus},
+};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+#[derive(Clone)]
+pub struct JobRepository {
+    db: Arc<Database>,
+}
+
+impl JobRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db: Arc::clone(db_conn),
+        }
+    }
+
+    /// Returns the id of the new job, None when a pending or running job holds the dedup key
+    #[instrument(err, skip(self, dto), fields(kind = ?dto.kind, dedup_key = ?dto.dedup_key))]
+    pub async fn enqueue(&self, dto: EnqueueJobDto) -> Result<Option<Uuid>> {
+        let id = crate::named_query!(
+            "job_enqueue",
+            sqlx::query_scalar!(
+                r#"
+                INSERT INTO job (kind, payload, dedup_key, max_attempts)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (dedup_key) WHERE dedup_key IS NOT NULL AND status IN ('pending', 'running')
+                DO NOTHING
+                RETURNING id
+                "#,
+                dto.kind as JobKind,
+                dto.payload,
+                dto.dedup_key,
+                dto.max_attempts,
+            )
+            .fetch_optional(self.db.get_pool())
+        )?;
+
+        Ok(id)
+    }
+
+    /// Lock up to `limit` due jobs for this worker. Running jobs whose lock is older than
+    /// `lease_seconds` belonged to a worker that died and are claimed again.
+    #[instrument(err, skip(self))]
+    pub async fn claim(&self, worker_id: &str, limit: i64, lease_seconds: f64) -> Result<Vec<Job>> {
+        let jobs = crate::named_query!(
+            "job_claim",
+            sqlx::query_as!(
+                Job,
+                r#"
+                UPDATE job
+                SET status = 'running', attempts = attempts + 1, locked_by = $1, locked_at = NOW()
+                WHERE id IN (
+                    SELECT id FROM job
+                    WHERE (status = 'pending' AND run_at <= NOW())
+                       OR (status = 'running'
+                           AND locked_at < NOW() - make_interval(secs => $3)
+                           AND attempts < max_attempts)
+                    ORDER BY run_at
+                    LIMIT $2
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id, kind as "kind: JobKind", payload, status as "status: JobStatus",
+                    dedup_key, attempts, max_attempts, run_at, locked_by, locked_at, last_error,
+                    created_at, finished_at
+                "#,
+                worker_id,
+                limit,
+                lease_seconds,
+            )
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(jobs)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn complete(&self, id: Uuid) -> Result<()> {
+        crate::named_query!(
+            "job_complete",
+            sqlx::query!(
+                r#"
+                UPDATE job
+                SET status = 'succeeded', last_error = NULL, finished_at = NOW()
+                WHERE id = $1 AND status = 'running'
+                "#,
+                id,
+            )
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
+
+    /// Record a failed attempt, the job runs again at `retry_at` or is failed for good without it
+    #[instrument(err, skip(self))]
+    pub async fn fail(&self, id: Uuid, error: &str, retry_at: Option<DateTime<Utc>>) -> Result<()> {
+        crate::named_query!(
+            "job_fail",
+            sqlx::query!(
+                r#"
+                UPDATE job
+                SET status = CASE WHEN $3::TIMESTAMPTZ IS NULL THEN 'failed' ELSE 'pending' END::job_status,
+                    run_at = COALESCE($3, run_at),
+                    last_error = $2,
+                    locked_at = NULL,
+                    finished_at = CASE WHEN $3::TIMESTAMPTZ IS NULL THEN NOW() END
+                WHERE id = $1 AND status = 'running'
+                "#,
+                id,
+                error,
+                retry_at,
+            )
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
+
+    /// Fail running jobs whose worker died on their last attempt, returns how many were failed
+    #[instrument(err, skip(self))]
+    pub async fn fail_abandoned(&self, lease_seconds: f64) -> Result<u64> {
+        let result = crate::named_query!(
+            "job_fail_abandoned",
+            sqlx::query!(
+                r#"
+                UPDATE job
+                SET status = 'failed',
+                    last_error = 'The worker stopped before the job finished',
+                    finished_at = NOW()
+                WHERE status = 'running'
+                  AND locked_at < NOW() - make_interval(secs => $1)
+                  AND attempts >= max_attempts
+                "#,
+                lease_seconds,
+            )
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Queue a failed job again with fresh attempts. None when the job is not failed or
+    /// another job with its dedup key is already queued.
+    #[instrument(err, skip(self))]
+    pub async fn retry(&self, id: Uuid) -> Result<Option<Job>> {
+        let job = crate::named_query!(
+            "job_retry",
+            sqlx::query_as!(
+                Job,
+                r#"
+                UPDATE job
+                SET status = 'pending', attempts = 0, run_at = NOW(), locked_by = NULL,
+                    locked_at = NULL, finished_at = NULL
+                WHERE id = $1
+                  AND status = 'failed'
+                  AND NOT EXISTS (
+                      SELECT 1 FROM job queued
+                      WHERE queued.dedup_key = job.dedup_key
+                        AND queued.status IN ('pending', 'running')
+                  )
+                RETURNING id, kind as "kind: JobKind", payload, status as "status: JobStatus",
+                    dedup_key, attempts, max_attempts, run_at, locked_by, locked_at, last_error,
+                    created_at, finished_at
+                "#,
+                id,
+            )
+            .fetch_optional(self.db.get_pool())
+        )?;
+
+        Ok(job)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<Job>> {
+        let job = crate::named_query!(
+            "job_get_by_id",
+            sqlx::query_as!(
+                Job,
+                r#"
+                SELECT id, kind as "kind: JobKind", payload, status as "status: JobStatus",
+                    dedup_key, attempts, max_attempts, run_at, locked_by, locked_at, last_error,
+                    created_at, finished_at
+                FROM job
+                WHERE id = $1
+                "#,
+                id,
+            )
+            .fetch_optional(self.db.get_pool())
+        )?;
+
+        Ok(job)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn filter(&self, query: JobQueryDto) -> Result<Vec<Job>> {
+        let jobs = crate::named_query!(
+            "job_filter",
+            sqlx::query_as!(
+                Job,
+                r#"
+                SELECT id, kind as "kind: JobKind", payload, status as "status: JobStatus",
+                    dedup_key, attempts, max_attempts, run_at, locked_by, locked_at, last_error,
+                    created_at, finished_at
+                FROM job
+                WHERE ($1::job_status IS NULL OR status = $1)
+                  AND ($2::job_kind IS NULL OR kind = $2)
+                  AND ($3::TIMESTAMPTZ IS NULL OR created_at < $3)
+                ORDER BY created_at DESC
+                LIMIT $4
+                "#,
+                query.status as Option<JobStatus>,
+                query.kind as Option<JobKind>,
+                query.cursor,
+                query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+            )
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(jobs)
+    }
+
+    /// Delete finished jobs older than `before`, returns how many were deleted
+    #[instrument(err, skip(self))]
+    pub async fn delete_finished(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = crate::named_query!(
+            "job_delete_finished",
+            sqlx::query!(
+                r#"
+                DELETE FROM job
+                WHERE status IN ('succeeded', 'failed') AND finished_at < $1
+                "#,
+                before,
+            )
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Create or update a schedule. The next run is only moved when the cron expression changed.
+    #[instrument(err, skip(self, payload))]
+    pub async fn upsert_schedule(
+        &self,
+        name: &str,
+        kind: JobKind,
+        payload: Value,
+        cron: &str,
+        next_run_at: DateTime<Utc>,
+    ) -> Result<()> {
+        crate::named_query!(
+            "job_schedule_upsert",
+            sqlx::query!(
+                r#"
+                INSERT INTO job_schedule (name, kind, payload, cron, next_run_at)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (name) DO UPDATE
+                SET kind = EXCLUDED.kind,
+                    payload = EXCLUDED.payload,
+                    cron = EXCLUDED.cron,
+                    next_run_at = CASE
+                        WHEN job_schedule.cron = EXCLUDED.cron THEN job_schedule.next_run_at
+                        ELSE EXCLUDED.next_run_at
+                    END
+                "#,
+                name,
+                kind as JobKind,
+                payload,
+                cron,
+                next_run_at,
+            )
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
+
+    /// Remove schedules that are no longer defined
+    #[instrument(err, skip(self))]
+    pub async fn delete_schedules_except(&self, names: &[String]) -> Result<u64> {
+        let result = crate::named_query!(
+            "job_schedule_delete_except",
+            sqlx::query!("DELETE FROM job_schedule WHERE NOT (name = ANY($1))", names,)
+                .execute(self.db.get_pool())
+        )?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn list_schedules(&self) -> Result<Vec<JobSchedule>> {
+        let schedules = crate::named_query!(
+            "job_schedule_list",
+            sqlx::query_as!(
+                JobSchedule,
+                r#"
+                SELECT name, kind as "kind: JobKind", payload, cron, next_run_at, last_run_at,
+                    last_job_id
+                FROM job_schedule
+                ORDER BY name
+                "#
+            )
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(schedules)
+    }
+
+    /// Move a due schedule on to its next run. Only one instance wins for each run,
+    /// returns false when another one already advanced the schedule.
+    #[instrument(err, skip(self))]
+    pub async fn advance_schedule(
+        &self,
+        name: &str,
+        due_at: DateTime<Utc>,
+        next_run_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let result = crate::named_query!(
+            "job_schedule_advance",
+            sqlx::query!(
+                r#"
+                UPDATE job_schedule
+                SET next_run_at = $3, last_run_at = NOW()
+                WHERE name = $1 AND next_run_at = $2
+                "#,
+                name,
+                due_at,
+                next_run_at,
+            )
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn set_schedule_job(&self, name: &str, job_id: Uuid) -> Result<()> {
+        crate::named_query!(
+            "job_schedule_set_job",
+            sqlx::query!(
+                "UPDATE job_schedule SET last_job_id = $2 WHERE name = $1",
+                name,
+                job_id,
+            )
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
+}
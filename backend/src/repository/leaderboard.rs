@@ -0,0 +1,298 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, prelude::FromRow, Row};
+use std::sync::Arc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    config::database::{Database, DatabaseTrait},
+    dto::{
+        leaderboard::{LeaderboardEntryDto, ReadLeaderboardDto},
+        user::read_user::ReadUserDto,
+    },
+};
+
+// The viewer and every friend whose visibility allows friends to see them,
+// users that opted out of leaderboards are never included
+const FRIEND_MEMBERS: &str = r#"
+    SELECT u.id, u.displayname, u.avatar_url, u.visibility_flags
+    FROM "user" u
+    WHERE u.leaderboard_opt_out IS NOT TRUE
+      AND (
+        u.id = $1
+        OR (
+            (u.visibility_flags & 1) = 1
+            AND EXISTS (
+                SELECT 1
+                FROM friend f
+                WHERE f.deleted IS NOT TRUE
+                  AND (
+                        (f.friend_1_id = $1 AND f.friend_2_id = u.id)
+                     OR (f.friend_2_id = $1 AND f.friend_1_id = u.id)
+                  )
+            )
+        )
+      )
+"#;
+
+#[derive(Clone)]
+pub struct LeaderboardRepository {
+    db: Arc<Database>,
+}
+
+pub struct LeaderboardFilter {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub category_ids: Option<Vec<Uuid>>,
+    pub tag_ids: Option<Vec<Uuid>>,
+    pub limit: i64,
+}
+
+struct LeaderboardEntryRow(LeaderboardEntryDto);
+
+impl FromRow<'_, PgRow> for LeaderboardEntryRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self(LeaderboardEntryDto {
+            rank: row.try_get("rank")?,
+            user: ReadUserDto {
+                id: row.try_get("id")?,
+                username: row.try_get("displayname")?,
+                avatar_url: row.try_get("avatar_url")?,
+                visibility_flags: row.try_get("visibility_flags")?,
+            },
+            minutes: row.try_get("minutes")?,
+            session_count: row.try_get("session_count")?,
+        }))
+    }
+}
+
+impl LeaderboardRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db: Arc::clone(db_conn),
+        }
+    }
+
+    /// Ranks the viewer and every friend whose visibility allows friends to see them.
+    /// Users that opted out of leaderboards are never included.
+    #[instrument(err, skip(self, filter), fields(viewer_id = %viewer_id))]
+    pub async fn rank_friends(
+        &self,
+        viewer_id: &str,
+        filter: &LeaderboardFilter,
+    ) -> Result<Vec<LeaderboardEntryDto>> {
+        let query = format!(
+            r#"
+                WITH members AS ({FRIEND_MEMBERS}),
+                totals AS (
+                    SELECT
+                        m.id,
+                        m.displayname,
+                        m.avatar_url,
+                        m.visibility_flags,
+                        CAST(COALESCE(SUM(
+                            EXTRACT(EPOCH FROM (LEAST(s.end_time, $3) - GREATEST(s.start_time, $2)))
+                        ), 0) / 60 AS FLOAT8) AS minutes,
+                        COUNT(s.id) AS session_count
+                    FROM members m
+                    LEFT JOIN session s
+                        ON s.user_id = m.id
+                        AND s.type = 'fixed'
+                        AND s.start_time < $3
+                        AND s.end_time > $2
+                        AND ($4::uuid[] IS NULL OR s.category_id = ANY($4))
+                        AND ($5::uuid[] IS NULL OR EXISTS (
+                            SELECT 1
+                            FROM tag_to_session tts
+                            WHERE tts.session_id = s.id AND tts.tag_id = ANY($5)
+                        ))
+                    GROUP BY m.id, m.displayname, m.avatar_url, m.visibility_flags
+                )
+                SELECT
+                    RANK() OVER (ORDER BY t.minutes DESC) AS rank,
+                    t.*
+                FROM totals t
+                ORDER BY t.minutes DESC, t.displayname ASC
+                LIMIT $6
+            "#
+        );
+
+        let rows = crate::named_query!(
+            "leaderboard_rank_friends",
+            sqlx::query_as::<_, LeaderboardEntryRow>(&query)
+                .bind(viewer_id)
+                .bind(filter.from)
+                .bind(filter.to)
+                .bind(&filter.category_ids)
+                .bind(&filter.tag_ids)
+                .bind(filter.limit)
+                .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    /// Changes whenever someone joins or leaves the viewer's friends leaderboard, e.g. through
+    /// a new or removed friendship, a block, a visibility change or an opt-out
+    #[instrument(err, skip(self), fields(viewer_id = %viewer_id))]
+    pub async fn friend_members_fingerprint(&self, viewer_id: &str) -> Result<String> {
+        let query = format!(
+            r#"
+                SELECT md5(COALESCE(string_agg(m.id, ',' ORDER BY m.id), ''))
+                FROM ({FRIEND_MEMBERS}) m
+            "#
+        );
+
+        let fingerprint: String = crate::named_query!(
+            "leaderboard_friend_members_fingerprint",
+            sqlx::query_scalar(&query)
+                .bind(viewer_id)
+                .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(fingerprint)
+    }
+
+    #[instrument(err, skip(self), fields(cache_key = %cache_key))]
+    pub async fn get_cached(&self, cache_key: &str) -> Result<Option<ReadLeaderboardDto>> {
+        let row = crate::named_query!(
+            "leaderboard_cache_get",
+            sqlx::query(
+                r#"
+                SELECT period_start, period_end, computed_at, entries
+                FROM leaderboard_cache
+                WHERE cache_key = $1 AND expires_at > NOW()
+            "#,
+            )
+            .bind(cache_key)
+            .fetch_optional(self.db.get_pool())
+        )?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        Ok(Some(ReadLeaderboardDto {
+            period_start: row.try_get("period_start")?,
+            period_end: row.try_get("period_end")?,
+            computed_at: row.try_get("computed_at")?,
+            entries: serde_json::from_value(row.try_get("entries")?)?,
+        }))
+    }
+
+    #[instrument(err, skip(self, leaderboard), fields(cache_key = %cache_key, viewer_id = %viewer_id))]
+    pub async fn store_cached(
+        &self,
+        cache_key: &str,
+        viewer_id: &str,
+        leaderboard: &ReadLeaderboardDto,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let member_ids: Vec<String> = leaderboard
+            .entries
+            .iter()
+            .map(|entry| entry.user.id.clone())
+            .collect();
+
+        crate::named_query!(
+            "leaderboard_cache_store",
+            sqlx::query(
+                r#"
+                INSERT INTO leaderboard_cache
+                    (cache_key, viewer_id, member_ids, period_start, period_end, entries, computed_at, expires_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (cache_key) DO UPDATE SET
+                    member_ids = EXCLUDED.member_ids,
+                    entries = EXCLUDED.entries,
+                    computed_at = EXCLUDED.computed_at,
+                    expires_at = EXCLUDED.expires_at
+            "#,
+            )
+            .bind(cache_key)
+            .bind(viewer_id)
+            .bind(member_ids)
+            .bind(leaderboard.period_start)
+            .bind(leaderboard.period_end)
+            .bind(serde_json::to_value(&leaderboard.entries)?)
+            .bind(leaderboard.computed_at)
+            .bind(expires_at)
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
+
+    /// Drops every cached leaderboard the user appears in, was computed for,
+    /// or that was computed for one of their friends (and could now include them)
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn invalidate_for_user(&self, user_id: &str) -> Result<u64> {
+        let result = crate::named_query!(
+            "leaderboard_cache_invalidate_user",
+            sqlx::query(
+                r#"
+                DELETE FROM leaderboard_cache lc
+                WHERE lc.viewer_id = $1
+                   OR $1 = ANY(lc.member_ids)
+                   OR EXISTS (
+                        SELECT 1
+                        FROM friend f
+                        WHERE f.deleted IS NOT TRUE
+                          AND (
+                                (f.friend_1_id = $1 AND f.friend_2_id = lc.viewer_id)
+                             OR (f.friend_2_id = $1 AND f.friend_1_id = lc.viewer_id)
+                          )
+                   )
+            "#,
+            )
+            .bind(user_id)
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Drops cached leaderboards past their expiry, returns how many were removed
+    #[instrument(err, skip(self))]
+    pub async fn delete_expired(&self) -> Result<u64> {
+        let result = crate::named_query!(
+            "leaderboard_cache_delete_expired",
+            sqlx::query("DELETE FROM leaderboard_cache WHERE expires_at < NOW()")
+                .execute(self.db.get_pool())
+        )?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn get_opt_out(&self, user_id: &str) -> Result<bool> {
+        let opt_out: bool = crate::named_query!(
+            "leaderboard_get_opt_out",
+            sqlx::query_scalar(r#"SELECT leaderboard_opt_out FROM "user" WHERE id = $1"#)
+                .bind(user_id)
+                .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(opt_out)
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id, opt_out = opt_out))]
+    pub async fn set_opt_out(&self, user_id: &str, opt_out: bool) -> Result<bool> {
+        let opt_out: bool = crate::named_query!(
+            "leaderboard_set_opt_out",
+            sqlx::query_scalar(
+                r#"
+                UPDATE "user"
+                SET leaderboard_opt_out = $1
+                WHERE id = $2
+                RETURNING leaderboard_opt_out
+            "#,
+            )
+            .bind(opt_out)
+            .bind(user_id)
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(opt_out)
+    }
+}
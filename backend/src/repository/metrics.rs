@@ -0,0 +1,455 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, prelude::FromRow, Row};
+use std::sync::Arc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    config::database::{Database, DatabaseTrait},
+    dto::metrics::{
+        ReadQueryLatencyDto, ReadRequestQueryDto, ReadRouteLatencyDto, ReadSlowRequestDto,
+        ReadStatusCodeRateDto,
+    },
+};
+
+// Percentiles are read from the merged latency histogram of a group: the bound of the
+// first histogram bucket that holds the given share of its samples
+const HISTOGRAM_PERCENTILES: &str = r#"
+    metrics_latency_bucket_bound(MIN(latency_bucket) FILTER (WHERE below >= 0.5 * total)) AS p50_ms,
+    metrics_latency_bucket_bound(MIN(latency_bucket) FILTER (WHERE below >= 0.95 * total)) AS p95_ms,
+    metrics_latency_bucket_bound(MIN(latency_bucket) FILTER (WHERE below >= 0.99 * total)) AS p99_ms
+"#;
+
+#[derive(Clone)]
+pub struct MetricsRepository {
+    db: Arc<Database>,
+}
+
+struct RouteLatencyRow(ReadRouteLatencyDto);
+
+impl FromRow<'_, PgRow> for RouteLatencyRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self(ReadRouteLatencyDto {
+            bucket_start: row.try_get("bucket_start")?,
+            method: row.try_get("method")?,
+            route: row.try_get("route")?,
+            request_count: row.try_get("request_count")?,
+            p50_ms: row.try_get("p50_ms")?,
+            p95_ms: row.try_get("p95_ms")?,
+            p99_ms: row.try_get("p99_ms")?,
+        }))
+    }
+}
+
+struct QueryLatencyRow(ReadQueryLatencyDto);
+
+impl FromRow<'_, PgRow> for QueryLatencyRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self(ReadQueryLatencyDto {
+            bucket_start: row.try_get("bucket_start")?,
+            query_name: row.try_get("query_name")?,
+            query_count: row.try_get("query_count")?,
+            p50_ms: row.try_get("p50_ms")?,
+            p95_ms: row.try_get("p95_ms")?,
+            p99_ms: row.try_get("p99_ms")?,
+        }))
+    }
+}
+
+struct StatusCodeRateRow(ReadStatusCodeRateDto);
+
+impl FromRow<'_, PgRow> for StatusCodeRateRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self(ReadStatusCodeRateDto {
+            bucket_start: row.try_get("bucket_start")?,
+            status_code: row.try_get("status_code")?,
+            request_count: row.try_get("request_count")?,
+            rate: row.try_get("rate")?,
+        }))
+    }
+}
+
+struct SlowRequestRow(ReadSlowRequestDto);
+
+impl FromRow<'_, PgRow> for SlowRequestRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self(ReadSlowRequestDto {
+            request_id: row.try_get("request_id")?,
+            method: row.try_get("method")?,
+            path: row.try_get("path")?,
+            status_code: row.try_get("status_code")?,
+            duration_ms: row.try_get("duration_ms")?,
+            user_id: row.try_get("user_id")?,
+            created_at: row.try_get("created_at")?,
+            queries: vec![],
+        }))
+    }
+}
+
+struct RequestQueryRow {
+    request_id: Uuid,
+    query: ReadRequestQueryDto,
+}
+
+impl FromRow<'_, PgRow> for RequestQueryRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            request_id: row.try_get("request_id")?,
+            query: ReadRequestQueryDto {
+                query_name: row.try_get("query_name")?,
+                duration_ms: row.try_get("duration_ms")?,
+                created_at: row.try_get("created_at")?,
+            },
+        })
+    }
+}
+
+impl MetricsRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db: Arc::clone(db_conn),
+        }
+    }
+
+    /// Latency percentiles per route, histograms of raw rows and hourly rollups are combined
+    #[instrument(err, skip(self))]
+    pub async fn route_latency(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        bucket: &str,
+    ) -> Result<Vec<ReadRouteLatencyDto>> {
+        let query = format!(
+            r#"
+            WITH histogram AS (
+                SELECT
+                    date_trunc($3, created_at) AS bucket_start,
+                    method,
+                    route,
+                    metrics_latency_bucket(duration_ms) AS latency_bucket,
+                    COUNT(*) AS request_count
+                FROM metrics_handler
+                WHERE created_at >= $1 AND created_at < $2
+                GROUP BY 1, 2, 3, 4
+                UNION ALL
+                SELECT date_trunc($3, bucket_start), method, route, latency_bucket, request_count
+                FROM metrics_handler_latency_rollup
+                WHERE bucket_start >= $1 AND bucket_start < $2
+            ),
+            merged AS (
+                SELECT bucket_start, method, route, latency_bucket, SUM(request_count) AS request_count
+                FROM histogram
+                GROUP BY 1, 2, 3, 4
+            ),
+            cumulative AS (
+                SELECT
+                    *,
+                    SUM(request_count) OVER (PARTITION BY bucket_start, method, route ORDER BY latency_bucket) AS below,
+                    SUM(request_count) OVER (PARTITION BY bucket_start, method, route) AS total
+                FROM merged
+            )
+            SELECT
+                bucket_start,
+                method,
+                route,
+                MAX(total)::BIGINT AS request_count,
+                {HISTOGRAM_PERCENTILES}
+            FROM cumulative
+            GROUP BY bucket_start, method, route
+            ORDER BY bucket_start, request_count DESC
+        "#
+        );
+
+        let rows = crate::named_query!(
+            "metrics_route_latency",
+            sqlx::query_as::<_, RouteLatencyRow>(&query)
+                .bind(from)
+                .bind(to)
+                .bind(bucket)
+                .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    /// Latency percentiles per `named_query!`, histograms of raw rows and hourly rollups are combined
+    #[instrument(err, skip(self))]
+    pub async fn query_latency(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        bucket: &str,
+    ) -> Result<Vec<ReadQueryLatencyDto>> {
+        let query = format!(
+            r#"
+            WITH histogram AS (
+                SELECT
+                    date_trunc($3, created_at) AS bucket_start,
+                    query_name,
+                    metrics_latency_bucket(duration_ms) AS latency_bucket,
+                    COUNT(*) AS query_count
+                FROM metrics_db_query
+                WHERE created_at >= $1 AND created_at < $2
+                GROUP BY 1, 2, 3
+                UNION ALL
+                SELECT date_trunc($3, bucket_start), query_name, latency_bucket, query_count
+                FROM metrics_db_query_latency_rollup
+                WHERE bucket_start >= $1 AND bucket_start < $2
+            ),
+            merged AS (
+                SELECT bucket_start, query_name, latency_bucket, SUM(query_count) AS query_count
+                FROM histogram
+                GROUP BY 1, 2, 3
+            ),
+            cumulative AS (
+                SELECT
+                    *,
+                    SUM(query_count) OVER (PARTITION BY bucket_start, query_name ORDER BY latency_bucket) AS below,
+                    SUM(query_count) OVER (PARTITION BY bucket_start, query_name) AS total
+                FROM merged
+            )
+            SELECT
+                bucket_start,
+                query_name,
+                MAX(total)::BIGINT AS query_count,
+                {HISTOGRAM_PERCENTILES}
+            FROM cumulative
+            GROUP BY bucket_start, query_name
+            ORDER BY bucket_start, query_count DESC
+        "#
+        );
+
+        let rows = crate::named_query!(
+            "metrics_query_latency",
+            sqlx::query_as::<_, QueryLatencyRow>(&query)
+                .bind(from)
+                .bind(to)
+                .bind(bucket)
+                .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    /// Requests per status code and their share of all requests in the bucket
+    #[instrument(err, skip(self))]
+    pub async fn status_code_rates(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        bucket: &str,
+    ) -> Result<Vec<ReadStatusCodeRateDto>> {
+        let rows = crate::named_query!(
+            "metrics_status_code_rates",
+            sqlx::query_as::<_, StatusCodeRateRow>(
+                r#"
+                WITH samples AS (
+                    SELECT date_trunc($3, created_at) AS bucket_start, status_code, COUNT(*) AS request_count
+                    FROM metrics_handler
+                    WHERE created_at >= $1 AND created_at < $2
+                    GROUP BY 1, 2
+                    UNION ALL
+                    SELECT date_trunc($3, bucket_start), status_code, request_count
+                    FROM metrics_handler_rollup
+                    WHERE bucket_start >= $1 AND bucket_start < $2
+                ),
+                counts AS (
+                    SELECT bucket_start, status_code, SUM(request_count)::BIGINT AS request_count
+                    FROM samples
+                    GROUP BY bucket_start, status_code
+                )
+                SELECT
+                    bucket_start,
+                    status_code,
+                    request_count,
+                    request_count::FLOAT8 / SUM(request_count) OVER (PARTITION BY bucket_start) AS rate
+                FROM counts
+                ORDER BY bucket_start, status_code
+            "#,
+            )
+            .bind(from)
+            .bind(to)
+            .bind(bucket)
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    /// Slowest raw requests with the queries they ran, matched by request id
+    #[instrument(err, skip(self))]
+    pub async fn slowest_requests(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<ReadSlowRequestDto>> {
+        let rows = crate::named_query!(
+            "metrics_slowest_requests",
+            sqlx::query_as::<_, SlowRequestRow>(
+                r#"
+                SELECT request_id, method, path, status_code, duration_ms, user_id, created_at
+                FROM metrics_handler
+                WHERE created_at >= $1 AND created_at < $2
+                ORDER BY duration_ms DESC
+                LIMIT $3
+            "#,
+            )
+            .bind(from)
+            .bind(to)
+            .bind(limit)
+            .fetch_all(self.db.get_pool())
+        )?;
+        let mut requests: Vec<_> = rows.into_iter().map(|row| row.0).collect();
+
+        let request_ids: Vec<Uuid> = requests.iter().map(|request| request.request_id).collect();
+        let queries = crate::named_query!(
+            "metrics_request_queries",
+            sqlx::query_as::<_, RequestQueryRow>(
+                r#"
+                SELECT request_id, query_name, duration_ms, created_at
+                FROM metrics_db_query
+                WHERE request_id = ANY($1)
+                ORDER BY created_at
+            "#,
+            )
+            .bind(&request_ids)
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        for row in queries {
+            if let Some(request) = requests
+                .iter_mut()
+                .find(|request| request.request_id == row.request_id)
+            {
+                request.queries.push(row.query);
+            }
+        }
+
+        Ok(requests)
+    }
+
+    /// Aggregate raw rows older than the cutoff into hourly rollups and delete them.
+    /// Returns how many request and query rows were rolled up.
+    #[instrument(err, skip(self))]
+    pub async fn roll_up(&self, cutoff: DateTime<Utc>) -> Result<(u64, u64)> {
+        let mut tx = self.db.get_pool().begin().await?;
+
+        crate::named_query!(
+            "metrics_roll_up_handler",
+            sqlx::query(
+                r#"
+                INSERT INTO metrics_handler_rollup AS r
+                    (bucket_start, method, route, status_code, request_count,
+                     duration_sum_ms, duration_max_ms)
+                SELECT
+                    date_trunc('hour', created_at),
+                    method,
+                    route,
+                    status_code,
+                    COUNT(*),
+                    SUM(duration_ms),
+                    MAX(duration_ms)
+                FROM metrics_handler
+                WHERE created_at < $1
+                GROUP BY 1, 2, 3, 4
+                ON CONFLICT (bucket_start, method, route, status_code) DO UPDATE
+                SET request_count = r.request_count + EXCLUDED.request_count,
+                    duration_sum_ms = r.duration_sum_ms + EXCLUDED.duration_sum_ms,
+                    duration_max_ms = GREATEST(r.duration_max_ms, EXCLUDED.duration_max_ms)
+            "#,
+            )
+            .bind(cutoff)
+            .execute(&mut *tx)
+        )?;
+
+        crate::named_query!(
+            "metrics_roll_up_handler_latency",
+            sqlx::query(
+                r#"
+                INSERT INTO metrics_handler_latency_rollup AS r
+                    (bucket_start, method, route, latency_bucket, request_count)
+                SELECT
+                    date_trunc('hour', created_at),
+                    method,
+                    route,
+                    metrics_latency_bucket(duration_ms),
+                    COUNT(*)
+                FROM metrics_handler
+                WHERE created_at < $1
+                GROUP BY 1, 2, 3, 4
+                ON CONFLICT (bucket_start, method, route, latency_bucket) DO UPDATE
+                SET request_count = r.request_count + EXCLUDED.request_count
+            "#,
+            )
+            .bind(cutoff)
+            .execute(&mut *tx)
+        )?;
+
+        let handler_rows = crate::named_query!(
+            "metrics_delete_rolled_up_handler",
+            sqlx::query("DELETE FROM metrics_handler WHERE created_at < $1")
+                .bind(cutoff)
+                .execute(&mut *tx)
+        )?;
+
+        crate::named_query!(
+            "metrics_roll_up_db_query",
+            sqlx::query(
+                r#"
+                INSERT INTO metrics_db_query_rollup AS r
+                    (bucket_start, query_name, query_count, duration_sum_ms, duration_max_ms)
+                SELECT
+                    date_trunc('hour', created_at),
+                    query_name,
+                    COUNT(*),
+                    SUM(duration_ms),
+                    MAX(duration_ms)
+                FROM metrics_db_query
+                WHERE created_at < $1
+                GROUP BY 1, 2
+                ON CONFLICT (bucket_start, query_name) DO UPDATE
+                SET query_count = r.query_count + EXCLUDED.query_count,
+                    duration_sum_ms = r.duration_sum_ms + EXCLUDED.duration_sum_ms,
+                    duration_max_ms = GREATEST(r.duration_max_ms, EXCLUDED.duration_max_ms)
+            "#,
+            )
+            .bind(cutoff)
+            .execute(&mut *tx)
+        )?;
+
+        crate::named_query!(
+            "metrics_roll_up_db_query_latency",
+            sqlx::query(
+                r#"
+                INSERT INTO metrics_db_query_latency_rollup AS r
+                    (bucket_start, query_name, latency_bucket, query_count)
+                SELECT
+                    date_trunc('hour', created_at),
+                    query_name,
+                    metrics_latency_bucket(duration_ms),
+                    COUNT(*)
+                FROM metrics_db_query
+                WHERE created_at < $1
+                GROUP BY 1, 2, 3
+                ON CONFLICT (bucket_start, query_name, latency_bucket) DO UPDATE
+                SET query_count = r.query_count + EXCLUDED.query_count
+            "#,
+            )
+            .bind(cutoff)
+            .execute(&mut *tx)
+        )?;
+
+        let query_rows = crate::named_query!(
+            "metrics_delete_rolled_up_db_query",
+            sqlx::query("DELETE FROM metrics_db_query WHERE created_at < $1")
+                .bind(cutoff)
+                .execute(&mut *tx)
+        )?;
+
+        tx.commit().await?;
+
+        Ok((handler_rows.rows_affected(), query_rows.rows_affected()))
+    }
+}
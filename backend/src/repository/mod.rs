@@ -1,11 +1,21 @@
+pub mod account_deletion;
+pub mod achievement;
+pub mod audit;
 pub mod auth;
 pub mod category;
 pub mod db_backup;
 pub mod feed;
 pub mod fixed_session;
 pub mod friends;
+pub mod job;
+pub mod leaderboard;
+pub mod metrics;
+pub mod moderation;
 pub mod notification;
+pub mod platform_stats;
 pub mod project;
+pub mod project_invite;
+pub mod rate_limit;
 pub mod release;
 pub mod sandbox_lifecycle;
 pub mod session_template;
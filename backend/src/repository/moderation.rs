@@ -0,0 +1,375 @@
+use anyhow::Result;
+use sqlx::{postgres::PgRow, prelude::FromRow, Postgres, QueryBuilder, Row};
+use std::sync::Arc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    config::database::{Database, DatabaseTrait},
+    dto::{
+        moderation::{
+            CreateUserReportDto, FilterUserReportsDto, ReadBlockedUserDto, ReadSuspensionDto,
+            ReadUserReportDto, ReviewUserReportDto,
+        },
+        user::read_user::ReadUserDto,
+    },
+};
+
+const REPORT_COLUMNS: &str = r#"
+    r.id,
+    r.reason,
+    r.details,
+    r.status,
+    r.reviewed_by,
+    r.reviewed_at,
+    r.resolution_note,
+    r.created_at,
+
+    reporter.id AS reporter_id,
+    reporter.displayname AS reporter_username,
+    reporter.avatar_url AS reporter_avatar_url,
+    reporter.visibility_flags AS reporter_visibility_flags,
+    reported.id AS reported_id,
+    reported.displayname AS reported_username,
+    reported.avatar_url AS reported_avatar_url,
+    reported.visibility_flags AS reported_visibility_flags
+"#;
+
+#[derive(Clone)]
+pub struct ModerationRepository {
+    db: Arc<Database>,
+}
+
+struct UserReportRow(ReadUserReportDto);
+
+impl FromRow<'_, PgRow> for UserReportRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self(ReadUserReportDto {
+            id: row.try_get("id")?,
+            reporter: ReadUserDto {
+                id: row.try_get("reporter_id")?,
+                username: row.try_get("reporter_username")?,
+                avatar_url: row.try_get("reporter_avatar_url")?,
+                visibility_flags: row.try_get("reporter_visibility_flags")?,
+            },
+            reported: ReadUserDto {
+                id: row.try_get("reported_id")?,
+                username: row.try_get("reported_username")?,
+                avatar_url: row.try_get("reported_avatar_url")?,
+                visibility_flags: row.try_get("reported_visibility_flags")?,
+            },
+            reason: row.try_get("reason")?,
+            details: row.try_get("details")?,
+            status: row.try_get("status")?,
+            reviewed_by: row.try_get("reviewed_by")?,
+            reviewed_at: row.try_get("reviewed_at")?,
+            resolution_note: row.try_get("resolution_note")?,
+            created_at: row.try_get("created_at")?,
+        }))
+    }
+}
+
+struct BlockedUserRow(ReadBlockedUserDto);
+
+impl FromRow<'_, PgRow> for BlockedUserRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self(ReadBlockedUserDto {
+            user: ReadUserDto {
+                id: row.try_get("id")?,
+                username: row.try_get("displayname")?,
+                avatar_url: row.try_get("avatar_url")?,
+                visibility_flags: row.try_get("visibility_flags")?,
+            },
+            created_at: row.try_get("created_at")?,
+        }))
+    }
+}
+
+impl ModerationRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db: Arc::clone(db_conn),
+        }
+    }
+
+    /// Blocks the user and tears down everything connecting the two users:
+    /// the friendship, pending friend requests and feed subscriptions in both directions
+    #[instrument(err, skip(self), fields(blocker_id = %blocker_id, blocked_id = %blocked_id))]
+    pub async fn block_user(&self, blocker_id: &str, blocked_id: &str) -> Result<()> {
+        let mut tx = self.db.get_pool().begin().await?;
+
+        crate::named_query!(
+            "user_block_create",
+            sqlx::query(
+                r#"
+                INSERT INTO user_block (blocker_id, blocked_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+            "#,
+            )
+            .bind(blocker_id)
+            .bind(blocked_id)
+            .execute(tx.as_mut())
+        )?;
+
+        crate::named_query!(
+            "user_block_remove_friendship",
+            sqlx::query(
+                r#"
+                UPDATE friend
+                SET deleted = true, deleted_at = NOW()
+                WHERE ((friend_1_id = $1 AND friend_2_id = $2) OR (friend_1_id = $2 AND friend_2_id = $1))
+                  AND deleted IS NOT TRUE
+            "#,
+            )
+            .bind(blocker_id)
+            .bind(blocked_id)
+            .execute(tx.as_mut())
+        )?;
+
+        crate::named_query!(
+            "user_block_cancel_friend_requests",
+            sqlx::query(
+                r#"
+                UPDATE friend_request
+                SET status = 'cancelled', updated_at = NOW(), updated_to_status = 'cancelled'
+                WHERE ((requestor_id = $1 AND recipient_id = $2) OR (requestor_id = $2 AND recipient_id = $1))
+                  AND status = 'pending'
+            "#,
+            )
+            .bind(blocker_id)
+            .bind(blocked_id)
+            .execute(tx.as_mut())
+        )?;
+
+        crate::named_query!(
+            "user_block_remove_subscriptions",
+            sqlx::query(
+                r#"
+                DELETE FROM feed_subscription
+                WHERE source_type = 'user'
+                  AND ((subscriber_id = $1 AND source_id = $2) OR (subscriber_id = $2 AND source_id = $1))
+            "#,
+            )
+            .bind(blocker_id)
+            .bind(blocked_id)
+            .execute(tx.as_mut())
+        )?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    #[instrument(err, skip(self), fields(blocker_id = %blocker_id, blocked_id = %blocked_id))]
+    pub async fn unblock_user(&self, blocker_id: &str, blocked_id: &str) -> Result<()> {
+        crate::named_query!(
+            "user_block_delete",
+            sqlx::query("DELETE FROM user_block WHERE blocker_id = $1 AND blocked_id = $2")
+                .bind(blocker_id)
+                .bind(blocked_id)
+                .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
+
+    #[instrument(err, skip(self), fields(blocker_id = %blocker_id))]
+    pub async fn list_blocked_users(&self, blocker_id: &str) -> Result<Vec<ReadBlockedUserDto>> {
+        let rows = crate::named_query!(
+            "user_block_list",
+            sqlx::query_as::<_, BlockedUserRow>(
+                r#"
+                SELECT u.id, u.displayname, u.avatar_url, u.visibility_flags, b.created_at
+                FROM user_block b
+                JOIN "user" u ON u.id = b.blocked_id
+                WHERE b.blocker_id = $1
+                ORDER BY b.created_at DESC
+            "#,
+            )
+            .bind(blocker_id)
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    /// Whether either of the users blocked the other one
+    #[instrument(err, skip(self), fields(user_id = %user_id, other_user_id = %other_user_id))]
+    pub async fn is_blocked_between(&self, user_id: &str, other_user_id: &str) -> Result<bool> {
+        let blocked: bool = crate::named_query!(
+            "user_block_exists",
+            sqlx::query_scalar(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1
+                    FROM user_block
+                    WHERE (blocker_id = $1 AND blocked_id = $2) OR (blocker_id = $2 AND blocked_id = $1)
+                )
+            "#,
+            )
+            .bind(user_id)
+            .bind(other_user_id)
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(blocked)
+    }
+
+    #[instrument(err, skip(self, dto), fields(reporter_id = %reporter_id, reported_id = %dto.user_id))]
+    pub async fn create_report(
+        &self,
+        dto: CreateUserReportDto,
+        reporter_id: &str,
+    ) -> Result<ReadUserReportDto> {
+        let query = format!(
+            r#"
+            WITH r AS (
+                INSERT INTO user_report (reporter_id, reported_id, reason, details)
+                VALUES ($1, $2, $3, $4)
+                RETURNING *
+            )
+            SELECT {REPORT_COLUMNS}
+            FROM r
+            JOIN "user" reporter ON reporter.id = r.reporter_id
+            JOIN "user" reported ON reported.id = r.reported_id
+        "#
+        );
+
+        let row = crate::named_query!(
+            "user_report_create",
+            sqlx::query_as::<_, UserReportRow>(&query)
+                .bind(reporter_id)
+                .bind(dto.user_id)
+                .bind(dto.reason)
+                .bind(dto.details)
+                .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(row.0)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn filter_reports(
+        &self,
+        filter: FilterUserReportsDto,
+    ) -> Result<Vec<ReadUserReportDto>> {
+        let mut query: QueryBuilder<'_, Postgres> = QueryBuilder::new(format!(
+            r#"
+            SELECT {REPORT_COLUMNS}
+            FROM user_report r
+            JOIN "user" reporter ON reporter.id = r.reporter_id
+            JOIN "user" reported ON reported.id = r.reported_id
+            WHERE 1 = 1
+        "#
+        ));
+
+        if let Some(status) = filter.status {
+            query.push(" AND r.status = ").push_bind(status);
+        }
+
+        if let Some(reported_id) = filter.reported_id {
+            query.push(" AND r.reported_id = ").push_bind(reported_id);
+        }
+
+        query
+            .push(" ORDER BY r.created_at DESC LIMIT ")
+            .push_bind(filter.limit.unwrap_or(100));
+
+        let rows = crate::named_query!(
+            "user_report_filter",
+            query
+                .build_query_as::<UserReportRow>()
+                .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    #[instrument(err, skip(self), fields(report_id = %report_id))]
+    pub async fn get_report(&self, report_id: Uuid) -> Result<Option<ReadUserReportDto>> {
+        let query = format!(
+            r#"
+            SELECT {REPORT_COLUMNS}
+            FROM user_report r
+            JOIN "user" reporter ON reporter.id = r.reporter_id
+            JOIN "user" reported ON reported.id = r.reported_id
+            WHERE r.id = $1
+        "#
+        );
+
+        let row = crate::named_query!(
+            "user_report_get",
+            sqlx::query_as::<_, UserReportRow>(&query)
+                .bind(report_id)
+                .fetch_optional(self.db.get_pool())
+        )?;
+
+        Ok(row.map(|row| row.0))
+    }
+
+    #[instrument(err, skip(self, dto), fields(report_id = %report_id, reviewer_id = %reviewer_id, status = ?dto.status))]
+    pub async fn review_report(
+        &self,
+        report_id: Uuid,
+        dto: &ReviewUserReportDto,
+        reviewer_id: &str,
+    ) -> Result<Option<ReadUserReportDto>> {
+        let query = format!(
+            r#"
+            WITH r AS (
+                UPDATE user_report
+                SET status = $2, resolution_note = $3, reviewed_by = $4, reviewed_at = NOW()
+                WHERE id = $1
+                RETURNING *
+            )
+            SELECT {REPORT_COLUMNS}
+            FROM r
+            JOIN "user" reporter ON reporter.id = r.reporter_id
+            JOIN "user" reported ON reported.id = r.reported_id
+        "#
+        );
+
+        let row = crate::named_query!(
+            "user_report_review",
+            sqlx::query_as::<_, UserReportRow>(&query)
+                .bind(report_id)
+                .bind(dto.status)
+                .bind(&dto.resolution_note)
+                .bind(reviewer_id)
+                .fetch_optional(self.db.get_pool())
+        )?;
+
+        Ok(row.map(|row| row.0))
+    }
+
+    #[instrument(err, skip(self, reason), fields(user_id = %user_id))]
+    pub async fn set_suspension(
+        &self,
+        user_id: &str,
+        reason: Option<String>,
+    ) -> Result<ReadSuspensionDto> {
+        let row = crate::named_query!(
+            "user_set_suspension",
+            sqlx::query(
+                r#"
+                UPDATE "user"
+                SET
+                    suspended_at = CASE WHEN $2::text IS NULL THEN NULL ELSE NOW() END,
+                    suspension_reason = $2
+                WHERE id = $1
+                RETURNING id, suspended_at, suspension_reason
+            "#,
+            )
+            .bind(user_id)
+            .bind(reason)
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(ReadSuspensionDto {
+            user_id: row.try_get("id")?,
+            suspended_at: row.try_get("suspended_at")?,
+            suspension_reason: row.try_get("suspension_reason")?,
+        })
+    }
+}
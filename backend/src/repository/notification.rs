@@ -20,6 +20,7 @@ use crate::{
             Notification, NotificationSource, NotificationSourceTypeSql, NotificationType,
             NotificationTypeSql, SystemNotificationData,
         },
+        release::ReleaseAudience,
         visibility::VisibilityFlags,
     },
     router::clerk::Actor,
@@ -142,6 +143,9 @@ impl NotificationMapper {
             NotificationTypeSql::ProjectCompleted => Ok(NotificationType::ProjectCompleted(
                 serde_json::from_value(content)?,
             )),
+            NotificationTypeSql::ProjectInvite => Ok(NotificationType::ProjectInvite(
+                serde_json::from_value(content)?,
+            )),
             NotificationTypeSql::AdminSandboxFailedDeploy => Ok(
                 NotificationType::AdminSandboxFailedDeploy(serde_json::from_value(content)?),
             ),
@@ -151,6 +155,18 @@ impl NotificationMapper {
             NotificationTypeSql::AdminBackupFailed => Ok(
                 NotificationType::AdminBackupFailed(serde_json::from_value(content)?),
             ),
+            NotificationTypeSql::AdminUserReported => Ok(NotificationType::AdminUserReported(
+                serde_json::from_value(content)?,
+            )),
+            NotificationTypeSql::AchievementUnlocked => Ok(
+                NotificationType::AchievementUnlocked(serde_json::from_value(content)?),
+            ),
+            NotificationTypeSql::AuthNewLogin => Ok(NotificationType::AuthNewLogin(
+                serde_json::from_value(content)?,
+            )),
+            NotificationTypeSql::AuthAccountAccessed => Ok(
+                NotificationType::AuthAccountAccessed(serde_json::from_value(content)?),
+            ),
         }
     }
 
@@ -182,6 +198,10 @@ impl NotificationMapper {
                 NotificationTypeSql::ProjectCompleted,
                 serde_json::to_value(data)?,
             )),
+            NotificationType::ProjectInvite(data) => Ok((
+                NotificationTypeSql::ProjectInvite,
+                serde_json::to_value(data)?,
+            )),
             NotificationType::AdminSandboxFailedDeploy(data) => Ok((
                 NotificationTypeSql::AdminSandboxFailedDeploy,
                 serde_json::to_value(data)?,
@@ -194,6 +214,22 @@ impl NotificationMapper {
                 NotificationTypeSql::AdminBackupFailed,
                 serde_json::to_value(data)?,
             )),
+            NotificationType::AdminUserReported(data) => Ok((
+                NotificationTypeSql::AdminUserReported,
+                serde_json::to_value(data)?,
+            )),
+            NotificationType::AchievementUnlocked(data) => Ok((
+                NotificationTypeSql::AchievementUnlocked,
+                serde_json::to_value(data)?,
+            )),
+            NotificationType::AuthNewLogin(data) => Ok((
+                NotificationTypeSql::AuthNewLogin,
+                serde_json::to_value(data)?,
+            )),
+            NotificationType::AuthAccountAccessed(data) => Ok((
+                NotificationTypeSql::AuthAccountAccessed,
+                serde_json::to_value(data)?,
+            )),
         }
     }
 }
@@ -231,6 +267,61 @@ impl NotificationRepository {
         Ok(notification_id)
     }
 
+    /// Send the same notification to every user in a release audience,
+    /// deleted and suspended users are left out
+    #[instrument(err, skip(self, source, notification_type))]
+    pub async fn create_for_audience(
+        &self,
+        source: NotificationSource,
+        notification_type: NotificationType,
+        audience: ReleaseAudience,
+    ) -> Result<u64> {
+        let (notification_type, content) =
+            NotificationMapper::serialize_notification_type(notification_type)?;
+        let (source_id, source_type) = NotificationMapper::serialize_source(source);
+
+        let result = sqlx::query!(
+            r#"
+                INSERT INTO notification (user_id, notification_type, source_id, source_type, content)
+                SELECT u.id, $1, $2, $3, $4
+                FROM "user" u
+                WHERE u.deleted_at IS NULL
+                  AND u.suspended_at IS NULL
+                  AND (
+                      $5 = 'everyone'::release_audience
+                      OR u.role = 'admin'
+                      OR ($5 = 'beta'::release_audience AND u.beta_opt_in)
+                  )
+            "#,
+            notification_type as NotificationTypeSql,
+            source_id,
+            source_type as NotificationSourceTypeSql,
+            content,
+            audience as ReleaseAudience
+        )
+        .execute(self.db.get_pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Withdraw the announcements sent for a release, seen or not
+    #[instrument(err, skip(self), fields(release_id = %release_id))]
+    pub async fn delete_release_announcements(&self, release_id: Uuid) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM notification
+                WHERE notification_type = 'system:new_release'
+                  AND (content->>'release_id')::UUID = $1
+            "#,
+            release_id
+        )
+        .execute(self.db.get_pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     #[instrument(err, skip(self), fields(user_id = %user_id))]
     pub async fn get_notifications(
         &self,
@@ -386,4 +477,20 @@ impl NotificationRepository {
 
         Ok(result.rows_affected())
     }
+
+    /// Delete notifications of all users older than the specified date
+    #[instrument(err, skip(self))]
+    pub async fn delete_older_than(&self, before_date: DateTime<Local>) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM notification
+                WHERE created_at < $1
+            "#,
+            before_date
+        )
+        .execute(self.db.get_pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
 }
@@ -0,0 +1,242 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::{
+    config::database::{Database, DatabaseTrait},
+    dto::platform_stats::{
+        ReadActiveUsersDto, ReadApiTokenAdoptionDto, ReadDailyActivityDto,
+        ReadSandboxConversionDto, ReadSignupsDto,
+    },
+};
+
+#[derive(Clone)]
+pub struct PlatformStatsRepository {
+    db: Arc<Database>,
+}
+
+impl PlatformStatsRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db: Arc::clone(db_conn),
+        }
+    }
+
+    /// Roll up users active since `since` and refresh the materialized views,
+    /// returns how many active (day, user) pairs were added
+    #[instrument(err, skip(self))]
+    pub async fn refresh(&self, since: DateTime<Utc>) -> Result<i64> {
+        let added = crate::named_query!(
+            "platform_stats_rollup_active_users",
+            sqlx::query_scalar!(
+                r#"SELECT rollup_platform_active_users($1) AS "added!""#,
+                since,
+            )
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        // Concurrent refreshes keep the views readable while they are rebuilt
+        crate::named_query!(
+            "platform_stats_refresh_daily_activity",
+            sqlx::query!("REFRESH MATERIALIZED VIEW CONCURRENTLY platform_daily_activity")
+                .execute(self.db.get_pool())
+        )?;
+        crate::named_query!(
+            "platform_stats_refresh_daily_signups",
+            sqlx::query!("REFRESH MATERIALIZED VIEW CONCURRENTLY platform_daily_signups")
+                .execute(self.db.get_pool())
+        )?;
+
+        Ok(added)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn active_users(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ReadActiveUsersDto>> {
+        let rows = crate::named_query!(
+            "platform_stats_active_users",
+            sqlx::query_as!(
+                ReadActiveUsersDto,
+                r#"
+                SELECT
+                    series.day AS "day!",
+                    (
+                        SELECT COUNT(*) FROM platform_active_user a
+                        WHERE a.day = series.day
+                    ) AS "daily!",
+                    (
+                        SELECT COUNT(DISTINCT a.user_id) FROM platform_active_user a
+                        WHERE a.day BETWEEN series.day - 6 AND series.day
+                    ) AS "weekly!",
+                    (
+                        SELECT COUNT(DISTINCT a.user_id) FROM platform_active_user a
+                        WHERE a.day BETWEEN series.day - 29 AND series.day
+                    ) AS "monthly!"
+                FROM (
+                    SELECT generate_series($1::DATE, $2::DATE, INTERVAL '1 day')::DATE AS day
+                ) series
+                ORDER BY series.day
+                "#,
+                from,
+                to,
+            )
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn signups(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<ReadSignupsDto>> {
+        let rows = crate::named_query!(
+            "platform_stats_signups",
+            sqlx::query_as!(
+                ReadSignupsDto,
+                r#"
+                SELECT day AS "day!", provider AS "provider!", signups AS "signups!"
+                FROM platform_daily_signups
+                WHERE day BETWEEN $1 AND $2
+                ORDER BY day, provider
+                "#,
+                from,
+                to,
+            )
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows)
+    }
+
+    /// Every day of the range, days without activity are zero
+    #[instrument(err, skip(self))]
+    pub async fn daily_activity(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ReadDailyActivityDto>> {
+        let rows = crate::named_query!(
+            "platform_stats_daily_activity",
+            sqlx::query_as!(
+                ReadDailyActivityDto,
+                r#"
+                SELECT
+                    series.day AS "day!",
+                    COALESCE(a.sessions, 0) AS "sessions!",
+                    COALESCE(a.tracked_hours, 0) AS "tracked_hours!",
+                    COALESCE(a.tracking_users, 0) AS "tracking_users!",
+                    COALESCE(a.stopwatch_starts, 0) AS "stopwatch_starts!",
+                    COALESCE(a.stopwatch_users, 0) AS "stopwatch_users!",
+                    COALESCE(a.feed_events, 0) AS "feed_events!",
+                    COALESCE(a.feed_reactions, 0) AS "feed_reactions!",
+                    COALESCE(a.reacting_users, 0) AS "reacting_users!"
+                FROM (
+                    SELECT generate_series($1::DATE, $2::DATE, INTERVAL '1 day')::DATE AS day
+                ) series
+                LEFT JOIN platform_daily_activity a ON a.day = series.day
+                ORDER BY series.day
+                "#,
+                from,
+                to,
+            )
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn api_token_adoption(&self) -> Result<ReadApiTokenAdoptionDto> {
+        let row = crate::named_query!(
+            "platform_stats_api_token_adoption",
+            sqlx::query_as!(
+                ReadApiTokenAdoptionDto,
+                r#"
+                WITH tokens AS (
+                    SELECT
+                        COUNT(*) FILTER (WHERE active) AS active_tokens,
+                        COUNT(DISTINCT user_id) FILTER (WHERE active) AS users_with_active_tokens,
+                        COUNT(*) FILTER (WHERE recently_used) AS tokens_used_last_30_days,
+                        COUNT(DISTINCT user_id) FILTER (WHERE recently_used)
+                            AS users_using_tokens_last_30_days,
+                        COALESCE(SUM(usage_count), 0)::BIGINT AS total_requests
+                    FROM (
+                        SELECT
+                            user_id,
+                            usage_count,
+                            revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW()) AS active,
+                            last_used_at > NOW() - INTERVAL '30 days' AS recently_used
+                        FROM api_tokens
+                    ) t
+                ),
+                active_users AS (
+                    SELECT COUNT(DISTINCT user_id) AS monthly_active_users
+                    FROM platform_active_user
+                    WHERE day > (NOW() AT TIME ZONE 'UTC')::DATE - 30
+                )
+                SELECT
+                    tokens.active_tokens AS "active_tokens!",
+                    tokens.users_with_active_tokens AS "users_with_active_tokens!",
+                    tokens.tokens_used_last_30_days AS "tokens_used_last_30_days!",
+                    tokens.users_using_tokens_last_30_days AS "users_using_tokens_last_30_days!",
+                    tokens.total_requests AS "total_requests!",
+                    active_users.monthly_active_users AS "monthly_active_users!",
+                    COALESCE(
+                        tokens.users_using_tokens_last_30_days::FLOAT8
+                            / NULLIF(active_users.monthly_active_users, 0),
+                        0
+                    ) AS "adoption_rate!"
+                FROM tokens, active_users
+                "#
+            )
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(row)
+    }
+
+    /// Conversions of the running sandbox are counted live, ended ones were recorded on teardown
+    #[instrument(err, skip(self))]
+    pub async fn sandbox_conversions(&self, limit: i64) -> Result<Vec<ReadSandboxConversionDto>> {
+        let rows = crate::named_query!(
+            "platform_stats_sandbox_conversions",
+            sqlx::query_as!(
+                ReadSandboxConversionDto,
+                r#"
+                SELECT
+                    id,
+                    status,
+                    started_at,
+                    ended_at,
+                    unique_users,
+                    converted_users AS "converted_users!",
+                    COALESCE(converted_users::FLOAT8 / NULLIF(unique_users, 0), 0)
+                        AS "conversion_rate!"
+                FROM (
+                    SELECT
+                        id,
+                        status,
+                        started_at,
+                        ended_at,
+                        unique_users,
+                        CASE
+                            WHEN status = 'active' THEN sandbox_converted_guests()::INTEGER
+                            ELSE converted_users
+                        END AS converted_users
+                    FROM sandbox_lifecycle
+                    ORDER BY started_at DESC
+                    LIMIT $1
+                ) lifecycle
+                ORDER BY started_at DESC
+                "#,
+                limit,
+            )
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows)
+    }
+}
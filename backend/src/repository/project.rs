@@ -1,8 +1,8 @@
 use anyhow::Result;
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
-use sqlx::{Postgres, QueryBuilder};
-use std::sync::Arc;
+use sqlx::{postgres::PgRow, prelude::FromRow, Postgres, QueryBuilder, Row};
+use std::{collections::HashMap, sync::Arc};
 use tracing::instrument;
 use uuid::Uuid;
 
@@ -11,10 +11,12 @@ use crate::{
     dto::project::{
         create_project::CreateProjectDto,
         filter_project::FilterProjectDto,
+        project_member::{MemberTimeBreakdownDto, ReadProjectMemberDto},
         read_project::{ProjectStatsDto, ReadProjectDetailsDto},
         update_project::UpdateProjectDto,
     },
-    entity::project::Project,
+    dto::user::read_user::ReadUserDto,
+    entity::project::{Project, ProjectMemberRole},
     router::clerk::Actor,
 };
 
@@ -36,6 +38,71 @@ pub struct ReadProjectRow {
     updated_at: DateTime<Local>,
 }
 
+#[derive(Debug)]
+struct ReadProjectDetailsRow {
+    id: Uuid,
+    name: String,
+    description: Option<String>,
+    image_url: Option<String>,
+    color: String,
+    completed: bool,
+    task_count: i64,
+    completed_task_count: i64,
+    role: ProjectMemberRole,
+    created_at: DateTime<Local>,
+    updated_at: DateTime<Local>,
+}
+
+#[derive(Debug)]
+struct ProjectStatsRow {
+    total_projects: i64,
+    active_projects: i64,
+    completed_projects: i64,
+    total_tasks: i64,
+    total_sessions: i64,
+    total_time_minutes: Option<f64>,
+}
+
+struct ProjectMemberRow(ReadProjectMemberDto);
+
+impl FromRow<'_, PgRow> for ProjectMemberRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self(ReadProjectMemberDto {
+            user: ReadUserDto {
+                id: row.try_get("id")?,
+                username: row.try_get("displayname")?,
+                avatar_url: row.try_get("avatar_url")?,
+                visibility_flags: row.try_get("visibility_flags")?,
+            },
+            role: row.try_get("role")?,
+            created_at: row.try_get("created_at")?,
+        }))
+    }
+}
+
+struct MemberTimeBreakdownRow {
+    project_id: Uuid,
+    breakdown: MemberTimeBreakdownDto,
+}
+
+impl FromRow<'_, PgRow> for MemberTimeBreakdownRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            project_id: row.try_get("project_id")?,
+            breakdown: MemberTimeBreakdownDto {
+                user: ReadUserDto {
+                    id: row.try_get("id")?,
+                    username: row.try_get("displayname")?,
+                    avatar_url: row.try_get("avatar_url")?,
+                    visibility_flags: row.try_get("visibility_flags")?,
+                },
+                session_count: row.try_get("session_count")?,
+                total_time_minutes: row.try_get("total_time_minutes")?,
+            },
+        })
+    }
+}
+
 pub trait ProjectRepositoryTrait {
     async fn create(&self, dto: CreateProjectDto, actor: &Actor) -> Result<Project>;
     async fn update(&self, dto: UpdateProjectDto, actor: &Actor) -> Result<Project>;
@@ -48,6 +115,25 @@ pub trait ProjectRepositoryTrait {
         actor: &Actor,
     ) -> Result<Vec<ReadProjectDetailsDto>>;
     async fn get_project_statistics(&self, actor: &Actor) -> Result<ProjectStatsDto>;
+    async fn get_member_role(
+        &self,
+        project_id: Uuid,
+        user_id: &str,
+    ) -> Result<Option<ProjectMemberRole>>;
+    async fn list_members(&self, project_id: Uuid) -> Result<Vec<ReadProjectMemberDto>>;
+    async fn upsert_member(
+        &self,
+        project_id: Uuid,
+        user_id: &str,
+        role: ProjectMemberRole,
+    ) -> Result<()>;
+    async fn remove_member(&self, project_id: Uuid, user_id: &str) -> Result<()>;
+    async fn count_owners(&self, project_id: Uuid) -> Result<i64>;
+    async fn get_member_time_breakdown(
+        &self,
+        project_ids: &[Uuid],
+        task_id: Option<Uuid>,
+    ) -> Result<Vec<(Uuid, MemberTimeBreakdownDto)>>;
     fn new(db: &Arc<Database>) -> Self;
     fn mapper(&self, row: ReadProjectRow) -> Project;
 }
@@ -122,7 +208,8 @@ impl ProjectRepositoryTrait for ProjectRepository {
                     project.created_at,
                     project.updated_at
                 FROM project
-                WHERE project.user_id =
+                JOIN project_member pm ON pm.project_id = project.id
+                WHERE pm.user_id =
             ",
         );
         query.push_bind(&actor.user_id);
@@ -154,7 +241,14 @@ impl ProjectRepositoryTrait for ProjectRepository {
         sqlx::query!(
             r#"
                 DELETE FROM project
-                WHERE project.id = $1 AND project.user_id = $2
+                WHERE project.id = $1
+                    AND EXISTS (
+                        SELECT 1
+                        FROM project_member pm
+                        WHERE pm.project_id = project.id
+                            AND pm.user_id = $2
+                            AND pm.role = 'owner'
+                    )
             "#,
             id,
             actor.user_id
@@ -199,7 +293,13 @@ impl ProjectRepositoryTrait for ProjectRepository {
                     color = COALESCE($5, color),
                     completed = COALESCE($6, completed)
                 WHERE id = $1
-                    AND user_id = $7
+                    AND EXISTS (
+                        SELECT 1
+                        FROM project_member pm
+                        WHERE pm.project_id = p.id
+                            AND pm.user_id = $7
+                            AND pm.role IN ('owner', 'editor')
+                    )
                 RETURNING
                     id,
                     name,
@@ -233,7 +333,7 @@ impl ProjectRepositoryTrait for ProjectRepository {
         let rows = crate::named_query!(
             "project_details",
             sqlx::query_as!(
-                ReadProjectDetailsDto,
+                ReadProjectDetailsRow,
                 r#"
                 SELECT
                     p.id,
@@ -244,12 +344,13 @@ impl ProjectRepositoryTrait for ProjectRepository {
                     p.completed,
                     COUNT(t.id) as "task_count!",
                     COUNT(CASE WHEN t.completed = true THEN 1 END) as "completed_task_count!",
+                    pm.role as "role: ProjectMemberRole",
                     p.created_at,
                     p.updated_at
                 FROM project p
+                JOIN project_member pm ON pm.project_id = p.id AND pm.user_id = $1
                 LEFT JOIN task t ON t.project_id = p.id
-                WHERE p.user_id = $1
-                GROUP BY p.id
+                GROUP BY p.id, pm.role
                 ORDER BY p.completed ASC, p.updated_at DESC
             "#,
                 actor.user_id
@@ -257,7 +358,36 @@ impl ProjectRepositoryTrait for ProjectRepository {
             .fetch_all(self.db.get_pool())
         )?;
 
-        Ok(rows)
+        let project_ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+        let mut breakdowns: HashMap<Uuid, Vec<MemberTimeBreakdownDto>> = HashMap::new();
+        for (project_id, breakdown) in self
+            .get_member_time_breakdown(&project_ids, None)
+            .await?
+        {
+            breakdowns.entry(project_id).or_default().push(breakdown);
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let member_breakdown = breakdowns.remove(&row.id).unwrap_or_default();
+
+                ReadProjectDetailsDto {
+                    id: row.id,
+                    name: row.name,
+                    description: row.description,
+                    image_url: row.image_url,
+                    color: row.color,
+                    completed: row.completed,
+                    task_count: row.task_count,
+                    completed_task_count: row.completed_task_count,
+                    role: row.role,
+                    member_breakdown,
+                    created_at: row.created_at,
+                    updated_at: row.updated_at,
+                }
+            })
+            .collect())
     }
 
     #[instrument(err, skip(self), fields(actor_id = %actor))]
@@ -265,7 +395,7 @@ impl ProjectRepositoryTrait for ProjectRepository {
         let stats = crate::named_query!(
             "project_statistics",
             sqlx::query_as!(
-                ProjectStatsDto,
+                ProjectStatsRow,
                 r#"
                 SELECT
                     COUNT(DISTINCT p.id) as "total_projects!",
@@ -275,15 +405,194 @@ impl ProjectRepositoryTrait for ProjectRepository {
                     COUNT(DISTINCT s.id) as "total_sessions!",
                     CAST(SUM(EXTRACT(EPOCH FROM (s.end_time - s.start_time)) / 60.0) AS DOUBLE PRECISION) as "total_time_minutes"
                 FROM project p
+                JOIN project_member pm ON pm.project_id = p.id AND pm.user_id = $1
                 LEFT JOIN task t ON t.project_id = p.id
                 LEFT JOIN session s ON s.task_id = t.id
-                WHERE p.user_id = $1
             "#,
                 actor.user_id
             )
             .fetch_one(self.db.get_pool())
         )?;
 
-        Ok(stats)
+        let project_ids: Vec<Uuid> = crate::named_query!(
+            "project_member_project_ids",
+            sqlx::query_scalar("SELECT project_id FROM project_member WHERE user_id = $1")
+                .bind(&actor.user_id)
+                .fetch_all(self.db.get_pool())
+        )?;
+
+        // the same user can be a member of several projects, sum their time across all of them
+        let mut member_breakdown: Vec<MemberTimeBreakdownDto> = Vec::new();
+        for (_, breakdown) in self
+            .get_member_time_breakdown(&project_ids, None)
+            .await?
+        {
+            match member_breakdown
+                .iter_mut()
+                .find(|existing| existing.user.id == breakdown.user.id)
+            {
+                Some(existing) => {
+                    existing.session_count += breakdown.session_count;
+                    existing.total_time_minutes += breakdown.total_time_minutes;
+                }
+                None => member_breakdown.push(breakdown),
+            }
+        }
+        member_breakdown.sort_by(|a, b| b.total_time_minutes.total_cmp(&a.total_time_minutes));
+
+        Ok(ProjectStatsDto {
+            total_projects: stats.total_projects,
+            active_projects: stats.active_projects,
+            completed_projects: stats.completed_projects,
+            total_tasks: stats.total_tasks,
+            total_sessions: stats.total_sessions,
+            total_time_minutes: stats.total_time_minutes,
+            member_breakdown,
+        })
+    }
+
+    #[instrument(err, skip(self), fields(project_id = %project_id, user_id = %user_id))]
+    async fn get_member_role(
+        &self,
+        project_id: Uuid,
+        user_id: &str,
+    ) -> Result<Option<ProjectMemberRole>> {
+        let role = crate::named_query!(
+            "project_member_role",
+            sqlx::query_scalar(
+                "SELECT role FROM project_member WHERE project_id = $1 AND user_id = $2"
+            )
+            .bind(project_id)
+            .bind(user_id)
+            .fetch_optional(self.db.get_pool())
+        )?;
+
+        Ok(role)
+    }
+
+    #[instrument(err, skip(self), fields(project_id = %project_id))]
+    async fn list_members(&self, project_id: Uuid) -> Result<Vec<ReadProjectMemberDto>> {
+        let rows = crate::named_query!(
+            "project_member_list",
+            sqlx::query_as::<_, ProjectMemberRow>(
+                r#"
+                SELECT
+                    u.id,
+                    u.displayname,
+                    u.avatar_url,
+                    u.visibility_flags,
+                    pm.role,
+                    pm.created_at
+                FROM project_member pm
+                JOIN "user" u ON u.id = pm.user_id
+                WHERE pm.project_id = $1
+                ORDER BY pm.role ASC, pm.created_at ASC
+            "#,
+            )
+            .bind(project_id)
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    #[instrument(err, skip(self), fields(project_id = %project_id, user_id = %user_id, role = ?role))]
+    async fn upsert_member(
+        &self,
+        project_id: Uuid,
+        user_id: &str,
+        role: ProjectMemberRole,
+    ) -> Result<()> {
+        crate::named_query!(
+            "project_member_upsert",
+            sqlx::query(
+                r#"
+                INSERT INTO project_member (project_id, user_id, role)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (project_id, user_id) DO UPDATE SET role = EXCLUDED.role
+            "#,
+            )
+            .bind(project_id)
+            .bind(user_id)
+            .bind(role)
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
+
+    #[instrument(err, skip(self), fields(project_id = %project_id, user_id = %user_id))]
+    async fn remove_member(&self, project_id: Uuid, user_id: &str) -> Result<()> {
+        crate::named_query!(
+            "project_member_remove",
+            sqlx::query("DELETE FROM project_member WHERE project_id = $1 AND user_id = $2")
+                .bind(project_id)
+                .bind(user_id)
+                .execute(self.db.get_pool())
+        )?;
+
+        Ok(())
+    }
+
+    #[instrument(err, skip(self), fields(project_id = %project_id))]
+    async fn count_owners(&self, project_id: Uuid) -> Result<i64> {
+        let count = crate::named_query!(
+            "project_member_count_owners",
+            sqlx::query_scalar(
+                "SELECT COUNT(*) FROM project_member WHERE project_id = $1 AND role = 'owner'"
+            )
+            .bind(project_id)
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(count)
+    }
+
+    /// Time every current member logged on the given projects, optionally restricted to a single task
+    #[instrument(err, skip(self, project_ids), fields(project_count = project_ids.len()))]
+    async fn get_member_time_breakdown(
+        &self,
+        project_ids: &[Uuid],
+        task_id: Option<Uuid>,
+    ) -> Result<Vec<(Uuid, MemberTimeBreakdownDto)>> {
+        if project_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = crate::named_query!(
+            "project_member_time_breakdown",
+            sqlx::query_as::<_, MemberTimeBreakdownRow>(
+                r#"
+                SELECT
+                    pm.project_id,
+                    u.id,
+                    u.displayname,
+                    u.avatar_url,
+                    u.visibility_flags,
+                    COUNT(s.id) AS session_count,
+                    CAST(COALESCE(SUM(EXTRACT(EPOCH FROM (s.end_time - s.start_time)) / 60), 0) AS FLOAT8) AS total_time_minutes
+                FROM project_member pm
+                JOIN "user" u ON u.id = pm.user_id
+                LEFT JOIN session s
+                    ON s.user_id = pm.user_id
+                    AND ($2::uuid IS NULL OR s.task_id = $2)
+                    AND (
+                        s.project_id = pm.project_id
+                        OR s.task_id IN (SELECT t.id FROM task t WHERE t.project_id = pm.project_id)
+                    )
+                WHERE pm.project_id = ANY($1)
+                GROUP BY pm.project_id, u.id, u.displayname, u.avatar_url, u.visibility_flags
+                ORDER BY total_time_minutes DESC
+            "#,
+            )
+            .bind(project_ids)
+            .bind(task_id)
+            .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.project_id, row.breakdown))
+            .collect())
     }
 }
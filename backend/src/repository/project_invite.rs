@@ -0,0 +1,333 @@
+use anyhow::Result;
+use sqlx::{postgres::PgRow, prelude::FromRow, Row};
+use std::sync::Arc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    config::database::{Database, DatabaseTrait},
+    dto::{project::project_invite::ReadProjectInviteDto, user::read_user::ReadUserDto},
+    entity::project::ProjectMemberRole,
+};
+
+const INVITE_COLUMNS: &str = r#"
+    i.id,
+    i.project_id,
+    p.name AS project_name,
+    i.role,
+    i.status,
+    i.created_at,
+
+    invitee.id AS invitee_id,
+    invitee.displayname AS invitee_username,
+    invitee.avatar_url AS invitee_avatar_url,
+    invitee.visibility_flags AS invitee_visibility_flags,
+    inviter.id AS inviter_id,
+    inviter.displayname AS inviter_username,
+    inviter.avatar_url AS inviter_avatar_url,
+    inviter.visibility_flags AS inviter_visibility_flags
+"#;
+
+const INVITE_JOINS: &str = r#"
+    JOIN project p ON p.id = i.project_id
+    JOIN "user" invitee ON invitee.id = i.invitee_id
+    LEFT JOIN "user" inviter ON inviter.id = i.inviter_id
+"#;
+
+#[derive(Clone)]
+pub struct ProjectInviteRepository {
+    db: Arc<Database>,
+}
+
+struct ProjectInviteRow(ReadProjectInviteDto);
+
+impl FromRow<'_, PgRow> for ProjectInviteRow {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let inviter_id: Option<String> = row.try_get("inviter_id")?;
+        let inviter = match inviter_id {
+            Some(id) => Some(ReadUserDto {
+                id,
+                username: row.try_get("inviter_username")?,
+                avatar_url: row.try_get("inviter_avatar_url")?,
+                visibility_flags: row.try_get("inviter_visibility_flags")?,
+            }),
+            None => None,
+        };
+
+        Ok(Self(ReadProjectInviteDto {
+            id: row.try_get("id")?,
+            project_id: row.try_get("project_id")?,
+            project_name: row.try_get("project_name")?,
+            invitee: ReadUserDto {
+                id: row.try_get("invitee_id")?,
+                username: row.try_get("invitee_username")?,
+                avatar_url: row.try_get("invitee_avatar_url")?,
+                visibility_flags: row.try_get("invitee_visibility_flags")?,
+            },
+            inviter,
+            role: row.try_get("role")?,
+            status: row.try_get("status")?,
+            created_at: row.try_get("created_at")?,
+        }))
+    }
+}
+
+impl ProjectInviteRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db: Arc::clone(db_conn),
+        }
+    }
+
+    /// Whether the two users are friends and neither of them blocked the other one
+    #[instrument(err, skip(self), fields(user_id = %user_id, other_user_id = %other_user_id))]
+    pub async fn can_invite(&self, user_id: &str, other_user_id: &str) -> Result<bool> {
+        let allowed: bool = crate::named_query!(
+            "project_invite_can_invite",
+            sqlx::query_scalar(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1
+                    FROM friend f
+                    WHERE f.deleted IS NOT TRUE
+                      AND (
+                            (f.friend_1_id = $1 AND f.friend_2_id = $2)
+                         OR (f.friend_1_id = $2 AND f.friend_2_id = $1)
+                      )
+                ) AND NOT EXISTS (
+                    SELECT 1
+                    FROM user_block b
+                    WHERE (b.blocker_id = $1 AND b.blocked_id = $2)
+                       OR (b.blocker_id = $2 AND b.blocked_id = $1)
+                )
+            "#,
+            )
+            .bind(user_id)
+            .bind(other_user_id)
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(allowed)
+    }
+
+    #[instrument(err, skip(self), fields(project_id = %project_id, user_id = %user_id))]
+    pub async fn is_member(&self, project_id: Uuid, user_id: &str) -> Result<bool> {
+        let member: bool = crate::named_query!(
+            "project_invite_is_member",
+            sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM project_member WHERE project_id = $1 AND user_id = $2)"
+            )
+            .bind(project_id)
+            .bind(user_id)
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(member)
+    }
+
+    /// Returns `None` when the user already has a pending invite to the project
+    #[instrument(err, skip(self), fields(project_id = %project_id, invitee_id = %invitee_id, inviter_id = %inviter_id))]
+    pub async fn create(
+        &self,
+        project_id: Uuid,
+        invitee_id: &str,
+        inviter_id: &str,
+        role: ProjectMemberRole,
+    ) -> Result<Option<ReadProjectInviteDto>> {
+        let query = format!(
+            r#"
+            WITH i AS (
+                INSERT INTO project_invite (project_id, invitee_id, inviter_id, role)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (project_id, invitee_id) WHERE status = 'pending' DO NOTHING
+                RETURNING *
+            )
+            SELECT {INVITE_COLUMNS}
+            FROM i
+            {INVITE_JOINS}
+        "#
+        );
+
+        let row = crate::named_query!(
+            "project_invite_create",
+            sqlx::query_as::<_, ProjectInviteRow>(&query)
+                .bind(project_id)
+                .bind(invitee_id)
+                .bind(inviter_id)
+                .bind(role)
+                .fetch_optional(self.db.get_pool())
+        )?;
+
+        Ok(row.map(|row| row.0))
+    }
+
+    #[instrument(err, skip(self), fields(project_id = %project_id))]
+    pub async fn list_pending_for_project(
+        &self,
+        project_id: Uuid,
+    ) -> Result<Vec<ReadProjectInviteDto>> {
+        let query = format!(
+            r#"
+            SELECT {INVITE_COLUMNS}
+            FROM project_invite i
+            {INVITE_JOINS}
+            WHERE i.project_id = $1 AND i.status = 'pending'
+            ORDER BY i.created_at DESC
+        "#
+        );
+
+        let rows = crate::named_query!(
+            "project_invite_list_for_project",
+            sqlx::query_as::<_, ProjectInviteRow>(&query)
+                .bind(project_id)
+                .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    #[instrument(err, skip(self), fields(invitee_id = %invitee_id))]
+    pub async fn list_pending_for_invitee(
+        &self,
+        invitee_id: &str,
+    ) -> Result<Vec<ReadProjectInviteDto>> {
+        let query = format!(
+            r#"
+            SELECT {INVITE_COLUMNS}
+            FROM project_invite i
+            {INVITE_JOINS}
+            WHERE i.invitee_id = $1 AND i.status = 'pending'
+            ORDER BY i.created_at DESC
+        "#
+        );
+
+        let rows = crate::named_query!(
+            "project_invite_list_for_invitee",
+            sqlx::query_as::<_, ProjectInviteRow>(&query)
+                .bind(invitee_id)
+                .fetch_all(self.db.get_pool())
+        )?;
+
+        Ok(rows.into_iter().map(|row| row.0).collect())
+    }
+
+    /// Accepts a pending invite addressed to the user and adds them to the project.
+    /// The invite can't be accepted anymore once the invitee and the inviter stopped
+    /// being friends or one of them blocked the other one
+    #[instrument(err, skip(self), fields(invite_id = %invite_id, invitee_id = %invitee_id))]
+    pub async fn accept(
+        &self,
+        invite_id: Uuid,
+        invitee_id: &str,
+    ) -> Result<Option<ReadProjectInviteDto>> {
+        let mut tx = self.db.get_pool().begin().await?;
+
+        let accepted: Option<(Uuid, ProjectMemberRole)> = crate::named_query!(
+            "project_invite_accept",
+            sqlx::query_as(
+                r#"
+                UPDATE project_invite i
+                SET status = 'accepted', responded_at = NOW()
+                WHERE i.id = $1
+                  AND i.invitee_id = $2
+                  AND i.status = 'pending'
+                  AND EXISTS (
+                    SELECT 1
+                    FROM friend f
+                    WHERE f.deleted IS NOT TRUE
+                      AND (
+                            (f.friend_1_id = i.inviter_id AND f.friend_2_id = i.invitee_id)
+                         OR (f.friend_1_id = i.invitee_id AND f.friend_2_id = i.inviter_id)
+                      )
+                  )
+                  AND NOT EXISTS (
+                    SELECT 1
+                    FROM user_block b
+                    WHERE (b.blocker_id = i.inviter_id AND b.blocked_id = i.invitee_id)
+                       OR (b.blocker_id = i.invitee_id AND b.blocked_id = i.inviter_id)
+                  )
+                RETURNING i.project_id, i.role
+            "#,
+            )
+            .bind(invite_id)
+            .bind(invitee_id)
+            .fetch_optional(tx.as_mut())
+        )?;
+
+        let Some((project_id, role)) = accepted else {
+            return Ok(None);
+        };
+
+        crate::named_query!(
+            "project_invite_add_member",
+            sqlx::query(
+                r#"
+                INSERT INTO project_member (project_id, user_id, role)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (project_id, user_id) DO NOTHING
+            "#,
+            )
+            .bind(project_id)
+            .bind(invitee_id)
+            .bind(role)
+            .execute(tx.as_mut())
+        )?;
+
+        let query = format!(
+            r#"
+            SELECT {INVITE_COLUMNS}
+            FROM project_invite i
+            {INVITE_JOINS}
+            WHERE i.id = $1
+        "#
+        );
+        let row = crate::named_query!(
+            "project_invite_find",
+            sqlx::query_as::<_, ProjectInviteRow>(&query)
+                .bind(invite_id)
+                .fetch_one(tx.as_mut())
+        )?;
+
+        tx.commit().await?;
+
+        Ok(Some(row.0))
+    }
+
+    #[instrument(err, skip(self), fields(invite_id = %invite_id, invitee_id = %invitee_id))]
+    pub async fn decline(&self, invite_id: Uuid, invitee_id: &str) -> Result<bool> {
+        let res = crate::named_query!(
+            "project_invite_decline",
+            sqlx::query(
+                r#"
+                UPDATE project_invite
+                SET status = 'declined', responded_at = NOW()
+                WHERE id = $1 AND invitee_id = $2 AND status = 'pending'
+            "#,
+            )
+            .bind(invite_id)
+            .bind(invitee_id)
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    #[instrument(err, skip(self), fields(invite_id = %invite_id, project_id = %project_id))]
+    pub async fn cancel(&self, invite_id: Uuid, project_id: Uuid) -> Result<bool> {
+        let res = crate::named_query!(
+            "project_invite_cancel",
+            sqlx::query(
+                r#"
+                UPDATE project_invite
+                SET status = 'cancelled', responded_at = NOW()
+                WHERE id = $1 AND project_id = $2 AND status = 'pending'
+            "#,
+            )
+            .bind(invite_id)
+            .bind(project_id)
+            .execute(self.db.get_pool())
+        )?;
+
+        Ok(res.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::config::database::{Database, DatabaseTrait};
+
+#[derive(Clone)]
+pub struct RateLimitRepository {
+    db: Arc<Database>,
+}
+
+impl RateLimitRepository {
+    pub fn new(db_conn: &Arc<Database>) -> Self {
+        Self {
+            db: Arc::clone(db_conn),
+        }
+    }
+
+    /// Count a hit in the window of the key, returns the hits of the window so far
+    #[instrument(err, skip(self))]
+    pub async fn hit(
+        &self,
+        key: &str,
+        window_start: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<i32> {
+        let hits: i32 = crate::named_query!(
+            "rate_limit_hit",
+            sqlx::query_scalar(
+                r#"
+                INSERT INTO rate_limit_bucket (key, window_start, hits, expires_at)
+                VALUES ($1, $2, 1, $3)
+                ON CONFLICT (key, window_start)
+                DO UPDATE SET hits = rate_limit_bucket.hits + 1
+                RETURNING hits
+            "#,
+            )
+            .bind(key)
+            .bind(window_start)
+            .bind(expires_at)
+            .fetch_one(self.db.get_pool())
+        )?;
+
+        Ok(hits)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn delete_expired(&self) -> Result<u64> {
+        let result = crate::named_query!(
+            "rate_limit_delete_expired",
+            sqlx::query("DELETE FROM rate_limit_bucket WHERE expires_at < NOW()")
+                .execute(self.db.get_pool())
+        )?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 use sqlx::{QueryBuilder, Row};
 use std::sync::Arc;
@@ -8,7 +9,7 @@ use uuid::Uuid;
 use crate::{
     config::database::{Database, DatabaseTrait},
     dto::release::{CreateReleaseDto, ReleaseListQueryDto, UpdateReleaseDto},
-    entity::release::{Release, ReleaseUser},
+    entity::release::{Release, ReleaseAudience, ReleaseUser},
 };
 
 #[derive(Clone)]
@@ -25,17 +26,20 @@ impl ReleaseRepository {
     pub async fn create_release(&self, dto: CreateReleaseDto) -> Result<Uuid> {
         let id = Uuid::new_v4();
         let tags_json = serde_json::to_value(&dto.tags)?;
+        let changelog_json = serde_json::to_value(&dto.changelog)?;
 
         sqlx::query!(
             r#"
-                INSERT INTO release (id, version, name, short_description, tags, seo_title, seo_description, seo_keywords)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                INSERT INTO release (id, version, name, short_description, tags, changelog, audience, seo_title, seo_description, seo_keywords)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             "#,
             id,
             dto.version,
             dto.name,
             dto.short_description,
             tags_json,
+            changelog_json,
+            dto.audience as ReleaseAudience,
             dto.seo_title,
             dto.seo_description,
             dto.seo_keywords
@@ -51,7 +55,8 @@ impl ReleaseRepository {
         let row = sqlx::query!(
             r#"
                 SELECT r.id, r.version, r.name, r.short_description, r.released, r.released_at,
-                       r.released_by, r.tags, r.seo_title, r.seo_description, r.seo_keywords,
+                       r.released_by, r.tags, r.changelog, r.audience as "audience: ReleaseAudience",
+                       r.scheduled_at, r.seo_title, r.seo_description, r.seo_keywords,
                        r.created_at, r.updated_at,
                        u.id as "user_id?", u.displayname as "user_username?", u.avatar_url as "user_avatar_url?"
                 FROM release r
@@ -76,6 +81,9 @@ impl ReleaseRepository {
                 avatar_url: r.user_avatar_url,
             }),
             tags: serde_json::from_value(r.tags).unwrap_or_default(),
+            changelog: serde_json::from_value(r.changelog).unwrap_or_default(),
+            audience: r.audience,
+            scheduled_at: r.scheduled_at.map(|dt| dt.into()),
             seo_title: r.seo_title,
             seo_description: r.seo_description,
             seo_keywords: r.seo_keywords,
@@ -85,18 +93,31 @@ impl ReleaseRepository {
     }
 
     #[instrument(err, skip(self))]
-    pub async fn get_release_by_version(&self, version: String) -> Result<Option<Release>> {
+    /// Looks up a release by version, hiding releases outside the viewer's audience.
+    /// Anonymous viewers only see releases for everyone.
+    pub async fn get_release_by_version(
+        &self,
+        version: String,
+        viewer_id: Option<String>,
+    ) -> Result<Option<Release>> {
         let row = sqlx::query!(
             r#"
                 SELECT r.id, r.version, r.name, r.short_description, r.released, r.released_at,
-                       r.released_by, r.tags, r.seo_title, r.seo_description, r.seo_keywords,
+                       r.released_by, r.tags, r.changelog, r.audience as "audience: ReleaseAudience",
+                       r.scheduled_at, r.seo_title, r.seo_description, r.seo_keywords,
                        r.created_at, r.updated_at,
                        u.id as "user_id?", u.displayname as "user_username?", u.avatar_url as "user_avatar_url?"
                 FROM release r
                 LEFT JOIN "user" u ON r.released_by = u.id
                 WHERE r.version = $1
+                  AND (r.audience = 'everyone'::release_audience OR EXISTS (
+                      SELECT 1 FROM "user" v
+                      WHERE v.id = $2
+                        AND (v.role = 'admin' OR (r.audience = 'beta'::release_audience AND v.beta_opt_in))
+                  ))
             "#,
-            version
+            version,
+            viewer_id
         )
         .fetch_optional(self.db.get_pool())
         .await?;
@@ -114,6 +135,9 @@ impl ReleaseRepository {
                 avatar_url: r.user_avatar_url,
             }),
             tags: serde_json::from_value(r.tags).unwrap_or_default(),
+            changelog: serde_json::from_value(r.changelog).unwrap_or_default(),
+            audience: r.audience,
+            scheduled_at: r.scheduled_at.map(|dt| dt.into()),
             seo_title: r.seo_title,
             seo_description: r.seo_description,
             seo_keywords: r.seo_keywords,
@@ -126,18 +150,25 @@ impl ReleaseRepository {
     pub async fn list_releases(&self, query: ReleaseListQueryDto) -> Result<Vec<Release>> {
         let mut qb = QueryBuilder::new(
             "SELECT r.id, r.version, r.name, r.short_description, r.released, r.released_at,
-                    r.released_by, r.tags, r.seo_title, r.seo_description, r.seo_keywords,
-                    r.created_at, r.updated_at,
+                    r.released_by, r.tags, r.changelog, r.audience, r.scheduled_at,
+                    r.seo_title, r.seo_description, r.seo_keywords, r.created_at, r.updated_at,
                     u.id as user_id, u.displayname as user_username, u.avatar_url as user_avatar_url
              FROM release r
              LEFT JOIN \"user\" u ON r.released_by = u.id",
         );
 
+        qb.push(" WHERE TRUE");
+
         if query.released_only.unwrap_or(false) {
-            qb.push(" WHERE r.released = ");
+            qb.push(" AND r.released = ");
             qb.push_bind(true);
         }
 
+        if let Some(audience) = query.audience {
+            qb.push(" AND r.audience = ");
+            qb.push_bind(audience);
+        }
+
         qb.push(" ORDER BY r.released_at DESC NULLS LAST, r.created_at DESC");
 
         if let Some(limit) = query.limit {
@@ -175,6 +206,12 @@ impl ReleaseRepository {
                     }),
                     tags: serde_json::from_value(row.try_get::<Value, _>("tags")?)
                         .unwrap_or_default(),
+                    changelog: serde_json::from_value(row.try_get::<Value, _>("changelog")?)
+                        .unwrap_or_default(),
+                    audience: row.try_get("audience")?,
+                    scheduled_at: row
+                        .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("scheduled_at")?
+                        .map(|dt| dt.into()),
                     seo_title: row.try_get("seo_title")?,
                     seo_description: row.try_get("seo_description")?,
                     seo_keywords: row.try_get("seo_keywords")?,
@@ -202,7 +239,9 @@ impl ReleaseRepository {
                     tags = COALESCE($5, tags),
                     seo_title = COALESCE($6, seo_title),
                     seo_description = COALESCE($7, seo_description),
-                    seo_keywords = COALESCE($8, seo_keywords)
+                    seo_keywords = COALESCE($8, seo_keywords),
+                    changelog = COALESCE($9, changelog),
+                    audience = COALESCE($10, audience)
                 WHERE id = $1
             "#,
             release_id,
@@ -212,7 +251,9 @@ impl ReleaseRepository {
             dto.tags.map(|t| serde_json::to_value(t).unwrap()),
             dto.seo_title,
             dto.seo_description,
-            dto.seo_keywords
+            dto.seo_keywords,
+            dto.changelog.map(|c| serde_json::to_value(c).unwrap()),
+            dto.audience as Option<ReleaseAudience>
         )
         .execute(self.db.get_pool())
         .await?;
@@ -227,7 +268,9 @@ impl ReleaseRepository {
                 UPDATE release
                 SET released = true,
                     released_at = NOW(),
-                    released_by = $2
+                    released_by = $2,
+                    scheduled_at = NULL,
+                    scheduled_by = NULL
                 WHERE id = $1 AND released = false
             "#,
             release_id,
@@ -239,6 +282,76 @@ impl ReleaseRepository {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Publish the release at `scheduled_at` on behalf of `scheduled_by`, replaces an earlier schedule
+    #[instrument(err, skip(self))]
+    pub async fn schedule_release(
+        &self,
+        release_id: Uuid,
+        scheduled_at: DateTime<Utc>,
+        scheduled_by: String,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE release
+                SET scheduled_at = $2,
+                    scheduled_by = $3
+                WHERE id = $1 AND released = false
+            "#,
+            release_id,
+            scheduled_at,
+            scheduled_by
+        )
+        .execute(self.db.get_pool())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn cancel_schedule(&self, release_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE release
+                SET scheduled_at = NULL,
+                    scheduled_by = NULL
+                WHERE id = $1 AND released = false AND scheduled_at IS NOT NULL
+            "#,
+            release_id
+        )
+        .execute(self.db.get_pool())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Publish the next release whose schedule is due, as released at its scheduled time.
+    /// Locked rows are skipped so concurrent workers never publish the same release twice.
+    #[instrument(err, skip(self))]
+    pub async fn publish_next_due_release(&self) -> Result<Option<Uuid>> {
+        let id = sqlx::query_scalar!(
+            r#"
+                UPDATE release
+                SET released = true,
+                    released_at = scheduled_at,
+                    released_by = scheduled_by,
+                    scheduled_at = NULL,
+                    scheduled_by = NULL
+                WHERE id = (
+                    SELECT id FROM release
+                    WHERE released = false AND scheduled_at <= NOW()
+                    ORDER BY scheduled_at
+                    LIMIT 1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id
+            "#
+        )
+        .fetch_optional(self.db.get_pool())
+        .await?;
+
+        Ok(id)
+    }
+
     #[instrument(err, skip(self))]
     pub async fn unpublish_release(&self, release_id: Uuid) -> Result<bool> {
         let result = sqlx::query!(
@@ -247,7 +360,7 @@ impl ReleaseRepository {
                 SET released = false,
                     released_at = NULL,
                     released_by = NULL
-                WHERE id = $1
+                WHERE id = $1 AND released = true
             "#,
             release_id
         )
@@ -272,19 +385,27 @@ impl ReleaseRepository {
     }
 
     #[instrument(err, skip(self))]
-    pub async fn get_latest_released(&self) -> Result<Option<Release>> {
+    /// Latest published release within the viewer's audience
+    pub async fn get_latest_released(&self, viewer_id: Option<String>) -> Result<Option<Release>> {
         let row = sqlx::query!(
             r#"
                 SELECT r.id, r.version, r.name, r.short_description, r.released, r.released_at,
-                       r.released_by, r.tags, r.seo_title, r.seo_description, r.seo_keywords,
+                       r.released_by, r.tags, r.changelog, r.audience as "audience: ReleaseAudience",
+                       r.scheduled_at, r.seo_title, r.seo_description, r.seo_keywords,
                        r.created_at, r.updated_at,
                        u.id as "user_id?", u.displayname as "user_username?", u.avatar_url as "user_avatar_url?"
                 FROM release r
                 LEFT JOIN "user" u ON r.released_by = u.id
                 WHERE r.released = true
+                  AND (r.audience = 'everyone'::release_audience OR EXISTS (
+                      SELECT 1 FROM "user" v
+                      WHERE v.id = $1
+                        AND (v.role = 'admin' OR (r.audience = 'beta'::release_audience AND v.beta_opt_in))
+                  ))
                 ORDER BY r.released_at DESC
                 LIMIT 1
-            "#
+            "#,
+            viewer_id
         )
         .fetch_optional(self.db.get_pool())
         .await?;
@@ -302,6 +423,9 @@ impl ReleaseRepository {
                 avatar_url: r.user_avatar_url,
             }),
             tags: serde_json::from_value(r.tags).unwrap_or_default(),
+            changelog: serde_json::from_value(r.changelog).unwrap_or_default(),
+            audience: r.audience,
+            scheduled_at: r.scheduled_at.map(|dt| dt.into()),
             seo_title: r.seo_title,
             seo_description: r.seo_description,
             seo_keywords: r.seo_keywords,
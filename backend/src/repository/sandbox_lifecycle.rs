@@ -7,6 +7,21 @@ use crate::{
     entity::sandbox_lifecycle::{SandboxLifecycle, SandboxStatus},
 };
 
+/// Tables that outlive a recycle: migrations, the lifecycle history, the job queue and its
+/// cron schedules, the JWT signing keys and the audit trail
+const PRESERVED_TABLES: &[&str] = &[
+    "_sqlx_migrations",
+    "sandbox_lifecycle",
+    "job",
+    "job_schedule",
+    "jwt_signing_key",
+    "audit_log",
+];
+
+fn is_reset_on_recycle(table: &str) -> bool {
+    !PRESERVED_TABLES.contains(&table)
+}
+
 #[derive(Clone)]
 pub struct SandboxLifecycleRepository {
     db_conn: Arc<Database>,
@@ -92,7 +107,9 @@ impl SandboxLifecycleRepository {
                 SET torndown_by = $2,
                     torndown_type = $3,
                     status = 'recycled',
-                    ended_at = NOW()
+                    ended_at = NOW(),
+                    -- guests are wiped below, their conversions are kept on the lifecycle
+                    converted_users = sandbox_converted_guests()
                 WHERE id = $1
                 "#,
                 id,
@@ -103,28 +120,26 @@ impl SandboxLifecycleRepository {
             .await?;
         }
 
-        sqlx::query!(
-            r#"
-            DO $$
-            DECLARE
-                r RECORD;
-            BEGIN
-                FOR r IN (
-                    SELECT tablename
-                    FROM pg_tables
-                    WHERE schemaname = 'public'
-                    AND tablename != '_sqlx_migrations'
-                    AND tablename != 'sandbox_lifecycle'
-                )
-                LOOP
-                    EXECUTE 'TRUNCATE TABLE ' || quote_ident(r.tablename) || ' CASCADE';
-                END LOOP;
-            END $$;
-            "#
+        let tables = sqlx::query_scalar!(
+            r#"SELECT tablename AS "tablename!" FROM pg_tables WHERE schemaname = 'public'"#
         )
-        .execute(&mut *tx)
+        .fetch_all(&mut *tx)
         .await?;
 
+        let tables = tables
+            .iter()
+            .filter(|table| is_reset_on_recycle(table))
+            .map(|table| format!("\"{}\"", table.replace('"', "\"\"")))
+            .collect::<Vec<_>>();
+
+        // No CASCADE, a preserved table referencing a wiped one fails the recycle instead of
+        // being emptied along with it
+        if !tables.is_empty() {
+            sqlx::query(&format!("TRUNCATE TABLE {}", tables.join(", ")))
+                .execute(&mut *tx)
+                .await?;
+        }
+
         tx.commit().await?;
         Ok(())
     }
@@ -139,6 +154,17 @@ impl SandboxLifecycleRepository {
         Ok(())
     }
 
+    /// Start of the window in which the guest converts by tracking time
+    pub async fn mark_guest_assigned(&self, guest_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE "user" SET guest_assigned_at = NOW() WHERE id = $1 AND guest_assigned_at IS NULL"#,
+            guest_id
+        )
+        .execute(self.db_conn.get_pool())
+        .await?;
+        Ok(())
+    }
+
     pub async fn get_all(&self, limit: i64) -> Result<Vec<SandboxLifecycle>, sqlx::Error> {
         sqlx::query_as!(
             SandboxLifecycle,
@@ -196,3 +222,29 @@ impl SandboxLifecycleRepository {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_reset_on_recycle;
+
+    #[test]
+    fn recycle_keeps_job_queue_keys_and_audit_trail() {
+        for table in [
+            "_sqlx_migrations",
+            "sandbox_lifecycle",
+            "job",
+            "job_schedule",
+            "jwt_signing_key",
+            "audit_log",
+        ] {
+            assert!(!is_reset_on_recycle(table), "{table} must survive a recycle");
+        }
+    }
+
+    #[test]
+    fn recycle_wipes_user_data() {
+        for table in ["user", "session", "project", "task", "notification"] {
+            assert!(is_reset_on_recycle(table), "{table} must be wiped on recycle");
+        }
+    }
+}
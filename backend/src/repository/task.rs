@@ -66,7 +66,14 @@ impl TaskRepositoryTrait for TaskRepository {
                 ReadTaskRow,
                 r#"
                 INSERT INTO task (project_id, name, description, user_id)
-                VALUES ($1, $2, $3, $4)
+                SELECT $1::uuid, $2::varchar, $3::text, $4::varchar
+                WHERE EXISTS (
+                    SELECT 1
+                    FROM project_member pm
+                    WHERE pm.project_id = $1
+                        AND pm.user_id = $4
+                        AND pm.role IN ('owner', 'editor')
+                )
                 RETURNING
                     id,
                     project_id,
@@ -115,7 +122,8 @@ impl TaskRepositoryTrait for TaskRepository {
                     task.created_at,
                     task.updated_at
                 FROM task
-                WHERE task.user_id =
+                JOIN project_member pm ON pm.project_id = task.project_id
+                WHERE pm.user_id =
             ",
         );
         query.push_bind(&actor.user_id);
@@ -151,7 +159,14 @@ impl TaskRepositoryTrait for TaskRepository {
         sqlx::query!(
             r#"
                 DELETE FROM task
-                WHERE task.id = $1 AND task.user_id = $2
+                WHERE task.id = $1
+                    AND EXISTS (
+                        SELECT 1
+                        FROM project_member pm
+                        WHERE pm.project_id = task.project_id
+                            AND pm.user_id = $2
+                            AND pm.role IN ('owner', 'editor')
+                    )
             "#,
             id,
             actor.user_id
@@ -196,7 +211,13 @@ impl TaskRepositoryTrait for TaskRepository {
                     completed = COALESCE($4, t.completed)
                 WHERE
                     t.id = $1
-                    AND t.user_id = $5
+                    AND EXISTS (
+                        SELECT 1
+                        FROM project_member pm
+                        WHERE pm.project_id = t.project_id
+                            AND pm.user_id = $5
+                            AND pm.role IN ('owner', 'editor')
+                    )
                 RETURNING
                     t.id,
                     t.project_id,
@@ -233,8 +254,8 @@ impl TaskRepositoryTrait for TaskRepository {
                     COUNT(DISTINCT s.id) as "total_sessions!",
                     CAST(SUM(EXTRACT(EPOCH FROM (s.end_time - s.start_time)) / 60.0) AS DOUBLE PRECISION) as "total_time_minutes"
                 FROM task t
+                JOIN project_member pm ON pm.project_id = t.project_id AND pm.user_id = $1
                 LEFT JOIN session s ON s.task_id = t.id
-                WHERE t.user_id = $1
             "#,
                 actor.user_id
             )
@@ -270,8 +291,9 @@ impl TaskRepositoryTrait for TaskRepository {
                     COUNT(s.id) as "session_count!",
                     CAST(COALESCE(SUM(EXTRACT(EPOCH FROM (s.end_time - s.start_time)) / 60), 0.0) AS DOUBLE PRECISION) as "total_time_minutes!"
                 FROM task t
+                JOIN project_member pm ON pm.project_id = t.project_id AND pm.user_id = $2
                 LEFT JOIN session s ON s.task_id = t.id
-                WHERE t.id = ANY($1) AND t.user_id = $2
+                WHERE t.id = ANY($1)
                 GROUP BY t.id, t.project_id, t.name, t.description, t.completed, t.user_id, t.created_at, t.updated_at
                 ORDER BY t.completed ASC, t.updated_at DESC
             "#,
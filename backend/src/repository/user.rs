@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use sqlx::{Postgres, QueryBuilder};
 use std::sync::Arc;
 use tracing::instrument;
@@ -15,6 +16,16 @@ pub struct UserRepository {
     db_conn: Arc<Database>,
 }
 
+/// What the authentication extractors need to know about an account on every request
+#[derive(Debug, Clone)]
+pub struct AccountAccess {
+    pub role: UserRole,
+    pub suspended_at: Option<DateTime<Utc>>,
+    pub suspension_reason: Option<String>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub sessions_revoked_at: Option<DateTime<Utc>>,
+}
+
 #[derive(sqlx::FromRow, Debug)]
 pub struct ReadUserRow {
     id: String,
@@ -27,6 +38,11 @@ pub struct ReadUserRow {
 pub struct FilterUsersDto {
     pub id: Option<IdFilter>,
     pub name: Option<String>,
+    /// Leave out users that blocked this user or were blocked by them
+    pub visible_to: Option<String>,
+    /// Leave out accounts waiting to be erased
+    pub exclude_deleted: bool,
+    pub limit: Option<i64>,
 }
 
 #[derive(Debug)]
@@ -109,7 +125,7 @@ impl UserRepository {
             r#"
                 SELECT id, displayname, avatar_url, visibility_flags
                 FROM "user"
-                WHERE
+                WHERE (
             "#,
         );
 
@@ -137,6 +153,31 @@ impl UserRepository {
                 .push_bind_unseparated(pattern);
         }
 
+        query.push(")");
+
+        if filter.exclude_deleted {
+            query.push(" AND deleted_at IS NULL");
+        }
+
+        if let Some(viewer_id) = filter.visible_to {
+            query
+                .push(
+                    r#"
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM user_block b
+                    WHERE (b.blocker_id = "user".id AND b.blocked_id = "#,
+                )
+                .push_bind(viewer_id.clone())
+                .push(") OR (b.blocker_id = ")
+                .push_bind(viewer_id)
+                .push(r#" AND b.blocked_id = "user".id))"#);
+        }
+
+        if let Some(limit) = filter.limit {
+            query.push(" LIMIT ").push_bind(limit);
+        }
+
         let rows = query
             .build_query_as::<ReadUserRow>()
             .fetch_all(self.db_conn.get_pool())
@@ -210,6 +251,104 @@ impl UserRepository {
         }
     }
 
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn is_suspended(&self, user_id: &str) -> Result<bool> {
+        let suspended: Option<bool> = crate::named_query!(
+            "user_is_suspended",
+            sqlx::query_scalar(r#"SELECT suspended_at IS NOT NULL FROM "user" WHERE id = $1"#)
+                .bind(user_id)
+                .fetch_optional(self.db_conn.get_pool())
+        )?;
+
+        Ok(suspended.unwrap_or(false))
+    }
+
+    /// Role and standing of the account, None when the user does not exist
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn get_account_access(&self, user_id: &str) -> Result<Option<AccountAccess>> {
+        let access = sqlx::query_as!(
+            AccountAccess,
+            r#"
+                SELECT
+                    role AS "role: UserRole",
+                    suspended_at,
+                    suspension_reason,
+                    deleted_at,
+                    sessions_revoked_at
+                FROM "user"
+                WHERE id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(self.db_conn.get_pool())
+        .await?;
+
+        Ok(access)
+    }
+
+    /// Returns the role the user had before, None when the user does not exist or was deleted
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn set_role(&self, user_id: &str, role: UserRole) -> Result<Option<UserRole>> {
+        let previous = sqlx::query_scalar!(
+            r#"
+                UPDATE "user" u
+                SET role = $2
+                FROM "user" old
+                WHERE u.id = $1 AND old.id = u.id AND u.deleted_at IS NULL
+                RETURNING old.role AS "role: UserRole"
+            "#,
+            user_id,
+            role as UserRole
+        )
+        .fetch_optional(self.db_conn.get_pool())
+        .await?;
+
+        Ok(previous)
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn get_beta_opt_in(&self, user_id: &str) -> Result<Option<bool>> {
+        let opt_in = sqlx::query_scalar!(
+            r#"SELECT beta_opt_in FROM "user" WHERE id = $1 AND deleted_at IS NULL"#,
+            user_id
+        )
+        .fetch_optional(self.db_conn.get_pool())
+        .await?;
+
+        Ok(opt_in)
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn set_beta_opt_in(&self, user_id: &str, opt_in: bool) -> Result<Option<bool>> {
+        let opt_in = sqlx::query_scalar!(
+            r#"
+                UPDATE "user"
+                SET beta_opt_in = $2
+                WHERE id = $1 AND deleted_at IS NULL
+                RETURNING beta_opt_in
+            "#,
+            user_id,
+            opt_in
+        )
+        .fetch_optional(self.db_conn.get_pool())
+        .await?;
+
+        Ok(opt_in)
+    }
+
+    /// Invalidate the access tokens the user holds right now
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn revoke_access_tokens(&self, user_id: &str) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE "user" SET sessions_revoked_at = NOW() WHERE id = $1"#,
+            user_id
+        )
+        .execute(self.db_conn.get_pool())
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn get_admin_ids(&self) -> Result<Vec<String>> {
         let ids = sqlx::query_scalar!(
             r#"SELECT id FROM "user" WHERE role = 'admin'"#
@@ -0,0 +1,20 @@
+use axum::{extract::State, routing::get, Router};
+use tracing::instrument;
+
+use crate::{
+    dto::achievement::AchievementProgressDto,
+    router::{clerk::Actor, response::ApiResponse, root::AppState},
+};
+
+pub fn achievement_router() -> Router<AppState> {
+    Router::new().route("/", get(list_achievements_handler))
+}
+
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn list_achievements_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResponse<Vec<AchievementProgressDto>> {
+    let res = state.achievement_service.get_progress(&actor).await;
+    ApiResponse::from_result(res)
+}
@@ -0,0 +1,30 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use tracing::instrument;
+
+use crate::{
+    dto::audit::{AuditLogQueryDto, ReadAuditLogDto},
+    router::{admin::AdminUser, response::ApiResponse, root::AppState},
+};
+
+pub fn admin_audit_router() -> Router<AppState> {
+    Router::new().route("/", get(list_audit_log))
+}
+
+#[instrument(skip(state))]
+async fn list_audit_log(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<AuditLogQueryDto>,
+) -> Result<Json<ApiResponse<Vec<ReadAuditLogDto>>>, StatusCode> {
+    let entries = state.audit_service.list(query).await.map_err(|e| {
+        tracing::error!("Failed to list audit log: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(ApiResponse::Success { data: entries }))
+}
@@ -1,29 +1,32 @@
 use axum::{
     body::Body,
     extract::{Path, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, StatusCode},
     response::Response,
     routing::{get, post},
     Json, Router,
 };
-use serde::Deserialize;
+use serde_json::json;
+use tokio_util::io::ReaderStream;
 use tracing::instrument;
 
 use crate::{
-    dto::db_backup::ReadDbBackupDto,
-    router::{admin::AdminUser, response::ApiResponse, root::AppState},
+    dto::db_backup::{ReadBackupDownloadUrlDto, ReadDbBackupDto, ReadDbBackupRestoreDto},
+    entity::{
+        audit::{AuditAction, AuditTargetType},
+        db_backup::DbBackup,
+    },
+    router::{admin::AdminUser, request::Audit, response::ApiResponse, root::AppState},
+    service::backup_service::backup_file_name,
 };
 
 pub fn admin_backups_router() -> Router<AppState> {
     Router::new()
-        .route("/", get(get_backups))
+        .route("/", get(get_backups).post(trigger_backup))
+        .route("/restores", get(get_restores))
         .route("/{backup_id}/download", get(download_backup))
-        .route("/{backup_id}/notify", post(notify_backup_result))
-}
-
-#[derive(Debug, Deserialize)]
-struct NotifyBackupRequest {
-    secret: String,
+        .route("/{backup_id}/download-url", get(get_download_url))
+        .route("/{backup_id}/restore", post(restore_backup))
 }
 
 #[instrument(skip(state))]
@@ -31,137 +34,227 @@ async fn get_backups(
     State(state): State<AppState>,
     AdminUser(_admin): AdminUser,
 ) -> Result<Json<ApiResponse<Vec<ReadDbBackupDto>>>, StatusCode> {
-    let backups = state.db_backup_repo.get_all().await.map_err(|e| {
+    let backups = state.backup_service.list_backups().await.map_err(|e| {
         tracing::error!("Failed to get backups: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    let backups_dto: Vec<ReadDbBackupDto> =
-        backups.into_iter().map(ReadDbBackupDto::from).collect();
+    Ok(Json(ApiResponse::Success { data: backups }))
+}
 
-    Ok(Json(ApiResponse::Success { data: backups_dto }))
+#[instrument(skip(state))]
+async fn trigger_backup(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+) -> ApiResponse<ReadDbBackupDto> {
+    let res = state.backup_service.trigger_backup(&admin).await;
+
+    if let Ok(backup) = &res {
+        audit
+            .entry(AuditAction::BackupTriggered, Some(&admin.user_id))
+            .target(AuditTargetType::Backup, backup.id)
+            .changes(None::<()>, Some(json!({ "file": backup.backup_file })))
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
 }
 
 #[instrument(skip(state))]
-async fn download_backup(
+async fn restore_backup(
     State(state): State<AppState>,
-    AdminUser(_admin): AdminUser,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
     Path(backup_id): Path<i32>,
-) -> Result<Response, StatusCode> {
-    let backup = state
-        .db_backup_repo
-        .get_by_id(backup_id)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to get backup {}: {}", backup_id, e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+) -> ApiResponse<ReadDbBackupRestoreDto> {
+    let res = state
+        .backup_service
+        .request_restore(backup_id, &admin)
+        .await;
 
-    let backup = backup.ok_or(StatusCode::NOT_FOUND)?;
+    if let Ok(restore) = &res {
+        audit
+            .entry(AuditAction::BackupRestoreRequested, Some(&admin.user_id))
+            .target(AuditTargetType::Backup, backup_id)
+            .changes(
+                None::<()>,
+                Some(json!({
+                    "restore_id": restore.id,
+                    "target_database": restore.target_database,
+                })),
+            )
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
 
-    let bucket = &state.config.s3.bucket_name;
+#[instrument(skip(state))]
+async fn get_restores(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+) -> ApiResponse<Vec<ReadDbBackupRestoreDto>> {
+    let res = state.backup_service.list_restores().await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state, headers))]
+async fn download_backup(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    Path(backup_id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let backup = get_downloadable_backup(&state, backup_id).await?;
     let key = &backup.backup_file;
 
+    // Ranges are checked against the recorded size, S3 never sees an unsatisfiable one
+    let range = match (headers.get(header::RANGE), backup.backup_size_bytes) {
+        (Some(value), Some(size)) => match parse_range(value.to_str().unwrap_or_default(), size) {
+            Ok(range) => range,
+            Err(()) => {
+                return Ok(Response::builder()
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(header::CONTENT_RANGE, format!("bytes */{}", size))
+                    .body(Body::empty())
+                    .unwrap());
+            }
+        },
+        _ => None,
+    };
+
     let object = state
-        .s3_client
-        .get_object()
-        .bucket(bucket)
-        .key(key)
-        .send()
+        .backup_service
+        .backup_contents(key, range)
         .await
         .map_err(|e| {
             tracing::error!("Failed to get backup file from S3: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    let bytes = object
-        .body
-        .collect()
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to read backup file bytes: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .into_bytes();
-
-    let filename = key.split('/').next_back().unwrap_or("backup.dump");
+    // Every request is recorded, a ranged one with the bytes it asked for
+    let requested_range = range.map(|(start, end)| format!("bytes={}-{}", start, end));
+    audit
+        .entry(AuditAction::BackupDownloaded, Some(&admin.user_id))
+        .target(AuditTargetType::Backup, backup_id)
+        .changes(
+            None::<()>,
+            Some(json!({ "file": key, "range": requested_range })),
+        )
+        .record()
+        .await;
 
-    Ok(Response::builder()
-        .status(StatusCode::OK)
+    let mut response = Response::builder()
         .header(header::CONTENT_TYPE, "application/octet-stream")
         .header(
             header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}\"", filename),
+            format!("attachment; filename=\"{}\"", backup_file_name(key)),
         )
-        .body(Body::from(bytes))
-        .unwrap())
+        .header(header::ACCEPT_RANGES, "bytes");
+    if let Some(length) = object.content_length() {
+        response = response.header(header::CONTENT_LENGTH, length);
+    }
+    if let Some(e_tag) = object.e_tag() {
+        response = response.header(header::ETAG, e_tag);
+    }
+    response = match object.content_range() {
+        Some(content_range) if range.is_some() => response
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_RANGE, content_range),
+        _ => response.status(StatusCode::OK),
+    };
+
+    // Streamed as it arrives from S3, the dump is never held in memory
+    let body = Body::from_stream(ReaderStream::new(object.body.into_async_read()));
+
+    Ok(response.body(body).unwrap())
 }
 
 #[instrument(skip(state))]
-async fn notify_backup_result(
+async fn get_download_url(
     State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
     Path(backup_id): Path<i32>,
-    Json(req): Json<NotifyBackupRequest>,
-) -> ApiResponse<()> {
-    let expected_secret =
-        std::env::var("BACKUP_NOTIFY_SECRET").unwrap_or_else(|_| "placeholder".into());
-    if req.secret != expected_secret {
-        return ApiResponse::Error {
-            message: "Invalid secret".to_string(),
-        };
-    }
+) -> Result<Json<ApiResponse<ReadBackupDownloadUrlDto>>, StatusCode> {
+    let backup = get_downloadable_backup(&state, backup_id).await?;
 
-    let backup = match state.db_backup_repo.get_by_id(backup_id).await {
-        Ok(Some(b)) => b,
-        Ok(None) => {
-            return ApiResponse::Error {
-                message: format!("Backup {} not found", backup_id),
-            }
-        }
-        Err(e) => {
-            tracing::error!("Failed to fetch backup {}: {}", backup_id, e);
-            return ApiResponse::Error {
-                message: "Failed to fetch backup".to_string(),
-            };
-        }
+    let download_url = state
+        .backup_service
+        .download_url(&backup)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to presign backup download: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    audit
+        .entry(AuditAction::BackupDownloaded, Some(&admin.user_id))
+        .target(AuditTargetType::Backup, backup_id)
+        .changes(
+            None::<()>,
+            Some(json!({
+                "file": backup.backup_file,
+                "presigned": true,
+                "expires_at": download_url.expires_at,
+            })),
+        )
+        .record()
+        .await;
+
+    Ok(Json(ApiResponse::Success { data: download_url }))
+}
+
+/// Only successful backups still in the bucket can be downloaded
+async fn get_downloadable_backup(state: &AppState, backup_id: i32) -> Result<DbBackup, StatusCode> {
+    let backup = state
+        .backup_service
+        .get_backup(backup_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get backup {}: {}", backup_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    backup
+        .filter(|backup| backup.status == "success")
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Parse a single `bytes=` range into inclusive offsets within `size`.
+/// Other units and multiple ranges are ignored, the whole dump is sent instead.
+fn parse_range(value: &str, size: i64) -> Result<Option<(u64, u64)>, ()> {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return Ok(None);
     };
 
-    let result = match backup.status.as_str() {
-        "success" => {
-            state
-                .notification_service
-                .notify_admins_backup_completed(
-                    backup.id,
-                    backup.backup_size_bytes,
-                    backup.duration_seconds,
-                    backup.started_at,
-                )
-                .await
-        }
-        "failed" => {
-            state
-                .notification_service
-                .notify_admins_backup_failed(
-                    backup.id,
-                    backup.error_message,
-                    backup.duration_seconds,
-                    backup.started_at,
-                )
-                .await
-        }
-        status => {
-            return ApiResponse::Error {
-                message: format!("Backup status '{}' is not notifiable", status),
-            };
+    let size = size.max(0) as u64;
+    let range = match (start.parse::<u64>(), end.parse::<u64>()) {
+        // bytes=500-999
+        (Ok(start), Ok(end)) if start <= end => (start, end.min(size.saturating_sub(1))),
+        // bytes=500-
+        (Ok(start), Err(_)) if end.is_empty() => (start, size.saturating_sub(1)),
+        // bytes=-500, the last 500 bytes
+        (Err(_), Ok(suffix)) if start.is_empty() && suffix > 0 => {
+            (size.saturating_sub(suffix), size.saturating_sub(1))
         }
+        (Err(_), Ok(0)) if start.is_empty() => return Err(()),
+        _ => return Ok(None),
     };
 
-    if let Err(e) = result {
-        tracing::error!("Failed to send backup notifications for {}: {}", backup_id, e);
-        return ApiResponse::Error {
-            message: "Failed to send notifications".to_string(),
-        };
+    if range.0 >= size {
+        return Err(());
     }
 
-    ApiResponse::Success { data: () }
+    Ok(Some(range))
 }
@@ -1,19 +1,40 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    routing::post,
+    extract::{Path, Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::{get, post},
     Json, Router,
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use tracing::instrument;
+use uuid::Uuid;
 
-use crate::router::{admin::AdminUser, response::ApiResponse, root::AppState};
+use crate::{
+    dto::impersonation::{
+        ReadImpersonatedRequestDto, ReadImpersonationSessionDto, StartImpersonationDto,
+    },
+    entity::{
+        audit::{AuditAction, AuditTargetType},
+        notification::AccountAccessedData,
+    },
+    router::{
+        admin::AdminUser,
+        request::{Audit, ValidatedRequest},
+        response::ApiResponse,
+        root::AppState,
+    },
+};
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ImpersonationResponse {
     impersonation_token: String,
     target_user_id: String,
+    expires_at: DateTime<Utc>,
+    read_only: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,15 +44,87 @@ pub struct StopImpersonationRequest {
 
 pub fn admin_impersonation_router() -> Router<AppState> {
     Router::new()
-        .route("/{user_id}", post(start_impersonation))
+        .route(
+            "/{user_id}",
+            get(list_impersonation_sessions).post(start_impersonation),
+        )
         .route("/stop", post(stop_impersonation))
+        .route(
+            "/sessions/{session_id}/requests",
+            get(list_impersonated_requests),
+        )
+}
+
+/// Resolves the `X-Impersonation-Token` header once per request for the extractors, rejects
+/// tokens without a live session and mutating requests of read-only sessions, and logs every
+/// impersonated request.
+pub async fn impersonation_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(token) = request
+        .headers()
+        .get("X-Impersonation-Token")
+        .and_then(|h| h.to_str().ok())
+    else {
+        return next.run(request).await;
+    };
+
+    // An expired or ended session must not fall back to the admin's own credentials
+    let session = match state.auth_service.validate_impersonation_token(token).await {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({"status": "fail", "message": "Impersonation session is not active"})),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to validate impersonation token: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    // The admin must always be able to end the session
+    let is_safe = matches!(method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || path.ends_with("/admin/impersonate/stop");
+
+    let response = if session.read_only && !is_safe {
+        (
+            StatusCode::FORBIDDEN,
+            Json(json!({"status": "fail", "message": "Impersonation session is read-only"})),
+        )
+            .into_response()
+    } else {
+        request.extensions_mut().insert(session.clone());
+        next.run(request).await
+    };
+
+    let _ = state
+        .auth_service
+        .log_impersonated_request(
+            session.id,
+            method.as_str(),
+            &path,
+            response.status().as_u16() as i16,
+        )
+        .await;
+
+    response
 }
 
 #[instrument(skip(state))]
 async fn start_impersonation(
     State(state): State<AppState>,
     AdminUser(admin): AdminUser,
+    audit: Audit,
     Path(target_user_id): Path<String>,
+    ValidatedRequest(payload): ValidatedRequest<StartImpersonationDto>,
 ) -> Result<Json<ApiResponse<ImpersonationResponse>>, StatusCode> {
     if admin.user_id == target_user_id {
         return Err(StatusCode::BAD_REQUEST);
@@ -55,18 +148,52 @@ async fn start_impersonation(
         return Err(StatusCode::NOT_FOUND);
     }
 
-    let impersonation_token = state
+    let (impersonation_token, expires_at) = state
         .auth_service
-        .start_impersonation(&admin.user_id, &target_user_id)
+        .start_impersonation(
+            &admin.user_id,
+            &target_user_id,
+            &payload.reason,
+            payload.read_only,
+        )
         .await
         .map_err(|e| {
             tracing::error!("Failed to start impersonation: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    audit
+        .entry(AuditAction::ImpersonationStarted, Some(&admin.user_id))
+        .target(AuditTargetType::User, &target_user_id)
+        .changes(
+            None::<()>,
+            Some(json!({
+                "reason": payload.reason,
+                "readOnly": payload.read_only,
+                "expiresAt": expires_at,
+            })),
+        )
+        .record()
+        .await;
+
+    let _ = state
+        .notification_service
+        .notify_account_accessed(
+            target_user_id.clone(),
+            AccountAccessedData {
+                reason: payload.reason.clone(),
+                read_only: payload.read_only,
+                started_at: Utc::now(),
+                expires_at,
+            },
+        )
+        .await;
+
     let response = ImpersonationResponse {
         impersonation_token,
         target_user_id,
+        expires_at,
+        read_only: payload.read_only,
     };
 
     Ok(Json(ApiResponse::Success { data: response }))
@@ -75,9 +202,18 @@ async fn start_impersonation(
 #[instrument(skip(state))]
 async fn stop_impersonation(
     State(state): State<AppState>,
-    AdminUser(_admin): AdminUser,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
     Json(req): Json<StopImpersonationRequest>,
 ) -> Result<StatusCode, StatusCode> {
+    let target_user_id = state
+        .auth_service
+        .validate_impersonation_token(&req.impersonation_token)
+        .await
+        .ok()
+        .flatten()
+        .map(|session| session.target_user_id);
+
     state
         .auth_service
         .stop_impersonation(&req.impersonation_token)
@@ -87,5 +223,47 @@ async fn stop_impersonation(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    let mut entry = audit.entry(AuditAction::ImpersonationStopped, Some(&admin.user_id));
+    if let Some(target_user_id) = target_user_id {
+        entry = entry.target(AuditTargetType::User, target_user_id);
+    }
+    entry.record().await;
+
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[instrument(skip(state))]
+async fn list_impersonation_sessions(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Path(target_user_id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<ReadImpersonationSessionDto>>>, StatusCode> {
+    let sessions = state
+        .auth_service
+        .list_impersonation_sessions(&target_user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list impersonation sessions: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: sessions }))
+}
+
+#[instrument(skip(state))]
+async fn list_impersonated_requests(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<ReadImpersonatedRequestDto>>>, StatusCode> {
+    let requests = state
+        .auth_service
+        .list_impersonated_requests(session_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list impersonated requests: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: requests }))
+}
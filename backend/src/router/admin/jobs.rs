@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use serde_json::json;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    dto::job::{JobQueryDto, ReadJobDto, ReadJobScheduleDto},
+    entity::audit::{AuditAction, AuditTargetType},
+    router::{admin::AdminUser, request::Audit, response::ApiResponse, root::AppState},
+};
+
+pub fn admin_jobs_router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_jobs))
+        .route("/schedules", get(list_schedules))
+        .route("/{job_id}", get(get_job))
+        .route("/{job_id}/retry", post(retry_job))
+}
+
+#[instrument(skip(state))]
+async fn list_jobs(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<JobQueryDto>,
+) -> Result<Json<ApiResponse<Vec<ReadJobDto>>>, StatusCode> {
+    let jobs = state.job_service.list(query).await.map_err(|e| {
+        tracing::error!("Failed to list jobs: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(ApiResponse::Success { data: jobs }))
+}
+
+#[instrument(skip(state))]
+async fn get_job(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ReadJobDto>>, StatusCode> {
+    let job = state.job_service.get(job_id).await.map_err(|e| {
+        tracing::error!("Failed to get job {}: {}", job_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let job = job.ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(ApiResponse::Success { data: job }))
+}
+
+#[instrument(skip(state))]
+async fn retry_job(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ReadJobDto>>, StatusCode> {
+    let job = state.job_service.retry(job_id).await.map_err(|e| {
+        tracing::error!("Failed to retry job {}: {}", job_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Only failed jobs without a queued duplicate can be retried
+    let Some(job) = job else {
+        let exists = state.job_service.get(job_id).await.map_err(|e| {
+            tracing::error!("Failed to get job {}: {}", job_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        return Err(if exists.is_some() {
+            StatusCode::CONFLICT
+        } else {
+            StatusCode::NOT_FOUND
+        });
+    };
+
+    audit
+        .entry(AuditAction::JobRetried, Some(&admin.user_id))
+        .target(AuditTargetType::Job, job_id)
+        .changes(
+            Some(json!({ "lastError": job.last_error })),
+            Some(json!({ "kind": job.kind, "status": job.status })),
+        )
+        .record()
+        .await;
+
+    Ok(Json(ApiResponse::Success { data: job }))
+}
+
+#[instrument(skip(state))]
+async fn list_schedules(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+) -> Result<Json<ApiResponse<Vec<ReadJobScheduleDto>>>, StatusCode> {
+    let schedules = state.job_service.list_schedules().await.map_err(|e| {
+        tracing::error!("Failed to list job schedules: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(ApiResponse::Success { data: schedules }))
+}
@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use tracing::instrument;
+
+use crate::{
+    dto::metrics::{
+        MetricsQueryDto, ReadQueryLatencyDto, ReadRouteLatencyDto, ReadSlowRequestDto,
+        ReadStatusCodeRateDto,
+    },
+    router::{admin::AdminUser, response::ApiResponse, root::AppState},
+    service::metrics_service::{MetricsRange, MetricsService},
+};
+
+pub fn admin_metrics_router() -> Router<AppState> {
+    Router::new()
+        .route("/routes", get(route_latency))
+        .route("/queries", get(query_latency))
+        .route("/status-codes", get(status_code_rates))
+        .route("/slow-requests", get(slowest_requests))
+}
+
+fn range(query: &MetricsQueryDto) -> Result<MetricsRange, StatusCode> {
+    MetricsService::range(query).map_err(|e| {
+        tracing::warn!("Invalid metrics range: {}", e);
+        StatusCode::BAD_REQUEST
+    })
+}
+
+/// Latency percentiles per route and time bucket
+#[instrument(skip(state))]
+async fn route_latency(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<MetricsQueryDto>,
+) -> Result<Json<ApiResponse<Vec<ReadRouteLatencyDto>>>, StatusCode> {
+    let latency = state
+        .metrics_service
+        .route_latency(range(&query)?)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get route latency: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: latency }))
+}
+
+/// Latency percentiles per named query and time bucket
+#[instrument(skip(state))]
+async fn query_latency(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<MetricsQueryDto>,
+) -> Result<Json<ApiResponse<Vec<ReadQueryLatencyDto>>>, StatusCode> {
+    let latency = state
+        .metrics_service
+        .query_latency(range(&query)?)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get query latency: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: latency }))
+}
+
+#[instrument(skip(state))]
+async fn status_code_rates(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<MetricsQueryDto>,
+) -> Result<Json<ApiResponse<Vec<ReadStatusCodeRateDto>>>, StatusCode> {
+    let rates = state
+        .metrics_service
+        .status_code_rates(range(&query)?)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get status code rates: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: rates }))
+}
+
+/// Slowest requests of the range with the queries they ran
+#[instrument(skip(state))]
+async fn slowest_requests(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<MetricsQueryDto>,
+) -> Result<Json<ApiResponse<Vec<ReadSlowRequestDto>>>, StatusCode> {
+    let requests = state
+        .metrics_service
+        .slowest_requests(range(&query)?, query.limit)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get slowest requests: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: requests }))
+}
@@ -1,8 +1,13 @@
+pub mod audit;
 pub mod backups;
 pub mod impersonation;
+pub mod jobs;
+pub mod metrics;
 pub mod release;
+pub mod reports;
 pub mod routes;
 pub mod sandbox;
+pub mod stats;
 pub mod users;
 
 use axum::{
@@ -10,7 +15,11 @@ use axum::{
     http::{request::Parts, StatusCode},
 };
 
-use super::{clerk::Actor, root::AppState};
+use super::{
+    clerk::{authorize_impersonator, Actor},
+    root::AppState,
+};
+use crate::dto::impersonation::ImpersonationSession;
 
 pub struct AdminUser(pub Actor);
 
@@ -21,27 +30,11 @@ impl FromRequestParts<AppState> for AdminUser {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        if let Some(impersonation_token) = parts
-            .headers
-            .get("X-Impersonation-Token")
-            .and_then(|h| h.to_str().ok())
-        {
-            if let Ok(Some((_target_user_id, admin_user_id))) = state
-                .auth_service
-                .validate_impersonation_token(impersonation_token)
-                .await
-            {
-                let admin_actor = state
-                    .user_service
-                    .get_actor_by_id(admin_user_id.clone())
-                    .await
-                    .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-                if let Some((actor, _display_name)) = admin_actor {
-                    if actor.is_admin() {
-                        return Ok(AdminUser(actor));
-                    }
-                }
+        if parts.headers.contains_key("X-Impersonation-Token") {
+            // Resolved by the impersonation middleware, which rejects invalid tokens
+            if let Some(session) = parts.extensions.get::<ImpersonationSession>() {
+                let admin = authorize_impersonator(state, session).await?;
+                return Ok(AdminUser(admin));
             }
             return Err(StatusCode::FORBIDDEN);
         }
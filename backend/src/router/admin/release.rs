@@ -8,8 +8,16 @@ use tracing::instrument;
 use uuid::Uuid;
 
 use crate::{
-    dto::release::{CreateReleaseDto, ReadReleaseDto, ReleaseListQueryDto, UpdateReleaseDto},
-    router::{admin::AdminUser, response::ApiResponse, root::AppState},
+    dto::release::{
+        CreateReleaseDto, ReadReleaseDto, ReleaseListQueryDto, ScheduleReleaseDto, UpdateReleaseDto,
+    },
+    entity::audit::{AuditAction, AuditTargetType},
+    router::{
+        admin::AdminUser,
+        request::{Audit, ValidatedRequest},
+        response::ApiResponse,
+        root::AppState,
+    },
 };
 
 pub fn admin_release_router() -> Router<AppState> {
@@ -23,6 +31,10 @@ pub fn admin_release_router() -> Router<AppState> {
         )
         .route("/{release_id}/publish", post(publish_release))
         .route("/{release_id}/unpublish", post(unpublish_release))
+        .route(
+            "/{release_id}/schedule",
+            post(schedule_release).delete(cancel_release_schedule),
+        )
 }
 
 #[instrument(skip(state))]
@@ -46,8 +58,9 @@ async fn list_all_releases(
 #[instrument(skip(state))]
 async fn create_release(
     State(state): State<AppState>,
-    AdminUser(_admin): AdminUser,
-    Json(dto): Json<CreateReleaseDto>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    ValidatedRequest(dto): ValidatedRequest<CreateReleaseDto>,
 ) -> Result<Json<ApiResponse<ReadReleaseDto>>, StatusCode> {
     let release = state
         .release_service
@@ -58,6 +71,13 @@ async fn create_release(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    audit
+        .entry(AuditAction::ReleaseCreated, Some(&admin.user_id))
+        .target(AuditTargetType::Release, release.id)
+        .changes(None::<()>, Some(&release))
+        .record()
+        .await;
+
     Ok(Json(ApiResponse::Success { data: release }))
 }
 
@@ -85,10 +105,13 @@ async fn get_release(
 #[instrument(skip(state))]
 async fn update_release(
     State(state): State<AppState>,
-    AdminUser(_admin): AdminUser,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
     Path(release_id): Path<Uuid>,
-    Json(dto): Json<UpdateReleaseDto>,
+    ValidatedRequest(dto): ValidatedRequest<UpdateReleaseDto>,
 ) -> Result<Json<ApiResponse<ReadReleaseDto>>, StatusCode> {
+    let before = release_for_audit(&state, release_id).await;
+
     let release = state
         .release_service
         .update_release(release_id, dto)
@@ -98,15 +121,25 @@ async fn update_release(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    audit
+        .entry(AuditAction::ReleaseUpdated, Some(&admin.user_id))
+        .target(AuditTargetType::Release, release_id)
+        .changes(before, Some(&release))
+        .record()
+        .await;
+
     Ok(Json(ApiResponse::Success { data: release }))
 }
 
 #[instrument(skip(state))]
 async fn delete_release(
     State(state): State<AppState>,
-    AdminUser(_admin): AdminUser,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
     Path(release_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    let before = release_for_audit(&state, release_id).await;
+
     state
         .release_service
         .delete_release(release_id)
@@ -116,6 +149,13 @@ async fn delete_release(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    audit
+        .entry(AuditAction::ReleaseDeleted, Some(&admin.user_id))
+        .target(AuditTargetType::Release, release_id)
+        .changes(before, None::<()>)
+        .record()
+        .await;
+
     Ok(Json(ApiResponse::Success { data: () }))
 }
 
@@ -123,26 +163,39 @@ async fn delete_release(
 async fn publish_release(
     State(state): State<AppState>,
     AdminUser(admin): AdminUser,
+    audit: Audit,
     Path(release_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    let before = release_for_audit(&state, release_id).await;
+
     state
         .release_service
-        .publish_release(release_id, admin.user_id)
+        .publish_release(release_id, admin.user_id.clone())
         .await
         .map_err(|e| {
             tracing::error!("Failed to publish release: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    audit
+        .entry(AuditAction::ReleasePublished, Some(&admin.user_id))
+        .target(AuditTargetType::Release, release_id)
+        .changes(before, release_for_audit(&state, release_id).await)
+        .record()
+        .await;
+
     Ok(Json(ApiResponse::Success { data: () }))
 }
 
 #[instrument(skip(state))]
 async fn unpublish_release(
     State(state): State<AppState>,
-    AdminUser(_admin): AdminUser,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
     Path(release_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    let before = release_for_audit(&state, release_id).await;
+
     state
         .release_service
         .unpublish_release(release_id)
@@ -152,5 +205,73 @@ async fn unpublish_release(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    audit
+        .entry(AuditAction::ReleaseUnpublished, Some(&admin.user_id))
+        .target(AuditTargetType::Release, release_id)
+        .changes(before, release_for_audit(&state, release_id).await)
+        .record()
+        .await;
+
     Ok(Json(ApiResponse::Success { data: () }))
 }
+
+/// Publish and announce the release at a future time instead of right away
+#[instrument(skip(state))]
+async fn schedule_release(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    Path(release_id): Path<Uuid>,
+    Json(dto): Json<ScheduleReleaseDto>,
+) -> ApiResponse<ReadReleaseDto> {
+    let before = release_for_audit(&state, release_id).await;
+
+    let res = state
+        .release_service
+        .schedule_release(release_id, dto, admin.user_id.clone())
+        .await;
+
+    if let Ok(release) = &res {
+        audit
+            .entry(AuditAction::ReleaseScheduled, Some(&admin.user_id))
+            .target(AuditTargetType::Release, release_id)
+            .changes(before, Some(release))
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state))]
+async fn cancel_release_schedule(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    Path(release_id): Path<Uuid>,
+) -> ApiResponse<ReadReleaseDto> {
+    let before = release_for_audit(&state, release_id).await;
+
+    let res = state.release_service.cancel_schedule(release_id).await;
+
+    if let Ok(release) = &res {
+        audit
+            .entry(AuditAction::ReleaseScheduleCancelled, Some(&admin.user_id))
+            .target(AuditTargetType::Release, release_id)
+            .changes(before, Some(release))
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
+
+// State of the release for the audit log, a failed lookup only leaves the entry without it
+async fn release_for_audit(state: &AppState, release_id: Uuid) -> Option<ReadReleaseDto> {
+    state
+        .release_service
+        .get_release_by_id(release_id)
+        .await
+        .ok()
+        .flatten()
+}
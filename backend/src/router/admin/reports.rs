@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    dto::moderation::{FilterUserReportsDto, ReadUserReportDto, ReviewUserReportDto},
+    entity::audit::{AuditAction, AuditTargetType},
+    router::{
+        admin::AdminUser,
+        request::{Audit, ValidatedRequest},
+        response::ApiResponse,
+        root::AppState,
+    },
+};
+
+pub fn admin_reports_router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_reports))
+        .route("/{report_id}", get(get_report).patch(review_report))
+}
+
+#[instrument(skip(state))]
+async fn list_reports(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<FilterUserReportsDto>,
+) -> Result<Json<ApiResponse<Vec<ReadUserReportDto>>>, StatusCode> {
+    let reports = state
+        .moderation_service
+        .filter_reports(query)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list user reports: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: reports }))
+}
+
+#[instrument(skip(state))]
+async fn get_report(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Path(report_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ReadUserReportDto>>, StatusCode> {
+    let report = state
+        .moderation_service
+        .get_report(report_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get user report {}: {}", report_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    match report {
+        Some(report) => Ok(Json(ApiResponse::Success { data: report })),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+#[instrument(skip(state, dto))]
+async fn review_report(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    Path(report_id): Path<Uuid>,
+    ValidatedRequest(dto): ValidatedRequest<ReviewUserReportDto>,
+) -> Result<Json<ApiResponse<ReadUserReportDto>>, StatusCode> {
+    let before = state
+        .moderation_service
+        .get_report(report_id)
+        .await
+        .ok()
+        .flatten();
+
+    let report = state
+        .moderation_service
+        .review_report(report_id, dto, &admin)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to review user report {}: {}", report_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    audit
+        .entry(AuditAction::ReportReviewed, Some(&admin.user_id))
+        .target(AuditTargetType::Report, report_id)
+        .changes(before, Some(&report))
+        .record()
+        .await;
+
+    Ok(Json(ApiResponse::Success { data: report }))
+}
@@ -2,8 +2,11 @@ use axum::Router;
 
 use crate::router::{
     admin::{
-        backups::admin_backups_router, impersonation::admin_impersonation_router,
-        release::admin_release_router, sandbox::admin_sandbox_router, users::admin_users_router,
+        audit::admin_audit_router, backups::admin_backups_router,
+        impersonation::admin_impersonation_router, jobs::admin_jobs_router,
+        metrics::admin_metrics_router, release::admin_release_router,
+        reports::admin_reports_router, sandbox::admin_sandbox_router, stats::admin_stats_router,
+        users::admin_users_router,
     },
     root::AppState,
 };
@@ -15,4 +18,9 @@ pub fn admin_router() -> Router<AppState> {
         .nest("/backups", admin_backups_router())
         .nest("/sandbox", admin_sandbox_router())
         .nest("/releases", admin_release_router())
+        .nest("/reports", admin_reports_router())
+        .nest("/audit", admin_audit_router())
+        .nest("/metrics", admin_metrics_router())
+        .nest("/jobs", admin_jobs_router())
+        .nest("/stats", admin_stats_router())
 }
@@ -10,7 +10,11 @@ use std::collections::HashMap;
 use uuid::Uuid;
 use tracing::instrument;
 
-use crate::router::{admin::AdminUser, response::ApiResponse, root::AppState};
+use crate::{
+    entity::audit::{AuditAction, AuditTargetType},
+    router::{admin::AdminUser, request::Audit, response::ApiResponse, root::AppState},
+    telemetry::PropagateTraceContext,
+};
 
 #[derive(Debug, Deserialize)]
 struct GetLifecyclesRequest {
@@ -68,6 +72,7 @@ pub fn admin_sandbox_router() -> Router<AppState> {
 #[instrument(skip(state))]
 async fn reset_sandbox_handler(
     State(state): State<AppState>,
+    audit: Audit,
     Json(req): Json<ResetSandboxRequest>,
 ) -> ApiResponse<()> {
     if state.config.server.app_env != crate::config::env::AppEnvironment::NowasterSandbox {
@@ -99,6 +104,20 @@ async fn reset_sandbox_handler(
 
     tracing::info!("✅ Sandbox reset complete");
 
+    // Resets are triggered by the scheduler or relayed from the main instance
+    let actor_id = (req.triggered_type == "user").then_some(req.triggered_by.as_str());
+    audit
+        .entry(AuditAction::SandboxReset, actor_id)
+        .changes(
+            None::<()>,
+            Some(serde_json::json!({
+                "triggeredBy": req.triggered_by,
+                "triggeredType": req.triggered_type,
+            })),
+        )
+        .record()
+        .await;
+
     ApiResponse::Success { data: () }
 }
 
@@ -175,6 +194,7 @@ async fn proxy_get_sandbox_lifecycles(
     let response = client
         .post(format!("{}/admin/sandbox/lifecycles", sandbox_url))
         .json(&serde_json::json!({ "secret": secret }))
+        .with_trace_context()
         .send()
         .await
         .map_err(|e| {
@@ -246,7 +266,8 @@ async fn proxy_get_sandbox_lifecycles(
 #[instrument(skip(state))]
 async fn proxy_reset_sandbox_handler(
     State(state): State<AppState>,
-    AdminUser(_admin): AdminUser,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
     Json(req): Json<ProxyResetRequest>,
 ) -> Result<Json<ApiResponse<()>>, StatusCode> {
     if state.config.server.app_env == crate::config::env::AppEnvironment::NowasterSandbox {
@@ -271,6 +292,7 @@ async fn proxy_reset_sandbox_handler(
             "triggeredBy": req.triggered_by,
             "triggeredType": req.triggered_type,
         }))
+        .with_trace_context()
         .send()
         .await
         .map_err(|e| {
@@ -283,5 +305,20 @@ async fn proxy_reset_sandbox_handler(
         StatusCode::BAD_GATEWAY
     })?;
 
+    if matches!(body, ApiResponse::Success { .. }) {
+        audit
+            .entry(AuditAction::SandboxReset, Some(&admin.user_id))
+            .target(AuditTargetType::Sandbox, &sandbox_url)
+            .changes(
+                None::<()>,
+                Some(serde_json::json!({
+                    "triggeredBy": req.triggered_by,
+                    "triggeredType": req.triggered_type,
+                })),
+            )
+            .record()
+            .await;
+    }
+
     Ok(Json(body))
 }
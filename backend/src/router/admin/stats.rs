@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use tracing::instrument;
+
+use crate::{
+    dto::platform_stats::{
+        PlatformStatsQueryDto, ReadActiveUsersDto, ReadApiTokenAdoptionDto, ReadDailyActivityDto,
+        ReadSandboxConversionDto, ReadSignupsDto,
+    },
+    router::{admin::AdminUser, response::ApiResponse, root::AppState},
+    service::platform_stats_service::{PlatformStatsService, StatsRange},
+};
+
+pub fn admin_stats_router() -> Router<AppState> {
+    Router::new()
+        .route("/active-users", get(active_users))
+        .route("/signups", get(signups))
+        .route("/activity", get(daily_activity))
+        .route("/api-tokens", get(api_token_adoption))
+        .route("/sandbox", get(sandbox_conversions))
+}
+
+fn range(query: &PlatformStatsQueryDto) -> Result<StatsRange, StatusCode> {
+    PlatformStatsService::range(query).map_err(|e| {
+        tracing::warn!("Invalid stats range: {}", e);
+        StatusCode::BAD_REQUEST
+    })
+}
+
+/// Daily, weekly and monthly active users for every day of the range
+#[instrument(skip(state))]
+async fn active_users(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<PlatformStatsQueryDto>,
+) -> Result<Json<ApiResponse<Vec<ReadActiveUsersDto>>>, StatusCode> {
+    let active_users = state
+        .platform_stats_service
+        .active_users(range(&query)?)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get active users: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: active_users }))
+}
+
+/// New users per day and how they signed up
+#[instrument(skip(state))]
+async fn signups(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<PlatformStatsQueryDto>,
+) -> Result<Json<ApiResponse<Vec<ReadSignupsDto>>>, StatusCode> {
+    let signups = state
+        .platform_stats_service
+        .signups(range(&query)?)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get signups: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: signups }))
+}
+
+/// Sessions, hours tracked, stopwatch usage and feed engagement per day
+#[instrument(skip(state))]
+async fn daily_activity(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Query(query): Query<PlatformStatsQueryDto>,
+) -> Result<Json<ApiResponse<Vec<ReadDailyActivityDto>>>, StatusCode> {
+    let activity = state
+        .platform_stats_service
+        .daily_activity(range(&query)?)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get daily activity: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: activity }))
+}
+
+#[instrument(skip(state))]
+async fn api_token_adoption(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+) -> Result<Json<ApiResponse<ReadApiTokenAdoptionDto>>, StatusCode> {
+    let adoption = state
+        .platform_stats_service
+        .api_token_adoption()
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get API token adoption: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: adoption }))
+}
+
+#[instrument(skip(state))]
+async fn sandbox_conversions(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+) -> Result<Json<ApiResponse<Vec<ReadSandboxConversionDto>>>, StatusCode> {
+    let conversions = state
+        .platform_stats_service
+        .sandbox_conversions()
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get sandbox conversions: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: conversions }))
+}
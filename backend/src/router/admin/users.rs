@@ -1,15 +1,32 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    routing::get,
+    routing::{get, patch, post},
     Json, Router,
 };
 use serde::Deserialize;
+use serde_json::json;
 use tracing::instrument;
 
 use crate::{
-    dto::user::read_user::ReadUserDto,
-    router::{admin::AdminUser, response::ApiResponse, root::AppState},
+    dto::{
+        moderation::{ReadSuspensionDto, SuspendUserDto},
+        user::{
+            account_deletion::{AdminDeleteAccountDto, ReadAccountDeletionDto},
+            admin_user::{
+                ForceLogoutDto, ReadForceLogoutDto, ReadUserAccountDto, ReadUserRoleDto,
+                UpdateUserRoleDto,
+            },
+            read_user::ReadUserDto,
+        },
+    },
+    entity::audit::{AuditAction, AuditTargetType},
+    router::{
+        admin::AdminUser,
+        request::{Audit, ValidatedRequest},
+        response::ApiResponse,
+        root::AppState,
+    },
 };
 
 #[derive(Debug, Deserialize)]
@@ -26,13 +43,25 @@ fn default_limit() -> i64 {
 pub fn admin_users_router() -> Router<AppState> {
     Router::new()
         .route("/search", get(search_users))
+        .route("/deletions", get(list_account_deletions))
         .route("/{user_id}", get(get_user_by_id))
+        .route("/{user_id}/account", get(get_user_account))
+        .route("/{user_id}/role", patch(change_user_role))
+        .route("/{user_id}/logout", post(force_logout_user))
+        .route(
+            "/{user_id}/suspend",
+            post(suspend_user).delete(unsuspend_user),
+        )
+        .route(
+            "/{user_id}/deletion",
+            post(delete_user_account).delete(cancel_user_account_deletion),
+        )
 }
 
 #[instrument(skip(state))]
 async fn search_users(
     State(state): State<AppState>,
-    AdminUser(_admin): AdminUser,
+    AdminUser(admin): AdminUser,
     Query(query): Query<SearchUsersQuery>,
 ) -> Result<Json<ApiResponse<Vec<ReadUserDto>>>, StatusCode> {
     if query.q.is_empty() {
@@ -41,7 +70,7 @@ async fn search_users(
 
     let users = state
         .user_service
-        .search_users(&query.q, query.limit)
+        .search_users(&query.q, query.limit, &admin)
         .await
         .map_err(|e| {
             tracing::error!("Failed to search users: {}", e);
@@ -71,3 +100,231 @@ async fn get_user_by_id(
         None => Err(StatusCode::NOT_FOUND),
     }
 }
+
+/// User together with their role, suspension, login methods, API tokens and active sessions
+#[instrument(skip(state))]
+async fn get_user_account(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+    Path(user_id): Path<String>,
+) -> Result<Json<ApiResponse<ReadUserAccountDto>>, StatusCode> {
+    let user = state
+        .user_service
+        .get_user_by_id(&user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get user {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let account = state
+        .auth_service
+        .get_user_account(user)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get account of user {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(ApiResponse::Success { data: account }))
+}
+
+#[instrument(skip(state))]
+async fn change_user_role(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    Path(user_id): Path<String>,
+    Json(dto): Json<UpdateUserRoleDto>,
+) -> ApiResponse<ReadUserRoleDto> {
+    let res = state
+        .user_service
+        .change_role(&user_id, dto.role.clone(), &admin)
+        .await
+        .map(|previous_role| ReadUserRoleDto {
+            user_id: user_id.clone(),
+            role: dto.role,
+            previous_role,
+        });
+
+    if let Ok(change) = &res {
+        audit
+            .entry(AuditAction::UserRoleChanged, Some(&admin.user_id))
+            .target(AuditTargetType::User, &user_id)
+            .changes(
+                Some(json!({ "role": change.previous_role })),
+                Some(json!({ "role": change.role })),
+            )
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
+
+/// Sign the user out on every device, access tokens that did not expire yet stop working too
+#[instrument(skip(state))]
+async fn force_logout_user(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    Path(user_id): Path<String>,
+    dto: Option<Json<ForceLogoutDto>>,
+) -> ApiResponse<ReadForceLogoutDto> {
+    let Json(dto) = dto.unwrap_or_default();
+
+    let res = state
+        .auth_service
+        .force_logout(&user_id, dto.revoke_api_tokens, "admin_forced_logout")
+        .await
+        .map(|revoked_api_tokens| ReadForceLogoutDto {
+            user_id: user_id.clone(),
+            revoked_api_tokens,
+        });
+
+    if let Ok(logout) = &res {
+        audit
+            .entry(AuditAction::UserSessionsRevoked, Some(&admin.user_id))
+            .target(AuditTargetType::User, &user_id)
+            .changes(None::<()>, Some(logout))
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state, dto))]
+async fn suspend_user(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    Path(user_id): Path<String>,
+    ValidatedRequest(dto): ValidatedRequest<SuspendUserDto>,
+) -> Result<Json<ApiResponse<ReadSuspensionDto>>, StatusCode> {
+    let suspension = state
+        .moderation_service
+        .suspend_user(&user_id, dto.reason, &admin)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to suspend user {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    audit
+        .entry(AuditAction::UserSuspended, Some(&admin.user_id))
+        .target(AuditTargetType::User, &user_id)
+        .changes(None::<()>, Some(&suspension))
+        .record()
+        .await;
+
+    Ok(Json(ApiResponse::Success { data: suspension }))
+}
+
+#[instrument(skip(state))]
+async fn unsuspend_user(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    Path(user_id): Path<String>,
+) -> Result<Json<ApiResponse<ReadSuspensionDto>>, StatusCode> {
+    let suspension = state
+        .moderation_service
+        .unsuspend_user(&user_id, &admin)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to unsuspend user {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    audit
+        .entry(AuditAction::UserUnsuspended, Some(&admin.user_id))
+        .target(AuditTargetType::User, &user_id)
+        .changes(None::<()>, Some(&suspension))
+        .record()
+        .await;
+
+    Ok(Json(ApiResponse::Success { data: suspension }))
+}
+
+#[instrument(skip(state))]
+async fn list_account_deletions(
+    State(state): State<AppState>,
+    AdminUser(_admin): AdminUser,
+) -> Result<Json<ApiResponse<Vec<ReadAccountDeletionDto>>>, StatusCode> {
+    let deletions = state
+        .account_deletion_service
+        .list_pending()
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list account deletions: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::Success { data: deletions }))
+}
+
+#[instrument(skip(state))]
+async fn delete_user_account(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    Path(user_id): Path<String>,
+    Json(dto): Json<AdminDeleteAccountDto>,
+) -> Result<Json<ApiResponse<ReadAccountDeletionDto>>, StatusCode> {
+    let deletion = if dto.immediate {
+        state
+            .account_deletion_service
+            .delete_immediately(&user_id, &admin)
+            .await
+    } else {
+        state
+            .account_deletion_service
+            .request_deletion(&user_id, &admin)
+            .await
+    }
+    .map_err(|e| {
+        tracing::error!("Failed to delete account of user {}: {}", user_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    audit
+        .entry(AuditAction::AccountDeletionRequested, Some(&admin.user_id))
+        .target(AuditTargetType::User, &user_id)
+        .changes(None::<()>, Some(&deletion))
+        .record()
+        .await;
+
+    Ok(Json(ApiResponse::Success { data: deletion }))
+}
+
+#[instrument(skip(state))]
+async fn cancel_user_account_deletion(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    audit: Audit,
+    Path(user_id): Path<String>,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    state
+        .account_deletion_service
+        .cancel_deletion(&user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to cancel account deletion of user {}: {}",
+                user_id,
+                e
+            );
+            StatusCode::NOT_FOUND
+        })?;
+
+    audit
+        .entry(AuditAction::AccountDeletionCancelled, Some(&admin.user_id))
+        .target(AuditTargetType::User, &user_id)
+        .record()
+        .await;
+
+    Ok(Json(ApiResponse::Success { data: () }))
+}
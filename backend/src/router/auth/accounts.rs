@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Redirect,
+    routing::{delete, get},
+    Router,
+};
+use axum_extra::extract::cookie::CookieJar;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::{
+    auth::generate_csrf_token,
+    entity::audit::{AuditAction, AuditTargetType},
+    repository::auth::oauth_account::OAuthAccount,
+    router::{
+        auth::routes::{start_oauth_flow, LINK_STATE_PREFIX},
+        clerk::Actor,
+        request::Audit,
+        response::ApiResponse,
+        root::AppState,
+    },
+};
+
+/// OAuth provider account the user can sign in with
+#[derive(Debug, Serialize)]
+pub struct ReadLinkedAccountDto {
+    pub provider: String,
+    pub provider_email: Option<String>,
+    pub linked_at: DateTime<Utc>,
+}
+
+impl From<OAuthAccount> for ReadLinkedAccountDto {
+    fn from(account: OAuthAccount) -> Self {
+        Self {
+            provider: account.provider,
+            provider_email: account.provider_email,
+            linked_at: account.created_at,
+        }
+    }
+}
+
+pub fn accounts_router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_accounts_handler))
+        .route("/{provider}/link", get(link_account_handler))
+        .route("/{provider}", delete(unlink_account_handler))
+}
+
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn list_accounts_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResponse<Vec<ReadLinkedAccountDto>> {
+    let res = state
+        .auth_service
+        .list_oauth_accounts(&actor.user_id)
+        .await
+        .map(|accounts| {
+            accounts
+                .into_iter()
+                .map(ReadLinkedAccountDto::from)
+                .collect()
+        });
+
+    ApiResponse::from_result(res)
+}
+
+/// Start the OAuth flow of the provider, the callback links it to the signed in user
+#[instrument(skip(state, jar), fields(user_id = %actor))]
+async fn link_account_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(provider): Path<String>,
+    jar: CookieJar,
+) -> Result<(CookieJar, Redirect), StatusCode> {
+    let csrf_state = format!("{}{}", LINK_STATE_PREFIX, generate_csrf_token());
+    start_oauth_flow(&provider, &state, jar, csrf_state).await
+}
+
+#[instrument(skip(state), fields(user_id = %actor, provider = %provider))]
+async fn unlink_account_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    audit: Audit,
+    Path(provider): Path<String>,
+) -> ApiResponse<()> {
+    let res = state
+        .auth_service
+        .unlink_oauth_account(&actor.user_id, &provider)
+        .await;
+
+    if res.is_ok() {
+        audit
+            .entry(AuditAction::OauthAccountUnlinked, Some(&actor.user_id))
+            .target(AuditTargetType::User, &actor.user_id)
+            .changes(
+                Some(serde_json::json!({ "provider": provider })),
+                None::<()>,
+            )
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
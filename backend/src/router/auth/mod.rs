@@ -1,4 +1,7 @@
+pub mod accounts;
+pub mod passkeys;
 pub mod routes;
+pub mod sessions;
 pub mod tokens;
 
 pub use routes::auth_router;
@@ -0,0 +1,223 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, patch, post},
+    Json, Router,
+};
+use axum_extra::extract::cookie::CookieJar;
+use serde_json::json;
+use tracing::instrument;
+use uuid::Uuid;
+use webauthn_rs::prelude::{CreationChallengeResponse, RequestChallengeResponse};
+
+use crate::{
+    dto::passkey::{
+        FinishPasskeyLoginDto, FinishPasskeyRegistrationDto, FinishPasskeySignupDto,
+        PasskeyChallengeDto, ReadPasskeyDto, RenamePasskeyDto, StartPasskeySignupDto,
+    },
+    entity::audit::{AuditAction, AuditTargetType},
+    router::{
+        auth::routes::{login_response, TokenResponse},
+        clerk::Actor,
+        request::{Audit, ValidatedRequest},
+        response::ApiResponse,
+        root::AppState,
+    },
+};
+
+pub fn passkeys_router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_passkeys_handler))
+        .route("/register/start", post(start_registration_handler))
+        .route("/register/finish", post(finish_registration_handler))
+        .route("/signup/start", post(start_signup_handler))
+        .route("/signup/finish", post(finish_signup_handler))
+        .route("/login/start", post(start_login_handler))
+        .route("/login/finish", post(finish_login_handler))
+        .route(
+            "/{passkey_id}",
+            patch(rename_passkey_handler).delete(delete_passkey_handler),
+        )
+}
+
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn list_passkeys_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResponse<Vec<ReadPasskeyDto>> {
+    let res = state.passkey_service.list_passkeys(&actor.user_id).await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn start_registration_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResponse<PasskeyChallengeDto<CreationChallengeResponse>> {
+    let res = state
+        .passkey_service
+        .start_registration(&actor.user_id)
+        .await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state, payload), fields(user_id = %actor))]
+async fn finish_registration_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    audit: Audit,
+    ValidatedRequest(payload): ValidatedRequest<FinishPasskeyRegistrationDto>,
+) -> ApiResponse<ReadPasskeyDto> {
+    let res = state
+        .passkey_service
+        .finish_registration(&actor.user_id, payload)
+        .await;
+
+    if let Ok(passkey) = &res {
+        audit
+            .entry(AuditAction::PasskeyRegistered, Some(&actor.user_id))
+            .target(AuditTargetType::Passkey, passkey.id)
+            .changes(None::<()>, Some(json!({ "name": passkey.name })))
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state, payload))]
+async fn start_signup_handler(
+    State(state): State<AppState>,
+    ValidatedRequest(payload): ValidatedRequest<StartPasskeySignupDto>,
+) -> ApiResponse<PasskeyChallengeDto<CreationChallengeResponse>> {
+    let res = state.passkey_service.start_signup(&payload.username).await;
+    ApiResponse::from_result(res)
+}
+
+/// Create the account once its passkey is verified and sign it in
+#[instrument(skip(state, jar, payload))]
+async fn finish_signup_handler(
+    State(state): State<AppState>,
+    audit: Audit,
+    jar: CookieJar,
+    ValidatedRequest(payload): ValidatedRequest<FinishPasskeySignupDto>,
+) -> Result<(CookieJar, Json<ApiResponse<TokenResponse>>), Response> {
+    let (access_token, refresh_token, user_id) = match state
+        .passkey_service
+        .finish_signup(
+            payload,
+            audit.client().user_agent.as_deref(),
+            audit.client().ip,
+        )
+        .await
+    {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            tracing::error!("Passkey sign up failed: {}", e);
+            return Err(StatusCode::BAD_REQUEST.into_response());
+        }
+    };
+
+    audit
+        .entry(AuditAction::AuthLogin, Some(&user_id))
+        .target(AuditTargetType::User, &user_id)
+        .changes(
+            None::<()>,
+            Some(json!({ "provider": "passkey", "new_user": true })),
+        )
+        .record()
+        .await;
+
+    Ok(login_response(&state, jar, access_token, refresh_token))
+}
+
+#[instrument(skip(state))]
+async fn start_login_handler(
+    State(state): State<AppState>,
+) -> ApiResponse<PasskeyChallengeDto<RequestChallengeResponse>> {
+    let res = state.passkey_service.start_login().await;
+    ApiResponse::from_result(res)
+}
+
+/// Verify the passkey assertion and set the same auth cookies as the OAuth callback
+#[instrument(skip(state, jar, payload))]
+async fn finish_login_handler(
+    State(state): State<AppState>,
+    audit: Audit,
+    jar: CookieJar,
+    ValidatedRequest(payload): ValidatedRequest<FinishPasskeyLoginDto>,
+) -> Result<(CookieJar, Json<ApiResponse<TokenResponse>>), Response> {
+    let (access_token, refresh_token, user_id) = match state
+        .passkey_service
+        .finish_login(
+            payload,
+            audit.client().user_agent.as_deref(),
+            audit.client().ip,
+        )
+        .await
+    {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            tracing::error!("Passkey login failed: {}", e);
+            audit
+                .entry(AuditAction::AuthLoginFailed, None)
+                .changes(
+                    None::<()>,
+                    Some(json!({ "provider": "passkey", "error": e.to_string() })),
+                )
+                .record()
+                .await;
+            return Err(StatusCode::UNAUTHORIZED.into_response());
+        }
+    };
+
+    audit
+        .entry(AuditAction::AuthLogin, Some(&user_id))
+        .target(AuditTargetType::User, &user_id)
+        .changes(
+            None::<()>,
+            Some(json!({ "provider": "passkey", "new_user": false })),
+        )
+        .record()
+        .await;
+
+    Ok(login_response(&state, jar, access_token, refresh_token))
+}
+
+#[instrument(skip(state), fields(user_id = %actor, passkey_id = %passkey_id))]
+async fn rename_passkey_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(passkey_id): Path<Uuid>,
+    ValidatedRequest(payload): ValidatedRequest<RenamePasskeyDto>,
+) -> ApiResponse<()> {
+    let res = state
+        .passkey_service
+        .rename_passkey(&actor.user_id, passkey_id, &payload.name)
+        .await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor, passkey_id = %passkey_id))]
+async fn delete_passkey_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    audit: Audit,
+    Path(passkey_id): Path<Uuid>,
+) -> ApiResponse<()> {
+    let res = state
+        .passkey_service
+        .delete_passkey(&actor.user_id, passkey_id)
+        .await;
+
+    if res.is_ok() {
+        audit
+            .entry(AuditAction::PasskeyRemoved, Some(&actor.user_id))
+            .target(AuditTargetType::Passkey, passkey_id)
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
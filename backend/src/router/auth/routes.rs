@@ -1,24 +1,42 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Redirect, Response},
     routing::{get, post},
     Json, Router,
 };
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use time::Duration;
 use tracing::instrument;
 
 use crate::{
     auth::{
         generate_csrf_token,
+        keys::current_key_set,
         providers::{
-            discord::DiscordProvider, github::GitHubProvider, google::GoogleProvider, OAuthProvider,
+            discord::DiscordProvider,
+            github::GitHubProvider,
+            google::GoogleProvider,
+            oidc::{OidcAuthRequest, OidcProvider},
+            OAuthProvider,
         },
     },
+    dto::job::EnqueueJobDto,
+    entity::{
+        audit::{AuditAction, AuditTargetType},
+        job::JobKind,
+    },
     router::{
-        auth::tokens::api_tokens_router, clerk::Actor, response::ApiResponse, root::AppState,
+        auth::{
+            accounts::accounts_router, passkeys::passkeys_router, sessions::sessions_router,
+            tokens::api_tokens_router,
+        },
+        clerk::{Actor, OptionalActor},
+        request::{Audit, ClientInfo},
+        response::ApiResponse,
+        root::AppState,
     },
 };
 
@@ -48,6 +66,12 @@ pub struct RefreshRequest {
 
 const ACCESS_TOKEN_EXPIRE_SECONDS: i64 = 900;
 
+// PKCE verifier and nonce of OpenID Connect flows
+const OIDC_REQUEST_COOKIE: &str = "oidc_request";
+
+// CSRF states of flows that link a provider to the signed in user start with this
+pub(super) const LINK_STATE_PREFIX: &str = "link.";
+
 pub fn auth_router() -> Router<AppState> {
     Router::new()
         .route("/oauth/{provider}", get(oauth_authorize_handler))
@@ -56,7 +80,57 @@ pub fn auth_router() -> Router<AppState> {
         .route("/logout", post(logout_handler))
         .route("/me", get(get_current_user_handler))
         .route("/guest", post(assign_guest_handler))
+        .route("/jwks.json", get(jwks_handler))
+        .route("/providers", get(list_providers_handler))
         .nest("/tokens", api_tokens_router())
+        .nest("/sessions", sessions_router())
+        .nest("/accounts", accounts_router())
+        .nest("/passkeys", passkeys_router())
+}
+
+/// Login provider the frontend can offer
+#[derive(Debug, Serialize)]
+pub struct ReadLoginProviderDto {
+    pub id: String,
+    pub name: String,
+    pub oidc: bool,
+}
+
+async fn list_providers_handler(
+    State(state): State<AppState>,
+) -> ApiResponse<Vec<ReadLoginProviderDto>> {
+    let built_in = [
+        ("google", "Google"),
+        ("github", "GitHub"),
+        ("discord", "Discord"),
+    ]
+    .into_iter()
+    .map(|(id, name)| ReadLoginProviderDto {
+        id: id.to_string(),
+        name: name.to_string(),
+        oidc: false,
+    });
+    let oidc = state
+        .config
+        .oidc
+        .iter()
+        .map(|provider| ReadLoginProviderDto {
+            id: provider.name.clone(),
+            name: provider.display_name().to_string(),
+            oidc: true,
+        });
+
+    ApiResponse::Success {
+        data: built_in.chain(oidc).collect(),
+    }
+}
+
+/// Public keys verifying our access tokens, for services that validate them on their own
+async fn jwks_handler() -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(current_key_set().jwks()),
+    )
 }
 
 /// Initiate OAuth flow - redirect user to provider
@@ -75,8 +149,18 @@ async fn oauth_authorize_handler(
     let csrf_state = generate_csrf_token();
     println!("🚀 [AUTHORIZE] Generated CSRF state: {}", csrf_state);
 
+    start_oauth_flow(&provider, &state, jar, csrf_state).await
+}
+
+/// Redirect to the provider, the CSRF state is stored in a cookie and checked in the callback
+pub(super) async fn start_oauth_flow(
+    provider: &str,
+    state: &AppState,
+    mut jar: CookieJar,
+    csrf_state: String,
+) -> Result<(CookieJar, Redirect), StatusCode> {
     // Get provider config and build authorization URL
-    let auth_url = match provider.as_str() {
+    let auth_url = match provider {
         "google" => {
             let config = GoogleProvider::config_from(&state.config.google);
             GoogleProvider::build_authorization_url(&config, &csrf_state)
@@ -90,14 +174,40 @@ async fn oauth_authorize_handler(
             DiscordProvider::build_authorization_url(&config, &csrf_state)
         }
         _ => {
-            println!("❌ [AUTHORIZE] Invalid provider: {}", provider);
-            return Err(StatusCode::BAD_REQUEST);
+            let Some(config) = state.config.find_oidc_provider(provider) else {
+                println!("❌ [AUTHORIZE] Invalid provider: {}", provider);
+                return Err(StatusCode::BAD_REQUEST);
+            };
+
+            // The PKCE verifier and nonce are needed again in the callback
+            let request = OidcAuthRequest::generate();
+            jar = jar.add(oauth_flow_cookie(
+                state,
+                OIDC_REQUEST_COOKIE,
+                request.to_cookie_value(),
+            ));
+
+            OidcProvider::new(config)
+                .build_authorization_url(&csrf_state, &request)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to build OIDC authorization URL: {}", e);
+                    StatusCode::BAD_GATEWAY
+                })?
         }
     };
 
     println!("✅ [AUTHORIZE] Authorization URL: {}", auth_url);
 
-    let mut state_cookie_builder = Cookie::build(("oauth_state", csrf_state.clone()))
+    let jar = jar.add(oauth_flow_cookie(state, "oauth_state", csrf_state));
+
+    println!("✅ [AUTHORIZE] CSRF state cookie set, redirecting to provider");
+    Ok((jar, Redirect::to(&auth_url)))
+}
+
+/// Short lived cookie that carries state of the OAuth flow to the callback
+fn oauth_flow_cookie(state: &AppState, name: &'static str, value: String) -> Cookie<'static> {
+    let mut state_cookie_builder = Cookie::build((name, value))
         .path("/")
         .max_age(Duration::minutes(10))
         .http_only(true);
@@ -117,12 +227,7 @@ async fn oauth_authorize_handler(
         }
     }
 
-    let state_cookie = state_cookie_builder.build();
-
-    let jar = jar.add(state_cookie);
-
-    println!("✅ [AUTHORIZE] CSRF state cookie set, redirecting to provider");
-    Ok((jar, Redirect::to(&auth_url)))
+    state_cookie_builder.build()
 }
 
 /// Handle OAuth callback from provider
@@ -131,6 +236,8 @@ async fn oauth_callback_handler(
     Path(provider): Path<String>,
     Query(params): Query<CallbackParams>,
     State(state): State<AppState>,
+    OptionalActor(actor): OptionalActor,
+    audit: Audit,
     jar: CookieJar,
 ) -> Result<(CookieJar, Redirect), Response> {
     println!(
@@ -163,6 +270,7 @@ async fn oauth_callback_handler(
     }
 
     println!("✅ [CALLBACK] CSRF state validated");
+    let is_link_flow = stored_state.starts_with(LINK_STATE_PREFIX);
 
     // 2. Exchange code for access token and fetch user profile
     let profile = match provider.as_str() {
@@ -212,27 +320,112 @@ async fn oauth_callback_handler(
                 })?
         }
         _ => {
-            println!("❌ [CALLBACK] Invalid provider: {}", provider);
-            return Err((StatusCode::BAD_REQUEST, "Invalid provider").into_response());
+            let Some(config) = state.config.find_oidc_provider(&provider) else {
+                println!("❌ [CALLBACK] Invalid provider: {}", provider);
+                return Err((StatusCode::BAD_REQUEST, "Invalid provider").into_response());
+            };
+
+            let request = jar
+                .get(OIDC_REQUEST_COOKIE)
+                .and_then(|cookie| OidcAuthRequest::from_cookie_value(cookie.value()))
+                .ok_or_else(|| {
+                    (StatusCode::BAD_REQUEST, "Missing PKCE verifier").into_response()
+                })?;
+
+            OidcProvider::new(config)
+                .fetch_user_profile(&params.code, &request)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to fetch OIDC profile: {}", e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch profile").into_response()
+                })?
         }
     };
+    let jar = jar.remove(Cookie::from(OIDC_REQUEST_COOKIE));
 
     println!("✅ [CALLBACK] User profile fetched successfully");
 
-    // 3. Handle OAuth login (create/update user, link account, generate tokens)
-    let user_agent = None; // Could extract from headers if needed
-    let ip = None; // Could extract from ConnectInfo if needed
+    // Linking a provider to the signed in user instead of signing in
+    if is_link_flow {
+        let Some(actor) = actor else {
+            return Err((StatusCode::UNAUTHORIZED, "Sign in to link an account").into_response());
+        };
+
+        let query = match state
+            .auth_service
+            .link_oauth_account(&actor.user_id, &provider, profile)
+            .await
+        {
+            Ok(account) => {
+                audit
+                    .entry(AuditAction::OauthAccountLinked, Some(&actor.user_id))
+                    .target(AuditTargetType::OauthAccount, account.id)
+                    .changes(
+                        None::<()>,
+                        Some(json!({
+                            "provider": account.provider,
+                            "provider_email": account.provider_email,
+                        })),
+                    )
+                    .record()
+                    .await;
+                format!("linked={}", provider)
+            }
+            Err(e) => {
+                tracing::error!("Failed to link OAuth account: {}", e);
+                format!("link_error={}", urlencoding::encode(&e.to_string()))
+            }
+        };
+
+        let redirect_url = format!("{}/home/settings?{}", state.config.frontend.url, query);
+        return Ok((
+            jar.remove(Cookie::from("oauth_state")),
+            Redirect::to(&redirect_url),
+        ));
+    }
 
+    // 3. Handle OAuth login (create/update user, link account, generate tokens)
     println!("🔄 [CALLBACK] Calling handle_oauth_login...");
-    let (access_token, refresh_token, user_id, is_new_user) = state
+    let email = profile.email.clone();
+    let (access_token, refresh_token, user_id, is_new_user) = match state
         .auth_service
-        .handle_oauth_login(&provider, profile, user_agent, ip)
+        .handle_oauth_login(
+            &provider,
+            profile,
+            audit.client().user_agent.as_deref(),
+            audit.client().ip,
+        )
         .await
-        .map_err(|e| {
+    {
+        Ok(login) => login,
+        Err(e) => {
             println!("❌ [CALLBACK] Failed to handle OAuth login: {}", e);
             tracing::error!("Failed to handle OAuth login: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, "Login failed").into_response()
-        })?;
+            audit
+                .entry(AuditAction::AuthLoginFailed, None)
+                .changes(
+                    None::<()>,
+                    Some(json!({
+                        "provider": provider,
+                        "email": email,
+                        "error": e.to_string(),
+                    })),
+                )
+                .record()
+                .await;
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, "Login failed").into_response());
+        }
+    };
+
+    audit
+        .entry(AuditAction::AuthLogin, Some(&user_id))
+        .target(AuditTargetType::User, &user_id)
+        .changes(
+            None::<()>,
+            Some(json!({ "provider": provider, "new_user": is_new_user })),
+        )
+        .record()
+        .await;
 
     println!(
         "✅ [CALLBACK] Tokens generated for user: {}, New user: {}",
@@ -241,39 +434,7 @@ async fn oauth_callback_handler(
 
     println!("🔄 [CALLBACK] Setting auth cookies...");
 
-    let mut access_cookie_builder = Cookie::build(("access_token", access_token.clone()))
-        .path("/")
-        .max_age(Duration::minutes(15))
-        .http_only(true);
-
-    let mut refresh_cookie_builder = Cookie::build(("refresh_token", refresh_token.clone()))
-        .path("/")
-        .max_age(Duration::days(30))
-        .http_only(true);
-
-    match state.config.server.app_env {
-        crate::config::env::AppEnvironment::NowasterProduction
-        | crate::config::env::AppEnvironment::NowasterStaging
-        | crate::config::env::AppEnvironment::NowasterSandbox => {
-            access_cookie_builder = access_cookie_builder
-                .secure(true)
-                .same_site(axum_extra::extract::cookie::SameSite::None);
-            refresh_cookie_builder = refresh_cookie_builder
-                .secure(true)
-                .same_site(axum_extra::extract::cookie::SameSite::None);
-        }
-        crate::config::env::AppEnvironment::NowasterLocal => {
-            access_cookie_builder = access_cookie_builder
-                .secure(false)
-                .same_site(axum_extra::extract::cookie::SameSite::Lax);
-            refresh_cookie_builder = refresh_cookie_builder
-                .secure(false)
-                .same_site(axum_extra::extract::cookie::SameSite::Lax);
-        }
-    }
-
-    let access_cookie = access_cookie_builder.build();
-    let refresh_cookie = refresh_cookie_builder.build();
+    let (access_cookie, refresh_cookie) = auth_cookies(&state, &access_token, &refresh_token);
 
     println!(
         "✅ [CALLBACK] Cookies created: access_token={} chars, refresh_token={} chars",
@@ -307,85 +468,56 @@ async fn oauth_callback_handler(
 }
 
 /// Refresh access token using refresh token
+///
+/// Every refresh rotates the refresh token, the presented one can not be used again.
+/// When the refresh fails the auth cookies are cleared so the client signs in again.
 #[instrument(skip(state))]
 async fn refresh_token_handler(
     State(state): State<AppState>,
+    client: ClientInfo,
     jar: CookieJar,
     Json(req): Json<RefreshRequest>,
-) -> Result<(CookieJar, Json<ApiResponse<TokenResponse>>), StatusCode> {
+) -> Result<(CookieJar, Json<ApiResponse<TokenResponse>>), Response> {
     // Get refresh token from cookie or request body
-    let refresh_token = req
+    let Some(refresh_token) = req
         .refresh_token
         .or_else(|| jar.get("refresh_token").map(|c| c.value().to_string()))
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-
-    let user_agent = None;
-    let ip = None;
+    else {
+        return Err(StatusCode::UNAUTHORIZED.into_response());
+    };
 
-    // Refresh tokens
-    let (access_token, new_refresh_token, _user_id) = state
+    // Rotate tokens
+    let (access_token, new_refresh_token, _user_id) = match state
         .auth_service
-        .refresh_access_token(&refresh_token, user_agent, ip)
+        .refresh_access_token(&refresh_token, client.user_agent.as_deref(), client.ip)
         .await
-        .map_err(|e| {
+    {
+        Ok(tokens) => tokens,
+        Err(e) => {
             tracing::error!("Token refresh failed: {}", e);
-            StatusCode::UNAUTHORIZED
-        })?;
-
-    let mut access_cookie_builder = Cookie::build(("access_token", access_token.clone()))
-        .path("/")
-        .max_age(Duration::seconds(ACCESS_TOKEN_EXPIRE_SECONDS))
-        .http_only(true);
-
-    let mut refresh_cookie_builder = Cookie::build(("refresh_token", new_refresh_token.clone()))
-        .path("/")
-        .max_age(Duration::days(30))
-        .http_only(true);
-
-    match state.config.server.app_env {
-        crate::config::env::AppEnvironment::NowasterProduction
-        | crate::config::env::AppEnvironment::NowasterStaging
-        | crate::config::env::AppEnvironment::NowasterSandbox => {
-            access_cookie_builder = access_cookie_builder
-                .secure(true)
-                .same_site(axum_extra::extract::cookie::SameSite::None);
-            refresh_cookie_builder = refresh_cookie_builder
-                .secure(true)
-                .same_site(axum_extra::extract::cookie::SameSite::None);
-        }
-        crate::config::env::AppEnvironment::NowasterLocal => {
-            access_cookie_builder = access_cookie_builder
-                .secure(false)
-                .same_site(axum_extra::extract::cookie::SameSite::Lax);
-            refresh_cookie_builder = refresh_cookie_builder
-                .secure(false)
-                .same_site(axum_extra::extract::cookie::SameSite::Lax);
+            return Err((clear_auth_cookies(jar), StatusCode::UNAUTHORIZED).into_response());
         }
-    }
-
-    let access_cookie = access_cookie_builder.build();
-    let refresh_cookie = refresh_cookie_builder.build();
-
-    let jar = jar.add(access_cookie).add(refresh_cookie);
-
-    let response = TokenResponse {
-        access_token,
-        refresh_token: new_refresh_token,
-        expires_in: ACCESS_TOKEN_EXPIRE_SECONDS,
     };
 
-    Ok((jar, Json(ApiResponse::Success { data: response })))
+    Ok(login_response(&state, jar, access_token, new_refresh_token))
 }
 
 /// Logout user by revoking refresh token
 #[instrument(skip(state))]
 async fn logout_handler(
     State(state): State<AppState>,
+    audit: Audit,
     jar: CookieJar,
 ) -> Result<(CookieJar, StatusCode), StatusCode> {
     // Get refresh token from cookie
     if let Some(refresh_cookie) = jar.get("refresh_token") {
         let refresh_token = refresh_cookie.value();
+        let user_id = state
+            .auth_service
+            .get_user_from_refresh_token(refresh_token)
+            .await
+            .ok();
+
         state
             .auth_service
             .logout(refresh_token)
@@ -394,8 +526,20 @@ async fn logout_handler(
                 tracing::error!("Logout failed: {}", e);
                 StatusCode::INTERNAL_SERVER_ERROR
             })?;
+
+        if let Some(user_id) = user_id {
+            audit
+                .entry(AuditAction::AuthLogout, Some(&user_id))
+                .target(AuditTargetType::User, &user_id)
+                .record()
+                .await;
+        }
     }
 
+    Ok((clear_auth_cookies(jar), StatusCode::NO_CONTENT))
+}
+
+fn clear_auth_cookies(jar: CookieJar) -> CookieJar {
     let make_removal = |name: &'static str| {
         Cookie::build((name, ""))
             .path("/")
@@ -406,11 +550,61 @@ async fn logout_handler(
             .build()
     };
 
-    let jar = jar
-        .add(make_removal("access_token"))
-        .add(make_removal("refresh_token"));
+    jar.add(make_removal("access_token"))
+        .add(make_removal("refresh_token"))
+}
+
+/// Auth cookies of a session, cross-site outside of local development
+fn auth_cookies(
+    state: &AppState,
+    access_token: &str,
+    refresh_token: &str,
+) -> (Cookie<'static>, Cookie<'static>) {
+    let (secure, same_site) = match state.config.server.app_env {
+        crate::config::env::AppEnvironment::NowasterProduction
+        | crate::config::env::AppEnvironment::NowasterStaging
+        | crate::config::env::AppEnvironment::NowasterSandbox => (true, SameSite::None),
+        crate::config::env::AppEnvironment::NowasterLocal => (false, SameSite::Lax),
+    };
+
+    let access_cookie = Cookie::build(("access_token", access_token.to_string()))
+        .path("/")
+        .max_age(Duration::seconds(ACCESS_TOKEN_EXPIRE_SECONDS))
+        .http_only(true)
+        .secure(secure)
+        .same_site(same_site)
+        .build();
+
+    let refresh_cookie = Cookie::build(("refresh_token", refresh_token.to_string()))
+        .path("/")
+        .max_age(Duration::days(30))
+        .http_only(true)
+        .secure(secure)
+        .same_site(same_site)
+        .build();
+
+    (access_cookie, refresh_cookie)
+}
+
+/// Set the auth cookies of a new session, the tokens are returned as well for non-browser clients
+pub(super) fn login_response(
+    state: &AppState,
+    jar: CookieJar,
+    access_token: String,
+    refresh_token: String,
+) -> (CookieJar, Json<ApiResponse<TokenResponse>>) {
+    let (access_cookie, refresh_cookie) = auth_cookies(state, &access_token, &refresh_token);
 
-    Ok((jar, StatusCode::NO_CONTENT))
+    let response = TokenResponse {
+        access_token,
+        refresh_token,
+        expires_in: ACCESS_TOKEN_EXPIRE_SECONDS,
+    };
+
+    (
+        jar.add(access_cookie).add(refresh_cookie),
+        Json(ApiResponse::Success { data: response }),
+    )
 }
 
 /// Get current authenticated user
@@ -487,11 +681,14 @@ async fn assign_guest_handler(
             }
         };
         tracing::info!("Assigned new guest from pool: {}", id);
-        let _ = state.sandbox_service.increment_unique_users().await;
-        let sandbox_service = state.sandbox_service.clone();
-        tokio::spawn(async move {
-            let _ = sandbox_service.replenish_pool_if_needed().await;
-        });
+        let _ = state.sandbox_service.record_guest_assigned(&id).await;
+        let _ = state
+            .job_service
+            .enqueue(
+                EnqueueJobDto::new(JobKind::ReplenishSandboxPool)
+                    .dedup_key("replenish-sandbox-pool"),
+            )
+            .await;
         (id, name)
     };
 
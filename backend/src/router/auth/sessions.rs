@@ -0,0 +1,152 @@
+use axum::{
+    extract::{Path, State},
+    routing::{delete, get},
+    Router,
+};
+use axum_extra::extract::cookie::CookieJar;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    auth::device::describe_user_agent,
+    dto::impersonation::ReadImpersonationSessionDto,
+    entity::audit::{AuditAction, AuditTargetType},
+    repository::auth::tokens::RefreshTokenSession,
+    router::{clerk::Actor, request::Audit, response::ApiResponse, root::AppState},
+};
+
+/// Device the user is signed in on, backed by an active refresh token
+#[derive(Debug, Serialize)]
+pub struct ReadDeviceSessionDto {
+    pub id: Uuid,
+    pub device: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    pub is_current: bool,
+}
+
+impl ReadDeviceSessionDto {
+    pub fn from_session(session: RefreshTokenSession, is_current: bool) -> Self {
+        Self {
+            id: session.family_id,
+            device: describe_user_agent(session.user_agent.as_deref()),
+            user_agent: session.user_agent,
+            ip_address: session.ip_address.map(|ip| ip.ip().to_string()),
+            created_at: session.created_at,
+            last_used_at: session.last_used_at,
+            expires_at: session.expires_at,
+            is_current,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokeSessionsDto {
+    pub revoked: u64,
+}
+
+pub fn sessions_router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_sessions_handler))
+        .route("/others", delete(revoke_other_sessions_handler))
+        .route("/impersonations", get(list_impersonations_handler))
+        .route("/{session_id}", delete(revoke_session_handler))
+}
+
+#[instrument(skip(state, jar), fields(user_id = %actor))]
+async fn list_sessions_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    jar: CookieJar,
+) -> ApiResponse<Vec<ReadDeviceSessionDto>> {
+    let current_refresh_token = jar.get("refresh_token").map(|c| c.value().to_string());
+
+    let res = state
+        .auth_service
+        .list_sessions(&actor.user_id, current_refresh_token.as_deref())
+        .await
+        .map(|sessions| {
+            sessions
+                .into_iter()
+                .map(|(session, is_current)| {
+                    ReadDeviceSessionDto::from_session(session, is_current)
+                })
+                .collect()
+        });
+
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor, session_id = %session_id))]
+async fn revoke_session_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    audit: Audit,
+    Path(session_id): Path<Uuid>,
+) -> ApiResponse<()> {
+    let res = state
+        .auth_service
+        .revoke_session(&actor.user_id, session_id)
+        .await;
+
+    if res.is_ok() {
+        audit
+            .entry(AuditAction::SessionRevoked, Some(&actor.user_id))
+            .target(AuditTargetType::Session, session_id)
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state, jar), fields(user_id = %actor))]
+async fn revoke_other_sessions_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    audit: Audit,
+    jar: CookieJar,
+) -> ApiResponse<RevokeSessionsDto> {
+    let Some(current_refresh_token) = jar.get("refresh_token").map(|c| c.value().to_string())
+    else {
+        return ApiResponse::Error {
+            message: "Current session could not be determined".to_string(),
+        };
+    };
+
+    let res = state
+        .auth_service
+        .revoke_other_sessions(&actor.user_id, &current_refresh_token)
+        .await
+        .map(|revoked| RevokeSessionsDto { revoked });
+
+    if let Ok(sessions) = &res {
+        audit
+            .entry(AuditAction::SessionRevoked, Some(&actor.user_id))
+            .target(AuditTargetType::User, &actor.user_id)
+            .changes(None::<()>, Some(sessions))
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
+
+/// Times an admin accessed the account of the user
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn list_impersonations_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResponse<Vec<ReadImpersonationSessionDto>> {
+    let res = state
+        .auth_service
+        .list_impersonation_sessions(&actor.user_id)
+        .await;
+
+    ApiResponse::from_result(res)
+}
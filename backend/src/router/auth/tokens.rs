@@ -8,7 +8,10 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::router::{clerk::Actor, root::AppState};
+use crate::{
+    entity::audit::{AuditAction, AuditTargetType},
+    router::{clerk::Actor, request::Audit, root::AppState},
+};
 
 #[derive(Deserialize)]
 pub struct CreateTokenRequest {
@@ -92,6 +95,7 @@ async fn list_tokens(
 async fn create_token(
     actor: Actor,
     State(state): State<AppState>,
+    audit: Audit,
     Json(req): Json<CreateTokenRequest>,
 ) -> Result<Json<ApiResponse<TokenResponse>>, StatusCode> {
     let (token, id) = state
@@ -114,6 +118,19 @@ async fn create_token(
         .find(|t| t.id == id)
         .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    audit
+        .entry(AuditAction::ApiTokenCreated, Some(&actor.user_id))
+        .target(AuditTargetType::ApiToken, record.id)
+        .changes(
+            None::<()>,
+            Some(serde_json::json!({
+                "name": record.name,
+                "expires_at": record.expires_at,
+            })),
+        )
+        .record()
+        .await;
+
     Ok(Json(ApiResponse {
         status: "success".to_string(),
         data: TokenResponse {
@@ -130,6 +147,7 @@ async fn create_token(
 async fn revoke_token(
     actor: Actor,
     State(state): State<AppState>,
+    audit: Audit,
     Path(token_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
     state
@@ -138,6 +156,12 @@ async fn revoke_token(
         .await
         .map_err(|_| StatusCode::NOT_FOUND)?;
 
+    audit
+        .entry(AuditAction::ApiTokenRevoked, Some(&actor.user_id))
+        .target(AuditTargetType::ApiToken, token_id)
+        .record()
+        .await;
+
     Ok(Json(ApiResponse {
         status: "success".to_string(),
         data: serde_json::json!({ "success": true }),
@@ -7,10 +7,11 @@ use axum::{
     http::{request::Parts, StatusCode},
 };
 use axum_extra::extract::cookie::CookieJar;
+use serde::{Deserialize, Serialize};
 use sqlx::Type;
 
 use super::root::AppState;
-use crate::auth::validate_access_token;
+use crate::{auth::validate_access_token, dto::impersonation::ImpersonationSession};
 
 #[derive(Debug, Clone)]
 pub struct Actor {
@@ -24,8 +25,9 @@ impl Display for Actor {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Type)]
+#[derive(Debug, Clone, PartialEq, Type, Serialize, Deserialize)]
 #[sqlx(type_name = "user_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 #[derive(Default)]
 pub enum UserRole {
     #[default]
@@ -90,29 +92,21 @@ impl FromRequestParts<AppState> for Actor {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        if let Some(impersonation_token) = parts
-            .headers
-            .get("X-Impersonation-Token")
-            .and_then(|h| h.to_str().ok())
-        {
-            if let Ok(Some((target_user_id, _admin_user_id))) = state
-                .auth_service
-                .validate_impersonation_token(impersonation_token)
-                .await
-            {
-                tracing::Span::current().record("user_id", target_user_id.as_str());
-                let role = UserRole::User;
-                return Ok(Actor {
-                    user_id: target_user_id,
-                    role,
-                });
-            }
+        // Resolved by the impersonation middleware
+        if let Some(session) = parts.extensions.get::<ImpersonationSession>() {
+            authorize_impersonator(state, session).await?;
+            tracing::Span::current().record("user_id", session.target_user_id.as_str());
+            let role = UserRole::User;
+            return Ok(Actor {
+                user_id: session.target_user_id.clone(),
+                role,
+            });
         }
 
         if let Some(api_key) = parts.headers.get("X-API-Key").and_then(|h| h.to_str().ok()) {
-            if let Ok((user_id, role)) = state.auth_service.validate_api_token(api_key).await {
+            if let Ok((user_id, _)) = state.auth_service.validate_api_token(api_key).await {
                 tracing::Span::current().record("user_id", user_id.as_str());
-                return Ok(Actor { user_id, role });
+                return authorize(state, user_id, None).await;
             }
         }
 
@@ -121,15 +115,66 @@ impl FromRequestParts<AppState> for Actor {
             if let Ok(claims) =
                 validate_access_token(cookie.value(), state.config.server.app_env.as_str())
             {
-                let role = UserRole::from_str(&claims.role).unwrap_or(UserRole::User);
                 tracing::Span::current().record("user_id", claims.sub.as_str());
-                return Ok(Actor {
-                    user_id: claims.sub,
-                    role,
-                });
+                return authorize(state, claims.sub, Some(claims.iat)).await;
             }
         }
 
         Err(StatusCode::UNAUTHORIZED)
     }
 }
+
+/// Check the account behind a valid credential. Suspended and deleted accounts are rejected and
+/// the role is read from the database, so role changes apply before the access token expires.
+/// Access tokens issued before an admin signed the user out everywhere are rejected too.
+async fn authorize(
+    state: &AppState,
+    user_id: String,
+    issued_at: Option<i64>,
+) -> Result<Actor, StatusCode> {
+    let access = state
+        .auth_service
+        .get_account_access(&user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to check account of user {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if access.deleted_at.is_some() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    if access.suspended_at.is_some() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if let (Some(issued_at), Some(revoked_at)) = (issued_at, access.sessions_revoked_at) {
+        if issued_at < revoked_at.timestamp() {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    Ok(Actor {
+        user_id,
+        role: access.role,
+    })
+}
+
+/// Check the admin behind an impersonation session like their own credential, so a suspended,
+/// demoted or signed out admin loses the sessions they started.
+pub(crate) async fn authorize_impersonator(
+    state: &AppState,
+    session: &ImpersonationSession,
+) -> Result<Actor, StatusCode> {
+    let admin = authorize(
+        state,
+        session.admin_user_id.clone(),
+        Some(session.created_at.timestamp()),
+    )
+    .await?;
+
+    if !admin.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(admin)
+}
@@ -1,6 +1,6 @@
 use axum::{
     extract::{Query, State},
-    routing::{delete, patch},
+    routing::{delete, get, patch},
     Router,
 };
 use tracing::instrument;
@@ -10,8 +10,9 @@ use crate::{
     router::{clerk::Actor, request::ValidatedRequest, response::ApiResponse, root::AppState},
     service::friend_service::{
         AcceptFriendRequestDto, CancelFriendRequestDto, CreateFriendRequestDto,
-        FriendRequestStatus, ProcessFriendRequestDto, ReadFriendRequestDto, ReadFriendRequestsDto,
-        ReadFriendshipDto, RejectFriendRequestDto, RemoveFriendDto,
+        DismissFriendSuggestionDto, FriendRequestStatus, FriendSuggestionQueryDto,
+        ProcessFriendRequestDto, ReadFriendRequestDto, ReadFriendRequestsDto,
+        ReadFriendSuggestionDto, ReadFriendshipDto, RejectFriendRequestDto, RemoveFriendDto,
     },
 };
 
@@ -27,6 +28,10 @@ pub fn friend_router() -> Router<AppState> {
             "/friend",
             delete(remove_friend_handler).get(list_friends_handler),
         )
+        .route(
+            "/suggestions",
+            get(list_friend_suggestions_handler).delete(dismiss_friend_suggestion_handler),
+        )
 }
 
 #[instrument( skip(state), fields(user_id = %actor, request_id = %payload.request_id))]
@@ -85,7 +90,7 @@ async fn create_friend_request_handler(
 ) -> ApiResponse<ReadFriendRequestDto> {
     let recipient = state
         .user_service
-        .get_user_by_name(payload.recipient_name)
+        .get_user_by_name(payload.recipient_name, &actor)
         .await;
 
     let recipient = match recipient {
@@ -162,3 +167,29 @@ async fn remove_friend_handler(
     let result = state.friend_service.remove_friend(payload, &actor).await;
     ApiResponse::from_result(result)
 }
+
+#[instrument( skip(state), fields(user_id = %actor))]
+async fn list_friend_suggestions_handler(
+    State(state): State<AppState>,
+    Query(query): Query<FriendSuggestionQueryDto>,
+    actor: Actor,
+) -> ApiResponse<Vec<ReadFriendSuggestionDto>> {
+    let result = state
+        .friend_service
+        .list_friend_suggestions(query, &actor)
+        .await;
+    ApiResponse::from_result(result)
+}
+
+#[instrument( skip(state), fields(user_id = %actor, dismissed_user_id = %payload.user_id))]
+async fn dismiss_friend_suggestion_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    ValidatedRequest(payload): ValidatedRequest<DismissFriendSuggestionDto>,
+) -> ApiResponse<()> {
+    let result = state
+        .friend_service
+        .dismiss_friend_suggestion(payload, &actor)
+        .await;
+    ApiResponse::from_result(result)
+}
@@ -0,0 +1,55 @@
+use axum::{
+    extract::State,
+    routing::{get, post},
+    Router,
+};
+use tracing::instrument;
+
+use crate::{
+    dto::leaderboard::{LeaderboardQueryDto, LeaderboardSettingsDto, ReadLeaderboardDto},
+    router::{clerk::Actor, request::ValidatedRequest, response::ApiResponse, root::AppState},
+};
+
+pub fn leaderboard_router() -> Router<AppState> {
+    Router::new()
+        .route("/friends", post(friends_leaderboard_handler))
+        .route(
+            "/settings",
+            get(get_settings_handler).patch(update_settings_handler),
+        )
+}
+
+#[instrument(skip(state), fields(user_id = %actor, period = ?payload.period))]
+async fn friends_leaderboard_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    ValidatedRequest(payload): ValidatedRequest<LeaderboardQueryDto>,
+) -> ApiResponse<ReadLeaderboardDto> {
+    let res = state
+        .leaderboard_service
+        .get_friends_leaderboard(payload, &actor)
+        .await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn get_settings_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResponse<LeaderboardSettingsDto> {
+    let res = state.leaderboard_service.get_settings(&actor).await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn update_settings_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    ValidatedRequest(payload): ValidatedRequest<LeaderboardSettingsDto>,
+) -> ApiResponse<LeaderboardSettingsDto> {
+    let res = state
+        .leaderboard_service
+        .update_settings(payload, &actor)
+        .await;
+    ApiResponse::from_result(res)
+}
@@ -0,0 +1,53 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header::CONTENT_TYPE, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+
+use crate::{auth::crypto::sha256_hash, metrics::PrometheusExporter, router::root::AppState};
+
+pub fn prometheus_router() -> Router<AppState> {
+    Router::new().route("/metrics", get(prometheus_metrics))
+}
+
+/// Prometheus text exposition, only served when the exporter is enabled and a token is configured
+async fn prometheus_metrics(
+    State(state): State<AppState>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Response {
+    let (Some(exporter), Some(token)) = (&state.prometheus, &state.config.metrics.prometheus_token)
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    // Hashing first keeps the comparison from leaking how much of the token matched
+    let authorized =
+        bearer.is_some_and(|TypedHeader(bearer)| sha256_hash(bearer.token()) == sha256_hash(token));
+    if !authorized {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    match exporter.render() {
+        Ok(body) => ([(CONTENT_TYPE, PrometheusExporter::CONTENT_TYPE)], body).into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to render Prometheus metrics");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Records the template of the matched route on the request span, metrics are labelled by it
+pub async fn record_matched_route(request: Request, next: Next) -> Response {
+    if let Some(route) = request.extensions().get::<MatchedPath>() {
+        tracing::Span::current().record("route", route.as_str());
+    }
+
+    next.run(request).await
+}
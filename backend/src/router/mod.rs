@@ -1,11 +1,16 @@
+pub mod achievement;
 pub mod admin;
 pub mod auth;
 pub mod category;
 pub mod clerk;
 pub mod feed;
 pub mod friend;
+pub mod leaderboard;
+pub mod metrics;
+pub mod moderation;
 pub mod notification;
 pub mod project;
+pub mod rate_limit;
 pub mod release;
 pub mod request;
 pub mod response;
@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, State},
+    routing::{delete, get, post},
+    Router,
+};
+use tracing::instrument;
+
+use crate::{
+    dto::moderation::{BlockUserDto, CreateUserReportDto, ReadBlockedUserDto, ReadUserReportDto},
+    router::{clerk::Actor, request::ValidatedRequest, response::ApiResponse, root::AppState},
+};
+
+pub fn moderation_router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/blocks",
+            get(list_blocked_users_handler).post(block_user_handler),
+        )
+        .route("/blocks/{user_id}", delete(unblock_user_handler))
+        .route("/reports", post(report_user_handler))
+}
+
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn list_blocked_users_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResponse<Vec<ReadBlockedUserDto>> {
+    let res = state.moderation_service.list_blocked_users(&actor).await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor, blocked_id = %payload.user_id))]
+async fn block_user_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    ValidatedRequest(payload): ValidatedRequest<BlockUserDto>,
+) -> ApiResponse<()> {
+    let res = state.moderation_service.block_user(payload, &actor).await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor, blocked_id = %user_id))]
+async fn unblock_user_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    Path(user_id): Path<String>,
+) -> ApiResponse<()> {
+    let res = state.moderation_service.unblock_user(user_id, &actor).await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state, payload), fields(user_id = %actor, reported_id = %payload.user_id))]
+async fn report_user_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    ValidatedRequest(payload): ValidatedRequest<CreateUserReportDto>,
+) -> ApiResponse<ReadUserReportDto> {
+    let res = state.moderation_service.report_user(payload, &actor).await;
+    ApiResponse::from_result(res)
+}
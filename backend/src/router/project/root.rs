@@ -1,6 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
-    routing::{delete, get},
+    routing::{delete, get, patch, post},
     Router,
 };
 use thiserror::Error;
@@ -12,6 +12,8 @@ use crate::{
         project::{
             create_project::CreateProjectDto,
             filter_project::FilterProjectDto,
+            project_invite::ReadProjectInviteDto,
+            project_member::{AddProjectMemberDto, ReadProjectMemberDto, UpdateProjectMemberDto},
             read_project::{ProjectStatsDto, ReadProjectDetailsDto, ReadProjectDto},
             update_project::UpdateProjectDto,
         },
@@ -30,11 +32,33 @@ pub fn project_router() -> Router<AppState> {
         )
         .route("/details", get(get_projects_details))
         .route("/statistics", get(get_project_statistics_handler))
+        .route("/invites", get(get_my_project_invites_handler))
+        .route(
+            "/invites/{invite_id}/accept",
+            post(accept_project_invite_handler),
+        )
+        .route(
+            "/invites/{invite_id}/decline",
+            post(decline_project_invite_handler),
+        )
         .route(
             "/{project_id}",
             delete(delete_project_handler).get(get_project_by_id_handler),
         )
         .route("/{project_id}/tasks", get(get_tasks_by_project_handler))
+        .route(
+            "/{project_id}/members",
+            get(get_project_members_handler).post(add_project_member_handler),
+        )
+        .route(
+            "/{project_id}/members/{user_id}",
+            patch(update_project_member_handler).delete(remove_project_member_handler),
+        )
+        .route("/{project_id}/invites", get(get_project_invites_handler))
+        .route(
+            "/{project_id}/invites/{invite_id}",
+            delete(cancel_project_invite_handler),
+        )
 }
 
 #[instrument(skip(state), fields(user_id = %actor))]
@@ -125,6 +149,118 @@ async fn get_tasks_by_project_handler(
     ApiResponse::from_result(res)
 }
 
+#[instrument(skip(state), fields(user_id = %actor, project_id = %project_id))]
+async fn get_project_members_handler(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    actor: Actor,
+) -> ApiResponse<Vec<ReadProjectMemberDto>> {
+    let res = state.project_service.get_members(project_id, &actor).await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor, project_id = %project_id))]
+async fn add_project_member_handler(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    actor: Actor,
+    ValidatedRequest(payload): ValidatedRequest<AddProjectMemberDto>,
+) -> ApiResponse<ReadProjectInviteDto> {
+    let res = state
+        .project_invite_service
+        .invite_member(project_id, payload, &actor)
+        .await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor, project_id = %project_id, member_id = %member_id))]
+async fn update_project_member_handler(
+    State(state): State<AppState>,
+    Path((project_id, member_id)): Path<(Uuid, String)>,
+    actor: Actor,
+    ValidatedRequest(payload): ValidatedRequest<UpdateProjectMemberDto>,
+) -> ApiResponse<Vec<ReadProjectMemberDto>> {
+    let res = state
+        .project_service
+        .update_member(project_id, member_id, payload, &actor)
+        .await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor, project_id = %project_id, member_id = %member_id))]
+async fn remove_project_member_handler(
+    State(state): State<AppState>,
+    Path((project_id, member_id)): Path<(Uuid, String)>,
+    actor: Actor,
+) -> ApiResponse<()> {
+    let res = state
+        .project_service
+        .remove_member(project_id, member_id, &actor)
+        .await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor, project_id = %project_id))]
+async fn get_project_invites_handler(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    actor: Actor,
+) -> ApiResponse<Vec<ReadProjectInviteDto>> {
+    let res = state
+        .project_invite_service
+        .get_project_invites(project_id, &actor)
+        .await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor, project_id = %project_id, invite_id = %invite_id))]
+async fn cancel_project_invite_handler(
+    State(state): State<AppState>,
+    Path((project_id, invite_id)): Path<(Uuid, Uuid)>,
+    actor: Actor,
+) -> ApiResponse<()> {
+    let res = state
+        .project_invite_service
+        .cancel_invite(project_id, invite_id, &actor)
+        .await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn get_my_project_invites_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResponse<Vec<ReadProjectInviteDto>> {
+    let res = state.project_invite_service.get_my_invites(&actor).await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor, invite_id = %invite_id))]
+async fn accept_project_invite_handler(
+    State(state): State<AppState>,
+    Path(invite_id): Path<Uuid>,
+    actor: Actor,
+) -> ApiResponse<ReadProjectInviteDto> {
+    let res = state
+        .project_invite_service
+        .accept_invite(invite_id, &actor)
+        .await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor, invite_id = %invite_id))]
+async fn decline_project_invite_handler(
+    State(state): State<AppState>,
+    Path(invite_id): Path<Uuid>,
+    actor: Actor,
+) -> ApiResponse<()> {
+    let res = state
+        .project_invite_service
+        .decline_invite(invite_id, &actor)
+        .await;
+    ApiResponse::from_result(res)
+}
+
 #[derive(Error, Debug)]
 pub enum ProjectError {
     #[error("Something went wrong")]
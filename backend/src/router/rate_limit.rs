@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{header::RETRY_AFTER, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::extract::cookie::CookieJar;
+use serde_json::json;
+
+use crate::{
+    auth::validate_access_token,
+    router::{request::ClientInfo, root::AppState},
+    service::rate_limit_service::{RateLimitDecision, RateLimitPolicy},
+};
+
+const fn per_minute(name: &'static str, limit: u32) -> RateLimitPolicy {
+    RateLimitPolicy {
+        name,
+        limit,
+        window: Duration::from_secs(60),
+        fail_closed: false,
+    }
+}
+
+const fn per_hour(name: &'static str, limit: u32) -> RateLimitPolicy {
+    RateLimitPolicy {
+        name,
+        limit,
+        window: Duration::from_secs(60 * 60),
+        fail_closed: false,
+    }
+}
+
+// Routes that create users or check credentials are rejected while the counters are
+// unavailable, everything else stays reachable
+const fn fail_closed(policy: RateLimitPolicy) -> RateLimitPolicy {
+    RateLimitPolicy {
+        fail_closed: true,
+        ..policy
+    }
+}
+
+// OAuth redirects and callbacks hit the providers and create users
+const OAUTH_POLICY: RateLimitPolicy = fail_closed(per_minute("oauth", 20));
+// Every guest is a new user
+const GUEST_POLICY: RateLimitPolicy = fail_closed(per_hour("guest", 10));
+// Every passkey sign up is a new user as well
+const PASSKEY_SIGNUP_POLICY: RateLimitPolicy = fail_closed(per_hour("passkey_signup", 10));
+const AUTH_POLICY: RateLimitPolicy = fail_closed(per_minute("auth", 60));
+const FRIEND_REQUEST_POLICY: RateLimitPolicy = per_hour("friend_request", 30);
+const REACTION_POLICY: RateLimitPolicy = per_minute("reaction", 60);
+// Scripts using API tokens get a smaller budget than people clicking through the app
+const API_TOKEN_POLICY: RateLimitPolicy = per_minute("api_token", 120);
+const DEFAULT_POLICY: RateLimitPolicy = per_minute("default", 300);
+
+/// Who a request is counted against, the most specific identity wins
+enum RateLimitKey {
+    User(String),
+    ApiToken(String),
+    Ip(String),
+}
+
+impl RateLimitKey {
+    // Only the signature of the access token is checked, invalid sessions are rejected by the
+    // handlers anyway. API keys are looked up, an unknown key would otherwise get a fresh budget.
+    async fn from_headers(headers: &HeaderMap, client: &ClientInfo, state: &AppState) -> Self {
+        let jar = CookieJar::from_headers(headers);
+        if let Some(cookie) = jar.get("access_token") {
+            if let Ok(claims) =
+                validate_access_token(cookie.value(), state.config.server.app_env.as_str())
+            {
+                return Self::User(claims.sub);
+            }
+        }
+
+        if let Some(api_key) = headers.get("X-API-Key").and_then(|h| h.to_str().ok()) {
+            if let Ok(Some(token_id)) = state.auth_service.find_active_api_token(api_key).await {
+                return Self::ApiToken(token_id.to_string());
+            }
+        }
+
+        Self::Ip(
+            client
+                .ip
+                .map(|ip| ip.to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+        )
+    }
+
+    fn key_type(&self) -> &'static str {
+        match self {
+            Self::User(_) => "user",
+            Self::ApiToken(_) => "api_token",
+            Self::Ip(_) => "ip",
+        }
+    }
+
+    fn bucket(&self) -> String {
+        match self {
+            Self::User(id) | Self::ApiToken(id) | Self::Ip(id) => {
+                format!("{}:{}", self.key_type(), id)
+            }
+        }
+    }
+}
+
+fn policy_for(method: &Method, path: &str, key: &RateLimitKey) -> RateLimitPolicy {
+    let path = path.strip_prefix("/api").unwrap_or(path);
+
+    match (method, path) {
+        (_, p) if p.starts_with("/auth/oauth/") || p.starts_with("/auth/callback/") => OAUTH_POLICY,
+        (&Method::POST, "/auth/guest") => GUEST_POLICY,
+        (&Method::POST, "/auth/passkeys/signup/finish") => PASSKEY_SIGNUP_POLICY,
+        (_, p) if p.starts_with("/auth/") => AUTH_POLICY,
+        (&Method::POST, "/friends/request") => FRIEND_REQUEST_POLICY,
+        (&Method::POST, p) if p.starts_with("/feed/reaction") => REACTION_POLICY,
+        _ if matches!(key, RateLimitKey::ApiToken(_)) => API_TOKEN_POLICY,
+        _ => DEFAULT_POLICY,
+    }
+}
+
+/// Rejects clients that used up the budget of the route with `429 Too Many Requests`.
+/// When the counter backend fails, the request is rejected on fail closed policies
+/// and let through on all others.
+pub async fn rate_limit_middleware(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.method() == Method::OPTIONS {
+        return next.run(request).await;
+    }
+
+    let key = RateLimitKey::from_headers(request.headers(), &client, &state).await;
+    let policy = policy_for(request.method(), request.uri().path(), &key);
+
+    let decision = match state.rate_limit_service.check(&key.bucket(), &policy).await {
+        Ok(decision) => decision,
+        Err(_) if policy.fail_closed => {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({"status": "fail", "message": "Try again later"})),
+            )
+                .into_response();
+        }
+        Err(_) => RateLimitDecision::Allowed,
+    };
+
+    let RateLimitDecision::Limited { retry_after } = decision else {
+        return next.run(request).await;
+    };
+
+    let user_id = match &key {
+        RateLimitKey::User(user_id) => user_id.as_str(),
+        _ => "",
+    };
+    tracing::warn!(
+        target: "rate_limit",
+        policy = policy.name,
+        key_type = key.key_type(),
+        method = %request.method(),
+        path = request.uri().path(),
+        user_id,
+        "Rate limit exceeded"
+    );
+
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(RETRY_AFTER, retry_after.as_secs().to_string())],
+        Json(json!({"status": "fail", "message": "Too many requests"})),
+    )
+        .into_response()
+}
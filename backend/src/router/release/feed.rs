@@ -0,0 +1,110 @@
+use chrono::{DateTime, Local, SecondsFormat};
+
+use crate::{dto::release::ReadPublicReleaseDto, entity::release::ChangelogKind};
+
+pub const CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+
+/// Atom feed of published releases, entries link to the release pages of the frontend
+pub fn render_atom(frontend_url: &str, releases: &[ReadPublicReleaseDto]) -> String {
+    let base = frontend_url.trim_end_matches('/');
+    let updated = releases
+        .iter()
+        .map(|r| r.released_at)
+        .max()
+        .unwrap_or_else(Local::now);
+
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    xml.push_str("<title>Nowaster releases</title>");
+    xml.push_str(&format!("<id>{}/releases</id>", escape(base)));
+    xml.push_str(&format!(
+        r#"<link rel="alternate" type="text/html" href="{}/releases"/>"#,
+        escape(base)
+    ));
+    xml.push_str(&format!("<updated>{}</updated>", timestamp(updated)));
+
+    for release in releases {
+        let url = format!(
+            "{}/releases/{}",
+            base,
+            urlencoding::encode(&release.version)
+        );
+
+        xml.push_str("<entry>");
+        xml.push_str(&format!(
+            "<title>{} {}</title>",
+            escape(&release.version),
+            escape(&release.name)
+        ));
+        xml.push_str(&format!("<id>{}</id>", escape(&url)));
+        xml.push_str(&format!(
+            r#"<link rel="alternate" type="text/html" href="{}"/>"#,
+            escape(&url)
+        ));
+        xml.push_str(&format!(
+            "<updated>{}</updated>",
+            timestamp(release.released_at)
+        ));
+        if let Some(author) = &release.released_by {
+            xml.push_str(&format!(
+                "<author><name>{}</name></author>",
+                escape(&author.username)
+            ));
+        }
+        if let Some(summary) = &release.short_description {
+            xml.push_str(&format!("<summary>{}</summary>", escape(summary)));
+        }
+        for tag in &release.tags {
+            xml.push_str(&format!(r#"<category term="{}"/>"#, escape(tag)));
+        }
+        if !release.changelog.is_empty() {
+            xml.push_str(&format!(
+                r#"<content type="html">{}</content>"#,
+                escape(&changelog_html(release))
+            ));
+        }
+        xml.push_str("</entry>");
+    }
+
+    xml.push_str("</feed>");
+    xml
+}
+
+fn changelog_html(release: &ReadPublicReleaseDto) -> String {
+    let mut html = String::new();
+
+    for section in &release.changelog {
+        html.push_str(&format!("<h3>{}</h3><ul>", section_title(section.kind)));
+        for item in &section.items {
+            html.push_str(&format!("<li>{}</li>", escape(item)));
+        }
+        html.push_str("</ul>");
+    }
+
+    html
+}
+
+fn section_title(kind: ChangelogKind) -> &'static str {
+    match kind {
+        ChangelogKind::Added => "Added",
+        ChangelogKind::Changed => "Changed",
+        ChangelogKind::Fixed => "Fixed",
+        ChangelogKind::Deprecated => "Deprecated",
+        ChangelogKind::Removed => "Removed",
+        ChangelogKind::Security => "Security",
+    }
+}
+
+fn timestamp(time: DateTime<Local>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -1,6 +1,7 @@
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     routing::get,
     Json, Router,
 };
@@ -8,12 +9,15 @@ use tracing::instrument;
 
 use crate::{
     dto::release::{LatestUnseenReleaseDto, ReadPublicReleaseDto},
-    router::{clerk::OptionalActor, response::ApiResponse, root::AppState},
+    router::{clerk::OptionalActor, release::feed, response::ApiResponse, root::AppState},
 };
 
+const FEED_RELEASES_LIMIT: i64 = 50;
+
 pub fn release_router() -> Router<AppState> {
     Router::new()
         .route("/", get(list_public_releases))
+        .route("/feed.atom", get(get_release_feed))
         .route("/latest", get(get_latest_release_unseen))
         .route("/{version}", get(get_release_by_version))
 }
@@ -34,6 +38,25 @@ async fn list_public_releases(
     Ok(Json(ApiResponse::Success { data: releases }))
 }
 
+#[instrument(skip(state))]
+async fn get_release_feed(State(state): State<AppState>) -> Response {
+    match state
+        .release_service
+        .list_feed_releases(FEED_RELEASES_LIMIT)
+        .await
+    {
+        Ok(releases) => (
+            [(header::CONTENT_TYPE, feed::CONTENT_TYPE)],
+            feed::render_atom(&state.config.frontend.url, &releases),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to build release feed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
 #[instrument(skip(state))]
 async fn get_release_by_version(
     State(state): State<AppState>,
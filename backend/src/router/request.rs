@@ -1,12 +1,22 @@
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+
 use axum::{
-    extract::{rejection::JsonRejection, FromRequest, Request},
+    extract::{rejection::JsonRejection, ConnectInfo, FromRequest, FromRequestParts, Request},
+    http::{header::USER_AGENT, request::Parts, HeaderMap},
     Json,
 };
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 use validator::Validate;
 
-use super::response::ApiResponse;
+use super::{response::ApiResponse, root::AppState};
+use crate::{
+    entity::audit::AuditAction,
+    service::audit_service::{AuditEntry, AuditService},
+};
 
 pub struct ValidatedRequest<T>(pub T);
 
@@ -37,3 +47,108 @@ where
         Ok(Self(parsed_value))
     }
 }
+
+// Matches the size of `refresh_tokens.user_agent`
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// User agent and IP address of the client that sent the request.
+/// Forwarding headers are only believed when the peer is a configured trusted proxy.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip: Option<IpAddr>,
+}
+
+impl FromRequestParts<AppState> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(USER_AGENT)
+            .and_then(|h| h.to_str().ok())
+            .map(|ua| ua.chars().take(MAX_USER_AGENT_LENGTH).collect());
+
+        let server = &state.config.server;
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| peer.ip())
+            .map(|peer| {
+                if server.is_trusted_proxy(peer) {
+                    forwarded_ip(&parts.headers, |ip| server.is_trusted_proxy(ip)).unwrap_or(peer)
+                } else {
+                    peer
+                }
+            });
+
+        Ok(Self { user_agent, ip })
+    }
+}
+
+/// Writes audit log entries attributed to the client that sent the request
+pub struct Audit {
+    service: AuditService,
+    client: ClientInfo,
+}
+
+impl FromRequestParts<AppState> for Audit {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let client = ClientInfo::from_request_parts(parts, state).await?;
+        Ok(Self {
+            service: state.audit_service.clone(),
+            client,
+        })
+    }
+}
+
+// Handlers are instrumented, only the client is worth recording on their spans
+impl std::fmt::Debug for Audit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.client.fmt(f)
+    }
+}
+
+impl Audit {
+    pub fn entry(&self, action: AuditAction, actor_id: Option<&str>) -> AuditEntry<'_> {
+        self.service.entry(action, actor_id, &self.client)
+    }
+
+    pub fn client(&self) -> &ClientInfo {
+        &self.client
+    }
+}
+
+/// Every proxy appends the address it received the request from to X-Forwarded-For, so the
+/// client is the last entry not added by a trusted proxy. Earlier entries can be forged.
+fn forwarded_ip(headers: &HeaderMap, is_trusted: impl Fn(IpAddr) -> bool) -> Option<IpAddr> {
+    let forwarded = headers
+        .get_all("X-Forwarded-For")
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(','))
+        .filter_map(|ip| ip.trim().parse::<IpAddr>().ok())
+        .collect::<Vec<_>>();
+
+    if forwarded.is_empty() {
+        return headers
+            .get("X-Real-IP")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|ip| ip.trim().parse().ok());
+    }
+
+    forwarded
+        .iter()
+        .rev()
+        .find(|ip| !is_trusted(**ip))
+        .or(forwarded.first())
+        .copied()
+}
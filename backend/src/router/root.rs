@@ -1,20 +1,31 @@
-use std::sync::Arc;
+use anyhow::{Context, Result};
+use std::{net::SocketAddr, sync::Arc};
 
 use axum::{
+    extract::connect_info::IntoMakeServiceWithConnectInfo,
     http::{self, Request},
-    routing::IntoMakeService,
+    middleware::Next,
+    response::Response,
     Router,
 };
 use uuid::Uuid;
 
 use crate::{
+    auth::keys::{init_key_set, spawn_key_reloader, JwtKeySource},
     config::database::Database,
+    dto::job::EnqueueJobDto,
+    entity::job::JobKind,
+    metrics::PrometheusExporter,
     repository::{
+        achievement::AchievementRepository,
         category::{CategoryRepository, CategoryRepositoryTrait},
         feed::FeedRepository,
         fixed_session::{FixedSessionRepository, SessionRepositoryTrait},
         friends::FriendsRepository,
+        leaderboard::LeaderboardRepository,
+        moderation::ModerationRepository,
         project::{ProjectRepository, ProjectRepositoryTrait},
+        project_invite::ProjectInviteRepository,
         session_template::RecurringSessionRepository,
         statistics::sessions::StatisticsRepository,
         stopwatch_session::StopwatchSessionRepository,
@@ -24,15 +35,27 @@ use crate::{
     },
     router::user::root::protected_user_router,
     service::{
+        account_deletion_service::AccountDeletionService,
+        achievement_service::AchievementService,
+        audit_service::AuditService,
         auth_service::AuthService,
+        backup_service::BackupService,
         category_service::CategoryService,
         feed::{
             events::FeedEventService, reactions::FeedReactionService,
             subscriptions::FeedSubscriptionService, visibility::FeedVisibilityService,
         },
         friend_service::{FriendService, FriendServiceTrait},
+        job_service::{JobHandlers, JobService},
+        leaderboard_service::LeaderboardService,
+        metrics_service::MetricsService,
+        moderation_service::ModerationService,
         notification_service::NotificationService,
+        passkey_service::PasskeyService,
+        platform_stats_service::PlatformStatsService,
+        project_invite_service::ProjectInviteService,
         project_service::ProjectService,
+        rate_limit_service::RateLimitService,
         release_service::ReleaseService,
         sandbox_service::SandboxService,
         session::{fixed::FixedSessionService, stopwatch::StopwatchSessionService},
@@ -42,17 +65,27 @@ use crate::{
         task_service::TaskService,
         user_service::UserService,
     },
+    telemetry::extract_trace_context,
 };
 
 use super::{
-    admin::routes::admin_router, auth::auth_router, category::root::category_router,
-    feed::root::feed_router, friend::root::friend_router, notification::root::notification_router,
+    achievement::root::achievement_router, admin::routes::admin_router, auth::auth_router, category::root::category_router,
+    feed::root::feed_router, friend::root::friend_router, leaderboard::root::leaderboard_router,
+    moderation::root::moderation_router, notification::root::notification_router,
     project::root::project_router, release::routes::release_router, session::root::session_router,
     statistics::root::statistics_router, tag::root::tag_router, task::root::task_router,
 };
 
+use super::{
+    admin::impersonation::impersonation_middleware,
+    metrics::{prometheus_router, record_matched_route},
+    rate_limit::rate_limit_middleware,
+};
 use tower_http::trace::TraceLayer;
 use tracing::info_span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+const REQUEST_ID_HEADER: &str = "x-request-id";
 
 #[derive(Clone)]
 pub struct Feed {
@@ -65,6 +98,9 @@ pub struct Feed {
 #[derive(Clone)]
 pub struct AppState {
     pub config: Arc<crate::Config>,
+    pub account_deletion_service: AccountDeletionService,
+    pub achievement_service: AchievementService,
+    pub audit_service: AuditService,
     pub auth_service: AuthService,
     pub session_service: FixedSessionService,
     pub stopwatch_service: StopwatchSessionService,
@@ -75,16 +111,28 @@ pub struct AppState {
     pub friend_service: Arc<dyn FriendServiceTrait + Send + Sync>,
     pub session_template_service: SessionTemplateService,
     pub notification_service: NotificationService,
+    pub passkey_service: PasskeyService,
     pub release_service: ReleaseService,
     pub project_service: ProjectService,
+    pub project_invite_service: ProjectInviteService,
     pub task_service: TaskService,
     pub sandbox_service: SandboxService,
-    pub db_backup_repo: crate::repository::db_backup::DbBackupRepository,
-    pub s3_client: aws_sdk_s3::Client,
+    pub leaderboard_service: LeaderboardService,
+    pub metrics_service: MetricsService,
+    pub platform_stats_service: PlatformStatsService,
+    pub moderation_service: ModerationService,
+    pub rate_limit_service: RateLimitService,
+    pub prometheus: Option<PrometheusExporter>,
+    pub backup_service: BackupService,
+    pub job_service: JobService,
     pub feed: Feed,
 }
 
-pub async fn get_router(db: Arc<Database>, config: Arc<crate::Config>) -> IntoMakeService<Router> {
+pub async fn get_router(
+    db: Arc<Database>,
+    config: Arc<crate::Config>,
+    prometheus: Option<PrometheusExporter>,
+) -> Result<IntoMakeServiceWithConnectInfo<Router, SocketAddr>> {
     let category_repo = CategoryRepository::new(&db);
     let tag_repo = TagRepository::new(&db);
     let session_repo = FixedSessionRepository::new(&db);
@@ -95,8 +143,19 @@ pub async fn get_router(db: Arc<Database>, config: Arc<crate::Config>) -> IntoMa
     let template_session_repo = RecurringSessionRepository::new(&db);
     let feed_repo = FeedRepository::new(&db);
     let project_repo = ProjectRepository::new(&db);
+    let project_invite_repo = ProjectInviteRepository::new(&db);
     let task_repo = TaskRepository::new(&db);
-    let db_backup_repo = crate::repository::db_backup::DbBackupRepository::new(&db);
+    let leaderboard_repo = LeaderboardRepository::new(&db);
+    let moderation_repo = ModerationRepository::new(&db);
+    let achievement_repo = AchievementRepository::new(&db);
+
+    // Load the JWT keys and keep reloading them, so keys can be rolled over without a restart
+    let jwt_key_source =
+        JwtKeySource::from_config(&config.jwt_keys, &db).context("JWT key error")?;
+    init_key_set(&jwt_key_source)
+        .await
+        .context("JWT key error")?;
+    spawn_key_reloader(jwt_key_source);
 
     // Initialize S3 client
     use aws_config::BehaviorVersion;
@@ -115,12 +174,21 @@ pub async fn get_router(db: Arc<Database>, config: Arc<crate::Config>) -> IntoMa
     let s3_client = aws_sdk_s3::Client::from_conf(s3_config);
 
     let auth_service = AuthService::new(&db, config.server.app_env.clone());
+    let audit_service = AuditService::new(&db);
+    let passkey_service = PasskeyService::new(&db, &config.frontend.url, auth_service.clone())
+        .context("Passkey config error")?;
+    let rate_limit_service = RateLimitService::new(&db, &config.rate_limit.backend);
+    let metrics_service = MetricsService::new(&db, &config.metrics);
+    let account_deletion_service = AccountDeletionService::new(&db, audit_service.clone());
     let category_service = CategoryService::new(category_repo.clone());
     let tag_service = TagService::new(tag_repo, category_repo.clone());
     let statistics_service = StatisticsService::new(statistics_repo);
 
     let notification_service = NotificationService::new(&db);
-    let release_service = ReleaseService::new(&db);
+
+    let backup_service = BackupService::new(&db, &config, s3_client, notification_service.clone());
+
+    let release_service = ReleaseService::new(&db, notification_service.clone());
     let sandbox_service = SandboxService::new(&db);
 
     // Initialize sandbox environment if needed
@@ -133,11 +201,51 @@ pub async fn get_router(db: Arc<Database>, config: Arc<crate::Config>) -> IntoMa
     let event_service = FeedEventService::new(feed_repo.clone());
     let subscription_service = FeedSubscriptionService::new(feed_repo.clone(), user_repo.clone());
 
+    let platform_stats_service = PlatformStatsService::new(&db);
+
     let user_service = UserService::new(
         user_repo.clone(),
         visibility_service.clone(),
         subscription_service.clone(),
+        achievement_repo.clone(),
+    );
+
+    let achievement_service = AchievementService::new(
+        achievement_repo,
+        notification_service.clone(),
+        event_service.clone(),
+        user_service.clone(),
+    );
+
+    let leaderboard_service = LeaderboardService::new(leaderboard_repo);
+
+    // Maintenance runs as queued jobs, scheduled by cron expressions
+    let job_service = JobService::new(
+        &db,
+        &config,
+        JobHandlers {
+            audit_service: audit_service.clone(),
+            notification_service: notification_service.clone(),
+            sandbox_service: sandbox_service.clone(),
+            visibility_service: visibility_service.clone(),
+            platform_stats_service: platform_stats_service.clone(),
+            rate_limit_service: rate_limit_service.clone(),
+            leaderboard_service: leaderboard_service.clone(),
+            metrics_service: metrics_service.clone(),
+            account_deletion_service: account_deletion_service.clone(),
+            backup_service: backup_service.clone(),
+            achievement_service: achievement_service.clone(),
+            release_service: release_service.clone(),
+        },
     );
+    job_service.spawn_workers();
+
+    // Award achievements users already qualify for, e.g. after new rules were added
+    let backfill =
+        EnqueueJobDto::new(JobKind::BackfillAchievements).dedup_key("backfill-achievements");
+    if let Err(e) = job_service.enqueue(backfill).await {
+        tracing::error!("Failed to enqueue the achievement backfill: {:#}", e);
+    }
 
     let session_service = FixedSessionService::new(
         session_repo.clone(),
@@ -146,6 +254,7 @@ pub async fn get_router(db: Arc<Database>, config: Arc<crate::Config>) -> IntoMa
         user_service.clone(),
         project_repo.clone(),
         task_repo.clone(),
+        achievement_service.clone(),
     );
 
     let task_service = TaskService::new(
@@ -153,19 +262,27 @@ pub async fn get_router(db: Arc<Database>, config: Arc<crate::Config>) -> IntoMa
         project_repo.clone(),
         event_service.clone(),
         user_service.clone(),
+        achievement_service.clone(),
     );
 
     let project_service = ProjectService::new(
-        project_repo,
+        project_repo.clone(),
         event_service.clone(),
         user_service.clone(),
         task_service.clone(),
         session_service.clone(),
+        achievement_service.clone(),
+    );
+    let project_invite_service = ProjectInviteService::new(
+        project_invite_repo,
+        project_service.clone(),
+        notification_service.clone(),
     );
     let reaction_service = FeedReactionService::new(
         feed_repo.clone(),
         notification_service.clone(),
         session_repo.clone(),
+        moderation_repo.clone(),
     );
     let friend_service = FriendService::new(
         friend_repo,
@@ -173,13 +290,26 @@ pub async fn get_router(db: Arc<Database>, config: Arc<crate::Config>) -> IntoMa
         subscription_service.clone(),
         notification_service.clone(),
     );
-    let stopwatch_service =
-        StopwatchSessionService::new(category_service.clone(), stopwatch_repo.clone());
+    let stopwatch_service = StopwatchSessionService::new(
+        category_service.clone(),
+        stopwatch_repo.clone(),
+        project_repo.clone(),
+        task_repo.clone(),
+    );
     let session_template_service =
         SessionTemplateService::new(template_session_repo, session_repo.clone());
+    let moderation_service = ModerationService::new(
+        moderation_repo,
+        visibility_service.clone(),
+        notification_service.clone(),
+        auth_service.clone(),
+    );
 
     let state = AppState {
         config: config.clone(),
+        account_deletion_service,
+        achievement_service,
+        audit_service,
         auth_service,
         friend_service: Arc::new(friend_service),
         session_service,
@@ -190,12 +320,20 @@ pub async fn get_router(db: Arc<Database>, config: Arc<crate::Config>) -> IntoMa
         stopwatch_service,
         session_template_service,
         notification_service,
+        passkey_service,
         release_service,
         project_service,
+        project_invite_service,
         task_service,
         sandbox_service,
-        db_backup_repo,
-        s3_client,
+        leaderboard_service,
+        metrics_service,
+        platform_stats_service,
+        moderation_service,
+        rate_limit_service,
+        prometheus,
+        backup_service,
+        job_service,
         feed: Feed {
             subscription_service,
             visibility_service,
@@ -218,6 +356,15 @@ pub async fn get_router(db: Arc<Database>, config: Arc<crate::Config>) -> IntoMa
         .nest("/statistics", statistics_router().with_state(state.clone()))
         .nest("/friends", friend_router().with_state(state.clone()))
         .nest("/feed", feed_router().with_state(state.clone()))
+        .nest(
+            "/achievements",
+            achievement_router().with_state(state.clone()),
+        )
+        .nest(
+            "/leaderboard",
+            leaderboard_router().with_state(state.clone()),
+        )
+        .nest("/moderation", moderation_router().with_state(state.clone()))
         .nest(
             "/notifications",
             notification_router().with_state(state.clone()),
@@ -234,7 +381,11 @@ pub async fn get_router(db: Arc<Database>, config: Arc<crate::Config>) -> IntoMa
         | crate::config::env::AppEnvironment::NowasterSandbox => {
             let frontend_url = &config.frontend.url;
             println!("🌐 [CORS] Allowing origin: {}", frontend_url);
-            tower_http::cors::AllowOrigin::exact(frontend_url.parse::<http::HeaderValue>().unwrap())
+            tower_http::cors::AllowOrigin::exact(
+                frontend_url
+                    .parse::<http::HeaderValue>()
+                    .context("FRONTEND_URL is not a valid origin")?,
+            )
         }
         _ => tower_http::cors::AllowOrigin::predicate(|origin, _| {
             origin
@@ -261,18 +412,35 @@ pub async fn get_router(db: Arc<Database>, config: Arc<crate::Config>) -> IntoMa
             http::header::COOKIE,
             http::HeaderName::from_static("x-api-key"),
             http::HeaderName::from_static("x-impersonation-token"),
+            http::HeaderName::from_static(REQUEST_ID_HEADER),
+            http::HeaderName::from_static("traceparent"),
+            http::HeaderName::from_static("tracestate"),
         ])
+        .expose_headers([http::HeaderName::from_static(REQUEST_ID_HEADER)])
         .allow_credentials(true);
 
-    Router::new()
+    let router = Router::new()
         .nest("/api", api_router)
+        .merge(prometheus_router().with_state(state.clone()))
+        .route_layer(axum::middleware::from_fn(record_matched_route))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            impersonation_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state,
+            rate_limit_middleware,
+        ))
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(make_span_for_request)
                 .on_response(record_response_status),
         )
+        .layer(axum::middleware::from_fn(request_id_middleware))
         .layer(cors)
-        .into_make_service()
+        .into_make_service_with_connect_info::<SocketAddr>();
+
+    Ok(router)
 }
 
 fn record_response_status<B>(
@@ -283,14 +451,41 @@ fn record_response_status<B>(
     span.record("http.status_code", response.status().as_u16());
 }
 
+fn request_id_from_headers(headers: &http::HeaderMap) -> Option<Uuid> {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| Uuid::parse_str(value).ok())
+}
+
+/// Keeps a valid incoming `X-Request-Id` or assigns a new one, and returns it on the response
+async fn request_id_middleware(mut request: axum::extract::Request, next: Next) -> Response {
+    let request_id = request_id_from_headers(request.headers()).unwrap_or_else(Uuid::new_v4);
+    let header = http::HeaderValue::from_str(&request_id.to_string())
+        .expect("UUIDs are valid header values");
+
+    request
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, header.clone());
+    let mut response = next.run(request).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, header);
+    response
+}
+
 fn make_span_for_request<B>(req: &Request<B>) -> tracing::Span {
-    let request_id = Uuid::new_v4();
-    info_span!(
+    // Always set by request_id_middleware, which runs first
+    let request_id = request_id_from_headers(req.headers()).unwrap_or_else(Uuid::new_v4);
+    let span = info_span!(
         "http_request",
         method = %req.method(),
         uri = %req.uri(),
         request_id = %request_id,
+        route = tracing::field::Empty,
         http.status_code = tracing::field::Empty,
         user_id = tracing::field::Empty,
-    )
+    );
+
+    // Continue the caller's trace, a no-op unless trace export is enabled
+    let _ = span.set_parent(extract_trace_context(req.headers()));
+    span
 }
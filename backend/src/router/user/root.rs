@@ -1,11 +1,14 @@
-use crate::dto::user::read_user::ReadUserDto;
+use crate::dto::user::account_deletion::ReadAccountDeletionDto;
+use crate::dto::user::beta_opt_in::BetaOptInDto;
+use crate::dto::user::read_user::{ReadUserDto, ReadUserProfileDto};
 use crate::dto::user::update_user::UpdateUserDto;
 use crate::dto::user::update_visibility::{UpdateVisibilityDto, UpdateVisibilitySettingsDto};
+use crate::entity::audit::{AuditAction, AuditTargetType};
 use crate::router::clerk::Actor;
-use crate::router::request::ValidatedRequest;
+use crate::router::request::{Audit, ValidatedRequest};
 use crate::router::response::ApiResponse;
 use crate::router::root::AppState;
-use axum::routing::patch;
+use axum::routing::{get, patch};
 use axum::{extract::State, Router};
 use thiserror::Error;
 use tracing::instrument;
@@ -17,6 +20,16 @@ pub fn protected_user_router() -> Router<AppState> {
             patch(update_user_handler).get(get_current_user_handler),
         )
         .route("/visibility", patch(update_visibility_handler))
+        .route(
+            "/beta",
+            get(get_beta_opt_in_handler).put(update_beta_opt_in_handler),
+        )
+        .route(
+            "/deletion",
+            get(get_account_deletion_handler)
+                .post(request_account_deletion_handler)
+                .delete(cancel_account_deletion_handler),
+        )
 }
 
 #[instrument(skip(state))]
@@ -33,8 +46,8 @@ async fn update_user_handler(
 async fn get_current_user_handler(
     State(state): State<AppState>,
     actor: Actor,
-) -> ApiResponse<ReadUserDto> {
-    let res = match state.user_service.get_user_by_id(&actor.user_id).await {
+) -> ApiResponse<ReadUserProfileDto> {
+    let res = match state.user_service.get_user_profile(&actor.user_id).await {
         Ok(Some(user)) => Ok(user),
         Ok(None) => Err(UserError::UserNotFound),
         Err(e) => Err(e),
@@ -46,16 +59,115 @@ async fn get_current_user_handler(
 async fn update_visibility_handler(
     State(state): State<AppState>,
     actor: Actor,
+    audit: Audit,
     ValidatedRequest(payload): ValidatedRequest<UpdateVisibilitySettingsDto>,
 ) -> ApiResponse<ReadUserDto> {
+    let before = state
+        .user_service
+        .get_user_by_id(&actor.user_id)
+        .await
+        .ok()
+        .flatten()
+        .map(|user| user.visibility_flags);
+
     let visibility_dto: UpdateVisibilityDto = payload.into();
     let res = state
         .user_service
-        .update_visibility(actor.user_id, visibility_dto)
+        .update_visibility(actor.user_id.clone(), visibility_dto)
+        .await;
+
+    if let Ok(user) = &res {
+        audit
+            .entry(AuditAction::VisibilityChanged, Some(&actor.user_id))
+            .target(AuditTargetType::User, &actor.user_id)
+            .changes(before, Some(user.visibility_flags))
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn get_beta_opt_in_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResponse<BetaOptInDto> {
+    let res = state.user_service.get_beta_opt_in(&actor.user_id).await;
+    ApiResponse::from_result(res)
+}
+
+/// Opt into or out of announcements of beta releases
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn update_beta_opt_in_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    ValidatedRequest(payload): ValidatedRequest<BetaOptInDto>,
+) -> ApiResponse<BetaOptInDto> {
+    let res = state
+        .user_service
+        .set_beta_opt_in(&actor.user_id, payload)
         .await;
     ApiResponse::from_result(res)
 }
 
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn get_account_deletion_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+) -> ApiResponse<Option<ReadAccountDeletionDto>> {
+    let res = state
+        .account_deletion_service
+        .get_deletion(&actor.user_id)
+        .await;
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn request_account_deletion_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    audit: Audit,
+) -> ApiResponse<ReadAccountDeletionDto> {
+    let res = state
+        .account_deletion_service
+        .request_deletion(&actor.user_id, &actor)
+        .await;
+
+    if let Ok(deletion) = &res {
+        audit
+            .entry(AuditAction::AccountDeletionRequested, Some(&actor.user_id))
+            .target(AuditTargetType::User, &actor.user_id)
+            .changes(None::<()>, Some(deletion))
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
+
+#[instrument(skip(state), fields(user_id = %actor))]
+async fn cancel_account_deletion_handler(
+    State(state): State<AppState>,
+    actor: Actor,
+    audit: Audit,
+) -> ApiResponse<()> {
+    let res = state
+        .account_deletion_service
+        .cancel_deletion(&actor.user_id)
+        .await;
+
+    if res.is_ok() {
+        audit
+            .entry(AuditAction::AccountDeletionCancelled, Some(&actor.user_id))
+            .target(AuditTargetType::User, &actor.user_id)
+            .record()
+            .await;
+    }
+
+    ApiResponse::from_result(res)
+}
+
 #[derive(Error, Debug)]
 pub enum UserError {
     #[error("User not found")]
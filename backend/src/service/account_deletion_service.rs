@@ -0,0 +1,134 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::{
+    config::database::Database,
+    dto::user::account_deletion::ReadAccountDeletionDto,
+    entity::audit::{AuditAction, AuditTargetType},
+    repository::account_deletion::AccountDeletionRepository,
+    router::{clerk::Actor, request::ClientInfo},
+    service::audit_service::AuditService,
+};
+
+// Users can still log in and cancel during the grace period
+const GRACE_PERIOD_DAYS: i64 = 30;
+
+#[derive(Clone)]
+pub struct AccountDeletionService {
+    repo: AccountDeletionRepository,
+    audit_service: AuditService,
+}
+
+impl AccountDeletionService {
+    pub fn new(db: &Arc<Database>, audit_service: AuditService) -> Self {
+        Self {
+            repo: AccountDeletionRepository::new(db),
+            audit_service,
+        }
+    }
+
+    /// Schedule the erasure of the account after the grace period
+    #[instrument(err, skip(self), fields(user_id = %user_id, requested_by = %requested_by))]
+    pub async fn request_deletion(
+        &self,
+        user_id: &str,
+        requested_by: &Actor,
+    ) -> Result<ReadAccountDeletionDto> {
+        let scheduled_for = Utc::now() + Duration::days(GRACE_PERIOD_DAYS);
+
+        self.repo
+            .schedule(user_id, &requested_by.user_id, scheduled_for)
+            .await?
+            .ok_or_else(|| anyhow!("Account was already deleted"))
+    }
+
+    /// Erase the account right away, only admins skip the grace period
+    #[instrument(err, skip(self), fields(user_id = %user_id, admin_id = %admin))]
+    pub async fn delete_immediately(
+        &self,
+        user_id: &str,
+        admin: &Actor,
+    ) -> Result<ReadAccountDeletionDto> {
+        if !admin.is_admin() {
+            return Err(anyhow!("Only admins can delete accounts immediately"));
+        }
+
+        self.repo
+            .schedule(user_id, &admin.user_id, Utc::now())
+            .await?
+            .ok_or_else(|| anyhow!("Account was already deleted"))?;
+
+        self.erase(user_id).await?;
+
+        self.repo
+            .get(user_id)
+            .await?
+            .ok_or_else(|| anyhow!("Account deletion not found"))
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn cancel_deletion(&self, user_id: &str) -> Result<()> {
+        if !self.repo.cancel(user_id).await? {
+            return Err(anyhow!("No pending account deletion"));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn get_deletion(&self, user_id: &str) -> Result<Option<ReadAccountDeletionDto>> {
+        self.repo.get(user_id).await
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn list_pending(&self) -> Result<Vec<ReadAccountDeletionDto>> {
+        self.repo.list_pending().await
+    }
+
+    /// Erase every account whose grace period is over, returns how many were erased.
+    /// Fails when any account could not be erased, so the job is retried for the rest.
+    #[instrument(err, skip(self))]
+    pub async fn erase_due_accounts(&self) -> Result<usize> {
+        let due = self.repo.list_due().await?;
+        let mut erased = 0;
+        let mut failed = 0;
+
+        for deletion in due {
+            // One broken account must not block the others
+            match self.erase(&deletion.user_id).await {
+                Ok(()) => erased += 1,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to erase deleted account {}: {:#}",
+                        deletion.user_id,
+                        e
+                    );
+                    failed += 1;
+                }
+            }
+        }
+
+        if failed > 0 {
+            anyhow::bail!("Failed to erase {} accounts, erased {}", failed, erased);
+        }
+
+        Ok(erased)
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    async fn erase(&self, user_id: &str) -> Result<()> {
+        let deletion = self.repo.get(user_id).await?;
+        self.repo.erase_user(user_id).await?;
+
+        self.audit_service
+            .entry(AuditAction::AccountDeleted, None, &ClientInfo::default())
+            .target(AuditTargetType::User, user_id)
+            .changes(deletion, None::<()>)
+            .record()
+            .await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use tracing::instrument;
+
+use crate::{
+    auth::crypto::sha256_hash,
+    dto::{
+        achievement::{AchievementProgressDto, ReadAchievementDto},
+        feed::CreateFeedEventDto,
+    },
+    entity::{
+        achievement::{AchievementRule, ACHIEVEMENTS},
+        feed::{AchievementEventData, FeedEventSource, FeedEventType},
+    },
+    repository::achievement::AchievementRepository,
+    router::clerk::Actor,
+    service::{
+        feed::events::FeedEventService, notification_service::NotificationService,
+        user_service::UserService,
+    },
+};
+
+#[derive(Clone)]
+pub struct AchievementService {
+    repo: AchievementRepository,
+    notification_service: NotificationService,
+    event_service: FeedEventService,
+    user_service: UserService,
+}
+
+impl AchievementService {
+    pub fn new(
+        repo: AchievementRepository,
+        notification_service: NotificationService,
+        event_service: FeedEventService,
+        user_service: UserService,
+    ) -> Self {
+        Self {
+            repo,
+            notification_service,
+            event_service,
+            user_service,
+        }
+    }
+
+    /// Awards every achievement the user qualifies for and did not have yet.
+    /// Each new achievement creates a notification and, if the rule asks for it, a feed event.
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn evaluate(&self, user_id: &str) -> Result<Vec<ReadAchievementDto>> {
+        let awarded = self.award_qualified(user_id).await?;
+        if awarded.is_empty() {
+            return Ok(awarded);
+        }
+
+        let user = self
+            .user_service
+            .get_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+
+        for achievement in &awarded {
+            self.notification_service
+                .notify_achievement_unlocked(user_id.to_string(), achievement)
+                .await?;
+
+            if AchievementRule::find(&achievement.key).is_some_and(|rule| rule.publish_to_feed) {
+                self.event_service
+                    .publish_event(CreateFeedEventDto {
+                        id: None,
+                        data: FeedEventType::AchievementUnlocked(AchievementEventData {
+                            achievement_key: achievement.key.clone(),
+                            name: achievement.name.clone(),
+                            description: achievement.description.clone(),
+                        }),
+                        source: FeedEventSource::User(user.clone()),
+                    })
+                    .await?;
+            }
+        }
+
+        Ok(awarded)
+    }
+
+    /// Awards achievements users already qualify for, e.g. after adding rules to the catalog.
+    /// Users are notified but nothing is published to the feed, the milestones are old news.
+    /// Runs once per version of the rule catalog, returns `None` when it already ran.
+    #[instrument(err, skip(self))]
+    pub async fn backfill(&self) -> Result<Option<usize>> {
+        let catalog_version = Self::catalog_version();
+        if self.repo.is_backfilled(&catalog_version).await? {
+            return Ok(None);
+        }
+
+        let user_ids = self.repo.list_active_user_ids().await?;
+        let mut awarded_count = 0;
+
+        for user_id in user_ids {
+            let awarded = self.award_qualified(&user_id).await?;
+            for achievement in &awarded {
+                self.notification_service
+                    .notify_achievement_unlocked(user_id.clone(), achievement)
+                    .await?;
+            }
+            awarded_count += awarded.len();
+        }
+
+        // Awarding is idempotent, a backfill that failed halfway just runs again
+        self.repo
+            .mark_backfilled(&catalog_version, awarded_count as i32)
+            .await?;
+
+        Ok(Some(awarded_count))
+    }
+
+    #[instrument(err, skip(self), fields(actor_id = %actor))]
+    pub async fn get_progress(&self, actor: &Actor) -> Result<Vec<AchievementProgressDto>> {
+        let progress = self.repo.get_progress(&actor.user_id).await?;
+        let badges = self.repo.list_badges(&actor.user_id).await?;
+
+        Ok(ACHIEVEMENTS
+            .iter()
+            .map(|rule| AchievementProgressDto {
+                key: rule.key.to_string(),
+                name: rule.name.to_string(),
+                description: rule.description.to_string(),
+                metric: rule.metric,
+                threshold: rule.threshold,
+                current: progress.value(rule.metric),
+                awarded_at: badges
+                    .iter()
+                    .find(|badge| badge.key == rule.key)
+                    .map(|badge| badge.awarded_at),
+            })
+            .collect())
+    }
+
+    // Only what decides who qualifies is part of the version, renames need no backfill
+    fn catalog_version() -> String {
+        let rules = ACHIEVEMENTS
+            .iter()
+            .map(|rule| format!("{}:{:?}:{}", rule.key, rule.metric, rule.threshold))
+            .collect::<Vec<_>>()
+            .join("|");
+        sha256_hash(&rules)
+    }
+
+    async fn award_qualified(&self, user_id: &str) -> Result<Vec<ReadAchievementDto>> {
+        let progress = self.repo.get_progress(user_id).await?;
+        let keys: Vec<String> = ACHIEVEMENTS
+            .iter()
+            .filter(|rule| rule.is_satisfied(&progress))
+            .map(|rule| rule.key.to_string())
+            .collect();
+
+        self.repo.award(user_id, &keys).await
+    }
+}
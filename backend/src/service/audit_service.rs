@@ -0,0 +1,79 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::{
+    config::database::Database,
+    dto::audit::{AuditLogQueryDto, CreateAuditLogDto, ReadAuditLogDto},
+    entity::audit::{AuditAction, AuditTargetType},
+    repository::audit::AuditRepository,
+    router::request::ClientInfo,
+};
+
+#[derive(Clone)]
+pub struct AuditService {
+    repo: AuditRepository,
+}
+
+/// Audit log entry that is being built, see [`AuditService::entry`]
+pub struct AuditEntry<'a> {
+    service: &'a AuditService,
+    dto: CreateAuditLogDto,
+}
+
+impl AuditService {
+    pub fn new(db: &Arc<Database>) -> Self {
+        Self {
+            repo: AuditRepository::new(db),
+        }
+    }
+
+    pub fn entry(
+        &self,
+        action: AuditAction,
+        actor_id: Option<&str>,
+        client: &ClientInfo,
+    ) -> AuditEntry<'_> {
+        AuditEntry {
+            service: self,
+            dto: CreateAuditLogDto::new(action, actor_id, client),
+        }
+    }
+
+    /// Record an action in the audit log.
+    /// Failures are only logged, a failed audit write must not undo the action itself.
+    #[instrument(skip(self, dto), fields(action = ?dto.action, actor_id = ?dto.actor_id))]
+    pub async fn record(&self, dto: CreateAuditLogDto) {
+        let action = dto.action;
+        if let Err(e) = self.repo.create(dto).await {
+            tracing::error!("Failed to write {:?} to the audit log: {:#}", action, e);
+        }
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn list(&self, query: AuditLogQueryDto) -> Result<Vec<ReadAuditLogDto>> {
+        self.repo.filter(query).await
+    }
+}
+
+impl AuditEntry<'_> {
+    pub fn target(mut self, target_type: AuditTargetType, target_id: impl ToString) -> Self {
+        self.dto = self.dto.target(target_type, target_id);
+        self
+    }
+
+    /// State of the target before and after the action
+    pub fn changes(
+        mut self,
+        before: Option<impl Serialize>,
+        after: Option<impl Serialize>,
+    ) -> Self {
+        self.dto = self.dto.changes(before, after);
+        self
+    }
+
+    pub async fn record(self) {
+        self.service.record(self.dto).await
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use std::net::IpAddr;
 use std::sync::Arc;
@@ -7,18 +8,33 @@ use uuid::Uuid;
 
 use crate::{
     auth::{
-        generate_access_token, generate_refresh_token, revoke_refresh_token, validate_refresh_token,
+        crypto::sha256_hash, device::describe_user_agent, generate_access_token,
+        generate_refresh_token, revoke_refresh_token, validate_refresh_token,
     },
     config::{database::{Database, DatabaseTrait}, env::AppEnvironment},
+    dto::{
+        impersonation::{
+            ImpersonationSession, ReadImpersonatedRequestDto, ReadImpersonationSessionDto,
+        },
+        user::{admin_user::ReadUserAccountDto, read_user::ReadUserDto},
+    },
     repository::{
         auth::{
             api_tokens::{ApiTokenRecord, ApiTokenRepository},
             impersonation::ImpersonationRepository,
-            oauth_account::{OAuthAccountRepository, OAuthAccountRepositoryTrait},
+            oauth_account::{OAuthAccount, OAuthAccountRepository, OAuthAccountRepositoryTrait},
+            passkey::PasskeyRepository,
+            tokens::{
+                is_new_device, list_active_user_tokens, revoke_all_user_tokens,
+                revoke_other_user_tokens, revoke_user_token_family, rotate_refresh_token,
+                RefreshTokenSession,
+            },
         },
-        user::UserRepository,
+        user::{AccountAccess, UserRepository},
     },
-    router::clerk::UserRole,
+    entity::notification::NewLoginData,
+    router::{auth::sessions::ReadDeviceSessionDto, clerk::UserRole},
+    service::notification_service::NotificationService,
 };
 
 use crate::auth::providers::UserProfile;
@@ -27,8 +43,10 @@ use crate::auth::providers::UserProfile;
 pub struct AuthService {
     user_repo: UserRepository,
     oauth_repo: OAuthAccountRepository,
+    passkey_repo: PasskeyRepository,
     api_token_repo: ApiTokenRepository,
     impersonation_repo: ImpersonationRepository,
+    notification_service: NotificationService,
     pool: Arc<PgPool>,
     app_env: AppEnvironment,
 }
@@ -38,8 +56,10 @@ impl AuthService {
         Self {
             user_repo: UserRepository::new(database),
             oauth_repo: OAuthAccountRepository::new(database),
+            passkey_repo: PasskeyRepository::new(database),
             api_token_repo: ApiTokenRepository::new(database),
             impersonation_repo: ImpersonationRepository::new(database),
+            notification_service: NotificationService::new(database),
             pool: Arc::new(database.get_pool().clone()),
             app_env,
         }
@@ -92,6 +112,13 @@ impl AuthService {
             let existing_user = self.user_repo.find_by_email(&profile.email).await?;
 
             let user_id = if let Some(user) = existing_user {
+                // Anyone can claim an unverified email, linking it would hand over the account
+                if !profile.email_verified {
+                    anyhow::bail!(
+                        "A user with this email already exists, sign in and link {} from the settings",
+                        provider
+                    );
+                }
                 println!("🔐 [AUTH] Found existing user by email: {}", user.id);
                 user.id
             } else {
@@ -129,46 +156,92 @@ impl AuthService {
             user_id
         };
 
-        // 2. Get user with role and display name for token generation
-        println!("🔐 [AUTH] Fetching actor for user: {}", user_id);
+        // 2. Generate access and refresh tokens, new users have no known devices yet
+        let (access_token, refresh_token) = self
+            .issue_login_tokens(&user_id, !is_new_user, user_agent, ip)
+            .await?;
+
+        println!(
+            "🔐 [AUTH] ✅ OAuth login completed successfully! New user: {}",
+            is_new_user
+        );
+        Ok((access_token, refresh_token, user_id, is_new_user))
+    }
+
+    /// Sign in a user that proved possession of one of their passkeys
+    ///
+    /// Returns (access_token, refresh_token)
+    #[instrument(err, skip(self))]
+    pub async fn handle_passkey_login(
+        &self,
+        user_id: &str,
+        user_agent: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> Result<(String, String)> {
+        self.issue_login_tokens(user_id, true, user_agent, ip).await
+    }
+
+    /// Sign in an account that was just created with a passkey
+    ///
+    /// Returns (access_token, refresh_token)
+    #[instrument(err, skip(self))]
+    pub async fn handle_passkey_signup(
+        &self,
+        user_id: &str,
+        user_agent: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> Result<(String, String)> {
+        self.issue_login_tokens(user_id, false, user_agent, ip)
+            .await
+    }
+
+    /// Issue the tokens of a new session, shared by every way to sign in
+    async fn issue_login_tokens(
+        &self,
+        user_id: &str,
+        notify_new_device: bool,
+        user_agent: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> Result<(String, String)> {
+        if self.user_repo.is_suspended(user_id).await? {
+            return Err(anyhow::anyhow!("Account is suspended"));
+        }
+
         let (actor, display_name) = self
             .user_repo
-            .get_actor_by_id(user_id.clone())
+            .get_actor_by_id(user_id.to_string())
             .await?
-            .context("User not found after creation")?;
-
-        println!("🔐 [AUTH] Actor found with role: {:?}", actor.role);
-
-        // Use the actual user_id from the database (supports both UUID and Clerk IDs)
-        let user_id_str = &actor.user_id;
-        println!("🔐 [AUTH] User ID: {}", user_id_str);
+            .context("User not found")?;
 
-        // 3. Generate access token (JWT, 15 minutes)
-        println!("🔐 [AUTH] Generating access token...");
+        // Access token (JWT, 15 minutes)
         let access_token = generate_access_token(
-            user_id_str,
+            &actor.user_id,
             actor.role,
             display_name,
             self.app_env.as_str().to_string(),
         )?;
-        println!(
-            "🔐 [AUTH] Access token generated (length: {})",
-            access_token.len()
-        );
 
-        // 4. Generate refresh token (30 days)
-        println!("🔐 [AUTH] Generating refresh token...");
-        let refresh_token = generate_refresh_token(user_id_str, user_agent, ip, &self.pool).await?;
-        println!(
-            "🔐 [AUTH] Refresh token generated (length: {})",
-            refresh_token.len()
-        );
+        // Warn the user about sign ins from devices or networks we have not seen before
+        if notify_new_device && is_new_device(&actor.user_id, user_agent, ip, &self.pool).await? {
+            let _ = self
+                .notification_service
+                .notify_new_login(
+                    actor.user_id.clone(),
+                    NewLoginData {
+                        device: describe_user_agent(user_agent),
+                        user_agent: user_agent.map(str::to_string),
+                        ip_address: ip.map(|ip| ip.to_string()),
+                        logged_in_at: chrono::Local::now(),
+                    },
+                )
+                .await;
+        }
 
-        println!(
-            "🔐 [AUTH] ✅ OAuth login completed successfully! New user: {}",
-            is_new_user
-        );
-        Ok((access_token, refresh_token, user_id_str.to_string(), is_new_user))
+        // Refresh token (30 days)
+        let refresh_token =
+            generate_refresh_token(&actor.user_id, user_agent, ip, &self.pool).await?;
+
+        Ok((access_token, refresh_token))
     }
 
     /// Refresh access token using refresh token
@@ -181,8 +254,14 @@ impl AuthService {
         user_agent: Option<&str>,
         ip: Option<IpAddr>,
     ) -> Result<(String, String, String)> {
-        // 1. Validate refresh token and get user_id
-        let user_id = validate_refresh_token(refresh_token, &self.pool).await?;
+        // 1. Rotate the refresh token within its family, a reused token revokes the family
+        let (user_id, new_refresh_token) =
+            rotate_refresh_token(refresh_token, user_agent, ip, &self.pool).await?;
+
+        if self.user_repo.is_suspended(&user_id).await? {
+            revoke_refresh_token(&new_refresh_token, "user_suspended", &self.pool).await?;
+            return Err(anyhow::anyhow!("Account is suspended"));
+        }
 
         // 2. Get user with role and display name
         let (actor, display_name) = self
@@ -199,14 +278,183 @@ impl AuthService {
             self.app_env.as_str().to_string(),
         )?;
 
-        // 4. Generate new refresh token (rotation)
-        let new_refresh_token =
-            generate_refresh_token(&user_id, user_agent, ip, &self.pool).await?;
+        Ok((access_token, new_refresh_token, user_id))
+    }
 
-        // 5. Revoke old refresh token
-        revoke_refresh_token(refresh_token, "rotated", &self.pool).await?;
+    /// Link an OAuth account to a signed in user, one account per provider
+    #[instrument(err, skip(self, profile))]
+    pub async fn link_oauth_account(
+        &self,
+        user_id: &str,
+        provider: &str,
+        profile: UserProfile,
+    ) -> Result<OAuthAccount> {
+        if let Some(existing) = self
+            .oauth_repo
+            .find_by_provider_and_user_id(provider, &profile.provider_user_id)
+            .await?
+        {
+            if existing.user_id != user_id {
+                anyhow::bail!("This {} account is linked to another user", provider);
+            }
+        } else if self
+            .oauth_repo
+            .find_by_user_id(user_id)
+            .await?
+            .iter()
+            .any(|account| account.provider == provider)
+        {
+            anyhow::bail!("Another {} account is already linked, unlink it first", provider);
+        }
 
-        Ok((access_token, new_refresh_token, user_id))
+        self.oauth_repo
+            .upsert(
+                user_id,
+                provider,
+                &profile.provider_user_id,
+                Some(&profile.email),
+            )
+            .await
+    }
+
+    /// Unlink an OAuth account, the user must keep at least one way to sign in
+    #[instrument(err, skip(self))]
+    pub async fn unlink_oauth_account(&self, user_id: &str, provider: &str) -> Result<()> {
+        let accounts = self.oauth_repo.find_by_user_id(user_id).await?;
+        if !accounts.iter().any(|account| account.provider == provider) {
+            anyhow::bail!("No {} account is linked", provider);
+        }
+        if self.login_method_count(user_id).await? <= 1 {
+            anyhow::bail!("Cannot unlink the last login method");
+        }
+
+        self.oauth_repo
+            .delete_by_user_and_provider(user_id, provider)
+            .await?;
+        Ok(())
+    }
+
+    /// Linked OAuth accounts and passkeys the user can sign in with
+    #[instrument(err, skip(self))]
+    pub async fn login_method_count(&self, user_id: &str) -> Result<usize> {
+        let oauth_accounts = self.oauth_repo.find_by_user_id(user_id).await?.len();
+        let passkeys = self.passkey_repo.count_by_user(user_id).await?;
+
+        Ok(oauth_accounts + passkeys as usize)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn list_oauth_accounts(&self, user_id: &str) -> Result<Vec<OAuthAccount>> {
+        self.oauth_repo.find_by_user_id(user_id).await
+    }
+
+    /// Revoke every refresh token of the user, signing them out on all devices
+    #[instrument(err, skip(self))]
+    pub async fn revoke_all_sessions(&self, user_id: &str, reason: &str) -> Result<()> {
+        revoke_all_user_tokens(user_id, reason, &self.pool).await
+    }
+
+    /// Sign the user out everywhere, including the access tokens that have not expired yet
+    #[instrument(err, skip(self))]
+    pub async fn force_logout(
+        &self,
+        user_id: &str,
+        revoke_api_tokens: bool,
+        reason: &str,
+    ) -> Result<u64> {
+        revoke_all_user_tokens(user_id, reason, &self.pool).await?;
+        self.user_repo.revoke_access_tokens(user_id).await?;
+
+        if !revoke_api_tokens {
+            return Ok(0);
+        }
+        self.api_token_repo
+            .revoke_all_user_tokens(user_id, reason)
+            .await
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn get_account_access(&self, user_id: &str) -> Result<Option<AccountAccess>> {
+        self.user_repo.get_account_access(user_id).await
+    }
+
+    /// Role, standing, login methods, API tokens and active sessions of a user for admins
+    #[instrument(err, skip(self, user), fields(user_id = %user.id))]
+    pub async fn get_user_account(&self, user: ReadUserDto) -> Result<Option<ReadUserAccountDto>> {
+        let Some(access) = self.user_repo.get_account_access(&user.id).await? else {
+            return Ok(None);
+        };
+
+        let linked_accounts = self.oauth_repo.find_by_user_id(&user.id).await?;
+        let api_tokens = self.api_token_repo.list_user_tokens(&user.id).await?;
+        let sessions = list_active_user_tokens(&user.id, &self.pool).await?;
+
+        Ok(Some(ReadUserAccountDto {
+            user,
+            role: access.role,
+            suspended_at: access.suspended_at,
+            suspension_reason: access.suspension_reason,
+            deleted_at: access.deleted_at,
+            sessions_revoked_at: access.sessions_revoked_at,
+            linked_accounts: linked_accounts.into_iter().map(Into::into).collect(),
+            api_tokens: api_tokens.into_iter().map(Into::into).collect(),
+            sessions: sessions
+                .into_iter()
+                .map(|session| ReadDeviceSessionDto::from_session(session, false))
+                .collect(),
+        }))
+    }
+
+    /// Active refresh token sessions of the user, the one matching `current_refresh_token` is the current device
+    #[instrument(err, skip(self, current_refresh_token))]
+    pub async fn list_sessions(
+        &self,
+        user_id: &str,
+        current_refresh_token: Option<&str>,
+    ) -> Result<Vec<(RefreshTokenSession, bool)>> {
+        let current_hash = current_refresh_token.map(sha256_hash);
+        let sessions = list_active_user_tokens(user_id, &self.pool).await?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| {
+                let is_current = current_hash.as_deref() == Some(session.token_hash.as_str());
+                (session, is_current)
+            })
+            .collect())
+    }
+
+    /// Sign out a single device of the user, sessions are identified by their token family
+    #[instrument(err, skip(self))]
+    pub async fn revoke_session(&self, user_id: &str, session_id: Uuid) -> Result<()> {
+        let revoked =
+            revoke_user_token_family(session_id, user_id, "user_revoked_session", &self.pool)
+                .await?;
+        if !revoked {
+            anyhow::bail!("Session not found");
+        }
+        Ok(())
+    }
+
+    /// Sign out every device of the user except the one the request came from
+    #[instrument(err, skip(self, current_refresh_token))]
+    pub async fn revoke_other_sessions(
+        &self,
+        user_id: &str,
+        current_refresh_token: &str,
+    ) -> Result<u64> {
+        // Make sure the token we keep actually belongs to the user
+        if validate_refresh_token(current_refresh_token, &self.pool).await? != user_id {
+            anyhow::bail!("Current session does not belong to the user");
+        }
+
+        revoke_other_user_tokens(
+            user_id,
+            current_refresh_token,
+            "user_revoked_other_sessions",
+            &self.pool,
+        )
+        .await
     }
 
     /// Logout user by revoking refresh token
@@ -251,6 +499,11 @@ impl AuthService {
             .await
     }
 
+    #[instrument(err, skip(self, token))]
+    pub async fn find_active_api_token(&self, token: &str) -> Result<Option<Uuid>> {
+        self.api_token_repo.find_active_token_id(token).await
+    }
+
     #[instrument(err, skip(self, token))]
     pub async fn validate_api_token(&self, token: &str) -> Result<(String, UserRole)> {
         self.api_token_repo.validate_api_token(token).await
@@ -261,9 +514,11 @@ impl AuthService {
         &self,
         admin_user_id: &str,
         target_user_id: &str,
-    ) -> Result<String> {
+        reason: &str,
+        read_only: bool,
+    ) -> Result<(String, DateTime<Utc>)> {
         self.impersonation_repo
-            .create_impersonation_session(admin_user_id, target_user_id)
+            .create_impersonation_session(admin_user_id, target_user_id, reason, read_only)
             .await
     }
 
@@ -271,7 +526,7 @@ impl AuthService {
     pub async fn validate_impersonation_token(
         &self,
         token: &str,
-    ) -> Result<Option<(String, String)>> {
+    ) -> Result<Option<ImpersonationSession>> {
         self.impersonation_repo
             .validate_impersonation_token(token)
             .await
@@ -283,4 +538,35 @@ impl AuthService {
             .revoke_impersonation_token(token)
             .await
     }
+
+    #[instrument(err, skip(self), fields(session_id = %session_id))]
+    pub async fn log_impersonated_request(
+        &self,
+        session_id: Uuid,
+        method: &str,
+        path: &str,
+        status_code: i16,
+    ) -> Result<()> {
+        self.impersonation_repo
+            .log_request(session_id, method, path, status_code)
+            .await
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %target_user_id))]
+    pub async fn list_impersonation_sessions(
+        &self,
+        target_user_id: &str,
+    ) -> Result<Vec<ReadImpersonationSessionDto>> {
+        self.impersonation_repo
+            .list_sessions_for_target(target_user_id)
+            .await
+    }
+
+    #[instrument(err, skip(self), fields(session_id = %session_id))]
+    pub async fn list_impersonated_requests(
+        &self,
+        session_id: Uuid,
+    ) -> Result<Vec<ReadImpersonatedRequestDto>> {
+        self.impersonation_repo.list_requests(session_id).await
+    }
 }
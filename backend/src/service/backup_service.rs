@@ -0,0 +1,566 @@
+use anyhow::{anyhow, bail, Context, Result};
+use aws_sdk_s3::{
+    operation::get_object::GetObjectOutput, presigning::PresigningConfig, primitives::ByteStream,
+};
+use chrono::{Datelike, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{Connection, PgConnection};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    process::Command,
+};
+use tracing::instrument;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    config::{
+        database::Database,
+        env::{BackupConfig, Config},
+    },
+    dto::db_backup::{ReadBackupDownloadUrlDto, ReadDbBackupDto, ReadDbBackupRestoreDto},
+    entity::db_backup::DbBackup,
+    repository::db_backup::DbBackupRepository,
+    router::clerk::Actor,
+    service::notification_service::NotificationService,
+};
+
+// pg_restore output can be long, only the start ends up on the run
+const MAX_ERROR_LENGTH: usize = 2000;
+
+#[derive(Clone)]
+pub struct BackupService {
+    repo: DbBackupRepository,
+    notification_service: NotificationService,
+    s3_client: aws_sdk_s3::Client,
+    bucket: String,
+    database_url: String,
+    config: BackupConfig,
+}
+
+/// Connection settings handed to pg_dump and pg_restore through the environment,
+/// so the password never shows up in the process list
+struct PgTarget {
+    host: String,
+    port: u16,
+    user: String,
+    password: Option<String>,
+    database: String,
+    sslmode: Option<String>,
+}
+
+impl PgTarget {
+    fn parse(database_url: &str) -> Result<Self> {
+        let url = Url::parse(database_url).context("Invalid database URL")?;
+        let decode = |value: &str| -> Result<String> {
+            Ok(urlencoding::decode(value)
+                .context("Invalid database URL")?
+                .into_owned())
+        };
+
+        Ok(Self {
+            host: url.host_str().unwrap_or("localhost").to_string(),
+            port: url.port().unwrap_or(5432),
+            user: decode(url.username())?,
+            password: url.password().map(decode).transpose()?,
+            database: decode(url.path().trim_start_matches('/'))?,
+            sslmode: url
+                .query_pairs()
+                .find(|(key, _)| key == "sslmode")
+                .map(|(_, value)| value.into_owned()),
+        })
+    }
+
+    fn apply(&self, command: &mut Command) {
+        command
+            .env("PGHOST", &self.host)
+            .env("PGPORT", self.port.to_string())
+            .env("PGUSER", &self.user)
+            .env("PGDATABASE", &self.database);
+        if let Some(password) = &self.password {
+            command.env("PGPASSWORD", password);
+        }
+        if let Some(sslmode) = &self.sslmode {
+            command.env("PGSSLMODE", sslmode);
+        }
+    }
+}
+
+/// Dump file in the temp directory, removed when dropped
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new() -> Self {
+        Self(std::env::temp_dir().join(format!("nowaster-backup-{}.dump", Uuid::new_v4())))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+impl BackupService {
+    pub fn new(
+        db: &Arc<Database>,
+        config: &Config,
+        s3_client: aws_sdk_s3::Client,
+        notification_service: NotificationService,
+    ) -> Self {
+        Self {
+            repo: DbBackupRepository::new(db),
+            notification_service,
+            s3_client,
+            bucket: config.s3.bucket_name.clone(),
+            database_url: config.database.connection_url.clone(),
+            config: config.backup.clone(),
+        }
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn list_backups(&self) -> Result<Vec<ReadDbBackupDto>> {
+        let backups = self.repo.get_all().await?;
+        Ok(backups.into_iter().map(ReadDbBackupDto::from).collect())
+    }
+
+    #[instrument(err, skip(self), fields(backup_id = %backup_id))]
+    pub async fn get_backup(&self, backup_id: i32) -> Result<Option<DbBackup>> {
+        self.repo.get_by_id(backup_id).await
+    }
+
+    /// Open the dump for streaming, `range` is an inclusive byte range
+    #[instrument(err, skip(self))]
+    pub async fn backup_contents(
+        &self,
+        backup_file: &str,
+        range: Option<(u64, u64)>,
+    ) -> Result<GetObjectOutput> {
+        let object = self
+            .s3_client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(backup_file)
+            .set_range(range.map(|(start, end)| format!("bytes={}-{}", start, end)))
+            .send()
+            .await?;
+
+        Ok(object)
+    }
+
+    /// Short-lived URL the dump can be downloaded from without going through the backend
+    #[instrument(err, skip(self), fields(backup_id = %backup.id))]
+    pub async fn download_url(&self, backup: &DbBackup) -> Result<ReadBackupDownloadUrlDto> {
+        let expires_in = Duration::from_secs(self.config.download_url_ttl_seconds);
+        let request = self
+            .s3_client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&backup.backup_file)
+            .response_content_disposition(format!(
+                "attachment; filename=\"{}\"",
+                backup_file_name(&backup.backup_file)
+            ))
+            .presigned(PresigningConfig::expires_in(expires_in)?)
+            .await?;
+
+        Ok(ReadBackupDownloadUrlDto {
+            url: request.uri().to_string(),
+            expires_at: Utc::now() + expires_in,
+            checksum_sha256: backup.checksum_sha256.clone(),
+        })
+    }
+
+    /// Start a backup right away, it runs in the background
+    #[instrument(err, skip(self), fields(admin_id = %admin))]
+    pub async fn trigger_backup(&self, admin: &Actor) -> Result<ReadDbBackupDto> {
+        let backup_file = Self::backup_file();
+        let id = self
+            .repo
+            .claim(&admin.user_id, "user", &backup_file, None)
+            .await?
+            .ok_or_else(|| anyhow!("A backup is already running"))?;
+
+        let service = self.clone();
+        tokio::spawn(async move { service.run(id, &backup_file).await });
+
+        self.repo
+            .get_by_id(id)
+            .await?
+            .map(ReadDbBackupDto::from)
+            .ok_or_else(|| anyhow!("Backup not found"))
+    }
+
+    /// Start a backup when none was made within the configured interval. The dump can take
+    /// longer than a job may run, so it runs in the background like a manual one.
+    #[instrument(err, skip(self))]
+    pub async fn run_scheduled_backup(&self) -> Result<Option<i32>> {
+        let failed = self.repo.fail_stale().await?;
+        if failed > 0 {
+            tracing::warn!("Failed {} interrupted backups", failed);
+        }
+
+        let backup_file = Self::backup_file();
+        let Some(id) = self
+            .repo
+            .claim(
+                "scheduler",
+                "system",
+                &backup_file,
+                Some(self.config.interval_hours as i32),
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let service = self.clone();
+        tokio::spawn(async move { service.run(id, &backup_file).await });
+
+        Ok(Some(id))
+    }
+
+    pub fn schedule_enabled(&self) -> bool {
+        self.config.schedule_enabled
+    }
+
+    /// Restore a backup into the scratch database, it runs in the background
+    #[instrument(err, skip(self), fields(backup_id = %backup_id, admin_id = %admin))]
+    pub async fn request_restore(
+        &self,
+        backup_id: i32,
+        admin: &Actor,
+    ) -> Result<ReadDbBackupRestoreDto> {
+        let restore_url = self
+            .config
+            .restore_database_url
+            .clone()
+            .ok_or_else(|| anyhow!("No scratch database is configured for restores"))?;
+        let target = PgTarget::parse(&restore_url)?;
+        let mut target_conn = PgConnection::connect(&restore_url)
+            .await
+            .context("Failed to connect to the scratch database")?;
+        let is_app_database = self.repo.is_app_database(&mut target_conn).await?;
+        target_conn.close().await?;
+        if is_app_database {
+            bail!("The scratch database must not be the application database");
+        }
+
+        let backup = self
+            .repo
+            .get_by_id(backup_id)
+            .await?
+            .ok_or_else(|| anyhow!("Backup not found"))?;
+        if backup.status != "success" {
+            bail!("Only successful backups can be restored");
+        }
+
+        let Some(restore_lock) = self.repo.try_lock_restore().await? else {
+            bail!("Another restore is running");
+        };
+
+        let restore = self
+            .repo
+            .create_restore(backup_id, &admin.user_id, &target.database)
+            .await?;
+
+        let service = self.clone();
+        let restore_id = restore.id;
+        tokio::spawn(async move {
+            let error = service
+                .restore(&backup, &target)
+                .await
+                .err()
+                .map(|e| truncate_error(format!("{:#}", e)));
+            let _ = service
+                .repo
+                .finish_restore(restore_id, error.as_deref())
+                .await;
+            let _ = restore_lock.close().await;
+        });
+
+        Ok(restore)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn list_restores(&self) -> Result<Vec<ReadDbBackupRestoreDto>> {
+        self.repo.list_restores().await
+    }
+
+    /// Drop backups outside the retention policy from the bucket, returns how many were removed
+    #[instrument(err, skip(self))]
+    pub async fn apply_retention(&self) -> Result<usize> {
+        let backups: Vec<DbBackup> = self
+            .repo
+            .get_all()
+            .await?
+            .into_iter()
+            .filter(|backup| backup.status == "success" && backup.expired_at.is_none())
+            .collect();
+        let retained = retained_backups(&backups, self.config.keep_daily, self.config.keep_weekly);
+
+        let mut removed = 0;
+        for backup in backups.iter().filter(|b| !retained.contains(&b.id)) {
+            let deleted = self
+                .s3_client
+                .delete_object()
+                .bucket(&self.bucket)
+                .key(&backup.backup_file)
+                .send()
+                .await;
+
+            match deleted {
+                Ok(_) => {
+                    self.repo.mark_expired(backup.id).await?;
+                    removed += 1;
+                }
+                Err(e) => tracing::warn!(
+                    backup_id = backup.id,
+                    "Failed to remove expired backup: {}",
+                    e
+                ),
+            }
+        }
+
+        Ok(removed)
+    }
+
+    #[instrument(skip(self), fields(backup_id = %id))]
+    async fn run(&self, id: i32, backup_file: &str) {
+        let result = self.dump_and_upload(backup_file).await;
+
+        let recorded = match &result {
+            Ok((size_bytes, checksum)) => self.repo.mark_success(id, *size_bytes, checksum).await,
+            Err(e) => {
+                self.repo
+                    .mark_failed(id, &truncate_error(format!("{:#}", e)))
+                    .await
+            }
+        };
+        if recorded.is_err() {
+            return;
+        }
+
+        let Ok(Some(backup)) = self.repo.get_by_id(id).await else {
+            return;
+        };
+
+        if result.is_ok() {
+            let _ = self
+                .notification_service
+                .notify_admins_backup_completed(
+                    backup.id,
+                    backup.backup_size_bytes,
+                    backup.duration_seconds,
+                    backup.started_at,
+                )
+                .await;
+
+            if let Ok(removed) = self.apply_retention().await {
+                if removed > 0 {
+                    tracing::info!("Removed {} expired backups", removed);
+                }
+            }
+        } else {
+            let _ = self
+                .notification_service
+                .notify_admins_backup_failed(
+                    backup.id,
+                    backup.error_message,
+                    backup.duration_seconds,
+                    backup.started_at,
+                )
+                .await;
+        }
+    }
+
+    /// Dump the database, check the archive is readable and upload it.
+    /// Returns the size and SHA-256 of the dump.
+    async fn dump_and_upload(&self, backup_file: &str) -> Result<(i64, String)> {
+        let dump = TempFile::new();
+
+        let mut command = Command::new("pg_dump");
+        command
+            .arg("--format=custom")
+            .arg("--no-owner")
+            .arg("--file")
+            .arg(dump.path());
+        PgTarget::parse(&self.database_url)?.apply(&mut command);
+        run_command(&mut command).await?;
+
+        verify_archive(dump.path()).await?;
+        let (size_bytes, checksum) = checksum_file(dump.path()).await?;
+
+        let body = ByteStream::from_path(dump.path())
+            .await
+            .context("Failed to read the dump")?;
+        self.s3_client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(backup_file)
+            .content_type("application/octet-stream")
+            .body(body)
+            .send()
+            .await
+            .context("Failed to upload the dump")?;
+
+        Ok((size_bytes, checksum))
+    }
+
+    async fn restore(&self, backup: &DbBackup, target: &PgTarget) -> Result<()> {
+        let dump = TempFile::new();
+
+        let object = self
+            .s3_client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&backup.backup_file)
+            .send()
+            .await
+            .context("Failed to download the dump")?;
+        let mut reader = object.body.into_async_read();
+        let mut file = tokio::fs::File::create(dump.path()).await?;
+        tokio::io::copy(&mut reader, &mut file)
+            .await
+            .context("Failed to download the dump")?;
+        file.flush().await?;
+
+        let (_, checksum) = checksum_file(dump.path()).await?;
+        if backup
+            .checksum_sha256
+            .as_ref()
+            .is_some_and(|expected| *expected != checksum)
+        {
+            bail!("Checksum mismatch, the dump was modified after the backup");
+        }
+        verify_archive(dump.path()).await?;
+
+        let mut command = Command::new("pg_restore");
+        command
+            .arg("--clean")
+            .arg("--if-exists")
+            .arg("--no-owner")
+            .arg("--no-privileges")
+            .arg("--exit-on-error")
+            .arg("--dbname")
+            .arg(&target.database)
+            .arg(dump.path());
+        target.apply(&mut command);
+        run_command(&mut command).await?;
+
+        Ok(())
+    }
+
+    fn backup_file() -> String {
+        format!(
+            "backups/nowaster-{}.dump",
+            Utc::now().format("%Y%m%d-%H%M%S")
+        )
+    }
+}
+
+/// Name the dump is saved under, the last segment of its key
+pub fn backup_file_name(backup_file: &str) -> &str {
+    backup_file.rsplit('/').next().unwrap_or("backup.dump")
+}
+
+/// Ids of the newest backup of each of the last `keep_daily` days and `keep_weekly` weeks.
+/// The newest backup is always kept. Expects the backups newest first.
+fn retained_backups(backups: &[DbBackup], keep_daily: u32, keep_weekly: u32) -> HashSet<i32> {
+    let mut retained: HashSet<i32> = backups.first().map(|b| b.id).into_iter().collect();
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+
+    for backup in backups {
+        let Some(started_at) = backup.started_at else {
+            continue;
+        };
+
+        if days.len() < keep_daily as usize && days.insert(started_at.date_naive()) {
+            retained.insert(backup.id);
+        }
+
+        let week = started_at.iso_week();
+        if weeks.len() < keep_weekly as usize && weeks.insert((week.year(), week.week())) {
+            retained.insert(backup.id);
+        }
+    }
+
+    retained
+}
+
+/// pg_restore only lists the contents when it can read the whole archive
+async fn verify_archive(path: &Path) -> Result<()> {
+    let mut command = Command::new("pg_restore");
+    command.arg("--list").arg(path);
+    let listing = run_command(&mut command)
+        .await
+        .context("Dump verification failed")?;
+
+    if !listing
+        .lines()
+        .any(|line| !line.is_empty() && !line.starts_with(';'))
+    {
+        bail!("Dump verification failed: the archive is empty");
+    }
+
+    Ok(())
+}
+
+async fn checksum_file(path: &Path) -> Result<(i64, String)> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    let mut size = 0;
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as i64;
+    }
+
+    Ok((size, hex::encode(hasher.finalize())))
+}
+
+/// Run the command to completion, returns its stdout
+async fn run_command(command: &mut Command) -> Result<String> {
+    let program = command.as_std().get_program().to_string_lossy().to_string();
+    let output = command
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .with_context(|| format!("Failed to start {}", program))?;
+
+    if !output.status.success() {
+        bail!(
+            "{} failed ({}): {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn truncate_error(message: String) -> String {
+    match message.char_indices().nth(MAX_ERROR_LENGTH) {
+        Some((index, _)) => format!("{}…", &message[..index]),
+        None => message,
+    }
+}
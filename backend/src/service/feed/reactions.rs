@@ -4,10 +4,11 @@ use uuid::Uuid;
 
 use crate::{
     dto::feed::{CreateFeedReactionDto, ReadFeedReactionDto},
-    entity::feed::FeedEventType,
+    entity::feed::{FeedEventSource, FeedEventType},
     repository::{
         feed::FeedRepository,
         fixed_session::{FixedSessionRepository, SessionRepositoryTrait},
+        moderation::ModerationRepository,
     },
     router::clerk::Actor,
     service::notification_service::NotificationService,
@@ -18,21 +19,33 @@ pub struct FeedReactionService {
     feed_repository: FeedRepository,
     notification_service: NotificationService,
     session_repo: FixedSessionRepository,
+    moderation_repo: ModerationRepository,
 }
 
 impl FeedReactionService {
     #[instrument(err, skip(self))]
     pub async fn add_reaction(&self, dto: CreateFeedReactionDto, actor: &Actor) -> Result<()> {
+        let ev = self
+            .feed_repository
+            .get_feed_event_by_id(dto.feed_event_id)
+            .await?;
+
+        if let Some(FeedEventSource::User(source)) = ev.as_ref().map(|ev| &ev.source) {
+            if self
+                .moderation_repo
+                .is_blocked_between(&actor.user_id, &source.id)
+                .await?
+            {
+                return Err(anyhow::anyhow!("You cannot react to this event"));
+            }
+        }
+
         let reaction = self
             .feed_repository
             .create_reaction(dto.clone(), actor)
             .await?;
 
-        let Some(ev) = self
-            .feed_repository
-            .get_feed_event_by_id(dto.feed_event_id)
-            .await?
-        else {
+        let Some(ev) = ev else {
             return Ok(());
         };
 
@@ -80,11 +93,13 @@ impl FeedReactionService {
         repo: FeedRepository,
         notification_service: NotificationService,
         session_repo: FixedSessionRepository,
+        moderation_repo: ModerationRepository,
     ) -> Self {
         Self {
             feed_repository: repo,
             notification_service,
             session_repo,
+            moderation_repo,
         }
     }
 }
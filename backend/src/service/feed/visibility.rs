@@ -14,6 +14,18 @@ impl FeedVisibilityService {
         self.feed_repository.recalculate_visibility(user_id).await
     }
 
+    /// Recalculate visibility for every user with subscribers, returns the updated subscriptions.
+    /// A safety net for changes that did not trigger a recalculation themselves.
+    #[instrument(err, skip(self))]
+    pub async fn recalculate_all_visibility(&self) -> Result<u64> {
+        let mut affected = 0;
+        for user_id in self.feed_repository.get_user_source_ids().await? {
+            affected += self.recalculate_visibility(user_id).await?;
+        }
+
+        Ok(affected)
+    }
+
     #[instrument(err, skip(self), fields(friend1_id = %friendship.friend1.id, friend2_id = %friendship.friend2.id))]
     pub async fn recalculate_friendship_visibility(
         &self,
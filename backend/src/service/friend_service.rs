@@ -105,6 +105,44 @@ impl FromRow<'_, PgRow> for ReadFriendshipDto {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReadFriendSuggestionDto {
+    pub user: ReadUserDto,
+    pub mutual_friends: i64,
+    pub shared_projects: i64,
+    pub shared_categories: i64,
+    pub shared_tags: i64,
+    pub score: i64,
+}
+
+impl FromRow<'_, PgRow> for ReadFriendSuggestionDto {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            user: ReadUserDto {
+                id: row.try_get("id")?,
+                username: row.try_get("displayname")?,
+                avatar_url: row.try_get("avatar_url")?,
+                visibility_flags: row.try_get("visibility_flags")?,
+            },
+            mutual_friends: row.try_get("mutual_friends")?,
+            shared_projects: row.try_get("shared_projects")?,
+            shared_categories: row.try_get("shared_categories")?,
+            shared_tags: row.try_get("shared_tags")?,
+            score: row.try_get("score")?,
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct FriendSuggestionQueryDto {
+    pub limit: Option<i64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate, Debug)]
+pub struct DismissFriendSuggestionDto {
+    pub user_id: String,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AcceptFriendRequestDto {
     pub request_id: Uuid,
@@ -206,8 +244,23 @@ pub trait FriendServiceTrait {
         actor: &Actor,
         data: ReadFriendRequestsDto,
     ) -> Result<Vec<ReadFriendRequestDto>>;
+
+    async fn list_friend_suggestions(
+        &self,
+        query: FriendSuggestionQueryDto,
+        actor: &Actor,
+    ) -> Result<Vec<ReadFriendSuggestionDto>>;
+
+    async fn dismiss_friend_suggestion(
+        &self,
+        dto: DismissFriendSuggestionDto,
+        actor: &Actor,
+    ) -> Result<()>;
 }
 
+const DEFAULT_SUGGESTION_LIMIT: i64 = 10;
+const MAX_SUGGESTION_LIMIT: i64 = 50;
+
 #[derive(Clone)]
 pub struct FriendService {
     repo: FriendsRepository,
@@ -392,6 +445,32 @@ impl FriendServiceTrait for FriendService {
         let result = self.repo.list_friend_requests(data, actor).await?;
         Ok(result)
     }
+
+    #[instrument(err, skip(self), fields(actor_id = %actor, limit = ?query.limit))]
+    async fn list_friend_suggestions(
+        &self,
+        query: FriendSuggestionQueryDto,
+        actor: &Actor,
+    ) -> Result<Vec<ReadFriendSuggestionDto>> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_SUGGESTION_LIMIT)
+            .clamp(1, MAX_SUGGESTION_LIMIT);
+        self.repo.list_friend_suggestions(limit, actor).await
+    }
+
+    #[instrument(err, skip(self), fields(dismissed_user_id = %dto.user_id, actor_id = %actor))]
+    async fn dismiss_friend_suggestion(
+        &self,
+        dto: DismissFriendSuggestionDto,
+        actor: &Actor,
+    ) -> Result<()> {
+        if dto.user_id == actor.user_id {
+            return Err(anyhow::anyhow!("You cannot dismiss yourself"));
+        }
+
+        self.repo.dismiss_friend_suggestion(dto, actor).await
+    }
 }
 
 impl FriendService {
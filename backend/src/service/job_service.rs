@@ -0,0 +1,545 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+use tokio::sync::{Notify, Semaphore};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    config::{
+        database::{Database, DatabaseTrait},
+        env::{AppEnvironment, JobsConfig},
+    },
+    dto::job::{EnqueueJobDto, JobQueryDto, ReadJobDto, ReadJobScheduleDto},
+    entity::{
+        audit::{AuditAction, AuditTargetType},
+        job::{Job, JobKind},
+    },
+    repository::{auth::tokens::cleanup_expired_tokens, job::JobRepository},
+    router::request::ClientInfo,
+    service::{
+        account_deletion_service::AccountDeletionService, achievement_service::AchievementService,
+        audit_service::AuditService, backup_service::BackupService,
+        feed::visibility::FeedVisibilityService, leaderboard_service::LeaderboardService,
+        metrics_service::MetricsService, notification_service::NotificationService,
+        platform_stats_service::PlatformStatsService, rate_limit_service::RateLimitService,
+        release_service::ReleaseService, sandbox_service::SandboxService,
+    },
+};
+
+// Idle workers look for due jobs this often, jobs enqueued by this instance wake them right away
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+// How often due schedules are enqueued and abandoned jobs are cleaned up
+const SCHEDULER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+// A job running longer is cancelled. Its lock is considered abandoned a bit later,
+// so another worker only picks it up once the first one gave up.
+const JOB_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+const LOCK_LEASE_SECONDS: f64 = 15.0 * 60.0;
+
+// Retries wait 30s, 1m, 2m, ... up to an hour
+const RETRY_BASE_SECONDS: i64 = 30;
+const RETRY_MAX_SECONDS: i64 = 60 * 60;
+
+const MAX_ERROR_LENGTH: usize = 2000;
+
+/// Recurring job, times are UTC and the expression includes seconds
+struct ScheduleDefinition {
+    name: &'static str,
+    kind: JobKind,
+    cron: &'static str,
+    sandbox_only: bool,
+}
+
+const SCHEDULES: &[ScheduleDefinition] = &[
+    ScheduleDefinition {
+        name: "cleanup-expired-tokens",
+        kind: JobKind::CleanupExpiredTokens,
+        cron: "0 15 * * * *",
+        sandbox_only: false,
+    },
+    ScheduleDefinition {
+        name: "cleanup-notifications",
+        kind: JobKind::CleanupNotifications,
+        cron: "0 30 3 * * *",
+        sandbox_only: false,
+    },
+    ScheduleDefinition {
+        name: "recalculate-feed-visibility",
+        kind: JobKind::RecalculateFeedVisibility,
+        cron: "0 0 4 * * *",
+        sandbox_only: false,
+    },
+    ScheduleDefinition {
+        name: "publish-scheduled-releases",
+        kind: JobKind::PublishScheduledReleases,
+        cron: "0 * * * * *",
+        sandbox_only: false,
+    },
+    ScheduleDefinition {
+        name: "refresh-platform-stats",
+        kind: JobKind::RefreshPlatformStats,
+        cron: "0 45 * * * *",
+        sandbox_only: false,
+    },
+    ScheduleDefinition {
+        name: "cleanup-rate-limits",
+        kind: JobKind::CleanupRateLimits,
+        cron: "0 */5 * * * *",
+        sandbox_only: false,
+    },
+    ScheduleDefinition {
+        name: "cleanup-leaderboard-cache",
+        kind: JobKind::CleanupLeaderboardCache,
+        cron: "0 10 * * * *",
+        sandbox_only: false,
+    },
+    ScheduleDefinition {
+        name: "roll-up-metrics",
+        kind: JobKind::RollUpMetrics,
+        cron: "0 5 * * * *",
+        sandbox_only: false,
+    },
+    ScheduleDefinition {
+        name: "erase-deleted-accounts",
+        kind: JobKind::EraseDeletedAccounts,
+        cron: "0 20 * * * *",
+        sandbox_only: false,
+    },
+    // Only stored when backups are scheduled, a backup is made once the interval has passed
+    ScheduleDefinition {
+        name: "run-scheduled-backup",
+        kind: JobKind::RunScheduledBackup,
+        cron: "0 */10 * * * *",
+        sandbox_only: false,
+    },
+    ScheduleDefinition {
+        name: "recycle-sandbox",
+        kind: JobKind::RecycleSandbox,
+        cron: "0 0 5 * * *",
+        sandbox_only: true,
+    },
+    ScheduleDefinition {
+        name: "replenish-sandbox-pool",
+        kind: JobKind::ReplenishSandboxPool,
+        cron: "0 */5 * * * *",
+        sandbox_only: true,
+    },
+];
+
+/// Payload of `RecalculateFeedVisibility`, all users when no user is given
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FeedVisibilityPayload {
+    pub user_id: Option<String>,
+}
+
+/// The services jobs hand their work to
+#[derive(Clone)]
+pub struct JobHandlers {
+    pub audit_service: AuditService,
+    pub notification_service: NotificationService,
+    pub sandbox_service: SandboxService,
+    pub visibility_service: FeedVisibilityService,
+    pub platform_stats_service: PlatformStatsService,
+    pub rate_limit_service: RateLimitService,
+    pub leaderboard_service: LeaderboardService,
+    pub metrics_service: MetricsService,
+    pub account_deletion_service: AccountDeletionService,
+    pub backup_service: BackupService,
+    pub achievement_service: AchievementService,
+    pub release_service: ReleaseService,
+}
+
+#[derive(Clone)]
+pub struct JobService {
+    repo: JobRepository,
+    db: Arc<Database>,
+    config: JobsConfig,
+    app_env: AppEnvironment,
+    worker_id: String,
+    wake: Arc<Notify>,
+    handlers: JobHandlers,
+}
+
+impl JobService {
+    pub fn new(db: &Arc<Database>, config: &crate::Config, handlers: JobHandlers) -> Self {
+        Self {
+            repo: JobRepository::new(db),
+            db: db.clone(),
+            config: config.jobs.clone(),
+            app_env: config.server.app_env.clone(),
+            worker_id: format!("{}-{}", config.jobs.worker_id, std::process::id()),
+            wake: Arc::new(Notify::new()),
+            handlers,
+        }
+    }
+
+    /// Queue a job, returns None when it was deduplicated
+    #[instrument(err, skip(self, dto), fields(kind = ?dto.kind))]
+    pub async fn enqueue(&self, dto: EnqueueJobDto) -> Result<Option<Uuid>> {
+        let id = self.repo.enqueue(dto).await?;
+        if id.is_some() {
+            self.wake.notify_one();
+        }
+
+        Ok(id)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn list(&self, query: JobQueryDto) -> Result<Vec<ReadJobDto>> {
+        let jobs = self.repo.filter(query).await?;
+        Ok(jobs.into_iter().map(ReadJobDto::from).collect())
+    }
+
+    #[instrument(err, skip(self), fields(job_id = %id))]
+    pub async fn get(&self, id: Uuid) -> Result<Option<ReadJobDto>> {
+        Ok(self.repo.get_by_id(id).await?.map(ReadJobDto::from))
+    }
+
+    /// Run a failed job again with fresh attempts. None when the job is not failed or a
+    /// duplicate is already queued.
+    #[instrument(err, skip(self), fields(job_id = %id))]
+    pub async fn retry(&self, id: Uuid) -> Result<Option<ReadJobDto>> {
+        let Some(job) = self.repo.retry(id).await? else {
+            return Ok(None);
+        };
+        self.wake.notify_one();
+
+        Ok(Some(ReadJobDto::from(job)))
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn list_schedules(&self) -> Result<Vec<ReadJobScheduleDto>> {
+        let schedules = self.repo.list_schedules().await?;
+        Ok(schedules
+            .into_iter()
+            .map(ReadJobScheduleDto::from)
+            .collect())
+    }
+
+    /// Start the scheduler and the workers, unless this instance leaves the queue to others
+    pub fn spawn_workers(&self) {
+        if !self.config.worker_enabled {
+            return;
+        }
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            let _ = service.sync_schedules().await;
+
+            let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+            loop {
+                interval.tick().await;
+                let _ = service.enqueue_due_schedules().await;
+                let _ = service.cleanup().await;
+            }
+        });
+
+        let service = self.clone();
+        tokio::spawn(async move { service.run_worker().await });
+    }
+
+    /// Store the schedules defined for this environment and drop the rest
+    #[instrument(err, skip(self))]
+    async fn sync_schedules(&self) -> Result<()> {
+        let is_sandbox = self.app_env == AppEnvironment::NowasterSandbox;
+        let mut names = Vec::new();
+
+        for definition in SCHEDULES
+            .iter()
+            .filter(|d| !d.sandbox_only || is_sandbox)
+            .filter(|d| {
+                d.kind != JobKind::RunScheduledBackup
+                    || self.handlers.backup_service.schedule_enabled()
+            })
+        {
+            let next_run_at = next_run(definition.cron, Utc::now())?;
+            self.repo
+                .upsert_schedule(
+                    definition.name,
+                    definition.kind,
+                    serde_json::json!({}),
+                    definition.cron,
+                    next_run_at,
+                )
+                .await?;
+            names.push(definition.name.to_string());
+        }
+
+        self.repo.delete_schedules_except(&names).await?;
+        Ok(())
+    }
+
+    #[instrument(err, skip(self))]
+    async fn enqueue_due_schedules(&self) -> Result<()> {
+        let now = Utc::now();
+
+        for schedule in self.repo.list_schedules().await? {
+            if schedule.next_run_at > now {
+                continue;
+            }
+
+            // Runs missed while no instance was up are not caught up, the next one is after now
+            let next_run_at = next_run(&schedule.cron, now)?;
+            if !self
+                .repo
+                .advance_schedule(&schedule.name, schedule.next_run_at, next_run_at)
+                .await?
+            {
+                continue;
+            }
+
+            let job = EnqueueJobDto::new(schedule.kind)
+                .payload(&schedule.payload)
+                .dedup_key(format!("schedule:{}", schedule.name));
+            if let Some(job_id) = self.enqueue(job).await? {
+                self.repo.set_schedule_job(&schedule.name, job_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(err, skip(self))]
+    async fn cleanup(&self) -> Result<()> {
+        let abandoned = self.repo.fail_abandoned(LOCK_LEASE_SECONDS).await?;
+        if abandoned > 0 {
+            tracing::warn!("Failed {} abandoned jobs", abandoned);
+        }
+
+        let before = Utc::now() - Duration::days(self.config.retention_days as i64);
+        self.repo.delete_finished(before).await?;
+
+        Ok(())
+    }
+
+    /// Run up to `concurrency` jobs at a time. A free slot claims the next due job right away
+    /// instead of waiting for the jobs claimed with it.
+    async fn run_worker(&self) {
+        let slots = Arc::new(Semaphore::new(self.config.concurrency.max(1) as usize));
+
+        loop {
+            let Ok(slot) = slots.clone().acquire_owned().await else {
+                return;
+            };
+            let free = 1 + slots.available_permits() as i64;
+
+            let jobs = match self
+                .repo
+                .claim(&self.worker_id, free, LOCK_LEASE_SECONDS)
+                .await
+            {
+                Ok(jobs) => jobs,
+                Err(e) => {
+                    tracing::error!("Failed to claim jobs: {:#}", e);
+                    Vec::new()
+                }
+            };
+
+            if jobs.is_empty() {
+                drop(slot);
+                tokio::select! {
+                    _ = self.wake.notified() => {}
+                    _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                }
+                continue;
+            }
+
+            let mut slot = Some(slot);
+            for job in jobs {
+                // The claim asked for at most the free slots, so these never wait
+                let job_slot = match slot.take() {
+                    Some(slot) => slot,
+                    None => match slots.clone().acquire_owned().await {
+                        Ok(slot) => slot,
+                        Err(_) => return,
+                    },
+                };
+
+                let service = self.clone();
+                tokio::spawn(async move {
+                    service.run_job(job).await;
+                    drop(job_slot);
+                });
+            }
+        }
+    }
+
+    #[instrument(skip(self, job), fields(job_id = %job.id, kind = ?job.kind, attempt = job.attempts))]
+    async fn run_job(&self, job: Job) {
+        let result = match tokio::time::timeout(JOB_TIMEOUT, self.execute(&job)).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow!("Timed out after {}s", JOB_TIMEOUT.as_secs())),
+        };
+
+        let recorded = match result {
+            Ok(()) => self.repo.complete(job.id).await,
+            Err(e) => {
+                let retry_at = (job.attempts < job.max_attempts).then(|| retry_at(job.attempts));
+                tracing::warn!(
+                    retry_at = ?retry_at,
+                    "Job failed: {:#}",
+                    e
+                );
+                self.repo
+                    .fail(job.id, &truncate_error(format!("{:#}", e)), retry_at)
+                    .await
+            }
+        };
+
+        if let Err(e) = recorded {
+            tracing::error!("Failed to record the job result: {}", e);
+        }
+    }
+
+    async fn execute(&self, job: &Job) -> Result<()> {
+        match job.kind {
+            JobKind::CleanupExpiredTokens => {
+                let deleted = cleanup_expired_tokens(self.db.get_pool()).await?;
+                tracing::info!("Deleted {} expired refresh tokens", deleted);
+            }
+            JobKind::CleanupNotifications => {
+                let deleted = self
+                    .handlers
+                    .notification_service
+                    .cleanup_all_old_notifications(self.config.notification_retention_days as i64)
+                    .await?;
+                tracing::info!("Deleted {} old notifications", deleted);
+            }
+            JobKind::RecalculateFeedVisibility => {
+                let payload: FeedVisibilityPayload = serde_json::from_value(job.payload.clone())
+                    .context("Invalid feed visibility payload")?;
+                let updated = match payload.user_id {
+                    Some(user_id) => {
+                        self.handlers
+                            .visibility_service
+                            .recalculate_visibility(user_id)
+                            .await?
+                    }
+                    None => {
+                        self.handlers
+                            .visibility_service
+                            .recalculate_all_visibility()
+                            .await?
+                    }
+                };
+                tracing::info!("Recalculated visibility of {} subscriptions", updated);
+            }
+            JobKind::PublishScheduledReleases => {
+                let published = self.handlers.release_service.publish_due_releases().await?;
+
+                for release in published {
+                    tracing::info!("Published scheduled release {}", release.version);
+                    self.handlers
+                        .audit_service
+                        .entry(AuditAction::ReleasePublished, None, &ClientInfo::default())
+                        .target(AuditTargetType::Release, release.id)
+                        .changes(None::<()>, Some(&release))
+                        .record()
+                        .await;
+                }
+            }
+            JobKind::RefreshPlatformStats => {
+                let added = self.handlers.platform_stats_service.refresh().await?;
+                tracing::info!("Refreshed platform stats, {} new active user days", added);
+            }
+            JobKind::CleanupRateLimits => {
+                let deleted = self.handlers.rate_limit_service.cleanup().await?;
+                tracing::info!("Deleted {} expired rate limit counters", deleted);
+            }
+            JobKind::CleanupLeaderboardCache => {
+                let deleted = self.handlers.leaderboard_service.cleanup_cache().await?;
+                tracing::info!("Deleted {} expired cached leaderboards", deleted);
+            }
+            JobKind::RollUpMetrics => {
+                let (requests, queries) = self.handlers.metrics_service.roll_up().await?;
+                tracing::info!(
+                    "Rolled up {} request and {} query metrics",
+                    requests,
+                    queries
+                );
+            }
+            JobKind::EraseDeletedAccounts => {
+                let erased = self
+                    .handlers
+                    .account_deletion_service
+                    .erase_due_accounts()
+                    .await?;
+                tracing::info!("Erased {} deleted accounts", erased);
+            }
+            JobKind::RunScheduledBackup => {
+                if let Some(id) = self.handlers.backup_service.run_scheduled_backup().await? {
+                    tracing::info!("Started scheduled backup {}", id);
+                }
+            }
+            JobKind::BackfillAchievements => {
+                match self.handlers.achievement_service.backfill().await? {
+                    Some(awarded) => tracing::info!("Backfilled {} achievements", awarded),
+                    None => tracing::debug!("Achievements are already backfilled"),
+                }
+            }
+            // The guest pool lives in memory, the sandbox runs on a single instance
+            JobKind::ReplenishSandboxPool => {
+                if self.app_env == AppEnvironment::NowasterSandbox {
+                    self.handlers
+                        .sandbox_service
+                        .replenish_pool_if_needed()
+                        .await?;
+                }
+            }
+            JobKind::RecycleSandbox => {
+                if self.app_env != AppEnvironment::NowasterSandbox {
+                    return Ok(());
+                }
+
+                self.handlers
+                    .sandbox_service
+                    .perform_reset("scheduler", "system")
+                    .await?;
+
+                self.handlers
+                    .audit_service
+                    .entry(AuditAction::SandboxReset, None, &ClientInfo::default())
+                    .changes(
+                        None::<()>,
+                        Some(serde_json::json!({
+                            "triggeredBy": "scheduler",
+                            "triggeredType": "system",
+                            "jobId": job.id,
+                        })),
+                    )
+                    .record()
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn next_run(cron: &str, after: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    Schedule::from_str(cron)
+        .with_context(|| format!("Invalid cron expression '{}'", cron))?
+        .after(&after)
+        .next()
+        .ok_or_else(|| anyhow!("Cron expression '{}' never runs again", cron))
+}
+
+/// Exponential backoff with up to 10% jitter, so failed jobs do not retry in lockstep
+fn retry_at(attempts: i32) -> DateTime<Utc> {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    let delay = (RETRY_BASE_SECONDS << exponent).min(RETRY_MAX_SECONDS);
+    let jitter = rand::random::<f64>() * 0.1 * delay as f64;
+
+    Utc::now() + Duration::milliseconds(((delay as f64 + jitter) * 1000.0) as i64)
+}
+
+fn truncate_error(message: String) -> String {
+    match message.char_indices().nth(MAX_ERROR_LENGTH) {
+        Some((index, _)) => format!("{}…", &message[..index]),
+        None => message,
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use tracing::instrument;
+
+use crate::{
+    auth::crypto::sha256_hash,
+    dto::leaderboard::{
+        LeaderboardPeriod, LeaderboardQueryDto, LeaderboardSettingsDto, ReadLeaderboardDto,
+    },
+    repository::leaderboard::{LeaderboardFilter, LeaderboardRepository},
+    router::clerk::Actor,
+};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_CUSTOM_PERIOD_DAYS: i64 = 366;
+
+// Periods that are still running change with every new session, finished ones rarely do
+const ONGOING_PERIOD_TTL_MINUTES: i64 = 5;
+const FINISHED_PERIOD_TTL_MINUTES: i64 = 60;
+
+#[derive(Clone)]
+pub struct LeaderboardService {
+    repo: LeaderboardRepository,
+}
+
+impl LeaderboardService {
+    pub fn new(repo: LeaderboardRepository) -> Self {
+        Self { repo }
+    }
+
+    #[instrument(err, skip(self), fields(actor_id = %actor))]
+    pub async fn get_friends_leaderboard(
+        &self,
+        query: LeaderboardQueryDto,
+        actor: &Actor,
+    ) -> Result<ReadLeaderboardDto> {
+        let now = Utc::now();
+        let (from, to) = Self::resolve_period(&query, now)?;
+
+        let mut category_ids = query.category_ids.filter(|ids| !ids.is_empty());
+        let mut tag_ids = query.tag_ids.filter(|ids| !ids.is_empty());
+        if let Some(ids) = category_ids.as_mut() {
+            ids.sort();
+        }
+        if let Some(ids) = tag_ids.as_mut() {
+            ids.sort();
+        }
+
+        let filter = LeaderboardFilter {
+            from,
+            to,
+            category_ids,
+            tag_ids,
+            limit: query.limit.unwrap_or(DEFAULT_LIMIT),
+        };
+
+        // Keyed on who is on the leaderboard, so relation changes never serve a stale result
+        let members = self.repo.friend_members_fingerprint(&actor.user_id).await?;
+        let cache_key = Self::cache_key("friends", &actor.user_id, &members, &filter);
+        if let Some(cached) = self.repo.get_cached(&cache_key).await? {
+            return Ok(cached);
+        }
+
+        let entries = self.repo.rank_friends(&actor.user_id, &filter).await?;
+        let leaderboard = ReadLeaderboardDto {
+            period_start: from,
+            period_end: to,
+            computed_at: now,
+            entries,
+        };
+
+        let ttl = if to > now {
+            Duration::minutes(ONGOING_PERIOD_TTL_MINUTES)
+        } else {
+            Duration::minutes(FINISHED_PERIOD_TTL_MINUTES)
+        };
+
+        // A failed cache write should not fail the request
+        let _ = self
+            .repo
+            .store_cached(&cache_key, &actor.user_id, &leaderboard, now + ttl)
+            .await;
+
+        Ok(leaderboard)
+    }
+
+    /// Drop expired cached leaderboards, returns how many were removed
+    #[instrument(err, skip(self))]
+    pub async fn cleanup_cache(&self) -> Result<u64> {
+        self.repo.delete_expired().await
+    }
+
+    #[instrument(err, skip(self), fields(actor_id = %actor))]
+    pub async fn get_settings(&self, actor: &Actor) -> Result<LeaderboardSettingsDto> {
+        let opt_out = self.repo.get_opt_out(&actor.user_id).await?;
+        Ok(LeaderboardSettingsDto { opt_out })
+    }
+
+    #[instrument(err, skip(self), fields(actor_id = %actor, opt_out = dto.opt_out))]
+    pub async fn update_settings(
+        &self,
+        dto: LeaderboardSettingsDto,
+        actor: &Actor,
+    ) -> Result<LeaderboardSettingsDto> {
+        let opt_out = self.repo.set_opt_out(&actor.user_id, dto.opt_out).await?;
+        self.repo.invalidate_for_user(&actor.user_id).await?;
+        Ok(LeaderboardSettingsDto { opt_out })
+    }
+
+    fn resolve_period(
+        query: &LeaderboardQueryDto,
+        now: DateTime<Utc>,
+    ) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+        let today = now.date_naive();
+        let (from, to) = match query.period {
+            LeaderboardPeriod::Day => {
+                let from = Self::start_of_day(today);
+                (from, from + Duration::days(1))
+            }
+            LeaderboardPeriod::Week => {
+                let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                let from = Self::start_of_day(monday);
+                (from, from + Duration::weeks(1))
+            }
+            LeaderboardPeriod::Month => {
+                let first = today.with_day(1).ok_or(anyhow!("Invalid date"))?;
+                let next = if first.month() == 12 {
+                    NaiveDate::from_ymd_opt(first.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(first.year(), first.month() + 1, 1)
+                }
+                .ok_or(anyhow!("Invalid date"))?;
+                (Self::start_of_day(first), Self::start_of_day(next))
+            }
+            LeaderboardPeriod::Custom => {
+                let (Some(from), Some(to)) = (query.from, query.to) else {
+                    return Err(anyhow!("Custom period requires both 'from' and 'to'"));
+                };
+                if from >= to {
+                    return Err(anyhow!("'from' must be before 'to'"));
+                }
+                if to - from > Duration::days(MAX_CUSTOM_PERIOD_DAYS) {
+                    return Err(anyhow!(
+                        "Custom period cannot be longer than {} days",
+                        MAX_CUSTOM_PERIOD_DAYS
+                    ));
+                }
+                (from, to)
+            }
+        };
+
+        Ok((from, to))
+    }
+
+    fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+    }
+
+    fn cache_key(
+        scope: &str,
+        viewer_id: &str,
+        members: &str,
+        filter: &LeaderboardFilter,
+    ) -> String {
+        let join = |ids: &Option<Vec<uuid::Uuid>>| {
+            ids.as_ref()
+                .map(|ids| {
+                    ids.iter()
+                        .map(|id| id.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+                .unwrap_or_default()
+        };
+
+        sha256_hash(&format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}",
+            scope,
+            viewer_id,
+            members,
+            filter.from.timestamp(),
+            filter.to.timestamp(),
+            join(&filter.category_ids),
+            join(&filter.tag_ids),
+            filter.limit
+        ))
+    }
+}
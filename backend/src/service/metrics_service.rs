@@ -0,0 +1,114 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::{
+    config::{database::Database, env::MetricsConfig},
+    dto::metrics::{
+        MetricsBucket, MetricsQueryDto, ReadQueryLatencyDto, ReadRouteLatencyDto,
+        ReadSlowRequestDto, ReadStatusCodeRateDto,
+    },
+    repository::metrics::MetricsRepository,
+};
+
+const DEFAULT_RANGE_HOURS: i64 = 24;
+// Minute buckets over longer ranges return more rows than anyone can read
+const MAX_MINUTE_BUCKET_RANGE_HOURS: i64 = 48;
+const DEFAULT_SLOW_REQUEST_LIMIT: i64 = 20;
+const MAX_SLOW_REQUEST_LIMIT: i64 = 100;
+
+/// Time range of a metrics query that passed [`MetricsService::range`]
+#[derive(Clone, Copy, Debug)]
+pub struct MetricsRange {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    bucket: MetricsBucket,
+}
+
+#[derive(Clone)]
+pub struct MetricsService {
+    repo: MetricsRepository,
+    retention: Duration,
+}
+
+impl MetricsService {
+    pub fn new(db: &Arc<Database>, config: &MetricsConfig) -> Self {
+        Self {
+            repo: MetricsRepository::new(db),
+            retention: Duration::days(config.retention_days.into()),
+        }
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn route_latency(&self, range: MetricsRange) -> Result<Vec<ReadRouteLatencyDto>> {
+        self.repo
+            .route_latency(range.from, range.to, range.bucket.as_str())
+            .await
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn query_latency(&self, range: MetricsRange) -> Result<Vec<ReadQueryLatencyDto>> {
+        self.repo
+            .query_latency(range.from, range.to, range.bucket.as_str())
+            .await
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn status_code_rates(
+        &self,
+        range: MetricsRange,
+    ) -> Result<Vec<ReadStatusCodeRateDto>> {
+        self.repo
+            .status_code_rates(range.from, range.to, range.bucket.as_str())
+            .await
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn slowest_requests(
+        &self,
+        range: MetricsRange,
+        limit: Option<i64>,
+    ) -> Result<Vec<ReadSlowRequestDto>> {
+        let limit = limit
+            .unwrap_or(DEFAULT_SLOW_REQUEST_LIMIT)
+            .clamp(1, MAX_SLOW_REQUEST_LIMIT);
+
+        self.repo
+            .slowest_requests(range.from, range.to, limit)
+            .await
+    }
+
+    /// Roll up raw rows older than the retention period, whole hours only
+    #[instrument(err, skip(self))]
+    pub async fn roll_up(&self) -> Result<(u64, u64)> {
+        let cutoff = (Utc::now() - self.retention).duration_trunc(Duration::hours(1))?;
+        self.repo.roll_up(cutoff).await
+    }
+
+    /// Range of the query, defaults to the last day
+    pub fn range(query: &MetricsQueryDto) -> Result<MetricsRange> {
+        let to = query.to.unwrap_or_else(Utc::now);
+        let from = query
+            .from
+            .unwrap_or_else(|| to - Duration::hours(DEFAULT_RANGE_HOURS));
+
+        if from >= to {
+            return Err(anyhow!("from must be before to"));
+        }
+        if query.bucket == MetricsBucket::Minute
+            && to - from > Duration::hours(MAX_MINUTE_BUCKET_RANGE_HOURS)
+        {
+            return Err(anyhow!(
+                "Minute buckets are limited to {} hours",
+                MAX_MINUTE_BUCKET_RANGE_HOURS
+            ));
+        }
+
+        Ok(MetricsRange {
+            from,
+            to,
+            bucket: query.bucket,
+        })
+    }
+}
@@ -1,9 +1,21 @@
+pub mod account_deletion_service;
+pub mod achievement_service;
+pub mod audit_service;
 pub mod auth_service;
+pub mod backup_service;
 pub mod category_service;
 pub mod feed;
 pub mod friend_service;
+pub mod job_service;
+pub mod leaderboard_service;
+pub mod metrics_service;
+pub mod moderation_service;
 pub mod notification_service;
+pub mod passkey_service;
+pub mod platform_stats_service;
+pub mod project_invite_service;
 pub mod project_service;
+pub mod rate_limit_service;
 pub mod release_service;
 pub mod sandbox_service;
 pub mod session;
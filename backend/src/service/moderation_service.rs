@@ -0,0 +1,162 @@
+use anyhow::Result;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    dto::moderation::{
+        BlockUserDto, CreateUserReportDto, FilterUserReportsDto, ReadBlockedUserDto,
+        ReadSuspensionDto, ReadUserReportDto, ReviewUserReportDto,
+    },
+    repository::moderation::ModerationRepository,
+    router::clerk::Actor,
+    service::{
+        auth_service::AuthService, feed::visibility::FeedVisibilityService,
+        notification_service::NotificationService,
+    },
+};
+
+const MAX_REPORTS_LIMIT: i64 = 500;
+
+#[derive(Clone)]
+pub struct ModerationService {
+    repo: ModerationRepository,
+    visibility_service: FeedVisibilityService,
+    notification_service: NotificationService,
+    auth_service: AuthService,
+}
+
+impl ModerationService {
+    pub fn new(
+        repo: ModerationRepository,
+        visibility_service: FeedVisibilityService,
+        notification_service: NotificationService,
+        auth_service: AuthService,
+    ) -> Self {
+        Self {
+            repo,
+            visibility_service,
+            notification_service,
+            auth_service,
+        }
+    }
+
+    #[instrument(err, skip(self), fields(blocked_id = %dto.user_id, actor_id = %actor))]
+    pub async fn block_user(&self, dto: BlockUserDto, actor: &Actor) -> Result<()> {
+        if dto.user_id == actor.user_id {
+            return Err(anyhow::anyhow!("You cannot block yourself"));
+        }
+
+        self.repo.block_user(&actor.user_id, &dto.user_id).await?;
+        self.recalculate_visibility(&actor.user_id, &dto.user_id)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(err, skip(self), fields(blocked_id = %user_id, actor_id = %actor))]
+    pub async fn unblock_user(&self, user_id: String, actor: &Actor) -> Result<()> {
+        self.repo.unblock_user(&actor.user_id, &user_id).await?;
+        self.recalculate_visibility(&actor.user_id, &user_id)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(err, skip(self), fields(actor_id = %actor))]
+    pub async fn list_blocked_users(&self, actor: &Actor) -> Result<Vec<ReadBlockedUserDto>> {
+        self.repo.list_blocked_users(&actor.user_id).await
+    }
+
+    #[instrument(err, skip(self, dto), fields(reported_id = %dto.user_id, actor_id = %actor))]
+    pub async fn report_user(
+        &self,
+        dto: CreateUserReportDto,
+        actor: &Actor,
+    ) -> Result<ReadUserReportDto> {
+        if dto.user_id == actor.user_id {
+            return Err(anyhow::anyhow!("You cannot report yourself"));
+        }
+
+        let report = self.repo.create_report(dto, &actor.user_id).await?;
+
+        // Failing to notify admins should not fail the report, it is queued either way
+        let _ = self
+            .notification_service
+            .notify_admins_user_reported(&report)
+            .await;
+
+        Ok(report)
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn filter_reports(
+        &self,
+        mut filter: FilterUserReportsDto,
+    ) -> Result<Vec<ReadUserReportDto>> {
+        filter.limit = filter.limit.map(|limit| limit.clamp(1, MAX_REPORTS_LIMIT));
+        self.repo.filter_reports(filter).await
+    }
+
+    #[instrument(err, skip(self), fields(report_id = %report_id))]
+    pub async fn get_report(&self, report_id: Uuid) -> Result<Option<ReadUserReportDto>> {
+        self.repo.get_report(report_id).await
+    }
+
+    #[instrument(err, skip(self, dto), fields(report_id = %report_id, admin_id = %admin))]
+    pub async fn review_report(
+        &self,
+        report_id: Uuid,
+        dto: ReviewUserReportDto,
+        admin: &Actor,
+    ) -> Result<Option<ReadUserReportDto>> {
+        let Some(report) = self
+            .repo
+            .review_report(report_id, &dto, &admin.user_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        if dto.suspend {
+            let reason = dto
+                .resolution_note
+                .clone()
+                .unwrap_or_else(|| format!("Reported for {:?}", report.reason));
+            self.suspend_user(&report.reported.id, reason, admin)
+                .await?;
+        }
+
+        Ok(Some(report))
+    }
+
+    #[instrument(err, skip(self, reason), fields(user_id = %user_id, admin_id = %admin))]
+    pub async fn suspend_user(
+        &self,
+        user_id: &str,
+        reason: String,
+        admin: &Actor,
+    ) -> Result<ReadSuspensionDto> {
+        if user_id == admin.user_id {
+            return Err(anyhow::anyhow!("You cannot suspend yourself"));
+        }
+
+        let suspension = self.repo.set_suspension(user_id, Some(reason)).await?;
+        self.auth_service
+            .revoke_all_sessions(user_id, "user_suspended")
+            .await?;
+        Ok(suspension)
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id, admin_id = %admin))]
+    pub async fn unsuspend_user(&self, user_id: &str, admin: &Actor) -> Result<ReadSuspensionDto> {
+        self.repo.set_suspension(user_id, None).await
+    }
+
+    async fn recalculate_visibility(&self, user_id: &str, other_user_id: &str) -> Result<()> {
+        self.visibility_service
+            .recalculate_visibility(user_id.to_string())
+            .await?;
+        self.visibility_service
+            .recalculate_visibility(other_user_id.to_string())
+            .await?;
+        Ok(())
+    }
+}
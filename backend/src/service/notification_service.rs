@@ -5,14 +5,24 @@ use uuid::Uuid;
 
 use crate::{
     config::database::Database,
-    dto::notification::{
-        CreateNotificationDto, MarkNotificationsSeenDto, NotificationCountDto,
-        NotificationQueryDto, ReadNotificationDto,
+    dto::{
+        achievement::ReadAchievementDto,
+        moderation::ReadUserReportDto,
+        notification::{
+            CreateNotificationDto, MarkNotificationsSeenDto, NotificationCountDto,
+            NotificationQueryDto, ReadNotificationDto,
+        },
+        project::project_invite::ReadProjectInviteDto,
+        user::read_user::ReadUserDto,
     },
-    entity::notification::{
-        BackupCompletedData, BackupFailedData, FriendRequestAcceptedData, FriendRequestData,
-        NotificationSource, NotificationType, SandboxFailedDeployData, SessionReactionData,
-        SystemNotificationData,
+    entity::{
+        notification::{
+            AccountAccessedData, AchievementUnlockedData, BackupCompletedData, BackupFailedData,
+            FriendRequestAcceptedData, FriendRequestData, NewLoginData, NotificationSource,
+            NotificationType, ProjectInviteData, SandboxFailedDeployData, SessionReactionData,
+            SystemNotificationData, SystemReleaseData, UserReportedData,
+        },
+        release::ReleaseAudience,
     },
     repository::{notification::NotificationRepository, user::UserRepository},
     router::clerk::Actor,
@@ -128,6 +138,27 @@ impl NotificationService {
         self.create_notification(dto).await
     }
 
+    #[instrument(err, skip(self, invite), fields(invite_id = %invite.id, invitee_id = %invite.invitee.id))]
+    pub async fn notify_project_invite(
+        &self,
+        invite: &ReadProjectInviteDto,
+        inviter: ReadUserDto,
+    ) -> Result<Uuid> {
+        let dto = CreateNotificationDto {
+            user_id: invite.invitee.id.clone(),
+            source: NotificationSource::User(inviter.clone()),
+            notification_type: NotificationType::ProjectInvite(ProjectInviteData {
+                invite_id: invite.id,
+                project_id: invite.project_id,
+                project_name: invite.project_name.clone(),
+                inviter,
+                role: invite.role,
+            }),
+        };
+
+        self.create_notification(dto).await
+    }
+
     #[instrument(err, skip(self), fields(session_owner_id = %session_owner_user_id, reactor_id = %reactor_user_dto.id, session_id = %session_id))]
     pub async fn notify_session_reaction(
         &self,
@@ -159,37 +190,90 @@ impl NotificationService {
         self.create_notification(dto).await
     }
 
-    #[instrument(err, skip(self), fields(user_count = user_ids.len(), release_id = %release_id))]
+    #[instrument(err, skip(self, achievement), fields(user_id = %user_id, achievement_key = %achievement.key))]
+    pub async fn notify_achievement_unlocked(
+        &self,
+        user_id: String,
+        achievement: &ReadAchievementDto,
+    ) -> Result<Uuid> {
+        let dto = CreateNotificationDto {
+            user_id,
+            source: NotificationSource::System(SystemNotificationData {
+                system_id: "nowaster-achievements".to_string(),
+                system_name: "Nowaster Achievements".to_string(),
+            }),
+            notification_type: NotificationType::AchievementUnlocked(AchievementUnlockedData {
+                achievement_key: achievement.key.clone(),
+                name: achievement.name.clone(),
+                description: achievement.description.clone(),
+            }),
+        };
+
+        self.create_notification(dto).await
+    }
+
+    #[instrument(err, skip(self, data), fields(user_id = %user_id))]
+    pub async fn notify_new_login(&self, user_id: String, data: NewLoginData) -> Result<Uuid> {
+        let dto = CreateNotificationDto {
+            user_id,
+            source: NotificationSource::System(SystemNotificationData {
+                system_id: "nowaster-security".to_string(),
+                system_name: "Nowaster Security".to_string(),
+            }),
+            notification_type: NotificationType::AuthNewLogin(data),
+        };
+
+        self.create_notification(dto).await
+    }
+
+    #[instrument(err, skip(self, data), fields(user_id = %user_id))]
+    pub async fn notify_account_accessed(
+        &self,
+        user_id: String,
+        data: AccountAccessedData,
+    ) -> Result<Uuid> {
+        let dto = CreateNotificationDto {
+            user_id,
+            source: NotificationSource::System(SystemNotificationData {
+                system_id: "nowaster-security".to_string(),
+                system_name: "Nowaster Security".to_string(),
+            }),
+            notification_type: NotificationType::AuthAccountAccessed(data),
+        };
+
+        self.create_notification(dto).await
+    }
+
+    /// Announce a published release to its audience, returns how many users were notified
+    #[instrument(err, skip(self, short_description), fields(release_id = %release_id))]
     pub async fn notify_system_announcement(
         &self,
-        user_ids: Vec<String>,
+        audience: ReleaseAudience,
         release_id: Uuid,
         title: String,
         short_description: Option<String>,
-    ) -> Result<Vec<Uuid>> {
-        let mut notification_ids = Vec::new();
-
-        for user_id in user_ids {
-            let dto = CreateNotificationDto {
-                user_id,
-                source: NotificationSource::System(SystemNotificationData {
+    ) -> Result<u64> {
+        self.repository
+            .create_for_audience(
+                NotificationSource::System(SystemNotificationData {
                     system_id: "nowaster-system".to_string(),
                     system_name: "Nowaster".to_string(),
                 }),
-                notification_type: NotificationType::SystemNewRelease(
-                    crate::entity::notification::SystemReleaseData {
-                        release_id,
-                        title: title.clone(),
-                        short_description: short_description.clone(),
-                    },
-                ),
-            };
-
-            let notification_id = self.create_notification(dto).await?;
-            notification_ids.push(notification_id);
-        }
+                NotificationType::SystemNewRelease(SystemReleaseData {
+                    release_id,
+                    title,
+                    short_description,
+                }),
+                audience,
+            )
+            .await
+    }
 
-        Ok(notification_ids)
+    #[instrument(err, skip(self), fields(release_id = %release_id))]
+    pub async fn withdraw_system_announcement(&self, release_id: Uuid) -> Result<u64> {
+        self.repository
+            .delete_release_announcements(release_id)
+            .await
     }
 
     #[instrument(err, skip(self), fields(user_id = %user_id, days_old = days_old))]
@@ -200,6 +284,13 @@ impl NotificationService {
             .await
     }
 
+    /// Delete the notifications of all users older than `days_old` days
+    #[instrument(err, skip(self), fields(days_old = days_old))]
+    pub async fn cleanup_all_old_notifications(&self, days_old: i64) -> Result<u64> {
+        let cutoff_date = chrono::Local::now() - chrono::Duration::days(days_old);
+        self.repository.delete_older_than(cutoff_date).await
+    }
+
     #[instrument(err, skip(self), fields(sandbox_lifecycle_id = %sandbox_lifecycle_id))]
     pub async fn notify_admins_sandbox_failed_deploy(
         &self,
@@ -292,4 +383,33 @@ impl NotificationService {
 
         Ok(notification_ids)
     }
+
+    #[instrument(err, skip(self, report), fields(report_id = %report.id))]
+    pub async fn notify_admins_user_reported(
+        &self,
+        report: &ReadUserReportDto,
+    ) -> Result<Vec<Uuid>> {
+        let admin_ids = self.user_repo.get_admin_ids().await?;
+        let mut notification_ids = Vec::new();
+
+        for user_id in admin_ids {
+            let dto = CreateNotificationDto {
+                user_id,
+                source: NotificationSource::System(SystemNotificationData {
+                    system_id: "nowaster-moderation".to_string(),
+                    system_name: "Nowaster Moderation".to_string(),
+                }),
+                notification_type: NotificationType::AdminUserReported(UserReportedData {
+                    report_id: report.id,
+                    reported: report.reported.clone(),
+                    reason: report.reason,
+                }),
+            };
+
+            let id = self.create_notification(dto).await?;
+            notification_ids.push(id);
+        }
+
+        Ok(notification_ids)
+    }
 }
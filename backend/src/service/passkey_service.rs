@@ -0,0 +1,316 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{net::IpAddr, sync::Arc};
+use tracing::instrument;
+use uuid::Uuid;
+use webauthn_rs::prelude::{
+    CreationChallengeResponse, DiscoverableAuthentication, DiscoverableKey, PasskeyRegistration,
+    RequestChallengeResponse, Url, Webauthn, WebauthnBuilder,
+};
+use webauthn_rs_proto::ResidentKeyRequirement;
+
+use crate::{
+    config::database::Database,
+    dto::passkey::{
+        FinishPasskeyLoginDto, FinishPasskeyRegistrationDto, FinishPasskeySignupDto,
+        PasskeyChallengeDto, ReadPasskeyDto,
+    },
+    repository::{auth::passkey::PasskeyRepository, user::UserRepository},
+    service::auth_service::AuthService,
+};
+
+// The browser prompt times out after 5 minutes as well
+const CHALLENGE_TTL_MINUTES: i64 = 5;
+
+#[derive(Serialize, Deserialize)]
+struct RegistrationState {
+    user_handle: Uuid,
+    registration: PasskeyRegistration,
+}
+
+// The account of a passkey sign up is only created once the passkey is verified
+#[derive(Serialize, Deserialize)]
+struct SignupState {
+    user_handle: Uuid,
+    display_name: String,
+    registration: PasskeyRegistration,
+}
+
+#[derive(Clone)]
+pub struct PasskeyService {
+    repo: PasskeyRepository,
+    user_repo: UserRepository,
+    auth_service: AuthService,
+    webauthn: Arc<Webauthn>,
+}
+
+impl PasskeyService {
+    /// Passkeys are bound to the host of the frontend, changing it invalidates every passkey
+    pub fn new(db: &Arc<Database>, frontend_url: &str, auth_service: AuthService) -> Result<Self> {
+        let origin = Url::parse(frontend_url).context("Invalid frontend URL")?;
+        let rp_id = origin
+            .host_str()
+            .context("Frontend URL has no host")?
+            .to_string();
+
+        let webauthn = WebauthnBuilder::new(&rp_id, &origin)?
+            .rp_name("Nowaster")
+            .build()?;
+
+        Ok(Self {
+            repo: PasskeyRepository::new(db),
+            user_repo: UserRepository::new(db),
+            auth_service,
+            webauthn: Arc::new(webauthn),
+        })
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn list_passkeys(&self, user_id: &str) -> Result<Vec<ReadPasskeyDto>> {
+        let passkeys = self.repo.list_by_user(user_id).await?;
+        Ok(passkeys.into_iter().map(ReadPasskeyDto::from).collect())
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn start_registration(
+        &self,
+        user_id: &str,
+    ) -> Result<PasskeyChallengeDto<CreationChallengeResponse>> {
+        let (_, display_name) = self
+            .user_repo
+            .get_actor_by_id(user_id.to_string())
+            .await?
+            .context("User not found")?;
+
+        let existing = self.repo.list_by_user(user_id).await?;
+        let user_handle = existing
+            .first()
+            .map(|record| record.user_handle)
+            .unwrap_or_else(Uuid::new_v4);
+        // Authenticators refuse to register a second passkey for the same account
+        let exclude_credentials = existing
+            .iter()
+            .map(|record| record.passkey.cred_id().clone())
+            .collect();
+
+        let (options, registration) = self.webauthn.start_passkey_registration(
+            user_handle,
+            &display_name,
+            &display_name,
+            Some(exclude_credentials),
+        )?;
+        let options = require_discoverable(options);
+
+        let state = serde_json::to_value(RegistrationState {
+            user_handle,
+            registration,
+        })?;
+        let challenge_id = self
+            .repo
+            .create_challenge(Some(user_id), state, challenge_expiry())
+            .await?;
+
+        Ok(PasskeyChallengeDto {
+            challenge_id,
+            options,
+        })
+    }
+
+    #[instrument(err, skip(self, dto), fields(user_id = %user_id))]
+    pub async fn finish_registration(
+        &self,
+        user_id: &str,
+        dto: FinishPasskeyRegistrationDto,
+    ) -> Result<ReadPasskeyDto> {
+        let (owner, state) = self
+            .repo
+            .take_challenge(dto.challenge_id)
+            .await?
+            .ok_or_else(|| anyhow!("Passkey challenge expired"))?;
+        if owner.as_deref() != Some(user_id) {
+            return Err(anyhow!("Passkey challenge expired"));
+        }
+
+        let state: RegistrationState = serde_json::from_value(state)?;
+        let passkey = self
+            .webauthn
+            .finish_passkey_registration(&dto.credential, &state.registration)?;
+
+        let record = self
+            .repo
+            .create(user_id, state.user_handle, &dto.name, &passkey)
+            .await?;
+
+        Ok(record.into())
+    }
+
+    /// Start creating an account that signs in with a passkey only, no OAuth provider or email
+    #[instrument(err, skip(self))]
+    pub async fn start_signup(
+        &self,
+        display_name: &str,
+    ) -> Result<PasskeyChallengeDto<CreationChallengeResponse>> {
+        let user_handle = Uuid::new_v4();
+        let (options, registration) = self.webauthn.start_passkey_registration(
+            user_handle,
+            display_name,
+            display_name,
+            None,
+        )?;
+        let options = require_discoverable(options);
+
+        let state = serde_json::to_value(SignupState {
+            user_handle,
+            display_name: display_name.to_string(),
+            registration,
+        })?;
+        let challenge_id = self
+            .repo
+            .create_challenge(None, state, challenge_expiry())
+            .await?;
+
+        Ok(PasskeyChallengeDto {
+            challenge_id,
+            options,
+        })
+    }
+
+    /// Verify the new passkey, create the account and sign it in
+    ///
+    /// Returns (access_token, refresh_token, user_id)
+    #[instrument(err, skip(self, dto))]
+    pub async fn finish_signup(
+        &self,
+        dto: FinishPasskeySignupDto,
+        user_agent: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> Result<(String, String, String)> {
+        let (owner, state) = self
+            .repo
+            .take_challenge(dto.challenge_id)
+            .await?
+            .ok_or_else(|| anyhow!("Passkey challenge expired"))?;
+        let state: SignupState = match (owner, serde_json::from_value(state)) {
+            (None, Ok(state)) => state,
+            _ => return Err(anyhow!("Passkey challenge expired")),
+        };
+
+        let passkey = self
+            .webauthn
+            .finish_passkey_registration(&dto.credential, &state.registration)?;
+
+        let user_id = self
+            .repo
+            .create_account(&state.display_name, state.user_handle, &dto.name, &passkey)
+            .await?;
+
+        let (access_token, refresh_token) = self
+            .auth_service
+            .handle_passkey_signup(&user_id, user_agent, ip)
+            .await?;
+
+        Ok((access_token, refresh_token, user_id))
+    }
+
+    /// The browser offers the passkeys it holds for this site, so no email is asked for and
+    /// nothing tells whether an account exists
+    #[instrument(err, skip(self))]
+    pub async fn start_login(&self) -> Result<PasskeyChallengeDto<RequestChallengeResponse>> {
+        let (options, authentication) = self.webauthn.start_discoverable_authentication()?;
+
+        let challenge_id = self
+            .repo
+            .create_challenge(
+                None,
+                serde_json::to_value(authentication)?,
+                challenge_expiry(),
+            )
+            .await?;
+
+        Ok(PasskeyChallengeDto {
+            challenge_id,
+            options,
+        })
+    }
+
+    /// Verify the assertion and sign in the user the passkey belongs to
+    ///
+    /// Returns (access_token, refresh_token, user_id)
+    #[instrument(err, skip(self, dto))]
+    pub async fn finish_login(
+        &self,
+        dto: FinishPasskeyLoginDto,
+        user_agent: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> Result<(String, String, String)> {
+        let (_, state) = self
+            .repo
+            .take_challenge(dto.challenge_id)
+            .await?
+            .ok_or_else(|| anyhow!("Passkey challenge expired"))?;
+        let authentication: DiscoverableAuthentication =
+            serde_json::from_value(state).map_err(|_| anyhow!("Passkey challenge expired"))?;
+
+        let (user_handle, credential_id) = self
+            .webauthn
+            .identify_discoverable_authentication(&dto.credential)?;
+        let (user_id, mut record) = self
+            .repo
+            .find_by_credential_id(credential_id)
+            .await?
+            .filter(|(_, record)| record.user_handle == user_handle)
+            .context("Passkey not found")?;
+
+        let result = self.webauthn.finish_discoverable_authentication(
+            &dto.credential,
+            authentication,
+            &[DiscoverableKey::from(&record.passkey)],
+        )?;
+        record.passkey.update_credential(&result);
+        self.repo.record_login(record.id, &record.passkey).await?;
+
+        let (access_token, refresh_token) = self
+            .auth_service
+            .handle_passkey_login(&user_id, user_agent, ip)
+            .await?;
+
+        Ok((access_token, refresh_token, user_id))
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id, passkey_id = %id))]
+    pub async fn rename_passkey(&self, user_id: &str, id: Uuid, name: &str) -> Result<()> {
+        if !self.repo.rename(id, user_id, name).await? {
+            return Err(anyhow!("Passkey not found"));
+        }
+
+        Ok(())
+    }
+
+    /// Remove a passkey, the user must keep at least one way to sign in
+    #[instrument(err, skip(self), fields(user_id = %user_id, passkey_id = %id))]
+    pub async fn delete_passkey(&self, user_id: &str, id: Uuid) -> Result<()> {
+        if self.auth_service.login_method_count(user_id).await? <= 1 {
+            return Err(anyhow!("Cannot remove the last login method"));
+        }
+
+        if !self.repo.delete(id, user_id).await? {
+            return Err(anyhow!("Passkey not found"));
+        }
+
+        Ok(())
+    }
+}
+
+// Sign in without an email only works with passkeys the authenticator keeps for the site
+fn require_discoverable(mut options: CreationChallengeResponse) -> CreationChallengeResponse {
+    if let Some(selection) = options.public_key.authenticator_selection.as_mut() {
+        selection.require_resident_key = true;
+        selection.resident_key = Some(ResidentKeyRequirement::Required);
+    }
+    options
+}
+
+fn challenge_expiry() -> chrono::DateTime<Utc> {
+    Utc::now() + Duration::minutes(CHALLENGE_TTL_MINUTES)
+}
@@ -0,0 +1,91 @@
+use anyhow::{bail, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::{
+    config::database::Database,
+    dto::platform_stats::{
+        PlatformStatsQueryDto, ReadActiveUsersDto, ReadApiTokenAdoptionDto, ReadDailyActivityDto,
+        ReadSandboxConversionDto, ReadSignupsDto,
+    },
+    repository::platform_stats::PlatformStatsRepository,
+};
+
+const DEFAULT_RANGE_DAYS: i64 = 30;
+const MAX_RANGE_DAYS: i64 = 366;
+const SANDBOX_CONVERSIONS_LIMIT: i64 = 50;
+
+// Activity is rolled up again for a while, sources written late still end up in their day
+const ROLLUP_OVERLAP_HOURS: i64 = 48;
+
+/// Days of a stats query that passed [`PlatformStatsService::range`]
+#[derive(Clone, Copy, Debug)]
+pub struct StatsRange {
+    from: NaiveDate,
+    to: NaiveDate,
+}
+
+#[derive(Clone)]
+pub struct PlatformStatsService {
+    repo: PlatformStatsRepository,
+}
+
+impl PlatformStatsService {
+    pub fn new(db: &Arc<Database>) -> Self {
+        Self {
+            repo: PlatformStatsRepository::new(db),
+        }
+    }
+
+    /// Update the active user rollup and the materialized views, run by the job queue
+    #[instrument(err, skip(self))]
+    pub async fn refresh(&self) -> Result<i64> {
+        let since = Utc::now() - Duration::hours(ROLLUP_OVERLAP_HOURS);
+        self.repo.refresh(since).await
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn active_users(&self, range: StatsRange) -> Result<Vec<ReadActiveUsersDto>> {
+        self.repo.active_users(range.from, range.to).await
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn signups(&self, range: StatsRange) -> Result<Vec<ReadSignupsDto>> {
+        self.repo.signups(range.from, range.to).await
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn daily_activity(&self, range: StatsRange) -> Result<Vec<ReadDailyActivityDto>> {
+        self.repo.daily_activity(range.from, range.to).await
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn api_token_adoption(&self) -> Result<ReadApiTokenAdoptionDto> {
+        self.repo.api_token_adoption().await
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn sandbox_conversions(&self) -> Result<Vec<ReadSandboxConversionDto>> {
+        self.repo
+            .sandbox_conversions(SANDBOX_CONVERSIONS_LIMIT)
+            .await
+    }
+
+    /// Days of the query, defaults to the last 30 days
+    pub fn range(query: &PlatformStatsQueryDto) -> Result<StatsRange> {
+        let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+        let from = query
+            .from
+            .unwrap_or(to - Duration::days(DEFAULT_RANGE_DAYS - 1));
+
+        if from > to {
+            bail!("The range must start before it ends");
+        }
+        if (to - from).num_days() >= MAX_RANGE_DAYS {
+            bail!("The range cannot be longer than {} days", MAX_RANGE_DAYS);
+        }
+
+        Ok(StatsRange { from, to })
+    }
+}
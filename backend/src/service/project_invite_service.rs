@@ -0,0 +1,129 @@
+use anyhow::Result;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    dto::project::{project_invite::ReadProjectInviteDto, project_member::AddProjectMemberDto},
+    repository::project_invite::ProjectInviteRepository,
+    router::clerk::Actor,
+    service::{notification_service::NotificationService, project_service::ProjectService},
+};
+
+#[derive(Clone)]
+pub struct ProjectInviteService {
+    repo: ProjectInviteRepository,
+    project_service: ProjectService,
+    notification_service: NotificationService,
+}
+
+impl ProjectInviteService {
+    pub fn new(
+        repo: ProjectInviteRepository,
+        project_service: ProjectService,
+        notification_service: NotificationService,
+    ) -> Self {
+        Self {
+            repo,
+            project_service,
+            notification_service,
+        }
+    }
+
+    /// Members join a project by accepting an invite, owners can only invite their friends
+    #[instrument(err, skip(self), fields(project_id = %project_id, invitee_id = %dto.user_id, actor = %actor))]
+    pub async fn invite_member(
+        &self,
+        project_id: Uuid,
+        dto: AddProjectMemberDto,
+        actor: &Actor,
+    ) -> Result<ReadProjectInviteDto> {
+        self.project_service
+            .ensure_can_manage_members(project_id, actor)
+            .await?;
+
+        if dto.user_id == actor.user_id {
+            return Err(anyhow::anyhow!("User is already a member of this project"));
+        }
+
+        if !self.repo.can_invite(&actor.user_id, &dto.user_id).await? {
+            return Err(anyhow::anyhow!("You can only invite friends to a project"));
+        }
+
+        if self.repo.is_member(project_id, &dto.user_id).await? {
+            return Err(anyhow::anyhow!("User is already a member of this project"));
+        }
+
+        let invite = self
+            .repo
+            .create(project_id, &dto.user_id, &actor.user_id, dto.role)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("User was already invited to this project"))?;
+
+        if let Some(inviter) = invite.inviter.clone() {
+            if let Err(e) = self
+                .notification_service
+                .notify_project_invite(&invite, inviter)
+                .await
+            {
+                tracing::warn!(error = %e, invite_id = %invite.id, "Failed to notify invited user");
+            }
+        }
+
+        Ok(invite)
+    }
+
+    #[instrument(err, skip(self), fields(project_id = %project_id, actor = %actor))]
+    pub async fn get_project_invites(
+        &self,
+        project_id: Uuid,
+        actor: &Actor,
+    ) -> Result<Vec<ReadProjectInviteDto>> {
+        self.project_service
+            .ensure_can_manage_members(project_id, actor)
+            .await?;
+
+        self.repo.list_pending_for_project(project_id).await
+    }
+
+    #[instrument(err, skip(self), fields(project_id = %project_id, invite_id = %invite_id, actor = %actor))]
+    pub async fn cancel_invite(
+        &self,
+        project_id: Uuid,
+        invite_id: Uuid,
+        actor: &Actor,
+    ) -> Result<()> {
+        self.project_service
+            .ensure_can_manage_members(project_id, actor)
+            .await?;
+
+        if !self.repo.cancel(invite_id, project_id).await? {
+            return Err(anyhow::anyhow!("Invite not found"));
+        }
+        Ok(())
+    }
+
+    #[instrument(err, skip(self), fields(actor = %actor))]
+    pub async fn get_my_invites(&self, actor: &Actor) -> Result<Vec<ReadProjectInviteDto>> {
+        self.repo.list_pending_for_invitee(&actor.user_id).await
+    }
+
+    #[instrument(err, skip(self), fields(invite_id = %invite_id, actor = %actor))]
+    pub async fn accept_invite(
+        &self,
+        invite_id: Uuid,
+        actor: &Actor,
+    ) -> Result<ReadProjectInviteDto> {
+        self.repo
+            .accept(invite_id, &actor.user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Invite not found"))
+    }
+
+    #[instrument(err, skip(self), fields(invite_id = %invite_id, actor = %actor))]
+    pub async fn decline_invite(&self, invite_id: Uuid, actor: &Actor) -> Result<()> {
+        if !self.repo.decline(invite_id, &actor.user_id).await? {
+            return Err(anyhow::anyhow!("Invite not found"));
+        }
+        Ok(())
+    }
+}
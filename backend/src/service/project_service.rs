@@ -8,21 +8,28 @@ use crate::{
         project::{
             create_project::CreateProjectDto,
             filter_project::FilterProjectDto,
-            read_project::{ProjectStatsDto, ReadProjectDto, ReadProjectDetailsDto},
+            project_member::{ReadProjectMemberDto, UpdateProjectMemberDto},
+            read_project::{ProjectStatsDto, ReadProjectDetailsDto, ReadProjectDto},
             update_project::UpdateProjectDto,
         },
         session::filter_session::FilterSessionDto,
     },
     entity::{
-        feed::{CategoryTimeBreakdown, FeedEventSource, FeedEventType, ProjectEventData, TaskTimeBreakdown},
-        project::Project,
+        feed::{
+            CategoryTimeBreakdown, FeedContributor, FeedEventSource, FeedEventType,
+            ProjectEventData, TaskTimeBreakdown,
+        },
+        project::{Project, ProjectMemberRole},
     },
     repository::{
         fixed_session::SessionRepositoryTrait,
         project::{ProjectRepository, ProjectRepositoryTrait},
     },
     router::clerk::Actor,
-    service::{feed::events::FeedEventService, session::fixed::FixedSessionService, task_service::TaskService, user_service::UserService},
+    service::{
+        achievement_service::AchievementService, feed::events::FeedEventService,
+        session::fixed::FixedSessionService, task_service::TaskService, user_service::UserService,
+    },
 };
 
 #[derive(Clone)]
@@ -32,6 +39,7 @@ pub struct ProjectService {
     user_service: UserService,
     task_service: TaskService,
     session_service: FixedSessionService,
+    achievement_service: AchievementService,
 }
 
 impl ProjectService {
@@ -41,6 +49,7 @@ impl ProjectService {
         user_service: UserService,
         task_service: TaskService,
         session_service: FixedSessionService,
+        achievement_service: AchievementService,
     ) -> Self {
         Self {
             repo,
@@ -48,6 +57,7 @@ impl ProjectService {
             user_service,
             task_service,
             session_service,
+            achievement_service,
         }
     }
 
@@ -63,6 +73,13 @@ impl ProjectService {
 
     #[instrument(err, skip(self), fields(project_id = %id, actor = %actor))]
     pub async fn delete_project(&self, id: Uuid, actor: &Actor) -> Result<()> {
+        let role = self.repo.get_member_role(id, &actor.user_id).await?;
+        if role != Some(ProjectMemberRole::Owner) {
+            return Err(anyhow::anyhow!(
+                "You are not allowed to delete this project"
+            ));
+        }
+
         self.repo.delete_project(id, actor).await?;
         Ok(())
     }
@@ -89,7 +106,11 @@ impl ProjectService {
         actor: &Actor,
     ) -> Result<ReadProjectDto> {
         let project = self.repo.find_by_id(dto.id, actor).await?;
-        if project.user_id != actor.user_id {
+        let role = self
+            .repo
+            .get_member_role(project.id, &actor.user_id)
+            .await?;
+        if !role.is_some_and(|role| role.can_edit()) {
             return Err(anyhow::anyhow!(
                 "You are not allowed to update this project"
             ));
@@ -137,8 +158,7 @@ impl ProjectService {
                 std::collections::HashMap::new();
 
             for session in sessions {
-                let duration_minutes =
-                    (session.end_time - session.start_time).num_minutes() as f64;
+                let duration_minutes = (session.end_time - session.start_time).num_minutes() as f64;
                 category_map
                     .entry(session.category.id)
                     .and_modify(|(_, _, minutes)| *minutes += duration_minutes)
@@ -159,39 +179,70 @@ impl ProjectService {
                 })
                 .collect();
 
-            let user = self
-                .user_service
-                .get_user_by_id(&actor.user_id)
+            let mut contributors: Vec<FeedContributor> = self
+                .repo
+                .get_member_time_breakdown(&[res.id], None)
                 .await?
-                .ok_or_else(|| anyhow::anyhow!("User not found"))?;
-
-            // Publish feed event
-            self.event_service
-                .publish_event(CreateFeedEventDto {
-                    id: None,
-                    data: FeedEventType::ProjectCompleted(ProjectEventData {
-                        project_id: res.id,
-                        project_name: res.name.clone(),
-                        project_color: res.color.clone(),
-                        project_image_url: res.image_url.clone(),
-                        created_at: res.created_at,
-                        total_sessions,
-                        tasks_time_breakdown,
-                        categories_time_breakdown,
-                    }),
-                    source: FeedEventSource::User(user),
+                .into_iter()
+                .filter(|(_, breakdown)| breakdown.total_time_minutes > 0.0)
+                .map(|(_, breakdown)| FeedContributor {
+                    user: breakdown.user,
+                    minutes: breakdown.total_time_minutes,
                 })
-                .await?;
+                .collect();
+
+            // the member completing the project is credited even without logged time
+            if !contributors.iter().any(|c| c.user.id == actor.user_id) {
+                let user = self
+                    .user_service
+                    .get_user_by_id(&actor.user_id)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+                contributors.push(FeedContributor { user, minutes: 0.0 });
+            }
+
+            let data = FeedEventType::ProjectCompleted(ProjectEventData {
+                project_id: res.id,
+                project_name: res.name.clone(),
+                project_color: res.color.clone(),
+                project_image_url: res.image_url.clone(),
+                created_at: res.created_at,
+                total_sessions,
+                tasks_time_breakdown,
+                categories_time_breakdown,
+                contributors: contributors.clone(),
+            });
+
+            // Publish feed event for every contributor
+            for contributor in contributors {
+                // Achievements are a side effect, they must not fail completing the project
+                if let Err(e) = self
+                    .achievement_service
+                    .evaluate(&contributor.user.id)
+                    .await
+                {
+                    tracing::error!(
+                        "Failed to evaluate achievements of {}: {:#}",
+                        contributor.user.id,
+                        e
+                    );
+                }
+
+                self.event_service
+                    .publish_event(CreateFeedEventDto {
+                        id: None,
+                        data: data.clone(),
+                        source: FeedEventSource::User(contributor.user),
+                    })
+                    .await?;
+            }
         }
 
         Ok(ReadProjectDto::from(res))
     }
 
     #[instrument(err, skip(self), fields(actor = %actor))]
-    pub async fn get_projects_details(
-        &self,
-        actor: &Actor,
-    ) -> Result<Vec<ReadProjectDetailsDto>> {
+    pub async fn get_projects_details(&self, actor: &Actor) -> Result<Vec<ReadProjectDetailsDto>> {
         self.repo.get_projects_details(actor).await
     }
 
@@ -199,4 +250,93 @@ impl ProjectService {
     pub async fn get_project_statistics(&self, actor: &Actor) -> Result<ProjectStatsDto> {
         self.repo.get_project_statistics(actor).await
     }
+
+    #[instrument(err, skip(self), fields(project_id = %project_id, actor = %actor))]
+    pub async fn get_members(
+        &self,
+        project_id: Uuid,
+        actor: &Actor,
+    ) -> Result<Vec<ReadProjectMemberDto>> {
+        if self
+            .repo
+            .get_member_role(project_id, &actor.user_id)
+            .await?
+            .is_none()
+        {
+            return Err(anyhow::anyhow!("Project not found"));
+        }
+
+        self.repo.list_members(project_id).await
+    }
+
+    #[instrument(err, skip(self), fields(project_id = %project_id, user_id = %user_id, actor = %actor))]
+    pub async fn update_member(
+        &self,
+        project_id: Uuid,
+        user_id: String,
+        dto: UpdateProjectMemberDto,
+        actor: &Actor,
+    ) -> Result<Vec<ReadProjectMemberDto>> {
+        self.ensure_can_manage_members(project_id, actor).await?;
+
+        let current_role = self
+            .repo
+            .get_member_role(project_id, &user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("User is not a member of this project"))?;
+
+        if current_role == ProjectMemberRole::Owner
+            && dto.role != ProjectMemberRole::Owner
+            && self.repo.count_owners(project_id).await? <= 1
+        {
+            return Err(anyhow::anyhow!("A project must have at least one owner"));
+        }
+
+        self.repo
+            .upsert_member(project_id, &user_id, dto.role)
+            .await?;
+        self.repo.list_members(project_id).await
+    }
+
+    /// Owners can remove anyone, every member can remove themselves to leave the project
+    #[instrument(err, skip(self), fields(project_id = %project_id, user_id = %user_id, actor = %actor))]
+    pub async fn remove_member(
+        &self,
+        project_id: Uuid,
+        user_id: String,
+        actor: &Actor,
+    ) -> Result<()> {
+        if user_id != actor.user_id {
+            self.ensure_can_manage_members(project_id, actor).await?;
+        }
+
+        let role = self
+            .repo
+            .get_member_role(project_id, &user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("User is not a member of this project"))?;
+
+        if role == ProjectMemberRole::Owner && self.repo.count_owners(project_id).await? <= 1 {
+            return Err(anyhow::anyhow!("A project must have at least one owner"));
+        }
+
+        self.repo.remove_member(project_id, &user_id).await
+    }
+
+    pub(crate) async fn ensure_can_manage_members(
+        &self,
+        project_id: Uuid,
+        actor: &Actor,
+    ) -> Result<()> {
+        let role = self
+            .repo
+            .get_member_role(project_id, &actor.user_id)
+            .await?;
+        if !role.is_some_and(|role| role.can_manage_members()) {
+            return Err(anyhow::anyhow!(
+                "You are not allowed to manage members of this project"
+            ));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::instrument;
+
+use crate::{
+    config::{database::Database, env::RateLimitBackend},
+    repository::rate_limit::RateLimitRepository,
+};
+
+// Upper bound of in-memory counters, expired ones are dropped first when it is reached and
+// new clients then share one overflow counter per policy until there is room again
+const MAX_MEMORY_BUCKETS: usize = 100_000;
+
+/// Request budget of a group of routes, counted per client in fixed windows
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitPolicy {
+    pub name: &'static str,
+    pub limit: u32,
+    pub window: Duration,
+    // Reject requests while the counters cannot be read instead of letting them through
+    pub fail_closed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimitDecision {
+    Allowed,
+    Limited { retry_after: Duration },
+}
+
+#[derive(Clone)]
+enum RateLimitStore {
+    Disabled,
+    Memory(Arc<Mutex<HashMap<String, MemoryBucket>>>),
+    Postgres(RateLimitRepository),
+}
+
+struct MemoryBucket {
+    window_start: i64,
+    window_end: i64,
+    hits: u32,
+}
+
+#[derive(Clone)]
+pub struct RateLimitService {
+    store: RateLimitStore,
+}
+
+impl RateLimitService {
+    pub fn new(db: &Arc<Database>, backend: &RateLimitBackend) -> Self {
+        let store = match backend {
+            RateLimitBackend::Disabled => RateLimitStore::Disabled,
+            RateLimitBackend::Memory => RateLimitStore::Memory(Default::default()),
+            RateLimitBackend::Postgres => RateLimitStore::Postgres(RateLimitRepository::new(db)),
+        };
+
+        Self { store }
+    }
+
+    /// Count a request of the client against the policy.
+    /// Windows are aligned to the epoch, so every instance agrees on when they reset.
+    #[instrument(err, skip(self), fields(policy = policy.name))]
+    pub async fn check(&self, key: &str, policy: &RateLimitPolicy) -> Result<RateLimitDecision> {
+        let window_secs = policy.window.as_secs().max(1) as i64;
+        let now = Utc::now().timestamp();
+        let window_start = now - now.rem_euclid(window_secs);
+        let window_end = window_start + window_secs;
+        let mut bucket = format!("{}:{}", policy.name, key);
+
+        let hits = match &self.store {
+            RateLimitStore::Disabled => return Ok(RateLimitDecision::Allowed),
+            RateLimitStore::Memory(buckets) => {
+                let mut buckets = buckets
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                if buckets.len() >= MAX_MEMORY_BUCKETS && !buckets.contains_key(&bucket) {
+                    buckets.retain(|_, bucket| bucket.window_end > now);
+                    if buckets.len() >= MAX_MEMORY_BUCKETS {
+                        bucket = format!("{}:overflow", policy.name);
+                    }
+                }
+                let entry = buckets.entry(bucket).or_insert(MemoryBucket {
+                    window_start,
+                    window_end,
+                    hits: 0,
+                });
+                if entry.window_start != window_start {
+                    entry.window_start = window_start;
+                    entry.window_end = window_end;
+                    entry.hits = 0;
+                }
+                entry.hits += 1;
+                entry.hits
+            }
+            RateLimitStore::Postgres(repo) => {
+                let hits = repo
+                    .hit(&bucket, timestamp(window_start), timestamp(window_end))
+                    .await?;
+                hits.max(0) as u32
+            }
+        };
+
+        if hits > policy.limit {
+            Ok(RateLimitDecision::Limited {
+                retry_after: Duration::from_secs((window_end - now).max(1) as u64),
+            })
+        } else {
+            Ok(RateLimitDecision::Allowed)
+        }
+    }
+
+    /// Drop the counters of past windows, returns how many were removed. In-memory counters
+    /// belong to the instance running the job, other instances prune theirs when full.
+    #[instrument(err, skip(self))]
+    pub async fn cleanup(&self) -> Result<u64> {
+        match &self.store {
+            RateLimitStore::Disabled => Ok(0),
+            RateLimitStore::Memory(buckets) => {
+                let now = Utc::now().timestamp();
+                let mut buckets = buckets
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                let before = buckets.len();
+                buckets.retain(|_, bucket| bucket.window_end > now);
+                Ok((before - buckets.len()) as u64)
+            }
+            RateLimitStore::Postgres(repo) => repo.delete_expired().await,
+        }
+    }
+}
+
+fn timestamp(secs: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs, 0).unwrap_or_default()
+}
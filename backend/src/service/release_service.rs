@@ -1,4 +1,5 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
 use std::sync::Arc;
 use tracing::instrument;
 use uuid::Uuid;
@@ -7,20 +8,24 @@ use crate::{
     config::database::Database,
     dto::release::{
         CreateReleaseDto, LatestUnseenReleaseDto, ReadPublicReleaseDto, ReadReleaseDto,
-        ReleaseListQueryDto, UpdateReleaseDto,
+        ReleaseListQueryDto, ScheduleReleaseDto, UpdateReleaseDto,
     },
+    entity::release::{Release, ReleaseAudience},
     repository::release::ReleaseRepository,
+    service::notification_service::NotificationService,
 };
 
 #[derive(Clone)]
 pub struct ReleaseService {
     repository: ReleaseRepository,
+    notification_service: NotificationService,
 }
 
 impl ReleaseService {
-    pub fn new(db: &Arc<Database>) -> Self {
+    pub fn new(db: &Arc<Database>, notification_service: NotificationService) -> Self {
         Self {
             repository: ReleaseRepository::new(db),
+            notification_service,
         }
     }
 
@@ -48,7 +53,10 @@ impl ReleaseService {
         version: String,
         user_id: Option<String>,
     ) -> Result<Option<ReadPublicReleaseDto>> {
-        let release = self.repository.get_release_by_version(version).await?;
+        let release = self
+            .repository
+            .get_release_by_version(version, user_id.clone())
+            .await?;
 
         // Only return if released
         match release {
@@ -75,6 +83,7 @@ impl ReleaseService {
     pub async fn list_public_releases(&self) -> Result<Vec<ReadPublicReleaseDto>> {
         let query = ReleaseListQueryDto {
             released_only: Some(true),
+            audience: Some(ReleaseAudience::Everyone),
             limit: None,
             offset: None,
         };
@@ -86,12 +95,29 @@ impl ReleaseService {
             .collect())
     }
 
+    /// Most recently published releases for the public feed
+    #[instrument(err, skip(self))]
+    pub async fn list_feed_releases(&self, limit: i64) -> Result<Vec<ReadPublicReleaseDto>> {
+        let query = ReleaseListQueryDto {
+            released_only: Some(true),
+            audience: Some(ReleaseAudience::Everyone),
+            limit: Some(limit),
+            offset: None,
+        };
+
+        let releases = self.repository.list_releases(query).await?;
+        Ok(releases
+            .into_iter()
+            .map(ReadPublicReleaseDto::from)
+            .collect())
+    }
+
     #[instrument(err, skip(self))]
     pub async fn get_latest_released(
         &self,
         user_id: Option<String>,
     ) -> Result<Option<ReadPublicReleaseDto>> {
-        let latest = self.repository.get_latest_released().await?;
+        let latest = self.repository.get_latest_released(user_id.clone()).await?;
 
         match latest {
             Some(r) => {
@@ -110,7 +136,10 @@ impl ReleaseService {
         &self,
         user_id: String,
     ) -> Result<Option<LatestUnseenReleaseDto>> {
-        let latest = self.repository.get_latest_released().await?;
+        let latest = self
+            .repository
+            .get_latest_released(Some(user_id.clone()))
+            .await?;
 
         match latest {
             Some(release) => {
@@ -178,9 +207,71 @@ impl ReleaseService {
             return Err(anyhow!("Failed to publish release"));
         }
 
+        self.announce(release).await;
+
         Ok(())
     }
 
+    #[instrument(err, skip(self, dto))]
+    pub async fn schedule_release(
+        &self,
+        release_id: Uuid,
+        dto: ScheduleReleaseDto,
+        scheduled_by: String,
+    ) -> Result<ReadReleaseDto> {
+        if dto.scheduled_at <= Utc::now() {
+            bail!("The release must be scheduled in the future");
+        }
+
+        let release = self
+            .repository
+            .get_release_by_id(release_id)
+            .await?
+            .ok_or_else(|| anyhow!("Release not found"))?;
+
+        if release.released {
+            bail!("Release already published");
+        }
+
+        self.repository
+            .schedule_release(release_id, dto.scheduled_at.into(), scheduled_by)
+            .await?;
+
+        self.get_release_by_id(release_id)
+            .await?
+            .ok_or_else(|| anyhow!("Failed to fetch scheduled release"))
+    }
+
+    #[instrument(err, skip(self))]
+    pub async fn cancel_schedule(&self, release_id: Uuid) -> Result<ReadReleaseDto> {
+        let cancelled = self.repository.cancel_schedule(release_id).await?;
+
+        if !cancelled {
+            bail!("Release not found or not scheduled");
+        }
+
+        self.get_release_by_id(release_id)
+            .await?
+            .ok_or_else(|| anyhow!("Failed to fetch release"))
+    }
+
+    /// Publish and announce every release whose schedule is due, run by the job queue
+    #[instrument(err, skip(self))]
+    pub async fn publish_due_releases(&self) -> Result<Vec<ReadReleaseDto>> {
+        let mut published = Vec::new();
+
+        while let Some(release_id) = self.repository.publish_next_due_release().await? {
+            let Some(release) = self.repository.get_release_by_id(release_id).await? else {
+                continue;
+            };
+
+            self.announce(release.clone()).await;
+            published.push(ReadReleaseDto::from(release));
+        }
+
+        Ok(published)
+    }
+
     #[instrument(err, skip(self))]
     pub async fn unpublish_release(&self, release_id: Uuid) -> Result<()> {
         let unpublished = self.repository.unpublish_release(release_id).await?;
@@ -189,6 +280,15 @@ impl ReleaseService {
             return Err(anyhow!("Failed to unpublish release or release not found"));
         }
 
+        match self
+            .notification_service
+            .withdraw_system_announcement(release_id)
+            .await
+        {
+            Ok(withdrawn) => tracing::info!("Withdrew {} release notifications", withdrawn),
+            Err(e) => tracing::error!("Failed to withdraw release notifications: {}", e),
+        }
+
         Ok(())
     }
 
@@ -217,4 +317,22 @@ impl ReleaseService {
 
         Ok(())
     }
+
+    // The release is already public at this point, a failed announcement must not undo that
+    async fn announce(&self, release: Release) {
+        let res = self
+            .notification_service
+            .notify_system_announcement(
+                release.audience,
+                release.id,
+                release.name,
+                release.short_description,
+            )
+            .await;
+
+        match res {
+            Ok(notified) => tracing::info!("Announced release to {} users", notified),
+            Err(e) => tracing::error!("Failed to announce release: {}", e),
+        }
+    }
 }
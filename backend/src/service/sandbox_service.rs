@@ -39,7 +39,8 @@ impl SandboxService {
         self.lifecycle_repo.get_all(limit).await
     }
 
-    pub async fn increment_unique_users(&self) -> Result<(), sqlx::Error> {
+    pub async fn record_guest_assigned(&self, guest_id: &str) -> Result<(), sqlx::Error> {
+        self.lifecycle_repo.mark_guest_assigned(guest_id).await?;
         if let Some(active) = self.lifecycle_repo.get_active().await? {
             self.lifecycle_repo
                 .increment_unique_users(active.id)
@@ -21,7 +21,10 @@ use crate::{
         task::{TaskRepository, TaskRepositoryTrait},
     },
     router::clerk::Actor,
-    service::{feed::events::FeedEventService, user_service::UserService},
+    service::{
+        achievement_service::AchievementService, feed::events::FeedEventService,
+        user_service::UserService,
+    },
 };
 
 #[derive(Clone)]
@@ -32,6 +35,7 @@ pub struct FixedSessionService {
     user_service: UserService,
     project_repo: ProjectRepository,
     task_repo: TaskRepository,
+    achievement_service: AchievementService,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -49,6 +53,7 @@ impl FixedSessionService {
         user_service: UserService,
         project_repo: ProjectRepository,
         task_repo: TaskRepository,
+        achievement_service: AchievementService,
     ) -> Self {
         Self {
             fixed_repo: repo,
@@ -57,6 +62,7 @@ impl FixedSessionService {
             user_service,
             project_repo,
             task_repo,
+            achievement_service,
         }
     }
 
@@ -66,6 +72,14 @@ impl FixedSessionService {
         dto: CreateFixedSessionDto,
         actor: &Actor,
     ) -> Result<ReadFixedSessionDto> {
+        // Time can only be logged against projects and tasks the actor is a member of
+        if let Some(project_id) = dto.project_id {
+            self.project_repo.find_by_id(project_id, actor).await?;
+        }
+        if let Some(task_id) = dto.task_id {
+            self.task_repo.find_by_id(task_id, actor).await?;
+        }
+
         let res = self.fixed_repo.create(dto, actor).await?;
         let user = self
             .user_service
@@ -118,6 +132,15 @@ impl FixedSessionService {
             })
             .await?;
 
+        // Achievements are a side effect, they must not fail creating the session
+        if let Err(e) = self.achievement_service.evaluate(&actor.user_id).await {
+            tracing::error!(
+                "Failed to evaluate achievements of {}: {:#}",
+                actor.user_id,
+                e
+            );
+        }
+
         Ok(ReadFixedSessionDto::from(res))
     }
 
@@ -191,6 +214,13 @@ impl FixedSessionService {
         dto: UpdateFixedSessionDto,
         actor: &Actor,
     ) -> Result<ReadFixedSessionDto> {
+        if let Some(Some(project_id)) = dto.project_id {
+            self.project_repo.find_by_id(project_id, actor).await?;
+        }
+        if let Some(Some(task_id)) = dto.task_id {
+            self.task_repo.find_by_id(task_id, actor).await?;
+        }
+
         let res = self.fixed_repo.update_session(dto, actor).await?;
 
         Ok(ReadFixedSessionDto::from(res))
@@ -2,7 +2,11 @@ use crate::{
     dto::session::stopwatch_session::{
         CreateStopwatchSessionDto, ReadStopwatchSessionDto, UpdateStopwatchSessionDto,
     },
-    repository::stopwatch_session::StopwatchSessionRepository,
+    repository::{
+        project::{ProjectRepository, ProjectRepositoryTrait},
+        stopwatch_session::StopwatchSessionRepository,
+        task::{TaskRepository, TaskRepositoryTrait},
+    },
     router::clerk::Actor,
     service::category_service::CategoryService,
 };
@@ -14,16 +18,22 @@ use uuid::Uuid;
 pub struct StopwatchSessionService {
     category_service: CategoryService,
     stopwatch_repo: StopwatchSessionRepository,
+    project_repo: ProjectRepository,
+    task_repo: TaskRepository,
 }
 
 impl StopwatchSessionService {
     pub fn new(
         category_service: CategoryService,
         stopwatch_repo: StopwatchSessionRepository,
+        project_repo: ProjectRepository,
+        task_repo: TaskRepository,
     ) -> Self {
         Self {
             category_service,
             stopwatch_repo,
+            project_repo,
+            task_repo,
         }
     }
 
@@ -33,6 +43,14 @@ impl StopwatchSessionService {
         dto: CreateStopwatchSessionDto,
         actor: &Actor,
     ) -> Result<ReadStopwatchSessionDto> {
+        // Time can only be logged against projects and tasks the actor is a member of
+        if let Some(project_id) = dto.project_id {
+            self.project_repo.find_by_id(project_id, actor).await?;
+        }
+        if let Some(task_id) = dto.task_id {
+            self.task_repo.find_by_id(task_id, actor).await?;
+        }
+
         let category = match dto.clone().category {
             Some(cat) => {
                 let a = self
@@ -78,6 +96,13 @@ impl StopwatchSessionService {
         dto: UpdateStopwatchSessionDto,
         actor: &Actor,
     ) -> Result<ReadStopwatchSessionDto> {
+        if let Some(Some(project_id)) = dto.project_id {
+            self.project_repo.find_by_id(project_id, actor).await?;
+        }
+        if let Some(Some(task_id)) = dto.task_id {
+            self.task_repo.find_by_id(task_id, actor).await?;
+        }
+
         let res = self
             .stopwatch_repo
             .update_session(dto.clone(), actor)
@@ -14,7 +14,7 @@ use crate::{
         },
     },
     entity::{
-        feed::{FeedEventSource, FeedEventType, FeedProject, TaskEventData},
+        feed::{FeedContributor, FeedEventSource, FeedEventType, FeedProject, TaskEventData},
         task::Task,
     },
     repository::{
@@ -22,7 +22,10 @@ use crate::{
         task::{TaskRepository, TaskRepositoryTrait},
     },
     router::clerk::Actor,
-    service::{feed::events::FeedEventService, user_service::UserService},
+    service::{
+        achievement_service::AchievementService, feed::events::FeedEventService,
+        user_service::UserService,
+    },
 };
 
 #[derive(Clone)]
@@ -31,6 +34,7 @@ pub struct TaskService {
     project_repo: ProjectRepository,
     event_service: FeedEventService,
     user_service: UserService,
+    achievement_service: AchievementService,
 }
 
 impl TaskService {
@@ -39,17 +43,29 @@ impl TaskService {
         project_repo: ProjectRepository,
         event_service: FeedEventService,
         user_service: UserService,
+        achievement_service: AchievementService,
     ) -> Self {
         Self {
             repo,
             project_repo,
             event_service,
             user_service,
+            achievement_service,
         }
     }
 
     #[instrument(err, skip(self), fields(actor = %actor))]
     pub async fn create_task(&self, dto: CreateTaskDto, actor: &Actor) -> Result<ReadTaskDto> {
+        let role = self
+            .project_repo
+            .get_member_role(dto.project_id, &actor.user_id)
+            .await?;
+        if !role.is_some_and(|role| role.can_edit()) {
+            return Err(anyhow::anyhow!(
+                "You are not allowed to add tasks to this project"
+            ));
+        }
+
         let res = self.repo.create(dto, actor).await?;
         Ok(ReadTaskDto::from(res))
     }
@@ -120,7 +136,11 @@ impl TaskService {
     #[instrument(err, skip(self), fields(task_id = %dto.id, actor = %actor))]
     pub async fn update_task(&self, dto: UpdateTaskDto, actor: &Actor) -> Result<ReadTaskDto> {
         let task = self.repo.find_by_id(dto.id, actor).await?;
-        if task.user_id != actor.user_id {
+        let role = self
+            .project_repo
+            .get_member_role(task.project_id, &actor.user_id)
+            .await?;
+        if !role.is_some_and(|role| role.can_edit()) {
             return Err(anyhow::anyhow!("You are not allowed to update this task"));
         }
 
@@ -145,36 +165,77 @@ impl TaskService {
                     .find_by_id(res.project_id, actor)
                     .await?;
 
-                let user = self
-                    .user_service
-                    .get_user_by_id(&actor.user_id)
-                    .await?
-                    .ok_or_else(|| anyhow::anyhow!("User not found"))?;
-
-                self.event_service
-                    .publish_event(CreateFeedEventDto {
-                        id: None,
-                        data: FeedEventType::TaskCompleted(TaskEventData {
-                            task_id: res.id,
-                            task_name: res.name.clone(),
-                            task_description: res.description.clone(),
-                            hours_of_work: task_detail.total_time_minutes / 60.0,
-                            project: FeedProject {
-                                id: project.id,
-                                name: project.name.clone(),
-                                color: project.color.clone(),
-                                image_url: project.image_url.clone(),
-                            },
-                        }),
-                        source: FeedEventSource::User(user),
-                    })
-                    .await?;
+                let contributors = self.get_contributors(&res, actor).await?;
+                let data = FeedEventType::TaskCompleted(TaskEventData {
+                    task_id: res.id,
+                    task_name: res.name.clone(),
+                    task_description: res.description.clone(),
+                    hours_of_work: task_detail.total_time_minutes / 60.0,
+                    project: FeedProject {
+                        id: project.id,
+                        name: project.name.clone(),
+                        color: project.color.clone(),
+                        image_url: project.image_url.clone(),
+                    },
+                    contributors: contributors.clone(),
+                });
+
+                // every member that worked on the task gets the completion in their feed
+                for contributor in contributors {
+                    // Achievements are a side effect, they must not fail completing the task
+                    if let Err(e) = self
+                        .achievement_service
+                        .evaluate(&contributor.user.id)
+                        .await
+                    {
+                        tracing::error!(
+                            "Failed to evaluate achievements of {}: {:#}",
+                            contributor.user.id,
+                            e
+                        );
+                    }
+
+                    self.event_service
+                        .publish_event(CreateFeedEventDto {
+                            id: None,
+                            data: data.clone(),
+                            source: FeedEventSource::User(contributor.user),
+                        })
+                        .await?;
+                }
             }
         }
 
         Ok(ReadTaskDto::from(res))
     }
 
+    /// Members that logged time on the task, the actor completing it is always credited
+    #[instrument(err, skip(self, task), fields(task_id = %task.id, actor = %actor))]
+    async fn get_contributors(&self, task: &Task, actor: &Actor) -> Result<Vec<FeedContributor>> {
+        let mut contributors: Vec<FeedContributor> = self
+            .project_repo
+            .get_member_time_breakdown(&[task.project_id], Some(task.id))
+            .await?
+            .into_iter()
+            .filter(|(_, breakdown)| breakdown.total_time_minutes > 0.0)
+            .map(|(_, breakdown)| FeedContributor {
+                user: breakdown.user,
+                minutes: breakdown.total_time_minutes,
+            })
+            .collect();
+
+        if !contributors.iter().any(|c| c.user.id == actor.user_id) {
+            let user = self
+                .user_service
+                .get_user_by_id(&actor.user_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+            contributors.push(FeedContributor { user, minutes: 0.0 });
+        }
+
+        Ok(contributors)
+    }
+
     #[instrument(err, skip(self), fields(actor = %actor))]
     pub async fn get_task_statistics(&self, actor: &Actor) -> Result<TaskStatsDto> {
         self.repo.get_task_statistics(actor).await
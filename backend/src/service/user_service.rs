@@ -3,9 +3,15 @@ use tracing::instrument;
 
 use crate::{
     dto::user::{
-        read_user::ReadUserDto, update_user::UpdateUserDto, update_visibility::UpdateVisibilityDto,
+        beta_opt_in::BetaOptInDto,
+        read_user::{ReadUserDto, ReadUserProfileDto},
+        update_user::UpdateUserDto,
+        update_visibility::UpdateVisibilityDto,
+    },
+    repository::{
+        achievement::AchievementRepository,
+        user::{FilterUsersDto, IdFilter, UserRepository},
     },
-    repository::user::{FilterUsersDto, IdFilter, UserRepository},
     router::{
         clerk::{Actor, UserRole},
         user::root::UserError,
@@ -18,6 +24,7 @@ pub struct UserService {
     repo: UserRepository,
     visibility_service: FeedVisibilityService,
     subscription_service: FeedSubscriptionService,
+    achievement_repo: AchievementRepository,
 }
 
 impl UserService {
@@ -25,11 +32,13 @@ impl UserService {
         repo: UserRepository,
         visibility_service: FeedVisibilityService,
         subscription_service: FeedSubscriptionService,
+        achievement_repo: AchievementRepository,
     ) -> Self {
         Self {
             repo,
             visibility_service,
             subscription_service,
+            achievement_repo,
         }
     }
 
@@ -49,15 +58,19 @@ impl UserService {
         }
     }
 
-    #[instrument(err, skip(self), fields(username = %username))]
+    /// Deleted users and users that blocked the actor or were blocked by them can't be found by name
+    #[instrument(err, skip(self), fields(username = %username, actor = %actor))]
     pub async fn get_user_by_name(
         &self,
         username: String,
+        actor: &Actor,
     ) -> Result<Option<ReadUserDto>, UserError> {
         let user = self
             .repo
             .filter_users(FilterUsersDto {
                 name: Some(username),
+                visible_to: Some(actor.user_id.clone()),
+                exclude_deleted: true,
                 ..Default::default()
             })
             .await
@@ -86,6 +99,59 @@ impl UserService {
         Ok(user.first().cloned().map(Into::into))
     }
 
+    /// Promote or demote a user, returns the role they had before. Admins cannot change their
+    /// own role, which also keeps the last admin from demoting themselves.
+    #[instrument(err, skip(self), fields(user_id = %user_id, admin_id = %admin))]
+    pub async fn change_role(
+        &self,
+        user_id: &str,
+        role: UserRole,
+        admin: &Actor,
+    ) -> Result<UserRole> {
+        if user_id == admin.user_id {
+            anyhow::bail!("You cannot change your own role");
+        }
+
+        let Some(previous) = self.repo.set_role(user_id, role).await? else {
+            anyhow::bail!("User not found");
+        };
+        Ok(previous)
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn get_beta_opt_in(&self, user_id: &str) -> Result<BetaOptInDto> {
+        let Some(beta_opt_in) = self.repo.get_beta_opt_in(user_id).await? else {
+            anyhow::bail!("User not found");
+        };
+        Ok(BetaOptInDto { beta_opt_in })
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn set_beta_opt_in(&self, user_id: &str, dto: BetaOptInDto) -> Result<BetaOptInDto> {
+        let Some(beta_opt_in) = self.repo.set_beta_opt_in(user_id, dto.beta_opt_in).await? else {
+            anyhow::bail!("User not found");
+        };
+        Ok(BetaOptInDto { beta_opt_in })
+    }
+
+    #[instrument(err, skip(self), fields(user_id = %user_id))]
+    pub async fn get_user_profile(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<ReadUserProfileDto>, UserError> {
+        let Some(user) = self.get_user_by_id(user_id).await? else {
+            return Ok(None);
+        };
+
+        let badges = self
+            .achievement_repo
+            .list_badges(user_id)
+            .await
+            .map_err(|e| UserError::UnknownError(e.to_string()))?;
+
+        Ok(Some(ReadUserProfileDto { user, badges }))
+    }
+
     #[instrument(err, skip(self), fields(user_ids_count = user_ids.len()))]
     pub async fn get_users_by_ids(
         &self,
@@ -124,17 +190,21 @@ impl UserService {
         }
     }
 
-    #[instrument(err, skip(self))]
+    #[instrument(err, skip(self), fields(actor = %actor))]
     pub async fn search_users(
         &self,
         query: &str,
         limit: i64,
+        actor: &Actor,
     ) -> Result<Vec<ReadUserDto>, UserError> {
         let users = self
             .repo
             .filter_users(FilterUsersDto {
                 id: Some(IdFilter::Single(query.to_string())),
                 name: Some(query.to_string()),
+                visible_to: Some(actor.user_id.clone()),
+                exclude_deleted: true,
+                limit: Some(limit),
             })
             .await
             .map_err(|e| UserError::UnknownError(e.to_string()))?;
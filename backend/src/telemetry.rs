@@ -0,0 +1,53 @@
+use anyhow::Result;
+use axum::http::HeaderMap;
+use opentelemetry::{global, Context};
+use opentelemetry_http::{HeaderExtractor, HeaderInjector};
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::config::env::TracingConfig;
+
+/// Tracer provider exporting spans over OTLP/HTTP, None when no endpoint is configured
+pub fn init_tracer_provider(config: &TracingConfig) -> Result<Option<SdkTracerProvider>> {
+    if config.otlp_endpoint.is_none() {
+        return Ok(None);
+    }
+
+    let exporter = SpanExporter::builder().with_http().build()?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(config.service_name.clone())
+                .build(),
+        )
+        .build();
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider.clone());
+
+    Ok(Some(provider))
+}
+
+/// W3C trace context sent by the caller, the request span continues that trace
+pub fn extract_trace_context(headers: &HeaderMap) -> Context {
+    global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)))
+}
+
+pub trait PropagateTraceContext {
+    /// Adds the trace context of the current span, so the callee joins the same trace
+    fn with_trace_context(self) -> Self;
+}
+
+impl PropagateTraceContext for reqwest::RequestBuilder {
+    fn with_trace_context(self) -> Self {
+        let context = tracing::Span::current().context();
+        let mut headers = HeaderMap::new();
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&context, &mut HeaderInjector(&mut headers))
+        });
+
+        self.headers(headers)
+    }
+}
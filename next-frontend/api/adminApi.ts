@@ -6,8 +6,10 @@ const BASE_URL = "/admin";
 export const dbBackupSchema = z.object({
   backupFile: z.string(),
   backupSizeBytes: z.number().nullable(),
+  checksumSha256: z.string().nullable(),
   durationSeconds: z.number().nullable(),
   errorMessage: z.string().nullable(),
+  expiredAt: z.coerce.date().nullable(),
   finishedAt: z.coerce.date().nullable(),
   id: z.number(),
   startedAt: z.coerce.date().nullable(),
@@ -16,6 +18,7 @@ export const dbBackupSchema = z.object({
   triggerType: z.string(),
   userAvatarUrl: z.string().nullable(),
   userUsername: z.string().nullable(),
+  verifiedAt: z.coerce.date().nullable(),
 });
 
 export type DbBackup = z.infer<typeof dbBackupSchema>;
@@ -240,6 +240,7 @@ const BackupsPage: React.FC = () => {
                             <TableHead>Size</TableHead>
                             <TableHead>Trigger</TableHead>
                             <TableHead>Backup File</TableHead>
+                            <TableHead>SHA-256</TableHead>
                             <TableHead>Error</TableHead>
                             <TableHead>Download</TableHead>
                           </TableRow>
@@ -270,6 +271,14 @@ const BackupsPage: React.FC = () => {
                               >
                                 {backup.backupFile}
                               </TableCell>
+                              <TableCell
+                                className="font-mono text-xs"
+                                title={backup.checksumSha256 ?? undefined}
+                              >
+                                {backup.checksumSha256
+                                  ? `${backup.checksumSha256.slice(0, 12)}…`
+                                  : "—"}
+                              </TableCell>
                               <TableCell className="text-sm text-destructive">
                                 {backup.errorMessage ?? "—"}
                               </TableCell>